@@ -1,5 +1,6 @@
+use crate::bytecode::{decode_all, Instruction};
 use crate::constant_loaders::load_std_str;
-use crate::int_tools::bytes_index;
+use crate::int_tools::{bytes_slice, try_bytes_index, Truncated};
 use std::string::String;
 use std::vec::Vec;
 
@@ -8,6 +9,7 @@ pub struct BaseFunction {
     name: String,
     local_count: u16,
     bytes: Vec<u8>,
+    instructions: Vec<Option<Instruction>>,
     is_gen: bool,
 }
 
@@ -16,6 +18,7 @@ impl BaseFunction {
         BaseFunction {
             name,
             local_count,
+            instructions: decode_all(&bytes),
             bytes,
             is_gen: false,
         }
@@ -25,24 +28,31 @@ impl BaseFunction {
         BaseFunction {
             name,
             local_count,
+            instructions: decode_all(&bytes),
             bytes,
             is_gen: true,
         }
     }
 
-    pub fn parse(data: &[u8], index: &mut usize) -> BaseFunction {
-        let name = load_std_str(data, index);
-        let is_gen = data[*index] != 0;
-        *index += 1;
-        let var_count = bytes_index::<u16>(data, index);
-        let fn_size = bytes_index::<u32>(data, index) as usize;
-        let values = data[*index..*index + fn_size].to_vec();
-        *index += fn_size;
-        if is_gen {
+    /// Drops the ahead-of-time decoded instructions, forcing every
+    /// instruction to be decoded as it is executed.
+    #[cfg(test)]
+    pub(crate) fn without_decoding(mut self) -> BaseFunction {
+        self.instructions = Vec::new();
+        self
+    }
+
+    pub fn parse(data: &[u8], index: &mut usize) -> Result<BaseFunction, Truncated> {
+        let name = load_std_str(data, index)?;
+        let is_gen = try_bytes_index::<u8>(data, index)? != 0;
+        let var_count = try_bytes_index::<u16>(data, index)?;
+        let fn_size = try_bytes_index::<u32>(data, index)? as usize;
+        let values = bytes_slice(data, index, fn_size)?.to_vec();
+        Result::Ok(if is_gen {
             BaseFunction::new_gen(name, var_count, values)
         } else {
             BaseFunction::new(name, var_count, values)
-        }
+        })
     }
 
     pub fn get_name(&self) -> &str {
@@ -57,6 +67,15 @@ impl BaseFunction {
         &self.bytes
     }
 
+    /// Gets the instruction starting at the given position, decoding it if
+    /// it was not decoded ahead of time.
+    pub fn get_instruction(&self, pos: usize) -> Instruction {
+        match self.instructions.get(pos) {
+            Option::Some(Option::Some(instruction)) => *instruction,
+            _ => Instruction::decode(&self.bytes, pos),
+        }
+    }
+
     pub fn is_generator(&self) -> bool {
         self.is_gen
     }
@@ -28,8 +28,8 @@ pub fn get_operator(this: char, o: Operator) -> Variable {
     StdMethod::new_native(this, func).into()
 }
 
-pub fn attr_fn(s: &str) -> NativeMethod<char> {
-    match s {
+pub fn attr_fn(s: &str) -> Option<NativeMethod<char>> {
+    Option::Some(match s {
         "upper" => upper,
         "lower" => lower,
         "isUpper" => is_upper,
@@ -39,21 +39,21 @@ pub fn attr_fn(s: &str) -> NativeMethod<char> {
         "encode" => encode,
         "isDigit" => is_digit,
         "isNumeric" => is_numeric,
-        x => unimplemented!("char.{}", x),
-    }
+        _ => return Option::None,
+    })
 }
 
-pub fn get_attribute(this: char, s: &str) -> Variable {
-    let func = attr_fn(s);
-    StdMethod::new_native(this, func).into()
+pub fn get_attribute(this: char, s: &str) -> Option<Variable> {
+    let func = attr_fn(s)?;
+    Option::Some(StdMethod::new_native(this, func).into())
 }
 
-pub fn static_attr(s: &str) -> Variable {
+pub fn static_attr(s: &str) -> Option<Variable> {
     let func = match s {
         "fromInt" => from_int,
-        x => unimplemented!("str.{}", x),
+        _ => return Option::None,
     };
-    Function::Native(func).into()
+    Option::Some(Function::Native(func).into())
 }
 
 fn eq(this: char, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
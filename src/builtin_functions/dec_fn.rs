@@ -1,9 +1,11 @@
+use crate::check_args;
 use crate::method::{NativeMethod, StdMethod};
 use crate::operator::Operator;
 use crate::rational_var::RationalVar;
 use crate::runtime::Runtime;
 use crate::string_var::StringVar;
 use crate::variable::{FnResult, Variable};
+use num::Signed;
 
 pub fn op_fn(o: Operator) -> NativeMethod<RationalVar> {
     match o {
@@ -29,6 +31,19 @@ pub fn get_operator(this: RationalVar, o: Operator) -> Variable {
     StdMethod::new_native(this, func).into()
 }
 
+pub fn str_fn(s: &str) -> Option<NativeMethod<RationalVar>> {
+    Option::Some(match s {
+        "abs" => abs,
+        "signum" => signum,
+        _ => return Option::None,
+    })
+}
+
+pub fn get_attribute(this: RationalVar, s: &str) -> Option<Variable> {
+    let func = str_fn(s)?;
+    Option::Some(StdMethod::new_native(this, func).into())
+}
+
 fn add(this: RationalVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
     let mut sum: RationalVar = args.into_iter().map(RationalVar::from).sum();
     sum += this;
@@ -100,6 +115,16 @@ fn to_str(this: RationalVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnRe
     runtime.return_1(StringVar::from(format!("{}", *this)).into())
 }
 
+fn abs(this: RationalVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("dec.abs", &args, 0, runtime)?;
+    runtime.return_1(RationalVar::from(this.abs()).into())
+}
+
+fn signum(this: RationalVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("dec.signum", &args, 0, runtime)?;
+    runtime.return_1(RationalVar::from(this.signum()).into())
+}
+
 fn to_int(this: RationalVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
     debug_assert!(args.is_empty());
     runtime.return_1(this.to_integer().into())
@@ -108,7 +133,7 @@ fn to_int(this: RationalVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnRe
 #[cfg(test)]
 mod test {
     use crate::builtin_functions::dec_fn::{
-        add, div, eq, floor_div, greater_than, less_than, mul, sub, u_minus,
+        abs, add, div, eq, floor_div, greater_than, less_than, mul, signum, sub, u_minus,
     };
     use crate::int_var::IntVar;
     use crate::rational_var::RationalVar;
@@ -224,4 +249,25 @@ mod test {
         let result = Runtime::test(|runtime| greater_than(a, vec![d.into()], runtime));
         assert_eq!(result, Result::Ok(false.into()));
     }
+
+    #[test]
+    fn abs_signum() {
+        let half = BigRational::new(BigInt::one(), BigInt::from(2));
+        let cases = [
+            (half.clone(), half.clone(), BigRational::one()),
+            (-half.clone(), half, -BigRational::one()),
+            (
+                BigRational::zero(),
+                BigRational::zero(),
+                BigRational::zero(),
+            ),
+        ];
+        for (value, magnitude, sign) in cases.iter().cloned() {
+            let value = RationalVar::from(value);
+            let result = Runtime::test(|runtime| abs(value.clone(), vec![], runtime));
+            assert_eq!(result, Result::Ok(RationalVar::from(magnitude).into()));
+            let result = Runtime::test(|runtime| signum(value, vec![], runtime));
+            assert_eq!(result, Result::Ok(RationalVar::from(sign).into()));
+        }
+    }
 }
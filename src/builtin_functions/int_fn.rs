@@ -1,5 +1,4 @@
 use crate::custom_types::exceptions::{arithmetic_error, value_error};
-use crate::first;
 use crate::int_var::IntVar;
 use crate::method::{NativeMethod, StdMethod};
 use crate::operator::Operator;
@@ -8,8 +7,9 @@ use crate::runtime::Runtime;
 use crate::string_var::StringVar;
 use crate::tuple::LangTuple;
 use crate::variable::{FnResult, Variable};
+use crate::{check_args, first, first_n};
 use num::traits::Pow;
-use num::{Integer, Signed, ToPrimitive, Zero};
+use num::{BigInt, Integer, One, Signed, ToPrimitive, Zero};
 use std::ops::Neg;
 use std::vec::Vec;
 
@@ -48,17 +48,23 @@ pub fn get_operator(this: IntVar, o: Operator) -> Variable {
     StdMethod::new_native(this, func).into()
 }
 
-pub fn str_fn(s: &str) -> NativeMethod<IntVar> {
-    match s {
+pub fn str_fn(s: &str) -> Option<NativeMethod<IntVar>> {
+    Option::Some(match s {
         "strBase" => str_base,
+        "toString" => to_string,
+        "rotateLeft" => rotate_left,
+        "rotateRight" => rotate_right,
+        "wrappingAdd" => wrapping_add,
         "divRem" => div_rem,
-        _ => unimplemented!("int.{} unimplemented", s),
-    }
+        "abs" => abs,
+        "signum" => signum,
+        _ => return Option::None,
+    })
 }
 
-pub fn get_attribute(this: IntVar, s: &str) -> Variable {
-    let func = str_fn(s);
-    StdMethod::new_native(this, func).into()
+pub fn get_attribute(this: IntVar, s: &str) -> Option<Variable> {
+    let func = str_fn(s)?;
+    Option::Some(StdMethod::new_native(this, func).into())
 }
 
 fn add(this: IntVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
@@ -305,16 +311,88 @@ fn hash(this: IntVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
 }
 
 fn str_base(this: IntVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
-    debug_assert_eq!(args.len(), 1);
+    str_radix("int.strBase", this, args, runtime)
+}
+
+fn to_string(this: IntVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    str_radix("int.toString", this, args, runtime)
+}
+
+/// Formats an int in the given radix, without any prefix (e.g. `0x`),
+/// accepting the same radixes as `str.intBase`.
+fn str_radix(name: &str, this: IntVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args(name, &args, 1, runtime)?;
     let value: IntVar = first(args).into();
     match value.to_u32().filter(|x| (2..=36).contains(x)) {
         Option::Some(s) => runtime.return_1(this.to_str_radix(s).into()),
         Option::None => runtime.throw_quick(
             value_error(),
-            format!(
-                "int.strBase requires a radix between 2 and 36, not {}",
-                value
-            ),
+            format!("{} requires a radix between 2 and 36, not {}", name, value),
+        ),
+    }
+}
+
+fn abs(this: IntVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("int.abs", &args, 0, runtime)?;
+    runtime.return_1(Signed::abs(&this).into())
+}
+
+fn signum(this: IntVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("int.signum", &args, 0, runtime)?;
+    runtime.return_1(this.signum().into())
+}
+
+fn rotate_left(this: IntVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("int.rotateLeft", &args, 2, runtime)?;
+    let [bits, width] = first_n(args);
+    let width = word_width("int.rotateLeft", width, runtime)?;
+    let bits = word_shift(bits, width);
+    runtime.return_1(rotate(this, bits, width).into())
+}
+
+fn rotate_right(this: IntVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("int.rotateRight", &args, 2, runtime)?;
+    let [bits, width] = first_n(args);
+    let width = word_width("int.rotateRight", width, runtime)?;
+    let bits = word_shift(bits, width);
+    runtime.return_1(rotate(this, width - bits, width).into())
+}
+
+fn wrapping_add(this: IntVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("int.wrappingAdd", &args, 2, runtime)?;
+    let [other, width] = first_n(args);
+    let width = word_width("int.wrappingAdd", width, runtime)?;
+    let sum = BigInt::from(this) + BigInt::from(IntVar::from(other));
+    runtime.return_1(IntVar::from(sum & word_mask(width)).into())
+}
+
+/// Rotates the low `width` bits of `value` left by `bits`, where
+/// `bits < width`.
+fn rotate(value: IntVar, bits: usize, width: usize) -> IntVar {
+    let mask = word_mask(width);
+    let word = BigInt::from(value) & &mask;
+    let rotated = (&word << bits) | (word >> (width - bits));
+    IntVar::from(rotated & mask)
+}
+
+/// Reduces a shift to the range `[0, width)`, so negative shifts rotate in
+/// the opposite direction.
+fn word_shift(bits: Variable, width: usize) -> usize {
+    let bits = IntVar::from(bits).mod_floor(&IntVar::from(width));
+    bits.to_usize().unwrap()
+}
+
+fn word_mask(width: usize) -> BigInt {
+    (BigInt::one() << width) - 1
+}
+
+fn word_width(name: &str, width: Variable, runtime: &mut Runtime) -> Result<usize, ()> {
+    let width = IntVar::from(width);
+    match width.to_usize().filter(|&x| x != 0) {
+        Option::Some(x) => Result::Ok(x),
+        Option::None => runtime.throw_quick_native(
+            value_error(),
+            format!("{} requires a positive word width, not {}", name, width),
         ),
     }
 }
@@ -332,11 +410,15 @@ fn div_rem(this: IntVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult
 #[cfg(test)]
 mod test {
     use crate::builtin_functions::int_fn::{
-        add, div, eq, floor_div, greater_than, left_bs, less_than, mul, right_bs, sub, u_minus,
+        abs, add, div, eq, floor_div, greater_than, left_bs, less_than, mul, right_bs, rotate_left,
+        rotate_right, signum, sub, to_string, u_minus, wrapping_add,
     };
+    use crate::custom_types::exceptions::value_error;
     use crate::int_var::IntVar;
     use crate::rational_var::RationalVar;
     use crate::runtime::Runtime;
+    use crate::string_var::StringVar;
+    use crate::variable::{FnResult, Variable};
     use num::{BigInt, BigRational, One, Zero};
 
     #[test]
@@ -446,4 +528,127 @@ mod test {
         let result = Runtime::test(|runtime| left_bs(a, vec![b.into()], runtime));
         assert_eq!(result, Result::Ok(IntVar::from(32).into()))
     }
+
+    fn radix_str(value: i64, radix: i64) -> Result<Variable, ()> {
+        let value = IntVar::from(BigInt::from(value));
+        Runtime::test(|runtime| to_string(value, vec![IntVar::from(radix).into()], runtime))
+    }
+
+    #[test]
+    fn to_string_radix() {
+        let cases: &[(i64, i64, &str)] = &[
+            (5, 2, "101"),
+            (-5, 2, "-101"),
+            (255, 16, "ff"),
+            (-255, 16, "-ff"),
+            (35, 36, "z"),
+            (-1295, 36, "-zz"),
+            (0, 36, "0"),
+            (-8, 8, "-10"),
+            (i64::MIN, 16, "-8000000000000000"),
+        ];
+        for &(value, radix, expected) in cases {
+            assert_eq!(
+                radix_str(value, radix),
+                Result::Ok(StringVar::from(expected).into()),
+                "{} in base {}",
+                value,
+                radix
+            );
+        }
+    }
+
+    #[test]
+    fn to_string_invalid_radix() {
+        for &radix in &[0, 1, 37, -2] {
+            let result = Runtime::test(|runtime| {
+                let args = vec![IntVar::from(radix).into()];
+                assert!(to_string(IntVar::one(), args, runtime).is_err());
+                let exc = runtime.pop_err()?;
+                runtime.return_1(exc.get_type().into())
+            });
+            assert_eq!(result, Result::Ok(value_error().into()), "{}", radix);
+        }
+    }
+
+    fn word_op(
+        func: fn(IntVar, Vec<Variable>, &mut Runtime) -> FnResult,
+        value: u64,
+        arg: i64,
+        width: u32,
+    ) -> Result<Variable, ()> {
+        let value = IntVar::from(BigInt::from(value));
+        let args = vec![IntVar::from(arg).into(), IntVar::from(width).into()];
+        Runtime::test(|runtime| func(value, args, runtime))
+    }
+
+    fn int(value: u64) -> Result<Variable, ()> {
+        Result::Ok(IntVar::from(BigInt::from(value)).into())
+    }
+
+    #[test]
+    fn rotate_8() {
+        assert_eq!(word_op(rotate_left, 0b1000_0001, 1, 8), int(0b0000_0011));
+        assert_eq!(word_op(rotate_right, 0b1000_0001, 1, 8), int(0b1100_0000));
+        assert_eq!(word_op(rotate_left, 0b1000_0001, 9, 8), int(0b0000_0011));
+        assert_eq!(word_op(rotate_left, 0x1ff, 4, 8), int(0xff));
+    }
+
+    #[test]
+    fn rotate_32() {
+        assert_eq!(word_op(rotate_left, 0x8000_0001, 4, 32), int(0x0000_0018));
+        assert_eq!(word_op(rotate_right, 0x8000_0001, 4, 32), int(0x1800_0000));
+        assert_eq!(word_op(rotate_right, 0x1234_5678, 32, 32), int(0x1234_5678));
+        assert_eq!(word_op(rotate_left, 0x1234_5678, -8, 32), int(0x7812_3456));
+    }
+
+    #[test]
+    fn rotate_64() {
+        let value = 0x8000_0000_0000_0001;
+        assert_eq!(word_op(rotate_left, value, 1, 64), int(3));
+        assert_eq!(
+            word_op(rotate_right, value, 1, 64),
+            int(0xc000_0000_0000_0000)
+        );
+    }
+
+    #[test]
+    fn wrapping_add_widths() {
+        assert_eq!(word_op(wrapping_add, 0xff, 1, 8), int(0));
+        assert_eq!(word_op(wrapping_add, 0xf0, 0x20, 8), int(0x10));
+        assert_eq!(word_op(wrapping_add, 0xffff_ffff, 2, 32), int(1));
+        assert_eq!(word_op(wrapping_add, u64::MAX, 1, 64), int(0));
+        assert_eq!(word_op(wrapping_add, 0, -1, 64), int(u64::MAX));
+        assert_eq!(word_op(wrapping_add, 1, 2, 64), int(3));
+    }
+
+    #[test]
+    fn zero_width() {
+        for &func in &[rotate_left, rotate_right, wrapping_add] {
+            let result = Runtime::test(|runtime| {
+                let args = vec![IntVar::one().into(), IntVar::zero().into()];
+                assert!(func(IntVar::one(), args, runtime).is_err());
+                let exc = runtime.pop_err()?;
+                runtime.return_1(exc.get_type().into())
+            });
+            assert_eq!(result, Result::Ok(value_error().into()));
+        }
+    }
+
+    #[test]
+    fn abs_signum() {
+        let big = IntVar::from(BigInt::from(isize::MAX) * 4);
+        let cases = [
+            (IntVar::from(5), IntVar::from(5), IntVar::one()),
+            (IntVar::from(-5), IntVar::from(5), -IntVar::one()),
+            (IntVar::zero(), IntVar::zero(), IntVar::zero()),
+            (-big.clone(), big, -IntVar::one()),
+        ];
+        for (value, magnitude, sign) in cases.iter().cloned() {
+            let result = Runtime::test(|runtime| abs(value.clone(), vec![], runtime));
+            assert_eq!(result, Result::Ok(magnitude.into()));
+            let result = Runtime::test(|runtime| signum(value, vec![], runtime));
+            assert_eq!(result, Result::Ok(sign.into()));
+        }
+    }
 }
@@ -1,10 +1,11 @@
-use crate::first;
+use crate::custom_types::exceptions::null_error;
 use crate::method::StdMethod;
 use crate::name::Name;
 use crate::operator::Operator;
 use crate::runtime::Runtime;
 use crate::string_var::StringVar;
 use crate::variable::{FnResult, OptionVar, Variable};
+use crate::{check_args, first};
 
 pub fn str(this: OptionVar, runtime: &mut Runtime) -> Result<StringVar, ()> {
     Result::Ok(if this.depth == 1 {
@@ -47,13 +48,18 @@ fn fold_some(i: usize, x: &str) -> StringVar {
     (prefix + x + &*suffix).into()
 }
 
-pub fn get_attr(this: OptionVar, attr: &str) -> Variable {
+pub fn get_attr(this: OptionVar, attr: &str) -> Option<Variable> {
     let func = match attr {
         "map" => map_fn,
         "flatMap" => flat_map,
-        _ => unimplemented!("Option.{}", attr),
+        "orElse" => or_else,
+        "unwrapOr" => unwrap_or,
+        "filter" => filter,
+        "expect" => expect,
+        "flatten" => flatten,
+        _ => return Option::None,
     };
-    StdMethod::new_native(this, func).into()
+    Option::Some(StdMethod::new_native(this, func).into())
 }
 
 pub fn get_op(this: OptionVar, op: Operator) -> Variable {
@@ -68,7 +74,7 @@ pub fn get_op(this: OptionVar, op: Operator) -> Variable {
 
 pub fn index(this: OptionVar, name: Name) -> Variable {
     match name {
-        Name::Attribute(a) => get_attr(this, a),
+        Name::Attribute(a) => get_attr(this, a).unwrap_or_else(|| unimplemented!("Option.{}", a)),
         Name::Operator(o) => get_op(this, o),
     }
 }
@@ -106,6 +112,61 @@ fn flat_map(this: OptionVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnRe
     }
 }
 
+fn or_else(this: OptionVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("Option.orElse", &args, 1, runtime)?;
+    if this.value.is_some() {
+        runtime.return_1(Variable::Option(this))
+    } else {
+        runtime.return_1(first(args))
+    }
+}
+
+fn unwrap_or(this: OptionVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("Option.unwrapOr", &args, 1, runtime)?;
+    match this.into() {
+        Option::Some(val) => runtime.return_1(val),
+        Option::None => runtime.return_1(first(args)),
+    }
+}
+
+fn filter(this: OptionVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("Option.filter", &args, 1, runtime)?;
+    let result = match Option::<Variable>::from(this) {
+        Option::Some(val) => {
+            first(args).call((vec![val.clone()], runtime))?;
+            if runtime.pop_return().into_bool(runtime)? {
+                Option::Some(val)
+            } else {
+                Option::None
+            }
+        }
+        Option::None => Option::None,
+    };
+    runtime.return_1(result.into())
+}
+
+fn expect(this: OptionVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("Option.expect", &args, 1, runtime)?;
+    match this.into() {
+        Option::Some(val) => runtime.return_1(val),
+        Option::None => {
+            let message = first(args).str(runtime)?;
+            runtime.throw_quick(null_error(), message)
+        }
+    }
+}
+
+fn flatten(this: OptionVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("Option.flatten", &args, 0, runtime)?;
+    // Removing one level of nesting is just decrementing the depth, as both
+    // Some(None) and None have no value
+    if this.depth > 1 {
+        runtime.return_1(OptionVar::new(this.depth - 1, this.value).into())
+    } else {
+        runtime.return_1(this.into())
+    }
+}
+
 fn to_str(this: OptionVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
     debug_assert!(args.is_empty());
     let val = str(this, runtime)?;
@@ -126,11 +187,43 @@ fn to_hash(this: OptionVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnRes
 
 #[cfg(test)]
 mod test {
-    use crate::builtin_functions::option_fn::{to_repr, to_str};
+    use crate::builtin_functions::option_fn::{
+        expect, filter, flatten, map_fn, or_else, to_repr, to_str, unwrap_or,
+    };
+    use crate::custom_types::exceptions::null_error;
+    use crate::first;
+    use crate::function::Function;
     use crate::int_var::IntVar;
+    use crate::name::Name;
     use crate::runtime::Runtime;
     use crate::string_var::StringVar;
-    use num::One;
+    use crate::variable::{FnResult, OptionVar, Variable};
+    use num::{One, Zero};
+
+    fn double(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        let value = IntVar::from(first(args));
+        runtime.return_1((value * IntVar::from(2)).into())
+    }
+
+    fn is_positive(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        let value = IntVar::from(first(args));
+        runtime.return_1((value > IntVar::zero()).into())
+    }
+
+    fn option(value: Variable) -> OptionVar {
+        match value {
+            Variable::Option(x) => x,
+            x => panic!("Expected an option, got {:?}", x),
+        }
+    }
+
+    fn some(value: isize) -> Variable {
+        Option::Some(IntVar::from(value).into()).into()
+    }
+
+    fn none() -> Variable {
+        Option::None.into()
+    }
 
     #[test]
     fn string() {
@@ -151,4 +244,73 @@ mod test {
         let result = Runtime::test(|runtime| to_repr(none.into(), vec![], runtime));
         assert_eq!(result, Result::Ok(StringVar::from("null").into()));
     }
+
+    #[test]
+    fn map_unwrap_or() {
+        for (value, expected) in [(some(3), 6), (none(), -1)] {
+            let result = Runtime::test(|runtime| {
+                let func = Function::Native(double).into();
+                map_fn(option(value), vec![func], runtime)?;
+                let mapped = option(runtime.pop_return());
+                unwrap_or(mapped, vec![IntVar::from(-1).into()], runtime)
+            });
+            assert_eq!(result, Result::Ok(IntVar::from(expected).into()));
+        }
+    }
+
+    #[test]
+    fn or_else_values() {
+        let result = Runtime::test(|runtime| or_else(option(some(1)), vec![some(2)], runtime));
+        assert_eq!(result, Result::Ok(some(1)));
+        let result = Runtime::test(|runtime| or_else(option(none()), vec![some(2)], runtime));
+        assert_eq!(result, Result::Ok(some(2)));
+    }
+
+    #[test]
+    fn filter_values() {
+        for (value, expected) in [(some(1), some(1)), (some(-1), none()), (none(), none())] {
+            let result = Runtime::test(|runtime| {
+                let func = Function::Native(is_positive).into();
+                filter(option(value), vec![func], runtime)
+            });
+            assert_eq!(result, Result::Ok(expected));
+        }
+    }
+
+    #[test]
+    fn expect_some() {
+        let message = StringVar::from("msg").into();
+        let result = Runtime::test(|runtime| expect(option(some(1)), vec![message], runtime));
+        assert_eq!(result, Result::Ok(IntVar::from(1).into()));
+    }
+
+    #[test]
+    fn expect_none() {
+        let message = StringVar::from("value was missing").into();
+        let result = Runtime::test(|runtime| {
+            assert!(expect(option(none()), vec![message], runtime).is_err());
+            let exc = runtime.pop_err()?;
+            assert_eq!(exc.get_type(), null_error());
+            exc.index(Name::Attribute("msg"), runtime)?
+                .call((vec![], runtime))
+        });
+        let message = StringVar::from(result.unwrap());
+        assert!(message.contains("\nvalue was missing\n"));
+    }
+
+    #[test]
+    fn flatten_nested() {
+        let nested = |x: Variable| Variable::from(Option::Some(x));
+        let cases = [
+            (nested(some(1)), some(1)),
+            (nested(nested(some(1))), nested(some(1))),
+            (nested(none()), none()),
+            (none(), none()),
+            (some(1), some(1)),
+        ];
+        for (value, expected) in cases {
+            let result = Runtime::test(|runtime| flatten(option(value), vec![], runtime));
+            assert_eq!(result, Result::Ok(expected));
+        }
+    }
 }
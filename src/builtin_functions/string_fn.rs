@@ -1,25 +1,32 @@
 use crate::builtin_functions::Encoding;
 use crate::custom_types::bytes::LangBytes;
+use crate::custom_types::dict::Dict;
 use crate::custom_types::exceptions::{arithmetic_error, index_error, value_error};
 use crate::custom_types::list::List;
 use crate::custom_types::range::Range;
+use crate::custom_var::downcast_var;
+use crate::fmt::{self, format_template};
 use crate::function::Function;
 use crate::int_var::IntVar;
 use crate::looping::{NativeIterator, TypicalIterator};
 use crate::method::{NativeMethod, StdMethod};
 use crate::operator::Operator;
+use crate::rational_var::RationalVar;
 use crate::runtime::Runtime;
 use crate::std_type::Type;
 use crate::string_var::{AsciiVar, MaybeAscii, StrVar, StringVar};
-use crate::variable::{FnResult, Variable};
-use crate::{first, first_n, looping};
-use ascii::{AsAsciiStr, AsciiChar, AsciiStr, AsciiString};
+use crate::variable::{FnResult, InnerVar, Variable};
+use crate::{check_arg_range, check_args, first, first_n, looping};
+use ascii::{AsAsciiStr, AsAsciiStrError, AsciiChar, AsciiStr, AsciiString};
 use num::{BigInt, Num, One, Signed, ToPrimitive};
 use std::cell::Cell;
 use std::convert::TryInto;
 use std::fmt::Debug;
 use std::rc::Rc;
 use std::str::{from_utf8_unchecked, FromStr};
+#[cfg(feature = "unicode")]
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
 
 pub fn op_fn(o: Operator) -> NativeMethod<StringVar> {
     match o {
@@ -32,6 +39,7 @@ pub fn op_fn(o: Operator) -> NativeMethod<StringVar> {
         Operator::GetAttr => index,
         Operator::GetSlice => slice,
         Operator::Iter => str_iter,
+        Operator::IterSlice => iter_slice,
         Operator::Reversed => reversed,
         Operator::In => contains,
         Operator::Hash => hash,
@@ -43,26 +51,43 @@ pub fn get_operator(this: StringVar, o: Operator) -> Variable {
     StdMethod::new_native(this, op_fn(o)).into()
 }
 
-pub fn get_attr(this: StringVar, s: &str) -> Variable {
+pub fn get_attr(this: StringVar, s: &str) -> Option<Variable> {
     let func = match s {
-        "length" => return this.char_len().into(),
+        "length" => return Option::Some(this.char_len().into()),
         "get" => get,
         "upper" => upper,
         "lower" => lower,
+        "repeat" => repeat,
+        #[cfg(feature = "unicode")]
+        "caseFold" => case_fold,
+        #[cfg(feature = "unicode")]
+        "equalsIgnoreCase" => equals_ignore_case,
         "isUpper" => is_upper,
         "isLower" => is_lower,
         "join" => join,
+        "joinRepr" => join_repr,
+        "format" => format,
+        "formatMap" => format_map,
         "joinAll" => join_all,
         "startsWith" => starts_with,
         "endsWith" => ends_with,
         "split" => split,
+        "rsplit" => rsplit,
         "splitlines" => split_lines,
+        "splitLinesKeepEnds" => split_lines_keep_ends,
         "indexOf" => index_of,
         "lastIndexOf" => last_index_of,
-        "chars" => return chars(&this),
+        "chars" => return Option::Some(chars(&this)),
+        "charsIter" => chars_iter,
+        "graphemes" => graphemes,
         "encode" => encode,
+        "toAsciiBytes" => to_ascii_bytes,
+        #[cfg(feature = "unicode")]
+        "normalize" => normalize,
         "intBase" => int_base,
         "asInt" => as_int,
+        "parseInt" => parse_int,
+        "asDecimal" => as_decimal,
         "strip" => strip,
         "stripFront" => strip_front,
         "stripBack" => strip_back,
@@ -70,17 +95,17 @@ pub fn get_attr(this: StringVar, s: &str) -> Variable {
         "isAscii" => is_ascii,
         "isDigit" => is_digit,
         "isNumeric" => is_numeric,
-        x => unimplemented!("str.{}", x),
+        _ => return Option::None,
     };
-    StdMethod::new_native(this, func).into()
+    Option::Some(StdMethod::new_native(this, func).into())
 }
 
-pub fn static_attr(s: &str) -> Variable {
+pub fn static_attr(s: &str) -> Option<Variable> {
     let func = match s {
         "fromChars" => from_chars,
-        x => unimplemented!("str.{}", x),
+        _ => return Option::None,
     };
-    Function::Native(func).into()
+    Option::Some(Function::Native(func).into())
 }
 
 fn add(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
@@ -135,6 +160,50 @@ fn mul_str(mut result: String, args: Vec<Variable>, runtime: &mut Runtime) -> Fn
     runtime.return_1(StringVar::from(result).into())
 }
 
+fn repeat(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_arg_range("str.repeat", &args, 1..=2, runtime)?;
+    let mut args = args.into_iter();
+    let big_count = IntVar::from(args.next().unwrap());
+    let sep = args.next().map(StringVar::from).unwrap_or_default();
+    if big_count.is_negative() {
+        return runtime.throw_quick(
+            value_error(),
+            format!(
+                "str.repeat requires a non-negative count, not {}",
+                big_count
+            ),
+        );
+    }
+    let count = match big_count.to_usize() {
+        Option::Some(count) => count,
+        Option::None => return runtime.throw_quick(arithmetic_error(), mul_exc(big_count)),
+    };
+    if count == 0 {
+        return runtime.return_1(StringVar::default().into());
+    }
+    let total_len = count
+        .checked_mul(this.len())
+        .and_then(|x| x.checked_add((count - 1).checked_mul(sep.len())?));
+    if total_len.is_none() {
+        return runtime.throw_quick(
+            arithmetic_error(),
+            overflow_exc(count, this.len() + sep.len()),
+        );
+    }
+    let total_len = total_len.unwrap();
+    // Repeating nothing would otherwise loop `count` times to build an empty string
+    if total_len == 0 {
+        return runtime.return_1(StringVar::default().into());
+    }
+    let mut result = String::with_capacity(total_len);
+    result.push_str(&this);
+    for _ in 1..count {
+        result.push_str(&sep);
+        result.push_str(&this);
+    }
+    runtime.return_1(StringVar::from(result).into())
+}
+
 fn mul_exc(big_val: IntVar) -> String {
     format!(
         "Too many string repetitions: max number of shifts \
@@ -160,9 +229,9 @@ fn bool(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult
 
 fn int(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
     debug_assert!(args.is_empty());
-    match IntVar::from_str(&*this) {
-        Ok(val) => runtime.return_1(val.into()),
-        Err(_) => runtime.throw_quick(
+    match parse_int_str(&this, 10) {
+        Option::Some(val) => runtime.return_1(val.into()),
+        Option::None => runtime.throw_quick(
             value_error(),
             format!(
                 "Invalid input for int(str): {} is not a valid base-10 integer",
@@ -357,6 +426,12 @@ fn str_iter(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnRe
     })
 }
 
+fn iter_slice(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    debug_assert_eq!(args.len(), 1);
+    let range = Range::from_slice(this.char_len(), runtime, first(args))?;
+    runtime.return_1(Rc::new(StringSliceIter::new(this, &range)).into())
+}
+
 fn reversed(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
     debug_assert!(args.is_empty());
     runtime.return_1(match this.split_ascii() {
@@ -381,7 +456,7 @@ fn hash(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult
 }
 
 fn get(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
-    debug_assert_eq!(args.len(), 1);
+    check_args("str.get", &args, 1, runtime)?;
     let big_index = IntVar::from(first(args));
     let char = match this.as_maybe_ascii() {
         MaybeAscii::Standard(s) => get_non_ascii(s, &big_index),
@@ -391,17 +466,47 @@ fn get(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult
 }
 
 fn upper(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
-    debug_assert!(args.is_empty());
+    check_args("str.upper", &args, 0, runtime)?;
     runtime.return_1(this.to_uppercase().into())
 }
 
 fn lower(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
-    debug_assert!(args.is_empty());
+    check_args("str.lower", &args, 0, runtime)?;
     runtime.return_1(this.to_lowercase().into())
 }
 
+#[cfg(feature = "unicode")]
+fn case_fold(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("str.caseFold", &args, 0, runtime)?;
+    runtime.return_1(fold_case(&this).into())
+}
+
+#[cfg(feature = "unicode")]
+fn equals_ignore_case(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("str.equalsIgnoreCase", &args, 1, runtime)?;
+    let other = StringVar::from(first(args));
+    let result = match (this.as_ascii_str(), other.as_ascii_str()) {
+        (Result::Ok(a), Result::Ok(b)) => a.eq_ignore_ascii_case(b),
+        _ => fold_case(&this) == fold_case(&other),
+    };
+    runtime.return_1(result.into())
+}
+
+/// Applies Unicode full case folding to the given string.
+///
+/// This is the locale-independent default folding, so locale-specific rules
+/// (such as the Turkish dotted and dotless i) are not applied.
+#[cfg(feature = "unicode")]
+fn fold_case(value: &str) -> StringVar {
+    if value.is_ascii() {
+        value.to_ascii_lowercase().into()
+    } else {
+        caseless::default_case_fold_str(value).into()
+    }
+}
+
 fn is_upper(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
-    debug_assert!(args.is_empty());
+    check_args("str.isUpper", &args, 0, runtime)?;
     runtime.return_1(is_uppercase(this.as_maybe_ascii()).into())
 }
 
@@ -434,7 +539,7 @@ fn is_uppercase(value: MaybeAscii<'_>) -> bool {
 }
 
 fn is_lower(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
-    debug_assert!(args.is_empty());
+    check_args("str.isLower", &args, 0, runtime)?;
     runtime.return_1(is_lowercase(this.as_maybe_ascii()).into())
 }
 
@@ -467,18 +572,63 @@ fn is_lowercase(value: MaybeAscii<'_>) -> bool {
 }
 
 fn join(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
-    debug_assert!(args.len() == 1);
-    let iter = first(args).iter(runtime)?;
+    check_args("str.join", &args, 1, runtime)?;
+    let result = join_values(&this, first(args), Variable::str, runtime)?;
+    runtime.return_1(result.into())
+}
+
+fn join_repr(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("str.joinRepr", &args, 1, runtime)?;
+    let result = join_values(&this, first(args), Variable::repr, runtime)?;
+    runtime.return_1(result.into())
+}
+
+/// Joins the values of `iterable` with `sep`, converting each to a string
+/// with `to_str`.
+fn join_values(
+    sep: &str,
+    iterable: Variable,
+    to_str: fn(Variable, &mut Runtime) -> Result<StringVar, ()>,
+    runtime: &mut Runtime,
+) -> Result<StringVar, ()> {
+    let iter = iterable.iter(runtime)?;
+    let mut result = String::new();
     if let Option::Some(val) = iter.next(runtime)?.take_first() {
-        let mut result = val.str(runtime)?.as_owned();
+        result += &to_str(val, runtime)?;
         while let Option::Some(val) = iter.next(runtime)?.take_first() {
-            result += &this;
-            result += &val.str(runtime)?;
+            result += sep;
+            result += &to_str(val, runtime)?;
         }
-        runtime.return_1(StringVar::from(result).into())
-    } else {
-        runtime.return_1(StringVar::default().into())
     }
+    Result::Ok(result.into())
+}
+
+/// `str.format`: if the last argument is a dict, its values may also be
+/// referred to by name (it is still usable as a positional argument).
+fn format(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    let names = args
+        .last()
+        .and_then(|x| downcast_var::<Dict>(x.clone()).ok());
+    let result = format_template(&this, &args, names.as_deref(), runtime)?;
+    runtime.return_1(result.into())
+}
+
+/// `str.formatMap`: like `str.format`, but every field is looked up by name in
+/// the given dict.
+fn format_map(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("str.formatMap", &args, 1, runtime)?;
+    let names = match downcast_var::<Dict>(first(args)) {
+        Result::Ok(names) => names,
+        Result::Err(x) => {
+            let type_name = x.get_type().str();
+            return runtime.throw_quick(
+                value_error(),
+                format!("str.formatMap requires a dict, not {}", type_name),
+            );
+        }
+    };
+    let result = fmt::format_map(&this, &names, runtime)?;
+    runtime.return_1(result.into())
 }
 
 fn join_all(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
@@ -496,12 +646,19 @@ fn join_all(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnRe
 }
 
 fn starts_with(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
-    debug_assert_eq!(args.len(), 2);
+    check_args("str.startsWith", &args, 2, runtime)?;
     let [a, b] = first_n(args);
-    let val = StringVar::from(a);
+    let vals = affixes(a, runtime)?;
     let index = IntVar::from(b);
     let len = this.char_len();
-    match index.to_usize().and_then(|x| starts(this, val.as_str(), x)) {
+    let result = match index.to_usize() {
+        Option::Some(x) => vals
+            .iter()
+            .map(|val| starts(this.clone(), val.as_str(), x))
+            .try_fold(false, |acc, x| x.map(|x| acc || x)),
+        Option::None => Option::None,
+    };
+    match result {
         Option::Some(starts_with) => runtime.return_1(starts_with.into()),
         Option::None => runtime.throw_quick(
             index_error(),
@@ -532,13 +689,27 @@ fn starts(this: StringVar, val: &str, index: usize) -> Option<bool> {
 }
 
 fn ends_with(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
-    debug_assert_eq!(args.len(), 1);
-    let val = StringVar::from(first(args));
-    runtime.return_1(this.ends_with(&*val).into())
+    check_args("str.endsWith", &args, 1, runtime)?;
+    let vals = affixes(first(args), runtime)?;
+    runtime.return_1(vals.iter().any(|val| this.ends_with(val.as_str())).into())
+}
+
+/// The candidate affixes for `startsWith` and `endsWith`.
+///
+/// A single string is its own only candidate; tuples and other iterables
+/// match if any of their elements do.
+fn affixes(val: Variable, runtime: &mut Runtime) -> Result<Vec<StringVar>, ()> {
+    match val {
+        Variable::Normal(InnerVar::String(s)) => Result::Ok(vec![s]),
+        Variable::Normal(InnerVar::Tuple(t)) => {
+            Result::Ok(t.iter().cloned().map(StringVar::from).collect())
+        }
+        x => looping::collect(x, runtime),
+    }
 }
 
 fn split(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
-    debug_assert_eq!(args.len(), 2);
+    check_args("str.split", &args, 2, runtime)?;
     let [a, opt_count] = first_n(args);
     let pat = StringVar::from(a);
     if opt_count.is_null() {
@@ -567,16 +738,60 @@ fn split(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResul
     }
 }
 
+fn rsplit(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("str.rsplit", &args, 2, runtime)?;
+    let [a, opt_count] = first_n(args);
+    let pat = StringVar::from(a);
+    // Only the last `count - 1` separators split, so the leading part stays joined
+    let count = if opt_count.is_null() {
+        usize::MAX
+    } else {
+        let count = IntVar::from(opt_count);
+        if count.is_negative() {
+            return runtime.throw_quick(
+                value_error(),
+                format!("str.rsplit requires a non-negative count, not {}", count),
+            );
+        }
+        // Counts too large for a usize are never reached
+        count.to_usize().unwrap_or(usize::MAX)
+    };
+    let mut values: Vec<Variable> = this
+        .rsplitn(count, &*pat)
+        .map(|a| StringVar::from(a.to_owned()))
+        .map(Variable::from)
+        .collect();
+    // rsplitn yields the pieces back-to-front, but the list should stay in string order
+    values.reverse();
+    runtime.return_1(List::from_values(Type::String, values).into())
+}
+
 fn split_lines(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
-    debug_assert!(args.is_empty());
+    check_args("str.splitlines", &args, 0, runtime)?;
     let result = List::from_values(Type::String, this.owned_lines().map(From::from).collect());
     runtime.return_1(result.into())
 }
 
+fn split_lines_keep_ends(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("str.splitLinesKeepEnds", &args, 0, runtime)?;
+    let lines = this.owned_lines_keep_ends().map(From::from).collect();
+    runtime.return_1(List::from_values(Type::String, lines).into())
+}
+
 fn chars(this: &str) -> Variable {
     List::from_values(Type::Char, this.chars().map(Variable::from).collect()).into()
 }
 
+fn chars_iter(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("str.charsIter", &args, 0, runtime)?;
+    str_iter(this, args, runtime)
+}
+
+fn graphemes(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("str.graphemes", &args, 0, runtime)?;
+    runtime.return_1(Rc::new(GraphemeIter::new(this)).into())
+}
+
 fn from_chars(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
     debug_assert_eq!(args.len(), 1);
     let result = looping::collect::<String, char>(first(args), runtime)?;
@@ -584,7 +799,7 @@ fn from_chars(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
 }
 
 fn index_of(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
-    debug_assert_eq!(args.len(), 1);
+    check_args("str.indexOf", &args, 1, runtime)?;
     let chr: char = first(args).into();
     let index = match this.as_maybe_ascii() {
         MaybeAscii::Standard(s) => s.chars().position(|c| c == chr),
@@ -594,7 +809,7 @@ fn index_of(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnRe
 }
 
 fn last_index_of(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
-    debug_assert_eq!(args.len(), 1);
+    check_args("str.lastIndexOf", &args, 1, runtime)?;
     let chr: char = first(args).into();
     let index = match this.as_maybe_ascii() {
         MaybeAscii::Standard(s) => {
@@ -613,7 +828,7 @@ fn last_index_of(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) ->
 }
 
 fn encode(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
-    debug_assert_eq!(args.len(), 1);
+    check_args("str.encode", &args, 1, runtime)?;
     let encoding = match Encoding::from_str(&first(args).str(runtime)?) {
         Result::Ok(x) => x,
         Result::Err(x) => {
@@ -623,28 +838,14 @@ fn encode(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResu
     let byte_val = match encoding {
         Encoding::Ascii => match this.as_ascii_str() {
             Result::Ok(s) => s.as_bytes().to_vec(),
-            Result::Err(err) => {
-                return runtime.throw_quick(
-                    value_error(),
-                    format!(
-                        "Cannot convert to ascii: byte at position {} (value {}) is not in the range [0:128]", 
-                        err.valid_up_to(), this.as_str().as_bytes()[err.valid_up_to()]
-                    )
-                )
-            }
-        }
+            Result::Err(err) => return ascii_error(&this, err, runtime),
+        },
         Encoding::Utf8 => this.as_bytes().to_vec(),
-        Encoding::Utf16Le => this
-            .encode_utf16()
-            .flat_map(|x| x.to_le_bytes())
-            .collect(),
-        Encoding::Utf16Be => this
-            .encode_utf16()
-            .flat_map(|x| x.to_be_bytes())
-            .collect(),
+        Encoding::Utf16Le => this.encode_utf16().flat_map(|x| x.to_le_bytes()).collect(),
+        Encoding::Utf16Be => this.encode_utf16().flat_map(|x| x.to_be_bytes()).collect(),
         Encoding::Utf32Le => this
             .chars()
-            .flat_map(|x|(x as u32).to_le_bytes())
+            .flat_map(|x| (x as u32).to_le_bytes())
             .collect(),
         Encoding::Utf32Be => this
             .chars()
@@ -654,29 +855,76 @@ fn encode(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResu
     runtime.return_1(Rc::new(LangBytes::new(byte_val.to_vec())).into())
 }
 
+fn to_ascii_bytes(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("str.toAsciiBytes", &args, 0, runtime)?;
+    // Ascii strings are stored as their ascii bytes, so this is just a copy
+    let byte_val = match this.as_ascii_str() {
+        Result::Ok(s) => s.as_bytes().to_vec(),
+        Result::Err(err) => return ascii_error(&this, err, runtime),
+    };
+    runtime.return_1(Rc::new(LangBytes::new(byte_val)).into())
+}
+
+fn ascii_error(this: &StringVar, err: AsAsciiStrError, runtime: &mut Runtime) -> FnResult {
+    runtime.throw_quick(
+        value_error(),
+        format!(
+            "Cannot convert to ascii: byte at position {} (value {}) is not in the range [0:128]",
+            err.valid_up_to(),
+            this.as_bytes()[err.valid_up_to()]
+        ),
+    )
+}
+
 fn int_base(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
-    debug_assert_eq!(args.len(), 1);
+    parse_radix("str.intBase", this, args, runtime)
+}
+
+fn parse_int(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    parse_radix("str.parseInt", this, args, runtime)
+}
+
+fn parse_radix(
+    name: &str,
+    this: StringVar,
+    args: Vec<Variable>,
+    runtime: &mut Runtime,
+) -> FnResult {
+    check_args(name, &args, 1, runtime)?;
     let base: IntVar = first(args).into();
-    match base.to_u32().filter(|x| (2..=32).contains(x)) {
-        Option::Some(x) => runtime.return_1(
-            IntVar::from_str_radix(&this, x)
-                .ok()
-                .map(Variable::from)
-                .into(),
-        ),
+    match base.to_u32().filter(|x| (2..=36).contains(x)) {
+        Option::Some(x) => runtime.return_1(parse_int_str(&this, x).map(Variable::from).into()),
         Option::None => runtime.throw_quick(
             value_error(),
-            format!(
-                "str.intBase requires a radix between 2 and 36, not {}",
-                base
-            ),
+            format!("{} requires a radix between 2 and 36, not {}", name, base),
         ),
     }
 }
 
 fn as_int(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
-    debug_assert!(args.is_empty());
-    runtime.return_1(IntVar::from_str(&*this).ok().map(Variable::from).into())
+    check_args("str.asInt", &args, 0, runtime)?;
+    runtime.return_1(parse_int_str(&this, 10).map(Variable::from).into())
+}
+
+/// Parses an integer, allowing a leading sign and single underscores between
+/// digits (e.g. `-1_000`).
+pub(crate) fn parse_int_str(s: &str, radix: u32) -> Option<IntVar> {
+    let digits = s.strip_prefix(&['+', '-'][..]).unwrap_or(s);
+    if digits.starts_with('_') || digits.ends_with('_') || digits.contains("__") {
+        return Option::None;
+    }
+    let sign = &s[..s.len() - digits.len()];
+    let normalized = format!("{}{}", sign, digits.replace('_', ""));
+    // Checked separately, since from_str_radix also allows a sign here
+    if normalized[sign.len()..].starts_with(&['+', '-'][..]) {
+        return Option::None;
+    }
+    IntVar::from_str_radix(&normalized, radix).ok()
+}
+
+fn as_decimal(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("str.asDecimal", &args, 0, runtime)?;
+    runtime.return_1(RationalVar::from_str(&this).ok().map(Variable::from).into())
 }
 
 fn strip(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
@@ -724,12 +972,36 @@ fn expand_tab(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> Fn
 }
 
 fn is_ascii(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
-    debug_assert!(args.is_empty());
+    check_args("str.isAscii", &args, 0, runtime)?;
     runtime.return_1(this.is_ascii().into())
 }
 
+#[cfg(feature = "unicode")]
+fn normalize(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("str.normalize", &args, 1, runtime)?;
+    let form = first(args).str(runtime)?;
+    if !matches!(&*form, "NFC" | "NFD" | "NFKC" | "NFKD") {
+        return runtime.throw_quick(
+            value_error(),
+            format!("{} is not a valid normalization form", form),
+        );
+    }
+    // ASCII text is unchanged by every normalization form
+    if this.as_ascii_str().is_ok() {
+        return runtime.return_1(this.into());
+    }
+    let result: String = match &*form {
+        "NFC" => this.nfc().collect(),
+        "NFD" => this.nfd().collect(),
+        "NFKC" => this.nfkc().collect(),
+        "NFKD" => this.nfkd().collect(),
+        _ => unreachable!(),
+    };
+    runtime.return_1(StringVar::from(result).into())
+}
+
 fn is_numeric(this: StringVar, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
-    debug_assert!(args.is_empty());
+    check_args("str.isNumeric", &args, 0, runtime)?;
     runtime.return_1(is_num(this.as_maybe_ascii()).into())
 }
 
@@ -802,6 +1074,31 @@ pub struct AsciiRevIter {
     val: AsciiVar,
 }
 
+/// An iterator over the chars of a slice of a string, which does not create
+/// the intermediate sliced string.
+#[derive(Debug)]
+pub struct StringSliceIter {
+    val: StringVar,
+    /// The byte index of the next char to be yielded.
+    byte_index: Cell<usize>,
+    remaining: Cell<usize>,
+    step: usize,
+    reverse: bool,
+    /// Whether byte indices and char indices coincide.
+    ascii: bool,
+}
+
+/// An iterator over the extended grapheme clusters of a string.
+///
+/// Unlike [`StringIter`], which yields each Unicode scalar value as a `char`,
+/// this yields each user-perceived character as a `str`, so combining marks and
+/// emoji ZWJ sequences stay together with their base character.
+#[derive(Debug)]
+pub struct GraphemeIter {
+    index: Cell<usize>,
+    val: StringVar,
+}
+
 impl StringIter {
     fn new(val: StrVar) -> StringIter {
         StringIter {
@@ -874,6 +1171,95 @@ impl TypicalIterator for StringRevIter {
     }
 }
 
+impl StringSliceIter {
+    fn new(val: StringVar, range: &Range) -> StringSliceIter {
+        let mut indices = range.slice_indices(val.char_len());
+        let ascii = val.is_ascii();
+        let (byte_index, remaining) = match indices.next() {
+            Option::Some(first) => {
+                let byte_index = if ascii {
+                    first
+                } else {
+                    val.char_indices().nth(first).unwrap().0
+                };
+                (byte_index, indices.count() + 1)
+            }
+            Option::None => (0, 0),
+        };
+        StringSliceIter {
+            val,
+            byte_index: Cell::new(byte_index),
+            remaining: Cell::new(remaining),
+            step: range.get_step().abs().to_usize().unwrap_or(usize::MAX),
+            reverse: range.get_step().is_negative(),
+            ascii,
+        }
+    }
+
+    /// The byte index of the char `self.step` chars away from the one at
+    /// `index`, in the direction of iteration.
+    fn next_index(&self, index: usize) -> usize {
+        if self.ascii {
+            if self.reverse {
+                index - self.step
+            } else {
+                index + self.step
+            }
+        } else if self.reverse {
+            let mut indices = self.val[..index].char_indices();
+            indices.nth_back(self.step - 1).unwrap().0
+        } else {
+            let mut indices = self.val[index..].char_indices();
+            index + indices.nth(self.step).unwrap().0
+        }
+    }
+}
+
+impl TypicalIterator for StringSliceIter {
+    fn inner_next(&self) -> Option<Variable> {
+        let remaining = self.remaining.get();
+        if remaining == 0 {
+            return Option::None;
+        }
+        let index = self.byte_index.get();
+        let result = self.val[index..].chars().next().unwrap();
+        self.remaining.set(remaining - 1);
+        if remaining > 1 {
+            self.byte_index.set(self.next_index(index));
+        }
+        Option::Some(result.into())
+    }
+
+    fn get_type() -> Type {
+        unimplemented!()
+    }
+}
+
+impl GraphemeIter {
+    fn new(val: StringVar) -> GraphemeIter {
+        GraphemeIter {
+            val,
+            index: Cell::new(0),
+        }
+    }
+}
+
+impl TypicalIterator for GraphemeIter {
+    fn inner_next(&self) -> Option<Variable> {
+        // self.index is always at the end of a previously-yielded grapheme, which is also the
+        // start of the next one
+        let rest = &self.val[self.index.get()..];
+        rest.graphemes(true).next().map(|g| {
+            self.index.set(self.index.get() + g.len());
+            StringVar::from(g.to_owned()).into()
+        })
+    }
+
+    fn get_type() -> Type {
+        unimplemented!()
+    }
+}
+
 impl AsciiIter {
     fn new(val: AsciiVar) -> AsciiIter {
         AsciiIter {
@@ -924,3 +1310,497 @@ impl TypicalIterator for AsciiRevIter {
         unimplemented!()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::builtin_functions::string_fn::{
+        as_decimal, as_int, chars_iter, ends_with, graphemes, iter_slice, join, join_repr,
+        parse_int, repeat, rsplit, split, starts_with, to_ascii_bytes,
+    };
+    #[cfg(feature = "unicode")]
+    use crate::builtin_functions::string_fn::{case_fold, equals_ignore_case, normalize};
+    use crate::custom_types::bytes::LangBytes;
+    use crate::custom_types::exceptions::{arithmetic_error, value_error};
+    use crate::custom_types::list::List;
+    use crate::custom_types::slice::Slice;
+    use crate::custom_var::downcast_var;
+    use crate::int_var::IntVar;
+    use crate::method::NativeMethod;
+    use crate::rational_var::RationalVar;
+    use crate::runtime::Runtime;
+    use crate::std_type::Type;
+    use crate::string_var::StringVar;
+    use crate::tuple::LangTuple;
+    use crate::variable::Variable;
+    use ascii::AsciiString;
+    use num::BigRational;
+    use std::rc::Rc;
+    use std::str::FromStr;
+
+    fn split_values(
+        func: NativeMethod<StringVar>,
+        this: &str,
+        pat: &str,
+        count: Variable,
+    ) -> Vec<Variable> {
+        let this = StringVar::from(this.to_owned());
+        let pat = StringVar::from(pat.to_owned());
+        let result = Runtime::test(|runtime| func(this, vec![pat.into(), count], runtime));
+        let list = downcast_var::<List>(result.unwrap()).unwrap();
+        let values = list.values().to_vec();
+        values
+    }
+
+    fn strings(values: &[&str]) -> Vec<Variable> {
+        values
+            .iter()
+            .map(|&x| StringVar::from(x.to_owned()).into())
+            .collect()
+    }
+
+    #[test]
+    fn rsplit_no_count() {
+        let left = split_values(split, "a.b.c", ".", Variable::null());
+        let right = split_values(rsplit, "a.b.c", ".", Variable::null());
+        assert_eq!(left, strings(&["a", "b", "c"]));
+        assert_eq!(left, right);
+    }
+
+    #[test]
+    fn rsplit_count() {
+        let left = split_values(split, "a.b.c", ".", IntVar::from(2).into());
+        let right = split_values(rsplit, "a.b.c", ".", IntVar::from(2).into());
+        assert_eq!(left, strings(&["a", "b"]));
+        assert_eq!(right, strings(&["a.b", "c"]));
+    }
+
+    #[test]
+    fn rsplit_mirrors_split() {
+        // rsplit keeps the same trailing pieces as an unlimited split, with
+        // everything before them left joined as the first piece
+        let pieces = ["a", "b", "", "c"];
+        let full = split_values(split, "a.b..c", ".", Variable::null());
+        assert_eq!(full, strings(&pieces));
+        for count in 1..6 {
+            let right = split_values(rsplit, "a.b..c", ".", IntVar::from(count).into());
+            let kept = count.min(pieces.len());
+            let head = pieces[..=pieces.len() - kept].join(".");
+            assert_eq!(right.len(), kept);
+            assert_eq!(right[0], strings(&[&head])[0]);
+            assert_eq!(right[1..], full[pieces.len() + 1 - kept..]);
+        }
+        assert!(split_values(rsplit, "a.b..c", ".", IntVar::from(0).into()).is_empty());
+    }
+
+    #[test]
+    fn rsplit_negative_count() {
+        let this = StringVar::from("a.b.c".to_owned());
+        let result = Runtime::test(|runtime| {
+            let args = vec![StringVar::from(".").into(), IntVar::from(-1).into()];
+            assert!(rsplit(this, args, runtime).is_err());
+            let err = runtime.pop_err()?;
+            runtime.return_1(err.get_type().into())
+        });
+        assert_eq!(result, Result::Ok(value_error().into()));
+    }
+
+    #[test]
+    fn rsplit_large_count() {
+        let left = split_values(split, "a.b.c", ".", IntVar::from(5).into());
+        let right = split_values(rsplit, "a.b.c", ".", IntVar::from(5).into());
+        assert_eq!(left, right);
+    }
+
+    fn candidates(values: &[&str]) -> Variable {
+        LangTuple::from_vec(strings(values)).into()
+    }
+
+    #[test]
+    fn starts_with_tuple() {
+        let this = StringVar::from("foobar".to_owned());
+        let result = Runtime::test(|runtime| {
+            let args = vec![candidates(&["baz", "foo"]), IntVar::from(0).into()];
+            starts_with(this, args, runtime)
+        });
+        assert_eq!(result, Result::Ok(true.into()));
+    }
+
+    #[test]
+    fn starts_with_tuple_none() {
+        let this = StringVar::from("foobar".to_owned());
+        let result = Runtime::test(|runtime| {
+            let args = vec![candidates(&["baz", "bar"]), IntVar::from(0).into()];
+            starts_with(this, args, runtime)
+        });
+        assert_eq!(result, Result::Ok(false.into()));
+    }
+
+    #[test]
+    fn starts_with_tuple_index() {
+        let this = StringVar::from("foobar".to_owned());
+        let result = Runtime::test(|runtime| {
+            let args = vec![candidates(&["foo", "bar"]), IntVar::from(3).into()];
+            starts_with(this, args, runtime)
+        });
+        assert_eq!(result, Result::Ok(true.into()));
+    }
+
+    #[test]
+    fn ends_with_tuple() {
+        let this = StringVar::from("foobar".to_owned());
+        let result =
+            Runtime::test(|runtime| ends_with(this, vec![candidates(&["foo", "bar"])], runtime));
+        assert_eq!(result, Result::Ok(true.into()));
+    }
+
+    #[test]
+    fn ends_with_tuple_none() {
+        let this = StringVar::from("foobar".to_owned());
+        let result =
+            Runtime::test(|runtime| ends_with(this, vec![candidates(&["foo", "baz"])], runtime));
+        assert_eq!(result, Result::Ok(false.into()));
+    }
+
+    fn arity_error(name: &str, args: Vec<Variable>) -> Result<Variable, ()> {
+        let this = StringVar::from("a.b".to_owned());
+        Runtime::test(|runtime| {
+            assert!(runtime.call_attr(this.into(), name, args).is_err());
+            let exc = runtime.pop_err()?;
+            runtime.return_1(exc.get_type().into())
+        })
+    }
+
+    #[test]
+    fn too_few_args() {
+        let result = arity_error("split", vec![StringVar::from(".").into()]);
+        assert_eq!(result, Result::Ok(value_error().into()));
+    }
+
+    #[test]
+    fn too_many_args() {
+        let result = arity_error("upper", vec![IntVar::from(1).into()]);
+        assert_eq!(result, Result::Ok(value_error().into()));
+    }
+
+    fn parse(func: NativeMethod<StringVar>, this: &str, args: Vec<Variable>) -> Variable {
+        let this = StringVar::from(this.to_owned());
+        Runtime::test(|runtime| func(this, args, runtime)).unwrap()
+    }
+
+    fn none() -> Variable {
+        Option::None.into()
+    }
+
+    fn decimal(numerator: i64, denominator: i64) -> Variable {
+        let value = BigRational::new(numerator.into(), denominator.into());
+        Option::Some(RationalVar::from(value).into()).into()
+    }
+
+    #[test]
+    fn as_decimal_valid() {
+        assert_eq!(parse(as_decimal, "3/4", vec![]), decimal(3, 4));
+        assert_eq!(parse(as_decimal, "0.25", vec![]), decimal(1, 4));
+        assert_eq!(parse(as_decimal, "-1.5", vec![]), decimal(-3, 2));
+        assert_eq!(parse(as_decimal, ".5", vec![]), decimal(1, 2));
+        assert_eq!(parse(as_decimal, "12", vec![]), decimal(12, 1));
+    }
+
+    #[test]
+    fn as_decimal_invalid() {
+        for &text in &["", ".", "1.2.3", "1/0", "abc", "1e5", "--1", "+-1"] {
+            assert_eq!(parse(as_decimal, text, vec![]), none(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn parse_int_radix() {
+        let result = parse(parse_int, "ff", vec![IntVar::from(16).into()]);
+        assert_eq!(result, Option::Some(IntVar::from(255).into()).into());
+        let result = parse(parse_int, "12", vec![IntVar::from(2).into()]);
+        assert_eq!(result, none());
+    }
+
+    #[test]
+    fn as_int_underscores() {
+        let thousand = Option::Some(IntVar::from(1000).into()).into();
+        assert_eq!(parse(as_int, "1_000", vec![]), thousand);
+        let negative = Option::Some(IntVar::from(-1000).into()).into();
+        assert_eq!(parse(as_int, "-1_0_0_0", vec![]), negative);
+        for &text in &["_1", "1_", "1__0", "+_1", "_", "+", "++1", "+-1"] {
+            assert_eq!(parse(as_int, text, vec![]), none(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn as_int_sign() {
+        let result = parse(as_int, "+42", vec![]);
+        assert_eq!(result, Option::Some(IntVar::from(42).into()).into());
+        let big = "+100_000_000_000_000_000_000";
+        let expected = IntVar::from_str("100000000000000000000").unwrap();
+        assert_eq!(
+            parse(as_int, big, vec![]),
+            Option::Some(expected.into()).into()
+        );
+    }
+
+    #[test]
+    fn parse_int_underscores() {
+        let result = parse(parse_int, "ff_ff", vec![IntVar::from(16).into()]);
+        assert_eq!(result, Option::Some(IntVar::from(0xffff).into()).into());
+    }
+
+    fn list_of(values: Vec<Variable>) -> Variable {
+        List::from_values(Type::Object, values).into()
+    }
+
+    #[test]
+    fn join_ints() {
+        let values = (1..4).map(|x| IntVar::from(x).into()).collect();
+        let result = parse(join, ", ", vec![list_of(values)]);
+        assert_eq!(result, StringVar::from("1, 2, 3").into());
+        let mixed = vec![
+            IntVar::from(1).into(),
+            'a'.into(),
+            StringVar::from("b").into(),
+        ];
+        let result = parse(join, "", vec![list_of(mixed)]);
+        assert_eq!(result, StringVar::from("1ab").into());
+        let result = parse(join, ", ", vec![list_of(vec![])]);
+        assert_eq!(result, StringVar::from("").into());
+    }
+
+    #[test]
+    fn join_repr_strings() {
+        let values = vec![StringVar::from("a").into(), StringVar::from("b c").into()];
+        let result = parse(join_repr, ", ", vec![list_of(values)]);
+        assert_eq!(result, StringVar::from("\"a\", \"b c\"").into());
+        let result = parse(join_repr, ", ", vec![list_of(vec![])]);
+        assert_eq!(result, StringVar::from("").into());
+    }
+
+    #[test]
+    fn chars_iter_long() -> Result<(), ()> {
+        let text = "aé".repeat(10_000);
+        let this = StringVar::from(text.clone());
+        let found = Runtime::test(|runtime| {
+            let result = chars_iter(this, Vec::new(), runtime);
+            let result = result.map(|_| runtime.pop_return())?;
+            assert!(downcast_var::<List>(result.clone()).is_err());
+            let iter = result.iter(runtime)?;
+            let mut found = String::new();
+            while let Option::Some(c) = iter.next(runtime)?.take_first() {
+                found.push(c.into());
+            }
+            runtime.return_1(StringVar::from(found).into())
+        })?;
+        assert_eq!(found, StringVar::from(text).into());
+        Result::Ok(())
+    }
+
+    fn grapheme_values(text: &str) -> Result<Vec<Variable>, ()> {
+        let this = StringVar::from(text.to_owned());
+        Runtime::test(|runtime| {
+            graphemes(this, Vec::new(), runtime)?;
+            let iter = runtime.pop_return().iter(runtime)?;
+            let mut found = Vec::new();
+            while let Option::Some(g) = iter.next(runtime)?.take_first() {
+                found.push(g);
+            }
+            runtime.return_1(List::from_values(Type::String, found).into())
+        })
+        .map(|x| downcast_var::<List>(x).unwrap().values().to_vec())
+    }
+
+    #[test]
+    fn graphemes_combining() -> Result<(), ()> {
+        let text = "e\u{301}a\u{308}\u{304}o";
+        assert_eq!(text.chars().count(), 6);
+        let found = grapheme_values(text)?;
+        assert_eq!(found, strings(&["e\u{301}", "a\u{308}\u{304}", "o"]));
+        Result::Ok(())
+    }
+
+    #[test]
+    fn graphemes_zwj() -> Result<(), ()> {
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        let text = format!("a{}b", family);
+        let found = grapheme_values(&text)?;
+        assert_eq!(found, strings(&["a", family, "b"]));
+        Result::Ok(())
+    }
+
+    #[test]
+    fn graphemes_empty() -> Result<(), ()> {
+        assert!(grapheme_values("")?.is_empty());
+        Result::Ok(())
+    }
+
+    #[cfg(feature = "unicode")]
+    fn normalized(text: &str, form: &str) -> Result<Variable, ()> {
+        let this = StringVar::from(text.to_owned());
+        let form = StringVar::from(form.to_owned());
+        Runtime::test(|runtime| normalize(this, vec![form.into()], runtime))
+    }
+
+    #[test]
+    #[cfg(feature = "unicode")]
+    fn normalize_composed() -> Result<(), ()> {
+        let composed = "caf\u{e9}";
+        let decomposed = "cafe\u{301}";
+        assert_eq!(normalized(composed, "NFC")?, normalized(decomposed, "NFC")?);
+        assert_eq!(normalized(decomposed, "NFC")?, strings(&[composed])[0]);
+        assert_eq!(normalized(composed, "NFD")?, strings(&[decomposed])[0]);
+        Result::Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "unicode")]
+    fn normalize_compatibility() -> Result<(), ()> {
+        assert_eq!(normalized("\u{fb01}", "NFKC")?, strings(&["fi"])[0]);
+        assert_eq!(normalized("\u{fb01}", "NFC")?, strings(&["\u{fb01}"])[0]);
+        Result::Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "unicode")]
+    fn normalize_bad_form() {
+        let this = StringVar::from("abc".to_owned());
+        let form = StringVar::from("NFX".to_owned());
+        let result = Runtime::test(|runtime| {
+            assert!(normalize(this, vec![form.into()], runtime).is_err());
+            let err = runtime.pop_err()?;
+            runtime.return_1(err.get_type().into())
+        });
+        assert_eq!(result, Result::Ok(value_error().into()));
+    }
+
+    #[cfg(feature = "unicode")]
+    fn equal_ignoring_case(a: &str, b: &str) -> Result<Variable, ()> {
+        let this = StringVar::from(a.to_owned());
+        let other = StringVar::from(b.to_owned());
+        Runtime::test(|runtime| equals_ignore_case(this, vec![other.into()], runtime))
+    }
+
+    #[test]
+    #[cfg(feature = "unicode")]
+    fn equals_ignore_case_ascii() -> Result<(), ()> {
+        assert_eq!(equal_ignoring_case("Hello", "hELLO")?, true.into());
+        assert_eq!(equal_ignoring_case("Hello", "Help")?, false.into());
+        Result::Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "unicode")]
+    fn equals_ignore_case_folding() -> Result<(), ()> {
+        // Full case folding maps ß to "ss", which lowercasing does not
+        assert_eq!(equal_ignoring_case("STRASSE", "stra\u{df}e")?, true.into());
+        assert_eq!(equal_ignoring_case("\u{3a3}", "\u{3c2}")?, true.into());
+        Result::Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "unicode")]
+    fn case_fold_turkish_i() -> Result<(), ()> {
+        // Folding is locale-independent: dotted capital I folds to "i" followed by a combining
+        // dot, and dotless i is left as-is, so neither matches an ASCII i or I
+        let this = StringVar::from("\u{130}".to_owned());
+        let result = Runtime::test(|runtime| case_fold(this, Vec::new(), runtime))?;
+        assert_eq!(result, strings(&["i\u{307}"])[0]);
+        assert_eq!(equal_ignoring_case("\u{131}", "I")?, false.into());
+        Result::Ok(())
+    }
+
+    #[test]
+    fn to_ascii_bytes_ascii() -> Result<(), ()> {
+        let ascii = StringVar::from(AsciiString::from_ascii("abc").unwrap());
+        for this in [ascii, StringVar::from("abc")] {
+            let result = Runtime::test(|runtime| to_ascii_bytes(this, Vec::new(), runtime))?;
+            let bytes = downcast_var::<LangBytes>(result).unwrap();
+            assert_eq!(*bytes.get_value(), b"abc".to_vec());
+        }
+        Result::Ok(())
+    }
+
+    #[test]
+    fn to_ascii_bytes_not_ascii() {
+        let this = StringVar::from("ab\u{e9}".to_owned());
+        let result = Runtime::test(|runtime| {
+            assert!(to_ascii_bytes(this, Vec::new(), runtime).is_err());
+            let err = runtime.pop_err()?;
+            runtime.return_1(err.get_type().into())
+        });
+        assert_eq!(result, Result::Ok(value_error().into()));
+    }
+
+    fn repeated(this: &str, args: Vec<Variable>) -> Result<Variable, ()> {
+        let this = StringVar::from(this.to_owned());
+        Runtime::test(|runtime| repeat(this, args, runtime))
+    }
+
+    #[test]
+    fn repeat_plain() -> Result<(), ()> {
+        let count = |x: i32| vec![IntVar::from(x).into()];
+        assert_eq!(repeated("ab", count(3))?, strings(&["ababab"])[0]);
+        assert_eq!(repeated("ab", count(0))?, strings(&[""])[0]);
+        Result::Ok(())
+    }
+
+    #[test]
+    fn repeat_separator() -> Result<(), ()> {
+        let args = |x: i32| vec![IntVar::from(x).into(), strings(&[", "])[0].clone()];
+        assert_eq!(repeated("ab", args(3))?, strings(&["ab, ab, ab"])[0]);
+        assert_eq!(repeated("ab", args(1))?, strings(&["ab"])[0]);
+        Result::Ok(())
+    }
+
+    #[test]
+    fn repeat_empty() -> Result<(), ()> {
+        let args = vec![IntVar::from(usize::MAX).into(), strings(&[""])[0].clone()];
+        assert_eq!(repeated("", args)?, strings(&[""])[0]);
+        Result::Ok(())
+    }
+
+    #[test]
+    fn repeat_overflow() {
+        let this = StringVar::from("ab".to_owned());
+        let result = Runtime::test(|runtime| {
+            let args = vec![IntVar::from(usize::MAX).into()];
+            assert!(repeat(this, args, runtime).is_err());
+            let err = runtime.pop_err()?;
+            runtime.return_1(err.get_type().into())
+        });
+        assert_eq!(result, Result::Ok(arithmetic_error().into()));
+    }
+
+    fn slice_chars(this: StringVar, step: i32) -> Result<String, ()> {
+        let slice = Slice::new(Option::None, Option::None, Option::Some(step.into()));
+        let found = Runtime::test(|runtime| {
+            iter_slice(this, vec![Rc::new(slice).into()], runtime)?;
+            let iter = runtime.pop_return().iter(runtime)?;
+            let mut found = String::new();
+            while let Option::Some(c) = iter.next(runtime)?.take_first() {
+                found.push(c.into());
+            }
+            runtime.return_1(StringVar::from(found).into())
+        })?;
+        Result::Ok(StringVar::from(found).to_string())
+    }
+
+    #[test]
+    fn iter_slice_ascii() -> Result<(), ()> {
+        let this = StringVar::from(AsciiString::from_ascii("abcdefg").unwrap());
+        assert_eq!(slice_chars(this.clone(), 2)?, "aceg");
+        assert_eq!(slice_chars(this.clone(), -1)?, "gfedcba");
+        assert_eq!(slice_chars(this, -2)?, "geca");
+        Result::Ok(())
+    }
+
+    #[test]
+    fn iter_slice_multibyte() -> Result<(), ()> {
+        let this = StringVar::from("a\u{e9}b\u{1F600}c\u{4e2d}".to_owned());
+        assert_eq!(slice_chars(this.clone(), 2)?, "abc");
+        assert_eq!(slice_chars(this.clone(), -1)?, "\u{4e2d}c\u{1F600}b\u{e9}a");
+        assert_eq!(slice_chars(this, -2)?, "\u{4e2d}\u{1F600}\u{e9}");
+        Result::Ok(())
+    }
+}
@@ -19,13 +19,15 @@ pub fn get_operator(this: LangTuple, o: Operator) -> Variable {
     StdMethod::new_native(this, op_fn(o)).into()
 }
 
-pub fn get_attr(this: LangTuple, s: &str) -> Variable {
+pub fn get_attr(this: LangTuple, s: &str) -> Option<Variable> {
     if s == "length" {
-        return this.len().into();
+        return Option::Some(this.len().into());
     }
-    match s.parse() {
-        Result::Ok(x) => this[x].clone(),
-        Result::Err(_) => unimplemented!("tuple.{}", s),
+    let index: usize = s.parse().ok()?;
+    if index < this.len() {
+        Option::Some(this[index].clone())
+    } else {
+        Option::None
     }
 }
 
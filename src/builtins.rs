@@ -1,39 +1,117 @@
+use crate::builtin_functions::string_fn::parse_int_str;
+use crate::character;
+use crate::copy;
+use crate::custom_types::adaptors::{Chain, DropWhile, Filter, Flatten, GroupBy, Map, TakeWhile};
 use crate::custom_types::array::Array;
 use crate::custom_types::bytes::LangBytes;
+use crate::custom_types::default_dict::DefaultDict;
 use crate::custom_types::dict::Dict;
 use crate::custom_types::enumerate::Enumerate;
 use crate::custom_types::exceptions::{
     arithmetic_error, assertion_error, io_error, not_implemented, null_error, value_error,
 };
 use crate::custom_types::file::FileObj;
+use crate::custom_types::identity_map::IdentityMap;
 use crate::custom_types::interfaces::{Callable, Iterable, Iterator, Throwable};
+use crate::custom_types::lambda::Lambda;
 use crate::custom_types::list::List;
+use crate::custom_types::lru_cache::LruCache;
+use crate::custom_types::memoized::Memoized;
+use crate::custom_types::partial::Partial;
 use crate::custom_types::range::Range;
+use crate::custom_types::repeat::Repeat;
 use crate::custom_types::set::Set;
 use crate::custom_types::slice::Slice;
-use crate::first;
-use crate::fmt::format_internal;
+use crate::custom_types::zip::{Zip, ZipLongest};
+use crate::custom_var::downcast_var;
+use crate::fmt::{format_internal, format_text};
 use crate::function::Function;
+use crate::int_var::IntVar;
 use crate::name::Name;
 use crate::operator::Operator;
+use crate::quick_functions::{self, quick_add, quick_less_than};
+use crate::rational_var::RationalVar;
 use crate::runtime::Runtime;
+use crate::sort::merge_sort;
 use crate::std_type::Type;
 use crate::std_variable::{StdVarMethod, StdVariable};
 use crate::string_var::StringVar;
 use crate::test_fn::test_internal;
-use crate::variable::{FnResult, Variable};
+use crate::tuple::LangTuple;
+use crate::variable::{FnResult, InnerVar, Variable};
+use crate::{check_arg_range, check_args, first, first_n, looping, math, random, time};
+use num::{BigRational, Signed, ToPrimitive};
+use std::str::FromStr;
 
 fn print() -> Variable {
     Function::Native(print_impl).into()
 }
 
 fn print_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
-    for arg in args {
-        println!("{}", arg.str(runtime)?);
-    }
+    let stdout = runtime.stdout();
+    print_values(args, " ", "\n", &stdout, runtime)?;
+    runtime.return_0()
+}
+
+fn print_with() -> Variable {
+    Function::Native(print_with_impl).into()
+}
+
+/// `print` with its options given explicitly, as
+/// `printWith(values, sep, end, file)`.
+///
+/// Each option may be null, in which case it takes the same default as in
+/// `print`: a space, a newline, and `sys.stdout` respectively.
+fn print_with_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("printWith", &args, 4, runtime)?;
+    let [values, sep, end, file] = first_n(args);
+    let values = match values {
+        Variable::Normal(InnerVar::Tuple(t)) => t.iter().cloned().collect(),
+        x => looping::collect(x, runtime)?,
+    };
+    let sep = optional(sep).map_or_else(|| " ".into(), StringVar::from);
+    let end = optional(end).map_or_else(|| "\n".into(), StringVar::from);
+    let file = match optional(file) {
+        Option::Some(x) => match downcast_var::<FileObj>(x) {
+            Result::Ok(file) => file,
+            Result::Err(x) => {
+                return runtime.throw_quick(
+                    value_error(),
+                    format!("printWith requires a file, not {}", x.get_type().str()),
+                )
+            }
+        },
+        Option::None => runtime.stdout(),
+    };
+    print_values(values, &sep, &end, &file, runtime)?;
     runtime.return_0()
 }
 
+fn optional(value: Variable) -> Option<Variable> {
+    match value {
+        Variable::Option(x) => x.into(),
+        x => Option::Some(x),
+    }
+}
+
+fn print_values(
+    values: Vec<Variable>,
+    sep: &str,
+    end: &str,
+    file: &FileObj,
+    runtime: &mut Runtime,
+) -> Result<(), ()> {
+    let mut result = String::new();
+    for (i, value) in values.into_iter().enumerate() {
+        if i != 0 {
+            result += sep;
+        }
+        result += &value.str(runtime)?;
+    }
+    result += end;
+    file.write_str(&result, runtime)
+}
+
 fn input() -> Variable {
     Function::Native(input_impl).into()
 }
@@ -49,13 +127,138 @@ fn input_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
     runtime.return_0()
 }
 
+fn read_line() -> Variable {
+    Function::Native(read_line_impl).into()
+}
+
+/// Reads a line from `sys.stdin` without its line ending, first writing
+/// `prompt` to `sys.stdout` if given, and returning null at the end of input.
+fn read_line_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_arg_range("readLine", &args, 0..=1, runtime)?;
+    if let Option::Some(prompt) = args.into_iter().next().and_then(optional) {
+        let prompt = prompt.str(runtime)?;
+        runtime.stdout().write_str(&prompt, runtime)?;
+    }
+    let line = runtime.stdin().read_line(runtime)?;
+    runtime.return_1(line.map(Variable::from).into())
+}
+
+fn assert() -> Variable {
+    Function::Native(assert_impl).into()
+}
+
+fn assert_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_arg_range("assert", &args, 1..=2, runtime)?;
+    let mut args = args.into_iter();
+    if args.next().unwrap().into_bool(runtime)? {
+        return runtime.return_0();
+    }
+    let message = match args.next().and_then(optional) {
+        Option::Some(msg) => msg.str(runtime)?,
+        Option::None => "Assertion failed".into(),
+    };
+    runtime.throw_quick(assertion_error(), message)
+}
+
+fn int() -> Variable {
+    Function::Native(int_impl).into()
+}
+
+fn int_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_arg_range("int", &args, 1..=2, runtime)?;
+    let mut args = args.into_iter();
+    let value = args.next().unwrap();
+    let radix = match args.next().and_then(optional) {
+        Option::Some(x) => {
+            let radix = IntVar::from(x);
+            match radix.to_u32().filter(|x| (2..=36).contains(x)) {
+                Option::Some(x) => Option::Some(x),
+                Option::None => {
+                    return runtime.throw_quick(
+                        value_error(),
+                        format!("int() requires a radix between 2 and 36, not {}", radix),
+                    )
+                }
+            }
+        }
+        Option::None => Option::None,
+    };
+    match value {
+        Variable::Normal(InnerVar::String(s)) => {
+            let radix = radix.unwrap_or(10);
+            match parse_int_str(&s, radix) {
+                Option::Some(x) => runtime.return_1(x.into()),
+                Option::None => runtime.throw_quick(
+                    value_error(),
+                    format!(
+                        "Invalid input for int(): {} is not a valid base-{} integer",
+                        s.repr(),
+                        radix
+                    ),
+                ),
+            }
+        }
+        x if radix.is_some() => runtime.throw_quick(
+            value_error(),
+            format!(
+                "int() only takes a radix when converting a str, not {}",
+                x.get_type().str()
+            ),
+        ),
+        x @ Variable::Normal(
+            InnerVar::Bool(_)
+            | InnerVar::Bigint(_)
+            | InnerVar::Decimal(_)
+            | InnerVar::Char(_)
+            | InnerVar::Standard(_),
+        ) => {
+            let result = x.int(runtime)?;
+            runtime.return_1(result.into())
+        }
+        x => runtime.throw_quick(
+            value_error(),
+            format!("Cannot convert {} to int", x.get_type().str()),
+        ),
+    }
+}
+
+fn dec() -> Variable {
+    Function::Native(dec_impl).into()
+}
+
+fn dec_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("dec", &args, 1, runtime)?;
+    match first(args) {
+        x @ Variable::Normal(InnerVar::Decimal(_)) => runtime.return_1(x),
+        Variable::Normal(InnerVar::String(s)) => match RationalVar::from_str(&s) {
+            Result::Ok(x) => runtime.return_1(x.into()),
+            Result::Err(_) => runtime.throw_quick(
+                value_error(),
+                format!(
+                    "Invalid input for dec(): {} is not a valid decimal",
+                    s.repr()
+                ),
+            ),
+        },
+        x @ Variable::Normal(InnerVar::Bool(_) | InnerVar::Bigint(_) | InnerVar::Char(_)) => {
+            let value = BigRational::from_integer(x.int(runtime)?.into());
+            runtime.return_1(RationalVar::from(value).into())
+        }
+        x => runtime.throw_quick(
+            value_error(),
+            format!("Cannot convert {} to dec", x.get_type().str()),
+        ),
+    }
+}
+
 fn repr() -> Variable {
     Function::Native(repr_impl).into()
 }
 
 fn repr_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
-    debug_assert_eq!(args.len(), 1);
-    runtime.call_op(first(args), Operator::Repr, Vec::new())
+    check_args("repr", &args, 1, runtime)?;
+    let result = first(args).repr(runtime)?;
+    runtime.return_1(result.into())
 }
 
 fn iter() -> Variable {
@@ -71,9 +274,52 @@ fn reversed() -> Variable {
     Function::Native(reversed_impl).into()
 }
 
+/// `reversed(x)`: uses `operator reversed` where the value defines it, and
+/// otherwise collects the values into a list and iterates over that
+/// backwards.
 fn reversed_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
     debug_assert_eq!(args.len(), 1);
-    runtime.call_op(first(args), Operator::Reversed, Vec::new())
+    let value = first(args);
+    if has_reversed(&value, runtime) {
+        runtime.call_op(value, Operator::Reversed, Vec::new())
+    } else if is_iterable(&value, runtime) {
+        let values = looping::collect(value, runtime)?;
+        let list = List::from_values(Type::Object, values);
+        runtime.call_op(list.into(), Operator::Reversed, Vec::new())
+    } else {
+        runtime.throw_quick(
+            value_error(),
+            format!(
+                "Cannot reverse value of type '{}': it is not iterable",
+                value.get_type().str()
+            ),
+        )
+    }
+}
+
+fn has_reversed(value: &Variable, runtime: &Runtime) -> bool {
+    match value {
+        Variable::Normal(InnerVar::String(_)) => true,
+        Variable::Normal(InnerVar::Standard(var)) => var.has_operator(Operator::Reversed, runtime),
+        Variable::Normal(InnerVar::Custom(c)) => {
+            let cls = c.get_type();
+            cls == List::list_type() || cls == Range::range_type() || cls == LangBytes::bytes_type()
+        }
+        _ => false,
+    }
+}
+
+/// Whether a value can be iterated over.
+///
+/// Custom values can't be checked, so they are all assumed to be iterable.
+fn is_iterable(value: &Variable, runtime: &Runtime) -> bool {
+    match value {
+        Variable::Normal(InnerVar::String(_))
+        | Variable::Normal(InnerVar::Custom(_))
+        | Variable::Normal(InnerVar::Union(_)) => true,
+        Variable::Normal(InnerVar::Standard(var)) => var.has_operator(Operator::Iter, runtime),
+        _ => false,
+    }
 }
 
 fn id() -> Variable {
@@ -115,6 +361,677 @@ fn option_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
     runtime.return_1(opt.into())
 }
 
+fn repeat() -> Variable {
+    Function::Native(repeat_impl).into()
+}
+
+fn repeat_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    debug_assert!(args.len() == 1 || args.len() == 2);
+    if args.len() == 1 {
+        runtime.return_1(Repeat::new(first(args), Option::None).into())
+    } else {
+        let [value, count] = first_n(args);
+        if count.is_null() {
+            runtime.return_1(Repeat::new(value, Option::None).into())
+        } else {
+            runtime.return_1(Repeat::from_count(value, &IntVar::from(count)).into())
+        }
+    }
+}
+
+fn zip() -> Variable {
+    Function::Native(zip_impl).into()
+}
+
+fn zip_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    let iterators = args
+        .into_iter()
+        .map(|x| x.iter(runtime))
+        .collect::<Result<_, _>>()?;
+    runtime.return_1(Zip::new(iterators).into())
+}
+
+fn zip_longest() -> Variable {
+    Function::Native(zip_longest_impl).into()
+}
+
+fn zip_longest_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_arg_range("zipLongest", &args, 1.., runtime)?;
+    let mut args = args.into_iter();
+    let fill = args.next().unwrap();
+    let iterators = args.map(|x| x.iter(runtime)).collect::<Result<_, _>>()?;
+    runtime.return_1(ZipLongest::new(fill, iterators).into())
+}
+
+fn map() -> Variable {
+    Function::Native(map_impl).into()
+}
+
+fn map_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    debug_assert_eq!(args.len(), 2);
+    let [func, iterable] = first_n(args);
+    let iterable = iterable.iter(runtime)?;
+    runtime.return_1(Map::new(func, iterable).into())
+}
+
+fn chain() -> Variable {
+    Function::Native(chain_impl).into()
+}
+
+fn chain_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    let iterables = args
+        .into_iter()
+        .map(|x| x.iter(runtime))
+        .collect::<Result<_, _>>()?;
+    runtime.return_1(Chain::new(iterables).into())
+}
+
+fn flatten() -> Variable {
+    Function::Native(flatten_impl).into()
+}
+
+fn flatten_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("flatten", &args, 1, runtime)?;
+    let iterable = first(args).iter(runtime)?;
+    runtime.return_1(Flatten::new(iterable).into())
+}
+
+fn group_by() -> Variable {
+    Function::Native(group_by_impl).into()
+}
+
+fn group_by_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("groupBy", &args, 2, runtime)?;
+    let [iterable, key_fn] = first_n(args);
+    let iterable = iterable.iter(runtime)?;
+    runtime.return_1(GroupBy::new(key_fn, iterable).into())
+}
+
+fn div_mod() -> Variable {
+    Function::Native(div_mod_impl).into()
+}
+
+fn div_mod_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("divMod", &args, 2, runtime)?;
+    let [this, other] = first_n(args);
+    let (div, rem) = quick_functions::div_mod(this, other, runtime)?;
+    runtime.return_1(LangTuple::from_vec(vec![div, rem]).into())
+}
+
+fn deep_copy() -> Variable {
+    Function::Native(deep_copy_impl).into()
+}
+
+fn deep_copy_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("deepCopy", &args, 1, runtime)?;
+    let result = copy::deep_copy(first(args), runtime)?;
+    runtime.return_1(result)
+}
+
+fn filter() -> Variable {
+    Function::Native(filter_impl).into()
+}
+
+fn filter_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    debug_assert_eq!(args.len(), 2);
+    let [predicate, iterable] = first_n(args);
+    let iterable = iterable.iter(runtime)?;
+    runtime.return_1(Filter::new(predicate, iterable).into())
+}
+
+fn take_while() -> Variable {
+    Function::Native(take_while_impl).into()
+}
+
+fn take_while_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    debug_assert_eq!(args.len(), 2);
+    let [predicate, iterable] = first_n(args);
+    let iterable = iterable.iter(runtime)?;
+    runtime.return_1(TakeWhile::new(predicate, iterable).into())
+}
+
+fn drop_while() -> Variable {
+    Function::Native(drop_while_impl).into()
+}
+
+fn drop_while_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    debug_assert_eq!(args.len(), 2);
+    let [predicate, iterable] = first_n(args);
+    let iterable = iterable.iter(runtime)?;
+    runtime.return_1(DropWhile::new(predicate, iterable).into())
+}
+
+fn reduce() -> Variable {
+    Function::Native(reduce_impl).into()
+}
+
+fn reduce_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    debug_assert!(args.len() == 2 || args.len() == 3);
+    let mut args = args.into_iter();
+    let func = args.next().unwrap();
+    let iter = args.next().unwrap().iter(runtime)?;
+    let mut result = match args.next() {
+        Option::Some(initial) => initial,
+        Option::None => match iter.next(runtime)?.take_first() {
+            Option::Some(val) => val,
+            Option::None => {
+                return runtime.throw_quick(
+                    value_error(),
+                    "reduce() of empty iterable with no initial value",
+                )
+            }
+        },
+    };
+    while let Option::Some(val) = iter.next(runtime)?.take_first() {
+        func.clone().call((vec![result, val], runtime))?;
+        result = runtime.pop_return();
+    }
+    runtime.return_1(result)
+}
+
+fn less_than(a: &Variable, b: &Variable, runtime: &mut Runtime) -> Result<bool, ()> {
+    quick_less_than(a.clone(), b.clone(), runtime)?.into_bool(runtime)
+}
+
+fn sorted() -> Variable {
+    Function::Native(sorted_impl).into()
+}
+
+fn sorted_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    debug_assert!((1..=3).contains(&args.len()));
+    let mut args = args.into_iter();
+    let values: Vec<Variable> = looping::collect(args.next().unwrap(), runtime)?;
+    let key = args.next().filter(|x| !x.is_null());
+    let reverse = match args.next() {
+        Option::Some(x) => x.into_bool(runtime)?,
+        Option::None => false,
+    };
+    let pairs = match key {
+        Option::Some(key) => values
+            .into_iter()
+            .map(|x| {
+                key.clone().call((vec![x.clone()], runtime))?;
+                Result::Ok((runtime.pop_return(), x))
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+        Option::None => values.into_iter().map(|x| (x.clone(), x)).collect(),
+    };
+    // Reversing by swapping the comparison (instead of reversing the result)
+    // keeps the sort stable
+    let sorted = merge_sort(pairs, &mut |(a, _), (b, _)| {
+        if reverse {
+            less_than(b, a, runtime)
+        } else {
+            less_than(a, b, runtime)
+        }
+    })?;
+    let values = sorted.into_iter().map(|(_, x)| x).collect();
+    runtime.return_1(List::from_values(Type::Object, values).into())
+}
+
+fn min() -> Variable {
+    Function::Native(min_impl).into()
+}
+
+fn min_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    let result = extremum(args, false, runtime)?;
+    runtime.return_1(result)
+}
+
+fn max() -> Variable {
+    Function::Native(max_impl).into()
+}
+
+fn max_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    let result = extremum(args, true, runtime)?;
+    runtime.return_1(result)
+}
+
+fn clamp() -> Variable {
+    Function::Native(clamp_impl).into()
+}
+
+/// Bounds a value to the closed range `[lo, hi]`, using `<` so any ordered
+/// type may be clamped.
+fn clamp_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("clamp", &args, 3, runtime)?;
+    let [value, lo, hi] = first_n(args);
+    if less_than(&hi, &lo, runtime)? {
+        let message = format!(
+            "clamp() lower bound {} is greater than upper bound {}",
+            lo.repr(runtime)?,
+            hi.repr(runtime)?
+        );
+        return runtime.throw_quick(value_error(), message);
+    }
+    let result = if less_than(&value, &lo, runtime)? {
+        lo
+    } else if less_than(&hi, &value, runtime)? {
+        hi
+    } else {
+        value
+    };
+    runtime.return_1(result)
+}
+
+/// The shared implementation of `min` and `max`.
+///
+/// A single argument is an iterable to scan. Two arguments are an iterable
+/// and a key function if the second is callable, and are otherwise (like
+/// three or more arguments) the values to compare themselves.
+fn extremum(args: Vec<Variable>, is_max: bool, runtime: &mut Runtime) -> Result<Variable, ()> {
+    debug_assert!(!args.is_empty());
+    let (values, key) = match args.len() {
+        1 => (looping::collect(first(args), runtime)?, Option::None),
+        2 if is_callable(&args[1]) => {
+            let [iterable, key] = first_n(args);
+            (looping::collect(iterable, runtime)?, Option::Some(key))
+        }
+        _ => (args, Option::None),
+    };
+    let mut values = values.into_iter();
+    let mut best = match values.next() {
+        Option::Some(x) => x,
+        Option::None => {
+            let name = if is_max { "max" } else { "min" };
+            return runtime
+                .throw_quick_native(value_error(), format!("{}() of empty iterable", name));
+        }
+    };
+    let mut best_key = call_key(&key, best.clone(), runtime)?;
+    for value in values {
+        let value_key = call_key(&key, value.clone(), runtime)?;
+        // Only replace on strict inequality, so the first of equal values wins
+        let replace = if is_max {
+            less_than(&best_key, &value_key, runtime)?
+        } else {
+            less_than(&value_key, &best_key, runtime)?
+        };
+        if replace {
+            best = value;
+            best_key = value_key;
+        }
+    }
+    Result::Ok(best)
+}
+
+fn call_key(
+    key: &Option<Variable>,
+    value: Variable,
+    runtime: &mut Runtime,
+) -> Result<Variable, ()> {
+    match key {
+        Option::Some(key) => {
+            key.clone().call((vec![value], runtime))?;
+            Result::Ok(runtime.pop_return())
+        }
+        Option::None => Result::Ok(value),
+    }
+}
+
+pub(crate) fn is_callable(value: &Variable) -> bool {
+    match value {
+        Variable::Normal(InnerVar::Function(_)) | Variable::Normal(InnerVar::Method(_)) => true,
+        Variable::Normal(InnerVar::Custom(c)) => c.get_type() == Lambda::lambda_type(),
+        _ => false,
+    }
+}
+
+fn sum() -> Variable {
+    Function::Native(sum_impl).into()
+}
+
+fn sum_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_arg_range("sum", &args, 1..=2, runtime)?;
+    let mut args = args.into_iter();
+    let iter = args.next().unwrap().iter(runtime)?;
+    let mut result = args.next().unwrap_or_else(|| IntVar::from(0).into());
+    while let Option::Some(val) = iter.next(runtime)?.take_first() {
+        result = quick_add(result, val, runtime)?;
+    }
+    runtime.return_1(result)
+}
+
+fn count() -> Variable {
+    Function::Native(count_impl).into()
+}
+
+fn count_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("count", &args, 1, runtime)?;
+    let iter = first(args).iter(runtime)?;
+    let mut count: usize = 0;
+    while iter.next(runtime)?.take_first().is_some() {
+        count += 1;
+    }
+    runtime.return_1(IntVar::from(count).into())
+}
+
+fn last() -> Variable {
+    Function::Native(last_impl).into()
+}
+
+fn last_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("last", &args, 1, runtime)?;
+    let iter = first(args).iter(runtime)?;
+    let mut result = Option::None;
+    while let Option::Some(val) = iter.next(runtime)?.take_first() {
+        result = Option::Some(val);
+    }
+    runtime.return_1(result.into())
+}
+
+fn nth() -> Variable {
+    Function::Native(nth_impl).into()
+}
+
+fn nth_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("nth", &args, 2, runtime)?;
+    let [iterable, n] = first_n(args);
+    let n = IntVar::from(n);
+    if n.is_negative() {
+        return runtime.throw_quick(
+            value_error(),
+            format!("nth() requires a non-negative index, not {}", n),
+        );
+    }
+    // An index too large for a usize can never be reached anyway
+    let mut remaining = n.to_usize().unwrap_or(usize::MAX);
+    let iter = iterable.iter(runtime)?;
+    while let Option::Some(val) = iter.next(runtime)?.take_first() {
+        if remaining == 0 {
+            return runtime.return_1(Option::Some(val).into());
+        }
+        remaining -= 1;
+    }
+    runtime.return_1(Option::None.into())
+}
+
+fn to_list() -> Variable {
+    Function::Native(to_list_impl).into()
+}
+
+fn to_list_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("toList", &args, 1, runtime)?;
+    let values: Vec<Variable> = looping::collect(first(args), runtime)?;
+    runtime.return_1(List::from_values(Type::Object, values).into())
+}
+
+fn to_set() -> Variable {
+    Function::Native(to_set_impl).into()
+}
+
+fn to_set_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("toSet", &args, 1, runtime)?;
+    let values: Vec<Variable> = looping::collect(first(args), runtime)?;
+    let set = Set::new(Type::Object, values, runtime)?;
+    runtime.return_1(set.into())
+}
+
+fn to_dict() -> Variable {
+    Function::Native(to_dict_impl).into()
+}
+
+fn to_dict_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("toDict", &args, 1, runtime)?;
+    let pairs: Vec<Variable> = looping::collect(first(args), runtime)?;
+    let mut keys = Vec::with_capacity(pairs.len());
+    let mut values = Vec::with_capacity(pairs.len());
+    for pair in pairs {
+        match pair {
+            Variable::Normal(InnerVar::Tuple(t)) if t.len() == 2 => {
+                keys.push(t[0].clone());
+                values.push(t[1].clone());
+            }
+            x => {
+                let repr = x.repr(runtime)?;
+                return runtime.throw_quick(
+                    value_error(),
+                    format!("toDict() requires an iterable of pairs, got {}", repr),
+                );
+            }
+        }
+    }
+    let dict = Dict::from_args(keys, values, runtime)?;
+    runtime.return_1(dict.into())
+}
+
+fn all() -> Variable {
+    Function::Native(all_impl).into()
+}
+
+fn all_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    let result = all_any(args, true, runtime)?;
+    runtime.return_1(result.into())
+}
+
+fn any() -> Variable {
+    Function::Native(any_impl).into()
+}
+
+fn any_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    let result = all_any(args, false, runtime)?;
+    runtime.return_1(result.into())
+}
+
+/// The shared implementation of `all` and `any`.
+///
+/// Iteration stops at the first value whose truthiness (after applying the
+/// predicate, if one is given) is not `is_all`.
+fn all_any(args: Vec<Variable>, is_all: bool, runtime: &mut Runtime) -> Result<bool, ()> {
+    debug_assert!(args.len() == 1 || args.len() == 2);
+    let mut args = args.into_iter();
+    let iter = args.next().unwrap().iter(runtime)?;
+    let predicate = args.next();
+    while let Option::Some(val) = iter.next(runtime)?.take_first() {
+        let val = call_key(&predicate, val, runtime)?;
+        if val.into_bool(runtime)? != is_all {
+            return Result::Ok(!is_all);
+        }
+    }
+    Result::Ok(is_all)
+}
+
+fn chr() -> Variable {
+    Function::Native(chr_impl).into()
+}
+
+fn chr_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    debug_assert_eq!(args.len(), 1);
+    let value = IntVar::from(first(args));
+    match character::from_int(&value) {
+        Option::Some(c) => runtime.return_1(c.into()),
+        Option::None => runtime.throw_quick(
+            value_error(),
+            format!("chr() arg {} is not a valid code point", value),
+        ),
+    }
+}
+
+fn ord() -> Variable {
+    Function::Native(ord_impl).into()
+}
+
+fn ord_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    debug_assert_eq!(args.len(), 1);
+    let value = match first(args) {
+        Variable::Normal(InnerVar::Char(c)) => c,
+        Variable::Normal(InnerVar::String(s)) => {
+            let mut chars = s.chars();
+            match (chars.next(), chars.next()) {
+                (Option::Some(c), Option::None) => c,
+                _ => {
+                    return runtime.throw_quick(
+                        value_error(),
+                        format!(
+                            "ord() expected a string of length 1, but got one of length {}",
+                            s.char_len()
+                        ),
+                    )
+                }
+            }
+        }
+        x => {
+            return runtime.throw_quick(
+                value_error(),
+                format!("ord() expected a char or str, not '{}'", x.get_type().str()),
+            )
+        }
+    };
+    runtime.return_1(IntVar::from(value as u32).into())
+}
+
+fn is_instance() -> Variable {
+    Function::Native(is_instance_impl).into()
+}
+
+fn is_instance_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    debug_assert_eq!(args.len(), 2);
+    let [value, types] = first_n(args);
+    let types = type_args(types, runtime)?;
+    let result = types.iter().any(|t| t.is_type_of(&value, runtime));
+    runtime.return_1(result.into())
+}
+
+fn is_subclass() -> Variable {
+    Function::Native(is_subclass_impl).into()
+}
+
+fn is_subclass_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    debug_assert_eq!(args.len(), 2);
+    let [cls, types] = first_n(args);
+    let cls = match cls.into_type() {
+        Result::Ok(cls) => cls,
+        Result::Err(var) => return not_a_type(var, runtime),
+    };
+    let types = type_args(types, runtime)?;
+    let result = types.iter().any(|t| cls.is_subclass(t, runtime));
+    runtime.return_1(result.into())
+}
+
+/// Converts the second argument of `isInstance`/`isSubclass` into the list of
+/// types to check against, which is either a single type or a tuple of them.
+fn type_args(types: Variable, runtime: &mut Runtime) -> Result<Vec<Type>, ()> {
+    let values = match types {
+        Variable::Normal(InnerVar::Tuple(t)) => t.iter().cloned().collect(),
+        x => vec![x],
+    };
+    values
+        .into_iter()
+        .map(|x| match x.into_type() {
+            Result::Ok(t) => Result::Ok(t),
+            Result::Err(var) => not_a_type(var, runtime),
+        })
+        .collect()
+}
+
+fn make_exception() -> Variable {
+    Function::Native(make_exception_impl).into()
+}
+
+/// Creates an exception without throwing it.
+fn make_exception_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("makeException", &args, 2, runtime)?;
+    let [cls, msg] = first_n(args);
+    let cls = match cls.into_type() {
+        Result::Ok(cls) if cls.is_subclass(&Throwable::cls(), runtime) => cls,
+        Result::Ok(cls) => {
+            let message = format!("{} is not an exception type", cls.str());
+            return runtime.throw_quick(value_error(), message);
+        }
+        Result::Err(var) => {
+            let message = format!(
+                "Expected an exception type, got a value of type {}",
+                var.get_type().str()
+            );
+            return runtime.throw_quick(value_error(), message);
+        }
+    };
+    let msg = msg.str(runtime)?;
+    let result = cls.create_inst(vec![msg.into()], runtime)?;
+    runtime.return_1(result)
+}
+
+fn memoize() -> Variable {
+    Function::Native(memoize_impl).into()
+}
+
+/// Wraps a function so that its results are cached by its arguments.
+fn memoize_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("memoize", &args, 1, runtime)?;
+    runtime.return_1(Memoized::new(first(args)).into())
+}
+
+fn partial() -> Variable {
+    Function::Native(partial_impl).into()
+}
+
+/// Binds the leading arguments of a function.
+fn partial_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_arg_range("partial", &args, 1.., runtime)?;
+    let mut args = args.into_iter();
+    let function = args.next().unwrap();
+    runtime.return_1(Partial::new(function, args.collect()).into())
+}
+
+fn not_a_type<T>(var: Variable, runtime: &mut Runtime) -> Result<T, ()> {
+    let message = format!(
+        "Expected a type or tuple of types, got a value of type {}",
+        var.get_type().str()
+    );
+    runtime.throw_quick_native(value_error(), message)
+}
+
+fn get_attr() -> Variable {
+    Function::Native(get_attr_impl).into()
+}
+
+fn get_attr_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    debug_assert_eq!(args.len(), 2);
+    let [value, name] = first_n(args);
+    let name = StringVar::from(name);
+    let type_name = value.get_type().str();
+    match value.try_index_attr(&name, runtime)? {
+        Option::Some(result) => runtime.return_1(result),
+        Option::None => runtime.throw_quick(
+            value_error(),
+            format!("Value of type {} has no attribute {}", type_name, name),
+        ),
+    }
+}
+
+fn get_attr_or_null() -> Variable {
+    Function::Native(get_attr_or_null_impl).into()
+}
+
+fn get_attr_or_null_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    debug_assert_eq!(args.len(), 2);
+    let [value, name] = first_n(args);
+    let result = value.try_index_attr(&StringVar::from(name), runtime)?;
+    runtime.return_1(result.into())
+}
+
+fn has_attr() -> Variable {
+    Function::Native(has_attr_impl).into()
+}
+
+fn has_attr_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    debug_assert_eq!(args.len(), 2);
+    let [value, name] = first_n(args);
+    let result = value.try_index_attr(&StringVar::from(name), runtime)?;
+    runtime.return_1(result.is_some().into())
+}
+
+fn set_attr() -> Variable {
+    Function::Native(set_attr_impl).into()
+}
+
+fn set_attr_impl(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    debug_assert_eq!(args.len(), 3);
+    let [value, name, new_value] = first_n(args);
+    value.set(&StringVar::from(name), new_value, runtime)?;
+    runtime.return_0()
+}
+
 pub fn builtin_of(index: usize) -> Variable {
     match index {
         0 => print(),
@@ -153,6 +1070,55 @@ pub fn builtin_of(index: usize) -> Variable {
         33 => arithmetic_error().into(),
         34 => tst_internal(),
         35 => option(),
+        36 => repeat(),
+        37 => zip(),
+        38 => zip_longest(),
+        39 => map(),
+        40 => filter(),
+        41 => take_while(),
+        42 => drop_while(),
+        43 => reduce(),
+        44 => sorted(),
+        45 => min(),
+        46 => max(),
+        47 => sum(),
+        48 => is_instance(),
+        49 => is_subclass(),
+        50 => get_attr(),
+        51 => get_attr_or_null(),
+        52 => has_attr(),
+        53 => set_attr(),
+        54 => format(),
+        55 => all(),
+        56 => any(),
+        57 => chr(),
+        58 => ord(),
+        59 => math::module().into(),
+        60 => random::module().into(),
+        61 => time::module().into(),
+        62 => print_with(),
+        63 => read_line(),
+        64 => assert(),
+        65 => int(),
+        66 => dec(),
+        67 => count(),
+        68 => last(),
+        69 => nth(),
+        70 => to_list(),
+        71 => to_set(),
+        72 => to_dict(),
+        73 => chain(),
+        74 => flatten(),
+        75 => group_by(),
+        76 => deep_copy(),
+        77 => clamp(),
+        78 => div_mod(),
+        79 => make_exception(),
+        80 => IdentityMap::identity_map_type().into(),
+        81 => DefaultDict::default_dict_type().into(),
+        82 => LruCache::lru_cache_type().into(),
+        83 => memoize(),
+        84 => partial(),
         x => unimplemented!("Builtin number {}", x),
     }
 }
@@ -175,8 +1141,8 @@ pub fn default_methods(name: Name) -> Option<StdVarMethod> {
 
 fn default_repr(this: StdVariable, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
     debug_assert!(args.is_empty());
-    let result = format!("<{}: {:#X}>", this.get_type().str(), this.var_ptr());
-    runtime.return_1(StringVar::from(result).into())
+    let result = this.default_repr(runtime)?;
+    runtime.return_1(result.into())
 }
 
 fn default_str(this: StdVariable, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
@@ -214,6 +1180,1321 @@ fn fmt_internal() -> Variable {
     Function::Native(format_internal).into()
 }
 
+fn format() -> Variable {
+    Function::Native(format_text).into()
+}
+
 fn tst_internal() -> Variable {
     Function::Native(test_internal).into()
 }
+
+#[cfg(test)]
+mod test {
+    use crate::base_fn::BaseFunction;
+    use crate::builtins::{
+        all_impl, any_impl, assert_impl, chr_impl, clamp_impl, count_impl, dec_impl, div_mod_impl,
+        get_attr_impl, get_attr_or_null_impl, has_attr_impl, id_impl, int_impl, is_instance_impl,
+        is_subclass_impl, last_impl, make_exception_impl, max_impl, min_impl, nth_impl, ord_impl,
+        partial_impl, print_impl, print_with_impl, read_line_impl, reduce_impl, repr_impl,
+        reversed_impl, set_attr_impl, sorted_impl, sum_impl, to_dict_impl, to_list_impl,
+        to_set_impl, zip_longest_impl,
+    };
+    use crate::bytecode::Bytecode;
+    use crate::custom_types::adaptors::Map;
+    use crate::custom_types::dict::Dict;
+    use crate::custom_types::exceptions::{arithmetic_error, assertion_error, value_error};
+    use crate::custom_types::file::test::SharedBuffer;
+    use crate::custom_types::file::FileObj;
+    use crate::custom_types::list::List;
+    use crate::custom_types::range::Range;
+    use crate::custom_types::set::Set;
+    use crate::custom_var::downcast_var;
+    use crate::executor::execute;
+    use crate::file_info::FileInfo;
+    use crate::function::Function;
+    use crate::int_var::IntVar;
+    use crate::looping;
+    use crate::name::Name;
+    use crate::name_map::NameMap;
+    use crate::operator::Operator;
+    use crate::quick_functions::{quick_add, quick_floor_div, quick_mod};
+    use crate::rational_var::RationalVar;
+    use crate::runtime::Runtime;
+    use crate::std_type::Type;
+    use crate::std_variable::{StdVarMethod, StdVariable};
+    use crate::string_var::StringVar;
+    use crate::tuple::LangTuple;
+    use crate::variable::{FnResult, Variable};
+    use crate::{first, first_n};
+    use num::{BigInt, BigRational, Signed};
+    use std::cell::Cell;
+    use std::collections::{HashMap, HashSet};
+    use std::io::Cursor;
+    use std::rc::Rc;
+
+    fn add(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        let [a, b] = first_n(args);
+        let result = quick_add(a, b, runtime)?;
+        runtime.return_1(result)
+    }
+
+    fn negate(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        runtime.return_1((-IntVar::from(first(args))).into())
+    }
+
+    fn ints(values: &[i32]) -> Vec<Variable> {
+        values.iter().map(|&x| IntVar::from(x).into()).collect()
+    }
+
+    fn list_values(list: Result<Variable, ()>) -> Vec<Variable> {
+        let list = downcast_var::<List>(list.unwrap()).unwrap();
+        let values = list.values().to_vec();
+        values
+    }
+
+    fn range(start: i32, stop: i32) -> Variable {
+        Rc::new(Range::new(start.into(), stop.into(), 1.into())).into()
+    }
+
+    fn strings(values: &[&'static str]) -> Variable {
+        let values = values.iter().map(|&x| StringVar::from(x).into()).collect();
+        List::from_values(Type::String, values).into()
+    }
+
+    #[test]
+    fn reduce_ints() {
+        let args = vec![Function::Native(add).into(), range(0, 5)];
+        let result = Runtime::test(|runtime| reduce_impl(args, runtime));
+        assert_eq!(result, Result::Ok(IntVar::from(10).into()));
+    }
+
+    #[test]
+    fn reduce_initial() {
+        let args = vec![
+            Function::Native(add).into(),
+            range(0, 5),
+            IntVar::from(5).into(),
+        ];
+        let result = Runtime::test(|runtime| reduce_impl(args, runtime));
+        assert_eq!(result, Result::Ok(IntVar::from(15).into()));
+    }
+
+    #[test]
+    fn reduce_strings() {
+        let args = vec![Function::Native(add).into(), strings(&["a", "b", "c"])];
+        let result = Runtime::test(|runtime| reduce_impl(args, runtime));
+        assert_eq!(result, Result::Ok(StringVar::from("abc").into()));
+    }
+
+    #[test]
+    fn reduce_empty() {
+        let args = vec![Function::Native(add).into(), range(0, 0)];
+        let result = Runtime::test(|runtime| {
+            assert!(reduce_impl(args, runtime).is_err());
+            let exc = runtime.pop_err()?;
+            runtime.return_1(exc.get_type().into())
+        });
+        assert_eq!(result, Result::Ok(value_error().into()));
+    }
+
+    #[test]
+    fn reduce_empty_initial() {
+        let args = vec![Function::Native(add).into(), range(0, 0), Variable::null()];
+        let result = Runtime::test(|runtime| reduce_impl(args, runtime));
+        assert_eq!(result, Result::Ok(Variable::null()));
+    }
+
+    #[test]
+    fn sorted_set() {
+        let result = Runtime::test(|runtime| {
+            let set = Set::new(Type::Bigint, ints(&[3, 1, 4, 5, 9, 2, 6]), runtime)?;
+            sorted_impl(vec![set.into()], runtime)
+        });
+        assert_eq!(list_values(result), ints(&[1, 2, 3, 4, 5, 6, 9]));
+    }
+
+    #[test]
+    fn sorted_range() {
+        let result = Runtime::test(|runtime| sorted_impl(vec![range(-2, 3)], runtime));
+        assert_eq!(list_values(result), ints(&[-2, -1, 0, 1, 2]));
+    }
+
+    #[test]
+    fn sorted_iterator() {
+        let result = Runtime::test(|runtime| {
+            let iter = range(0, 4).iter(runtime)?;
+            let lazy = Map::new(Function::Native(negate).into(), iter);
+            sorted_impl(vec![lazy.into()], runtime)
+        });
+        assert_eq!(list_values(result), ints(&[-3, -2, -1, 0]));
+    }
+
+    #[test]
+    fn sorted_reverse() {
+        let args = vec![range(0, 4), Variable::null(), true.into()];
+        let result = Runtime::test(|runtime| sorted_impl(args, runtime));
+        assert_eq!(list_values(result), ints(&[3, 2, 1, 0]));
+    }
+
+    #[test]
+    fn sorted_mixed_numeric() {
+        let values = vec![
+            IntVar::from(1).into(),
+            decimal(1, 2),
+            true.into(),
+            decimal(2, 3),
+            false.into(),
+            IntVar::from(-1).into(),
+        ];
+        let list = List::from_values(Type::Object, values);
+        let result = Runtime::test(|runtime| sorted_impl(vec![list.into()], runtime));
+        let sorted = downcast_var::<List>(result.unwrap()).unwrap();
+        // Equal values (1 and true) keep their original order
+        let expected = vec![
+            IntVar::from(-1).into(),
+            false.into(),
+            decimal(1, 2),
+            decimal(2, 3),
+            IntVar::from(1).into(),
+            true.into(),
+        ];
+        assert_eq!(sorted.values().to_vec(), expected);
+    }
+
+    #[test]
+    fn sorted_key() {
+        let args = vec![range(0, 4), Function::Native(negate).into()];
+        let result = Runtime::test(|runtime| sorted_impl(args, runtime));
+        assert_eq!(list_values(result), ints(&[3, 2, 1, 0]));
+    }
+
+    #[test]
+    fn min_set() {
+        let result = Runtime::test(|runtime| {
+            let set = Set::new(Type::Bigint, ints(&[3, 1, 4, 5, 9, 2, 6]), runtime)?;
+            min_impl(vec![set.into()], runtime)
+        });
+        assert_eq!(result, Result::Ok(IntVar::from(1).into()));
+    }
+
+    #[test]
+    fn max_iterator() {
+        let result = Runtime::test(|runtime| {
+            let iter = range(0, 4).iter(runtime)?;
+            let lazy = Map::new(Function::Native(negate).into(), iter);
+            max_impl(vec![lazy.into()], runtime)
+        });
+        assert_eq!(result, Result::Ok(IntVar::from(0).into()));
+    }
+
+    #[test]
+    fn min_empty() {
+        let result = Runtime::test(|runtime| {
+            assert!(min_impl(vec![range(0, 0)], runtime).is_err());
+            let exc = runtime.pop_err()?;
+            runtime.return_1(exc.get_type().into())
+        });
+        assert_eq!(result, Result::Ok(value_error().into()));
+    }
+
+    #[test]
+    fn max_key() {
+        let args = vec![range(-3, 2), Function::Native(negate).into()];
+        let result = Runtime::test(|runtime| max_impl(args, runtime));
+        assert_eq!(result, Result::Ok(IntVar::from(-3).into()));
+    }
+
+    #[test]
+    fn min_variadic() {
+        let result = Runtime::test(|runtime| min_impl(ints(&[4, 2, 7]), runtime));
+        assert_eq!(result, Result::Ok(IntVar::from(2).into()));
+        let result = Runtime::test(|runtime| max_impl(ints(&[4, 2]), runtime));
+        assert_eq!(result, Result::Ok(IntVar::from(4).into()));
+    }
+
+    #[test]
+    fn min_max_pair() {
+        let result = Runtime::test(|runtime| min_impl(vec![str_var("b"), str_var("a")], runtime));
+        assert_eq!(result, Result::Ok(str_var("a")));
+        let result = Runtime::test(|runtime| max_impl(vec![str_var("b"), str_var("a")], runtime));
+        assert_eq!(result, Result::Ok(str_var("b")));
+    }
+
+    #[test]
+    fn clamp_ints() {
+        for &(value, expected) in &[(-5, 0), (3, 3), (12, 10)] {
+            let result = Runtime::test(|runtime| clamp_impl(ints(&[value, 0, 10]), runtime));
+            assert_eq!(result, Result::Ok(IntVar::from(expected).into()));
+        }
+    }
+
+    #[test]
+    fn clamp_strings() {
+        for &(value, expected) in &[("a", "b"), ("c", "c"), ("z", "d")] {
+            let args = vec![str_var(value), str_var("b"), str_var("d")];
+            let result = Runtime::test(|runtime| clamp_impl(args, runtime));
+            assert_eq!(result, Result::Ok(str_var(expected)));
+        }
+    }
+
+    #[test]
+    fn clamp_bad_bounds() {
+        let result = Runtime::test(|runtime| {
+            assert!(clamp_impl(ints(&[1, 5, 2]), runtime).is_err());
+            let exc = runtime.pop_err()?;
+            runtime.return_1(exc.get_type().into())
+        });
+        assert_eq!(result, Result::Ok(value_error().into()));
+    }
+
+    fn div_mod_pair(a: Variable, b: Variable) -> Result<(Variable, Variable), ()> {
+        let result = Runtime::test(|runtime| div_mod_impl(vec![a, b], runtime))?;
+        let [div, rem] = first_n(LangTuple::from(result).iter().cloned().collect());
+        Result::Ok((div, rem))
+    }
+
+    #[test]
+    fn div_mod_matches_operators() -> Result<(), ()> {
+        for &(a, b) in &[(-7, 2), (7, -2), (7, 2), (-7, -2)] {
+            let (a, b): (Variable, Variable) = (IntVar::from(a).into(), IntVar::from(b).into());
+            let expected = Runtime::test(|runtime| {
+                let div = quick_floor_div(a.clone(), b.clone(), runtime)?;
+                let rem = quick_mod(a.clone(), b.clone(), runtime)?;
+                runtime.return_1(LangTuple::from_vec(vec![div, rem]).into())
+            })?;
+            let (div, rem) = div_mod_pair(a, b)?;
+            assert_eq!(LangTuple::from_vec(vec![div, rem]), expected.into());
+        }
+        Result::Ok(())
+    }
+
+    #[test]
+    fn div_mod_decimal() -> Result<(), ()> {
+        let dec = |n: i32, d: i32| -> Variable {
+            RationalVar::from(BigRational::new(n.into(), d.into())).into()
+        };
+        let (div, rem) = div_mod_pair(dec(-15, 2), IntVar::from(2).into())?;
+        assert_eq!(div, IntVar::from(-3).into());
+        assert_eq!(rem, dec(-3, 2));
+        Result::Ok(())
+    }
+
+    #[test]
+    fn div_mod_zero() {
+        let result = Runtime::test(|runtime| {
+            assert!(div_mod_impl(ints(&[1, 0]), runtime).is_err());
+            let exc = runtime.pop_err()?;
+            runtime.return_1(exc.get_type().into())
+        });
+        assert_eq!(result, Result::Ok(arithmetic_error().into()));
+    }
+
+    #[test]
+    fn sum_ints() {
+        let result = Runtime::test(|runtime| sum_impl(vec![range(1, 4)], runtime));
+        assert_eq!(result, Result::Ok(IntVar::from(6).into()));
+        let result = Runtime::test(|runtime| sum_impl(vec![range(0, 0)], runtime));
+        assert_eq!(result, Result::Ok(IntVar::from(0).into()));
+    }
+
+    #[test]
+    fn sum_decimals() {
+        let half = |x: i32| -> Variable {
+            RationalVar::from(BigRational::new(BigInt::from(x), BigInt::from(2))).into()
+        };
+        let list = List::from_values(Type::Decimal, vec![half(1), half(3), half(5)]);
+        let result = Runtime::test(|runtime| sum_impl(vec![list.clone().into()], runtime));
+        assert_eq!(result, Result::Ok(half(9)));
+        let args = vec![list.into(), IntVar::from(1).into()];
+        let result = Runtime::test(|runtime| sum_impl(args, runtime));
+        assert_eq!(result, Result::Ok(half(11)));
+    }
+
+    #[test]
+    fn sum_strings_no_start() {
+        let result = Runtime::test(|runtime| {
+            assert!(sum_impl(vec![strings(&["a", "b"])], runtime).is_err());
+            let exc = runtime.pop_err()?;
+            runtime.return_1(exc.get_type().into())
+        });
+        assert_eq!(result, Result::Ok(value_error().into()));
+    }
+
+    #[test]
+    fn sum_arg_count() {
+        let msg = arg_count_error(sum_impl, Vec::new());
+        assert!(
+            msg.contains("sum() takes 1 to 2 arguments but 0 were given"),
+            "{}",
+            msg
+        );
+    }
+
+    #[test]
+    fn sum_strings() {
+        let args = vec![strings(&["a", "b", "c"]), StringVar::from("").into()];
+        let result = Runtime::test(|runtime| sum_impl(args, runtime));
+        assert_eq!(result, Result::Ok(StringVar::from("abc").into()));
+        let args = vec![strings(&[]), StringVar::from("").into()];
+        let result = Runtime::test(|runtime| sum_impl(args, runtime));
+        assert_eq!(result, Result::Ok(StringVar::from("").into()));
+    }
+
+    fn types(values: Vec<Type>) -> Variable {
+        LangTuple::from_vec(values.into_iter().map(Variable::from).collect()).into()
+    }
+
+    fn is_instance(value: Variable, types: Variable) -> bool {
+        let result = Runtime::test(|runtime| is_instance_impl(vec![value, types], runtime));
+        bool::from(result.unwrap())
+    }
+
+    fn is_subclass(cls: Type, types: Variable) -> bool {
+        let result = Runtime::test(|runtime| is_subclass_impl(vec![cls.into(), types], runtime));
+        bool::from(result.unwrap())
+    }
+
+    #[test]
+    fn is_instance_builtin() {
+        let one: Variable = IntVar::from(1).into();
+        assert!(is_instance(one.clone(), Type::Bigint.into()));
+        assert!(!is_instance(one.clone(), Type::String.into()));
+        assert!(is_instance(true.into(), Type::Bigint.into()));
+        assert!(is_instance(one, Type::Object.into()));
+        assert!(!is_instance(Variable::null(), Type::Bigint.into()));
+    }
+
+    #[test]
+    fn is_instance_custom() {
+        assert!(is_instance(range(0, 1), Range::range_type().into()));
+        assert!(!is_instance(range(0, 1), List::list_type().into()));
+        assert!(is_instance(strings(&["a"]), List::list_type().into()));
+        assert!(!is_instance(
+            IntVar::from(1).into(),
+            Range::range_type().into()
+        ));
+    }
+
+    #[test]
+    fn is_instance_tuple() {
+        let value: Variable = StringVar::from("a").into();
+        assert!(is_instance(
+            value.clone(),
+            types(vec![Type::Bigint, Type::String])
+        ));
+        assert!(!is_instance(value, types(vec![Type::Bigint, Type::Char])));
+        let collections = types(vec![List::list_type(), Set::set_type()]);
+        assert!(!is_instance(range(0, 1), collections.clone()));
+        assert!(is_instance(strings(&[]), collections));
+        assert!(!is_instance(range(0, 1), types(Vec::new())));
+    }
+
+    #[test]
+    fn is_subclass_builtin() {
+        assert!(is_subclass(Type::Bool, Type::Bigint.into()));
+        assert!(!is_subclass(Type::Bigint, Type::Bool.into()));
+        assert!(is_subclass(Range::range_type(), Range::range_type().into()));
+        assert!(is_subclass(Range::range_type(), Type::Object.into()));
+        assert!(!is_subclass(Range::range_type(), List::list_type().into()));
+    }
+
+    #[test]
+    fn is_subclass_tuple() {
+        let both = types(vec![Type::Bigint, Range::range_type()]);
+        assert!(is_subclass(Range::range_type(), both.clone()));
+        assert!(is_subclass(Type::Bool, both.clone()));
+        assert!(!is_subclass(Type::String, both));
+    }
+
+    #[test]
+    fn is_instance_not_type() {
+        let args = vec![IntVar::from(1).into(), IntVar::from(2).into()];
+        let result = Runtime::test(|runtime| {
+            assert!(is_instance_impl(args, runtime).is_err());
+            let exc = runtime.pop_err()?;
+            runtime.return_1(exc.get_type().into())
+        });
+        assert_eq!(result, Result::Ok(value_error().into()));
+    }
+
+    fn get_one(_this: StdVariable, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert!(args.is_empty());
+        runtime.return_1(IntVar::from(1).into())
+    }
+
+    /// An instance of a standard class with the field `x` (set to 0) and the
+    /// method `getOne`.
+    fn std_object() -> Variable {
+        let mut methods = NameMap::new();
+        methods.insert(Name::Attribute("getOne"), StdVarMethod::Native(get_one));
+        let cls = Type::new_std(
+            "Test".into(),
+            0,
+            Vec::new(),
+            HashSet::new(),
+            methods,
+            NameMap::new(),
+            HashMap::new(),
+        );
+        let cls = match cls {
+            Type::Standard(cls) => cls,
+            _ => unreachable!(),
+        };
+        let values = vec![("x".into(), IntVar::from(0).into())]
+            .into_iter()
+            .collect();
+        StdVariable::new(cls, values).into()
+    }
+
+    fn attr_args(value: &Variable, name: &'static str) -> Vec<Variable> {
+        vec![value.clone(), StringVar::from(name).into()]
+    }
+
+    #[test]
+    fn get_attr() {
+        let value = std_object();
+        let result = Runtime::test(|runtime| get_attr_impl(attr_args(&value, "x"), runtime));
+        assert_eq!(result, Result::Ok(IntVar::from(0).into()));
+        let result = Runtime::test(|runtime| {
+            get_attr_impl(attr_args(&value, "getOne"), runtime)?;
+            let method = runtime.pop_return();
+            method.call((Vec::new(), runtime))?;
+            let result = runtime.pop_return();
+            runtime.return_1(result)
+        });
+        assert_eq!(result, Result::Ok(IntVar::from(1).into()));
+    }
+
+    #[test]
+    fn get_attr_missing() {
+        let value = std_object();
+        let result = Runtime::test(|runtime| {
+            assert!(get_attr_impl(attr_args(&value, "y"), runtime).is_err());
+            let exc = runtime.pop_err()?;
+            runtime.return_1(exc.get_type().into())
+        });
+        assert_eq!(result, Result::Ok(value_error().into()));
+    }
+
+    #[test]
+    fn get_attr_or_null() {
+        let value = std_object();
+        let result =
+            Runtime::test(|runtime| get_attr_or_null_impl(attr_args(&value, "x"), runtime));
+        assert_eq!(
+            result,
+            Result::Ok(Option::Some(IntVar::from(0).into()).into())
+        );
+        let result =
+            Runtime::test(|runtime| get_attr_or_null_impl(attr_args(&value, "y"), runtime));
+        assert_eq!(result, Result::Ok(Option::None.into()));
+    }
+
+    #[test]
+    fn has_attr() {
+        let value = std_object();
+        for (name, expected) in [("x", true), ("getOne", true), ("y", false)] {
+            let result = Runtime::test(|runtime| has_attr_impl(attr_args(&value, name), runtime));
+            assert_eq!(result, Result::Ok(expected.into()), "{}", name);
+        }
+    }
+
+    fn builtin_values() -> Vec<Variable> {
+        let range = Range::new(IntVar::from(0), IntVar::from(3), IntVar::from(1));
+        vec![
+            IntVar::from(3).into(),
+            List::from_values(Type::Bigint, Vec::new()).into(),
+            Rc::new(range).into(),
+        ]
+    }
+
+    #[test]
+    fn has_attr_builtin_values() {
+        let [int, list, range] = first_n(builtin_values());
+        let cases = [
+            (&int, "toString", true),
+            (&int, "length", false),
+            (&list, "length", true),
+            (&list, "toString", false),
+            (&range, "length", true),
+            (&range, "y", false),
+        ];
+        for (value, name, expected) in cases {
+            let result = Runtime::test(|runtime| has_attr_impl(attr_args(value, name), runtime));
+            assert_eq!(result, Result::Ok(expected.into()), "{}", name);
+        }
+    }
+
+    #[test]
+    fn get_attr_or_null_builtin_values() {
+        for value in builtin_values() {
+            let result =
+                Runtime::test(|runtime| get_attr_or_null_impl(attr_args(&value, "y"), runtime));
+            assert_eq!(result, Result::Ok(Option::None.into()));
+        }
+    }
+
+    #[test]
+    fn set_attr() {
+        let value = std_object();
+        let result = Runtime::test(|runtime| {
+            let mut args = attr_args(&value, "x");
+            args.push(IntVar::from(5).into());
+            set_attr_impl(args, runtime)?;
+            get_attr_impl(attr_args(&value, "x"), runtime)
+        });
+        assert_eq!(result, Result::Ok(IntVar::from(5).into()));
+    }
+
+    fn id(value: Variable) -> Variable {
+        Runtime::test(|runtime| id_impl(vec![value], runtime)).unwrap()
+    }
+
+    #[test]
+    fn id_small_int() {
+        let ints = [-2, -1, 0, 1, 2];
+        assert_eq!(id(IntVar::from(1).into()), id(IntVar::from(1).into()));
+        for (i, x) in ints.iter().enumerate() {
+            for y in &ints[i + 1..] {
+                assert_ne!(id(IntVar::from(*x).into()), id(IntVar::from(*y).into()));
+            }
+        }
+    }
+
+    #[test]
+    fn id_value_types() {
+        let values: Vec<Variable> = vec![
+            Variable::null(),
+            false.into(),
+            true.into(),
+            IntVar::from(0).into(),
+            IntVar::from(1).into(),
+            '\0'.into(),
+            'a'.into(),
+            Type::Null.into(),
+            Type::Bigint.into(),
+            Type::Bigint.make_option().into(),
+            Type::Bigint.make_option_n(2).into(),
+            Function::Standard(0, 0).into(),
+            Function::Standard(0, 1).into(),
+            Function::Standard(1, 0).into(),
+        ];
+        for (i, x) in values.iter().enumerate() {
+            assert_eq!(id(x.clone()), id(x.clone()), "{:?}", x);
+            for y in &values[i + 1..] {
+                assert_ne!(id(x.clone()), id(y.clone()), "{:?} and {:?}", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn id_objects() {
+        let first = strings(&["a"]);
+        let second = strings(&["a"]);
+        assert_eq!(id(first.clone()), id(first.clone()));
+        assert_ne!(id(first), id(second));
+        let function: Variable = Function::Native(add).into();
+        assert_eq!(id(function.clone()), id(function));
+        assert_ne!(
+            id(Function::Native(add).into()),
+            id(Function::Native(negate).into())
+        );
+    }
+
+    fn reversed_values(value: Variable) -> Result<Vec<Variable>, ()> {
+        let mut result = Vec::new();
+        Runtime::test(|runtime| {
+            reversed_impl(vec![value], runtime)?;
+            let reversed = runtime.pop_return();
+            result = looping::collect(reversed, runtime)?;
+            runtime.return_1(true.into())
+        })?;
+        Result::Ok(result)
+    }
+
+    #[test]
+    fn reversed_list() {
+        let list = List::from_values(Type::Bigint, ints(&[1, 2, 3]));
+        assert_eq!(reversed_values(list.into()), Result::Ok(ints(&[3, 2, 1])));
+    }
+
+    #[test]
+    fn reversed_iterator() {
+        let mut lazy = Variable::null();
+        Runtime::test(|runtime| {
+            let iter = range(0, 4).iter(runtime)?;
+            lazy = Map::new(Function::Native(negate).into(), iter).into();
+            runtime.return_1(true.into())
+        })
+        .unwrap();
+        assert_eq!(reversed_values(lazy), Result::Ok(ints(&[-3, -2, -1, 0])));
+    }
+
+    #[test]
+    fn reversed_not_iterable() {
+        let result = Runtime::test(|runtime| {
+            assert!(reversed_impl(vec![IntVar::from(1).into()], runtime).is_err());
+            let exc = runtime.pop_err()?;
+            runtime.return_1(exc.get_type().into())
+        });
+        assert_eq!(result, Result::Ok(value_error().into()));
+    }
+
+    #[test]
+    fn all_any_empty() {
+        let all = Runtime::test(|runtime| all_impl(vec![range(0, 0)], runtime));
+        let any = Runtime::test(|runtime| any_impl(vec![range(0, 0)], runtime));
+        assert_eq!(all, Result::Ok(true.into()));
+        assert_eq!(any, Result::Ok(false.into()));
+    }
+
+    #[test]
+    fn all_any_values() {
+        let values = List::from_values(Type::Bigint, ints(&[1, 0, 2]));
+        let all = Runtime::test(|runtime| all_impl(vec![values.clone().into()], runtime));
+        let any = Runtime::test(|runtime| any_impl(vec![values.into()], runtime));
+        assert_eq!(all, Result::Ok(false.into()));
+        assert_eq!(any, Result::Ok(true.into()));
+    }
+
+    thread_local! {
+        static CALLS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    fn is_positive(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        CALLS.with(|c| c.set(c.get() + 1));
+        runtime.return_1(IntVar::from(first(args)).is_positive().into())
+    }
+
+    #[test]
+    fn all_any_predicate() {
+        let args = vec![range(1, 5), Function::Native(is_positive).into()];
+        let all = Runtime::test(|runtime| all_impl(args, runtime));
+        assert_eq!(all, Result::Ok(true.into()));
+        let args = vec![range(-4, 0), Function::Native(is_positive).into()];
+        let any = Runtime::test(|runtime| any_impl(args, runtime));
+        assert_eq!(any, Result::Ok(false.into()));
+    }
+
+    #[test]
+    fn all_any_short_circuit() {
+        CALLS.with(|c| c.set(0));
+        let args = vec![range(-1, 10), Function::Native(is_positive).into()];
+        let all = Runtime::test(|runtime| all_impl(args, runtime));
+        assert_eq!(all, Result::Ok(false.into()));
+        assert_eq!(CALLS.with(Cell::get), 1);
+        CALLS.with(|c| c.set(0));
+        let args = vec![range(-2, 10), Function::Native(is_positive).into()];
+        let any = Runtime::test(|runtime| any_impl(args, runtime));
+        assert_eq!(any, Result::Ok(true.into()));
+        assert_eq!(CALLS.with(Cell::get), 4);
+    }
+
+    fn throws(_args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        runtime.throw_quick(value_error(), "predicate failed")
+    }
+
+    #[test]
+    fn all_any_error() {
+        let result = Runtime::test(|runtime| {
+            let args = vec![range(0, 3), Function::Native(throws).into()];
+            assert!(any_impl(args, runtime).is_err());
+            let exc = runtime.pop_err()?;
+            runtime.return_1(exc.get_type().into())
+        });
+        assert_eq!(result, Result::Ok(value_error().into()));
+    }
+
+    #[test]
+    fn chr_ord_round_trip() {
+        for &c in &['a', '0', '\u{e9}', '\u{1f600}'] {
+            let code = Runtime::test(|runtime| ord_impl(vec![c.into()], runtime));
+            assert_eq!(code, Result::Ok(IntVar::from(c as u32).into()));
+            let result = Runtime::test(|runtime| chr_impl(vec![code.unwrap()], runtime));
+            assert_eq!(result, Result::Ok(c.into()));
+        }
+        let code = Runtime::test(|runtime| ord_impl(vec![StringVar::from("z").into()], runtime));
+        assert_eq!(code, Result::Ok(IntVar::from('z' as u32).into()));
+    }
+
+    fn error_type(
+        func: fn(Vec<Variable>, &mut Runtime) -> FnResult,
+        arg: Variable,
+    ) -> Result<Variable, ()> {
+        Runtime::test(|runtime| {
+            assert!(func(vec![arg], runtime).is_err());
+            let exc = runtime.pop_err()?;
+            runtime.return_1(exc.get_type().into())
+        })
+    }
+
+    #[test]
+    fn chr_invalid() {
+        for &code in &[0xD800, 0x110000, -1] {
+            let result = error_type(chr_impl, IntVar::from(code).into());
+            assert_eq!(result, Result::Ok(value_error().into()), "{}", code);
+        }
+    }
+
+    #[test]
+    fn ord_invalid() {
+        for &s in &["ab", ""] {
+            let result = error_type(ord_impl, StringVar::from(s).into());
+            assert_eq!(result, Result::Ok(value_error().into()), "{}", s);
+        }
+    }
+
+    fn null() -> Variable {
+        Option::None.into()
+    }
+
+    fn captured(func: impl FnOnce(&mut Runtime) -> FnResult) -> String {
+        let buffer = SharedBuffer::default();
+        let result = Runtime::test(|runtime| {
+            runtime.set_stdout(FileObj::from_writer("<test>", Box::new(buffer.clone())));
+            func(runtime)?;
+            runtime.return_1(true.into())
+        });
+        assert_eq!(result, Result::Ok(true.into()));
+        let output = String::from_utf8(buffer.0.borrow().clone()).unwrap();
+        output
+    }
+
+    fn str_var(value: &str) -> Variable {
+        StringVar::from(value.to_owned()).into()
+    }
+
+    #[test]
+    fn print_default() {
+        let args = vec![str_var("a"), IntVar::from(1).into(), str_var("b")];
+        let output = captured(|runtime| print_impl(args, runtime));
+        assert_eq!(output, "a 1 b\n");
+    }
+
+    #[test]
+    fn print_with_options() {
+        let values = || LangTuple::from_vec(vec![str_var("a"), str_var("b"), str_var("c")]);
+        let args = vec![values().into(), str_var(", "), str_var(""), null()];
+        let output = captured(|runtime| print_with_impl(args, runtime));
+        assert_eq!(output, "a, b, c");
+        let args = vec![values().into(), null(), str_var("!\n"), null()];
+        let output = captured(|runtime| print_with_impl(args, runtime));
+        assert_eq!(output, "a b c!\n");
+    }
+
+    #[test]
+    fn print_with_file() {
+        let buffer = SharedBuffer::default();
+        let file = FileObj::from_writer("<test>", Box::new(buffer.clone()));
+        let values = LangTuple::from_vec(vec![IntVar::from(1).into()]);
+        let args = vec![values.into(), null(), null(), file.into()];
+        let output = captured(|runtime| print_with_impl(args, runtime));
+        assert_eq!(output, "");
+        assert_eq!(&*buffer.0.borrow(), b"1\n");
+    }
+
+    #[test]
+    fn print_with_not_file() {
+        let values = LangTuple::from_vec(vec![IntVar::from(1).into()]);
+        let args = vec![values.into(), null(), null(), str_var("out")];
+        let result = Runtime::test(|runtime| {
+            assert!(print_with_impl(args, runtime).is_err());
+            let exc = runtime.pop_err()?;
+            runtime.return_1(exc.get_type().into())
+        });
+        assert_eq!(result, Result::Ok(value_error().into()));
+    }
+
+    #[test]
+    fn read_line_stdin() {
+        let input = Cursor::new(b"first line\nsecond\n".to_vec());
+        let mut lines = Vec::new();
+        let output = captured(|runtime| {
+            runtime.set_stdin(FileObj::from_reader("<test>", Box::new(input)));
+            for args in [vec![str_var("> ")], vec![], vec![null()]] {
+                read_line_impl(args, runtime)?;
+                lines.push(runtime.pop_return());
+            }
+            runtime.return_0()
+        });
+        assert_eq!(output, "> ");
+        let expected = vec![
+            Option::Some(str_var("first line")).into(),
+            Option::Some(str_var("second")).into(),
+            null(),
+        ];
+        assert_eq!(lines, expected);
+    }
+
+    #[test]
+    fn assert_passes() {
+        let result = Runtime::test(|runtime| {
+            assert_impl(vec![true.into()], runtime)?;
+            assert_impl(vec![IntVar::from(1).into(), str_var("unused")], runtime)?;
+            runtime.return_1(true.into())
+        });
+        assert_eq!(result, Result::Ok(true.into()));
+    }
+
+    #[test]
+    fn assert_fails() {
+        for (message, expected) in [(str_var("x > 0"), "x > 0"), (null(), "Assertion failed")] {
+            let result = Runtime::test(|runtime| {
+                assert!(assert_impl(vec![false.into(), message], runtime).is_err());
+                let exc = runtime.pop_err()?;
+                assert_eq!(exc.get_type(), assertion_error());
+                exc.index(Name::Attribute("msg"), runtime)?
+                    .call((vec![], runtime))
+            });
+            let message = StringVar::from(result.unwrap());
+            assert!(message.contains(&format!("\n{}\n", expected)));
+        }
+    }
+
+    fn convert(
+        func: fn(Vec<Variable>, &mut Runtime) -> FnResult,
+        args: Vec<Variable>,
+    ) -> Result<Variable, ()> {
+        Runtime::test(|runtime| match func(args, runtime) {
+            Result::Ok(()) => Result::Ok(()),
+            Result::Err(()) => {
+                let exc = runtime.pop_err()?;
+                runtime.return_1(exc.get_type().into())
+            }
+        })
+    }
+
+    fn decimal(numer: i64, denom: i64) -> Variable {
+        RationalVar::from(BigRational::new(numer.into(), denom.into())).into()
+    }
+
+    #[test]
+    fn int_conversions() {
+        let cases: Vec<(Variable, isize)> = vec![
+            (str_var("-1_000"), -1000),
+            (true.into(), 1),
+            (false.into(), 0),
+            ('a'.into(), 97),
+            (decimal(7, 2), 3),
+            (decimal(-7, 2), -3),
+            (IntVar::from(12).into(), 12),
+        ];
+        for (value, expected) in cases {
+            let result = convert(int_impl, vec![value.clone()]);
+            assert_eq!(
+                result,
+                Result::Ok(IntVar::from(expected).into()),
+                "{:?}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn int_radix() {
+        let result = convert(int_impl, vec![str_var("ff"), IntVar::from(16).into()]);
+        assert_eq!(result, Result::Ok(IntVar::from(255).into()));
+        let result = convert(int_impl, vec![str_var("12"), null()]);
+        assert_eq!(result, Result::Ok(IntVar::from(12).into()));
+        let result = convert(int_impl, vec![str_var("1"), IntVar::from(37).into()]);
+        assert_eq!(result, Result::Ok(value_error().into()));
+        let result = convert(int_impl, vec![true.into(), IntVar::from(2).into()]);
+        assert_eq!(result, Result::Ok(value_error().into()));
+    }
+
+    #[test]
+    fn int_invalid() {
+        for value in [str_var("12a"), str_var(""), str_var("1.5"), null()] {
+            let result = convert(int_impl, vec![value.clone()]);
+            assert_eq!(result, Result::Ok(value_error().into()), "{:?}", value);
+        }
+        let result = convert(int_impl, vec![str_var("19"), IntVar::from(8).into()]);
+        assert_eq!(result, Result::Ok(value_error().into()));
+    }
+
+    #[test]
+    fn dec_conversions() {
+        let cases: Vec<(Variable, Variable)> = vec![
+            (str_var("-1.25"), decimal(-5, 4)),
+            (str_var("3/4"), decimal(3, 4)),
+            (true.into(), decimal(1, 1)),
+            ('a'.into(), decimal(97, 1)),
+            (IntVar::from(12).into(), decimal(12, 1)),
+            (decimal(1, 3), decimal(1, 3)),
+        ];
+        for (value, expected) in cases {
+            let result = convert(dec_impl, vec![value.clone()]);
+            assert_eq!(result, Result::Ok(expected), "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn dec_invalid() {
+        for value in [str_var("1.2.3"), str_var("abc"), null()] {
+            let result = convert(dec_impl, vec![value.clone()]);
+            assert_eq!(result, Result::Ok(value_error().into()), "{:?}", value);
+        }
+    }
+
+    fn str_and_repr(value: Variable) -> (Variable, Variable) {
+        let result = Runtime::test(|runtime| {
+            // str(x) is the str type itself, which stringifies its argument
+            Type::String.push_create((vec![value.clone()], runtime))?;
+            let string = runtime.pop_return();
+            repr_impl(vec![value], runtime)?;
+            let repr = runtime.pop_return();
+            runtime.return_1(LangTuple::from_vec(vec![string, repr]).into())
+        });
+        let [string, repr] = first_n(LangTuple::from(result.unwrap()).iter().cloned().collect());
+        (string, repr)
+    }
+
+    #[test]
+    fn str_repr_builtins() {
+        let inner = List::from_values(Type::Object, vec![str_var("b"), 'c'.into()]);
+        let list = List::from_values(
+            Type::Object,
+            vec![IntVar::from(1).into(), str_var("a"), inner.into()],
+        );
+        let cases: Vec<(Variable, &str, &str)> = vec![
+            (IntVar::from(-3).into(), "-3", "-3"),
+            (true.into(), "true", "true"),
+            (str_var("a\"b"), "a\"b", "\"a\\\"b\""),
+            (
+                list.into(),
+                "[1, \"a\", [\"b\", c'c']]",
+                "[1, \"a\", [\"b\", c'c']]",
+            ),
+            (Option::Some(str_var("x")).into(), "Some(x)", "Some(\"x\")"),
+            (null(), "null", "null"),
+        ];
+        for (value, string, repr) in cases {
+            let (actual_str, actual_repr) = str_and_repr(value);
+            assert_eq!(actual_str, str_var(string));
+            assert_eq!(actual_repr, str_var(repr));
+        }
+    }
+
+    #[test]
+    fn container_str_quotes_strings() {
+        let set = Runtime::test(|runtime| {
+            let set = Set::new(Type::String, vec![str_var("a")], runtime)?;
+            runtime.return_1(set.into())
+        });
+        let dict = Runtime::test(|runtime| {
+            let dict = Dict::from_args(vec![str_var("a")], vec![str_var("b")], runtime)?;
+            runtime.return_1(dict.into())
+        });
+        let cases: Vec<(Variable, &str)> = vec![
+            (
+                List::from_values(Type::String, vec![str_var("a"), str_var("b")]).into(),
+                "[\"a\", \"b\"]",
+            ),
+            (
+                LangTuple::from_vec(vec![str_var("a"), str_var("b")]).into(),
+                "(\"a\", \"b\")",
+            ),
+            (set.unwrap(), "{\"a\"}"),
+            (dict.unwrap(), "{\"a\": \"b\"}"),
+        ];
+        for (value, expected) in cases {
+            let (actual_str, actual_repr) = str_and_repr(value);
+            assert_eq!(actual_str, str_var(expected));
+            assert_eq!(actual_repr, str_var(expected));
+        }
+    }
+
+    #[test]
+    fn make_exception_not_thrown() -> Result<(), ()> {
+        let result = Runtime::test(|runtime| {
+            let args = vec![value_error().into(), str_var("oops")];
+            make_exception_impl(args, runtime)?;
+            let exc = runtime.pop_return();
+            assert_eq!(exc.get_type(), value_error());
+            runtime.call_attr(exc, "msg", Vec::new())?;
+            let msg = runtime.pop_return();
+            runtime.return_1(msg)
+        })?;
+        let msg = StringVar::from(result);
+        assert!(msg.starts_with("ValueError:\noops"), "{}", msg);
+        Result::Ok(())
+    }
+
+    #[test]
+    fn make_exception_bad_type() {
+        let result = Runtime::test(|runtime| {
+            let args = vec![Type::Bigint.into(), str_var("oops")];
+            assert!(make_exception_impl(args, runtime).is_err());
+            let exc = runtime.pop_err()?;
+            runtime.return_1(exc.get_type().into())
+        });
+        assert_eq!(result, Result::Ok(value_error().into()));
+    }
+
+    fn some_int(value: isize) -> Variable {
+        Option::Some(IntVar::from(value).into()).into()
+    }
+
+    #[test]
+    fn count_last_nth_range() {
+        let result = Runtime::test(|runtime| count_impl(vec![range(2, 7)], runtime));
+        assert_eq!(result, Result::Ok(IntVar::from(5).into()));
+        let result = Runtime::test(|runtime| last_impl(vec![range(2, 7)], runtime));
+        assert_eq!(result, Result::Ok(some_int(6)));
+        let result = Runtime::test(|runtime| last_impl(vec![range(0, 0)], runtime));
+        assert_eq!(result, Result::Ok(null()));
+        for (n, expected) in [(0, some_int(2)), (4, some_int(6)), (5, null())] {
+            let args = vec![range(2, 7), IntVar::from(n).into()];
+            let result = Runtime::test(|runtime| nth_impl(args, runtime));
+            assert_eq!(result, Result::Ok(expected));
+        }
+    }
+
+    #[test]
+    fn nth_negative() {
+        let result = Runtime::test(|runtime| {
+            let args = vec![range(0, 3), IntVar::from(-1).into()];
+            assert!(nth_impl(args, runtime).is_err());
+            let exc = runtime.pop_err()?;
+            runtime.return_1(exc.get_type().into())
+        });
+        assert_eq!(result, Result::Ok(value_error().into()));
+    }
+
+    #[rustfmt::skip]
+    const YIELD_THREE: &[u8] = &[
+        Bytecode::LoadConst as u8, 0, 0,
+        Bytecode::Yield as u8, 0, 1,
+        Bytecode::LoadConst as u8, 0, 1,
+        Bytecode::Yield as u8, 0, 1,
+        Bytecode::LoadConst as u8, 0, 2,
+        Bytecode::Yield as u8, 0, 1,
+    ];
+
+    /// Calls `constants[3]` with a fresh generator and `constants[4]`,
+    /// storing the result in variable 0.
+    #[rustfmt::skip]
+    const CALL_WITH_GENERATOR: &[u8] = &[
+        Bytecode::LoadConst as u8, 0, 3,
+        Bytecode::LoadFunction as u8, 0, 1,
+        Bytecode::CallTos as u8, 0, 0,
+        Bytecode::LoadConst as u8, 0, 4,
+        Bytecode::CallTos as u8, 0, 2,
+        Bytecode::Store as u8, 0, 0,
+    ];
+
+    fn call_with_generator(
+        func: fn(Vec<Variable>, &mut Runtime) -> FnResult,
+        arg: isize,
+    ) -> Variable {
+        let callee = Function::Native(func).into();
+        let constants = vec![10.into(), 20.into(), 30.into(), callee, arg.into()];
+        let functions = vec![
+            BaseFunction::new(String::new(), 1, CALL_WITH_GENERATOR.to_vec()),
+            BaseFunction::new_gen(String::new(), 0, YIELD_THREE.to_vec()),
+        ];
+        let file = FileInfo::new(
+            String::new(),
+            constants,
+            functions,
+            HashMap::new(),
+            Vec::new(),
+        );
+        let mut runtime = Runtime::new(vec![file], 0);
+        execute(&mut runtime).unwrap();
+        runtime.load_value(0).clone()
+    }
+
+    fn count_of_first(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        let [iterable, _] = first_n(args);
+        count_impl(vec![iterable], runtime)
+    }
+
+    fn last_of_first(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        let [iterable, _] = first_n(args);
+        last_impl(vec![iterable], runtime)
+    }
+
+    #[test]
+    fn count_last_nth_generator() {
+        assert_eq!(
+            call_with_generator(count_of_first, 0),
+            IntVar::from(3).into()
+        );
+        assert_eq!(call_with_generator(last_of_first, 0), some_int(30));
+        assert_eq!(call_with_generator(nth_impl, 1), some_int(20));
+        assert_eq!(call_with_generator(nth_impl, 3), null());
+    }
+
+    #[rustfmt::skip]
+    const MAKE_GENERATOR: &[u8] = &[
+        Bytecode::LoadFunction as u8, 0, 1,
+        Bytecode::CallTos as u8, 0, 0,
+        Bytecode::Store as u8, 0, 0,
+    ];
+
+    /// Calls `func` with a generator yielding the three given values.
+    fn with_generator(
+        values: Vec<Variable>,
+        func: impl FnOnce(Variable, &mut Runtime) -> FnResult,
+    ) -> Variable {
+        assert_eq!(values.len(), 3);
+        let functions = vec![
+            BaseFunction::new(String::new(), 1, MAKE_GENERATOR.to_vec()),
+            BaseFunction::new_gen(String::new(), 0, YIELD_THREE.to_vec()),
+        ];
+        let constants = values.to_vec();
+        let file = FileInfo::new(
+            String::new(),
+            constants,
+            functions,
+            HashMap::new(),
+            Vec::new(),
+        );
+        let mut runtime = Runtime::new(vec![file], 0);
+        execute(&mut runtime).unwrap();
+        let generator = runtime.load_value(0).clone();
+        runtime.push_native();
+        func(generator, &mut runtime).unwrap();
+        runtime.pop_native();
+        runtime.pop_return()
+    }
+
+    #[test]
+    fn to_list_generator() {
+        let result = with_generator(ints(&[1, 2, 1]), |x, runtime| {
+            to_list_impl(vec![x], runtime)
+        });
+        let list = downcast_var::<List>(result).unwrap();
+        assert_eq!(list.values().to_vec(), ints(&[1, 2, 1]));
+    }
+
+    #[test]
+    fn to_set_generator() {
+        let result = with_generator(ints(&[1, 2, 1]), |x, runtime| to_set_impl(vec![x], runtime));
+        let set = downcast_var::<Set>(result).unwrap();
+        let result = Runtime::test(|runtime| {
+            let mut values: Vec<IntVar> = looping::collect(set.into(), runtime)?;
+            values.sort();
+            let values = values.into_iter().map(Variable::from).collect();
+            runtime.return_1(List::from_values(Type::Object, values).into())
+        });
+        let list = downcast_var::<List>(result.unwrap()).unwrap();
+        assert_eq!(list.values().to_vec(), ints(&[1, 2]));
+    }
+
+    fn pair(key: &str, value: i32) -> Variable {
+        LangTuple::from_vec(vec![str_var(key), IntVar::from(value).into()]).into()
+    }
+
+    #[test]
+    fn to_dict_generator() {
+        let pairs = vec![pair("a", 1), pair("b", 2), pair("a", 3)];
+        let result = with_generator(pairs, |x, runtime| {
+            to_dict_impl(vec![x], runtime)?;
+            let dict = runtime.pop_return();
+            let mut values = Vec::new();
+            for key in ["a", "b"] {
+                runtime.call_op(dict.clone(), Operator::GetAttr, vec![str_var(key)])?;
+                values.push(runtime.pop_return());
+            }
+            runtime.return_1(List::from_values(Type::Object, values).into())
+        });
+        let list = downcast_var::<List>(result).unwrap();
+        assert_eq!(list.values().to_vec(), ints(&[3, 2]));
+    }
+
+    #[test]
+    fn to_dict_not_pair() {
+        let values = vec![pair("a", 1), IntVar::from(2).into(), pair("c", 3)];
+        let result = with_generator(values, |x, runtime| {
+            assert!(to_dict_impl(vec![x], runtime).is_err());
+            let exc = runtime.pop_err()?;
+            runtime.return_1(exc.get_type().into())
+        });
+        assert_eq!(result, value_error().into());
+    }
+
+    #[test]
+    fn dict_from_pairs_generator() {
+        let pairs = vec![pair("a", 1), pair("b", 2), pair("a", 3)];
+        let result = with_generator(pairs, |x, runtime| {
+            let from_pairs = Dict::dict_type().index(Name::Attribute("fromPairs"), runtime);
+            from_pairs.call((vec![x], runtime))?;
+            let dict = runtime.pop_return();
+            let mut values = Vec::new();
+            for key in ["a", "b"] {
+                runtime.call_op(dict.clone(), Operator::GetAttr, vec![str_var(key)])?;
+                values.push(runtime.pop_return());
+            }
+            runtime.return_1(List::from_values(Type::Object, values).into())
+        });
+        let list = downcast_var::<List>(result).unwrap();
+        assert_eq!(list.values().to_vec(), ints(&[3, 2]));
+    }
+
+    fn arg_count_error(
+        func: fn(Vec<Variable>, &mut Runtime) -> FnResult,
+        args: Vec<Variable>,
+    ) -> StringVar {
+        let result = Runtime::test(|runtime| {
+            assert!(func(args, runtime).is_err());
+            let exc = runtime.pop_err()?;
+            assert_eq!(exc.get_type(), value_error());
+            runtime.call_attr(exc, "msg", Vec::new())
+        });
+        StringVar::from(result.unwrap())
+    }
+
+    #[test]
+    fn read_line_arg_count() {
+        let args = vec![str_var("a"), str_var("b")];
+        let msg = arg_count_error(read_line_impl, args);
+        assert!(
+            msg.contains("readLine() takes 0 to 1 arguments but 2 were given"),
+            "{}",
+            msg
+        );
+    }
+
+    #[test]
+    fn assert_arg_count() {
+        let msg = arg_count_error(assert_impl, Vec::new());
+        assert!(
+            msg.contains("assert() takes 1 to 2 arguments but 0 were given"),
+            "{}",
+            msg
+        );
+    }
+
+    #[test]
+    fn int_arg_count() {
+        let args = vec![str_var("1"), IntVar::from(10).into(), null()];
+        let msg = arg_count_error(int_impl, args);
+        assert!(
+            msg.contains("int() takes 1 to 2 arguments but 3 were given"),
+            "{}",
+            msg
+        );
+    }
+
+    #[test]
+    fn partial_no_function() {
+        let msg = arg_count_error(partial_impl, Vec::new());
+        assert!(
+            msg.contains("partial() takes at least 1 argument but 0 were given"),
+            "{}",
+            msg
+        );
+    }
+
+    #[test]
+    fn zip_longest_no_fill() {
+        let msg = arg_count_error(zip_longest_impl, Vec::new());
+        assert!(
+            msg.contains("zipLongest() takes at least 1 argument but 0 were given"),
+            "{}",
+            msg
+        );
+    }
+}
@@ -1,3 +1,7 @@
+use crate::int_tools::bytes_index;
+use num::FromPrimitive;
+use std::fmt::{self, Display, Formatter};
+
 #[derive(Debug, Clone, Copy, FromPrimitive, Ord, PartialOrd, Eq, PartialEq)]
 pub enum Bytecode {
     Nop = 0x0,
@@ -71,6 +75,8 @@ pub enum Bytecode {
     ExceptN = 0x43,
     Finally = 0x44,
     EndTry = 0x45,
+    Rethrow = 0x46,
+    EndExcept = 0x47,
     // Markers
     FuncDef = 0x48,
     ClassDef = 0x49,
@@ -111,6 +117,7 @@ pub enum Bytecode {
     UnpackIterable = 0x7A,
     PackIterable = 0x7B,
     SwapDyn = 0x7C,
+    UnpackFixed = 0x7D,
 }
 
 pub fn bytecode_size(b: Bytecode) -> (usize, usize) {
@@ -172,12 +179,9 @@ pub fn bytecode_size(b: Bytecode) -> (usize, usize) {
         Bytecode::Throw => (0, 0),
         Bytecode::ThrowQuick => (2, 0),
         Bytecode::EnterTry => (4, 0),
-        Bytecode::ExceptN => (2, 0),
-        Bytecode::Finally
-        | Bytecode::EndTry
-        | Bytecode::FuncDef
-        | Bytecode::ClassDef
-        | Bytecode::EndClass => (0, 0),
+        Bytecode::ExceptN | Bytecode::EndTry => (2, 0),
+        Bytecode::Finally | Bytecode::Rethrow | Bytecode::EndExcept => (0, 0),
+        Bytecode::FuncDef | Bytecode::ClassDef | Bytecode::EndClass => (0, 0),
         Bytecode::ForIter => (4, 2),
         Bytecode::ListCreate | Bytecode::SetCreate | Bytecode::DictCreate => (2, 0),
         Bytecode::ListAdd | Bytecode::SetAdd | Bytecode::DictAdd => (0, 0),
@@ -199,5 +203,214 @@ pub fn bytecode_size(b: Bytecode) -> (usize, usize) {
         Bytecode::DupTop2 => (0, 0),
         Bytecode::DupTopN => (2, 0),
         Bytecode::UnpackIterable | Bytecode::PackIterable | Bytecode::SwapDyn => (0, 0),
+        Bytecode::UnpackFixed => (2, 0),
+    }
+}
+
+/// A single instruction with its operands already decoded.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Instruction {
+    pub bytecode: Bytecode,
+    pub bytes_0: u32,
+    pub bytes_1: u32,
+    /// The total size of the instruction in bytes, including the bytecode
+    /// itself.
+    pub size: u32,
+}
+
+impl Instruction {
+    /// Decodes the instruction starting at `pos`.
+    ///
+    /// # Panics
+    /// If the byte at `pos` is not a valid bytecode, or if the operands run
+    /// past the end of `bytes`.
+    pub fn decode(bytes: &[u8], pos: usize) -> Instruction {
+        let current = bytes[pos];
+        let bytecode: Bytecode = FromPrimitive::from_u8(current)
+            .unwrap_or_else(|| panic!("Attempted to parse invalid bytecode: value {:x}", current));
+        let byte_size = bytecode_size(bytecode);
+        let byte_start = pos + 1;
+        Instruction {
+            bytecode,
+            bytes_0: get_bytes(bytes, byte_start, byte_size.0),
+            bytes_1: get_bytes(bytes, byte_start + byte_size.0, byte_size.1),
+            size: (byte_size.0 + byte_size.1 + 1) as u32,
+        }
+    }
+}
+
+/// Decodes every instruction in the function ahead of time.
+///
+/// The result is indexed by byte position, with the entries in the middle of
+/// an instruction left as [`None`]. Decoding stops at the first invalid or
+/// truncated instruction instead of panicking, as the function may never
+/// reach it; the executor falls back to decoding on the fly for any position
+/// without an entry, which will then panic as it always did.
+pub fn decode_all(bytes: &[u8]) -> Vec<Option<Instruction>> {
+    let mut result = vec![Option::None; bytes.len()];
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let bytecode: Bytecode = match FromPrimitive::from_u8(bytes[pos]) {
+            Option::Some(b) => b,
+            Option::None => break,
+        };
+        let (size_0, size_1) = bytecode_size(bytecode);
+        if pos + size_0 + size_1 >= bytes.len() {
+            break;
+        }
+        let instruction = Instruction::decode(bytes, pos);
+        result[pos] = Option::Some(instruction);
+        pos += instruction.size as usize;
+    }
+    result
+}
+
+/// Checks that `bytes` consists entirely of valid instructions, each with all
+/// of its operands present.
+pub fn validate(bytes: &[u8]) -> Result<(), BytecodeError> {
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let bytecode: Bytecode =
+            FromPrimitive::from_u8(bytes[pos]).ok_or(BytecodeError::InvalidOpcode {
+                pos,
+                value: bytes[pos],
+            })?;
+        let (size_0, size_1) = bytecode_size(bytecode);
+        let size = size_0 + size_1 + 1;
+        if pos + size > bytes.len() {
+            return Result::Err(BytecodeError::Truncated { pos, bytecode });
+        }
+        pos += size;
+    }
+    Result::Ok(())
+}
+
+/// The reason [`validate`] rejected a function's bytecode.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum BytecodeError {
+    InvalidOpcode { pos: usize, value: u8 },
+    Truncated { pos: usize, bytecode: Bytecode },
+}
+
+impl Display for BytecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            BytecodeError::InvalidOpcode { pos, value } => {
+                write!(f, "invalid bytecode {:#x} at position {}", value, pos)
+            }
+            BytecodeError::Truncated { pos, bytecode } => write!(
+                f,
+                "operands of {:?} at position {} run past the end of the function",
+                bytecode, pos
+            ),
+        }
+    }
+}
+
+fn get_bytes(bytes: &[u8], mut start: usize, byte_count: usize) -> u32 {
+    match byte_count {
+        0 => 0,
+        2 => bytes_index::<u16>(bytes, &mut start) as u32,
+        4 => bytes_index::<u32>(bytes, &mut start),
+        _ => panic!("Invalid number for bytes: {}", byte_count),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bytecode::{decode_all, validate, Bytecode, BytecodeError, Instruction};
+
+    #[rustfmt::skip]
+    const PROGRAM: &[u8] = &[
+        Bytecode::LoadConst as u8, 0, 1,
+        Bytecode::Store as u8, 0, 0,
+        Bytecode::LoadValue as u8, 0, 0,
+        Bytecode::LessThan as u8,
+        Bytecode::JumpFalse as u8, 0, 0, 0x1, 0x2,
+        Bytecode::CallOp as u8, 0, 3, 0, 4,
+        Bytecode::Plus as u8,
+    ];
+
+    #[test]
+    fn decoded_matches() {
+        let decoded = decode_all(PROGRAM);
+        assert_eq!(decoded.len(), PROGRAM.len());
+        let mut pos = 0;
+        while pos < PROGRAM.len() {
+            let instruction = Instruction::decode(PROGRAM, pos);
+            assert_eq!(decoded[pos], Option::Some(instruction));
+            let end = pos + instruction.size as usize;
+            assert!(decoded[pos + 1..end].iter().all(Option::is_none));
+            pos += instruction.size as usize;
+        }
+        assert_eq!(pos, PROGRAM.len());
+    }
+
+    #[test]
+    fn decoded_operands() {
+        let decoded = decode_all(PROGRAM);
+        let expected = [
+            (0, Bytecode::LoadConst, 1, 0, 3),
+            (10, Bytecode::JumpFalse, 0x102, 0, 5),
+            (15, Bytecode::CallOp, 3, 4, 5),
+            (20, Bytecode::Plus, 0, 0, 1),
+        ];
+        for (pos, bytecode, bytes_0, bytes_1, size) in expected {
+            let instruction = Instruction {
+                bytecode,
+                bytes_0,
+                bytes_1,
+                size,
+            };
+            assert_eq!(decoded[pos], Option::Some(instruction));
+        }
+    }
+
+    #[test]
+    fn decode_truncated() {
+        let bytes = &PROGRAM[..PROGRAM.len() - 3];
+        let decoded = decode_all(bytes);
+        assert_eq!(decoded.len(), bytes.len());
+        assert!(decoded[10].is_some());
+        assert!(decoded[15..].iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn decode_invalid() {
+        let bytes = &[Bytecode::Plus as u8, 0xFF, Bytecode::Plus as u8];
+        assert_eq!(
+            decode_all(bytes),
+            vec![
+                Option::Some(Instruction::decode(bytes, 0)),
+                Option::None,
+                Option::None,
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_valid() {
+        assert_eq!(validate(PROGRAM), Result::Ok(()));
+        assert_eq!(validate(&[]), Result::Ok(()));
+    }
+
+    #[test]
+    fn validate_invalid() {
+        let bytes = &[Bytecode::Plus as u8, 0xFF, Bytecode::Plus as u8];
+        let error = BytecodeError::InvalidOpcode {
+            pos: 1,
+            value: 0xFF,
+        };
+        assert_eq!(validate(bytes), Result::Err(error));
+    }
+
+    #[test]
+    fn validate_truncated() {
+        let bytes = &PROGRAM[..PROGRAM.len() - 3];
+        let error = BytecodeError::Truncated {
+            pos: 15,
+            bytecode: Bytecode::CallOp,
+        };
+        assert_eq!(validate(bytes), Result::Err(error));
     }
 }
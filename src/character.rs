@@ -1,5 +1,13 @@
+use crate::int_var::IntVar;
+use num::ToPrimitive;
 use std::borrow::Cow;
 
+/// The char with the given code point, or [`None`] if it is out of range or
+/// a surrogate.
+pub fn from_int(value: &IntVar) -> Option<char> {
+    value.to_u32().and_then(char::from_u32)
+}
+
 pub fn repr(value: char) -> Cow<'static, str> {
     match value {
         '\\' => r"\\".into(),
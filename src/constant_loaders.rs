@@ -3,7 +3,7 @@ use crate::builtins::builtin_of;
 use crate::custom_types::bytes::LangBytes;
 use crate::custom_types::range::Range;
 use crate::fmt::FormatArgs;
-use crate::int_tools::bytes_index;
+use crate::int_tools::{bytes_slice, try_bytes_index, Truncated};
 use crate::int_var::IntVar;
 use crate::method::InnerMethod;
 use crate::name_map::NameMap;
@@ -25,151 +25,150 @@ use std::hash::Hash;
 use std::rc::Rc;
 use std::sync::Arc;
 
-pub fn load_std_str(data: &[u8], index: &mut usize) -> String {
-    let size = bytes_index::<u32>(data, index);
+pub fn load_std_str(data: &[u8], index: &mut usize) -> Result<String, Truncated> {
+    let size = try_bytes_index::<u32>(data, index)?;
     let mut value: Vec<u8> = Vec::with_capacity(size as usize);
     for _ in 0..size {
-        let mut char = data[*index];
-        *index += 1;
+        let mut char = try_bytes_index::<u8>(data, index)?;
         value.push(char);
         while char >= 0b1100_0000 {
-            char = data[*index];
-            *index += 1;
+            char = try_bytes_index::<u8>(data, index)?;
             value.push(char);
         }
     }
-    String::from_utf8(value).expect("UTF-8 error")
+    Result::Ok(String::from_utf8(value).expect("UTF-8 error"))
 }
 
-pub fn load_ascii_str(data: &[u8], index: &mut usize) -> Box<[AsciiChar]> {
-    let size = bytes_index::<u32>(data, index);
+pub fn load_ascii_str(data: &[u8], index: &mut usize) -> Result<Box<[AsciiChar]>, Truncated> {
+    let size = try_bytes_index::<u32>(data, index)?;
     let mut value = Vec::with_capacity(size as usize);
     for _ in 0..size {
-        let char = AsciiChar::from_ascii(data[*index])
-            .unwrap_or_else(|_| panic!("Character value {} is invalid ASCII", data[*index]));
-        *index += 1;
+        let byte = try_bytes_index::<u8>(data, index)?;
+        let char = AsciiChar::from_ascii(byte)
+            .unwrap_or_else(|_| panic!("Character value {} is invalid ASCII", byte));
         value.push(char);
     }
-    value.into_boxed_slice()
+    Result::Ok(value.into_boxed_slice())
 }
 
-pub fn load_str(data: &[u8], index: &mut usize) -> Variable {
-    StringVar::from_leak(load_std_str(data, index)).into()
+pub fn load_str(data: &[u8], index: &mut usize) -> Result<Variable, Truncated> {
+    Result::Ok(StringVar::from_leak(load_std_str(data, index)?).into())
 }
 
-pub fn load_builtin(data: &[u8], index: &mut usize) -> Variable {
-    builtin_of(bytes_index::<u32>(data, index) as usize)
+pub fn load_builtin(data: &[u8], index: &mut usize) -> Result<Variable, Truncated> {
+    Result::Ok(builtin_of(try_bytes_index::<u32>(data, index)? as usize))
 }
 
-pub fn load_int(data: &[u8], index: &mut usize) -> Variable {
-    let value = bytes_index::<i32>(data, index);
-    value.into()
+pub fn load_int(data: &[u8], index: &mut usize) -> Result<Variable, Truncated> {
+    let value = try_bytes_index::<i32>(data, index)?;
+    Result::Ok(value.into())
 }
 
-pub fn load_bigint(data: &[u8], index: &mut usize) -> Variable {
-    inner_bigint(data, index).into()
+pub fn load_bigint(data: &[u8], index: &mut usize) -> Result<Variable, Truncated> {
+    Result::Ok(inner_bigint(data, index)?.into())
 }
 
-fn inner_bigint(data: &[u8], index: &mut usize) -> BigInt {
-    let count = bytes_index::<u32>(data, index);
+fn inner_bigint(data: &[u8], index: &mut usize) -> Result<BigInt, Truncated> {
+    let count = try_bytes_index::<u32>(data, index)?;
     let mut values: Vec<u32> = Vec::with_capacity(count as usize);
     for _ in 0..count {
-        values.push(bytes_index::<u32>(data, index));
+        values.push(try_bytes_index::<u32>(data, index)?);
     }
     values.reverse(); // Comes in big-endian, little-endian needed
-    BigInt::new(Sign::Plus, values)
+    Result::Ok(BigInt::new(Sign::Plus, values))
 }
 
-pub fn load_decimal(data: &[u8], index: &mut usize) -> Variable {
-    let count = bytes_index::<u32>(data, index);
-    let scale = bytes_index::<u32>(data, index);
+pub fn load_decimal(data: &[u8], index: &mut usize) -> Result<Variable, Truncated> {
+    let count = try_bytes_index::<u32>(data, index)?;
+    let scale = try_bytes_index::<u32>(data, index)?;
     let mut values: Vec<u32> = Vec::with_capacity(count as usize);
     for _ in 0..count {
-        values.push(bytes_index::<u32>(data, index));
+        values.push(try_bytes_index::<u32>(data, index)?);
     }
-    RationalVar::new(BigRational::new(
-        BigInt::new(Sign::Plus, values),
-        pow(BigInt::from_u64(10).unwrap(), scale as usize),
-    ))
-    .into()
+    Result::Ok(
+        RationalVar::new(BigRational::new(
+            BigInt::new(Sign::Plus, values),
+            pow(BigInt::from_u64(10).unwrap(), scale as usize),
+        ))
+        .into(),
+    )
 }
 
-pub fn function_index(data: &[u8], index: &mut usize) -> u32 {
-    bytes_index::<u32>(data, index)
+pub fn function_index(data: &[u8], index: &mut usize) -> Result<u32, Truncated> {
+    try_bytes_index::<u32>(data, index)
 }
 
-pub fn class_index(data: &[u8], index: &mut usize) -> u32 {
-    bytes_index::<u32>(data, index)
+pub fn class_index(data: &[u8], index: &mut usize) -> Result<u32, Truncated> {
+    try_bytes_index::<u32>(data, index)
 }
 
-pub fn load_bool(data: &[u8], index: &mut usize) -> Variable {
-    let value = data[*index];
-    *index += 1;
-    (value != 0).into()
+pub fn load_bool(data: &[u8], index: &mut usize) -> Result<Variable, Truncated> {
+    let value = try_bytes_index::<u8>(data, index)?;
+    Result::Ok((value != 0).into())
 }
 
-pub fn option_index(data: &[u8], index: &mut usize) -> u16 {
-    bytes_index::<u16>(data, index)
+pub fn option_index(data: &[u8], index: &mut usize) -> Result<u16, Truncated> {
+    try_bytes_index::<u16>(data, index)
 }
 
-pub fn load_bytes(data: &[u8], index: &mut usize) -> Variable {
-    let len = bytes_index::<u32>(data, index) as usize;
-    let byte_arr = &data[*index..*index + len];
-    *index += len;
-    Rc::new(LangBytes::new(byte_arr.to_vec())).into()
+pub fn load_bytes(data: &[u8], index: &mut usize) -> Result<Variable, Truncated> {
+    let len = try_bytes_index::<u32>(data, index)? as usize;
+    let byte_arr = bytes_slice(data, index, len)?;
+    Result::Ok(Rc::new(LangBytes::new(byte_arr.to_vec())).into())
 }
 
-pub fn load_range(data: &[u8], index: &mut usize) -> Variable {
-    let start = get_range_index(data, index);
-    let stop = get_range_index(data, index);
-    let step = get_range_index(data, index);
-    Rc::new(Range::new(
-        start.unwrap_or_else(Zero::zero),
-        stop.unwrap_or_else(Zero::zero),
-        step.unwrap_or_else(One::one),
-    ))
-    .into()
+pub fn load_range(data: &[u8], index: &mut usize) -> Result<Variable, Truncated> {
+    let start = get_range_index(data, index)?;
+    let stop = get_range_index(data, index)?;
+    let step = get_range_index(data, index)?;
+    Result::Ok(
+        Rc::new(Range::new(
+            start.unwrap_or_else(Zero::zero),
+            stop.unwrap_or_else(Zero::zero),
+            step.unwrap_or_else(One::one),
+        ))
+        .into(),
+    )
 }
 
-pub fn load_char(data: &[u8], index: &mut usize) -> Variable {
-    bytes_index::<char>(data, index).into()
+pub fn load_char(data: &[u8], index: &mut usize) -> Result<Variable, Truncated> {
+    Result::Ok(try_bytes_index::<char>(data, index)?.into())
 }
 
-pub fn load_ascii(data: &[u8], index: &mut usize) -> Variable {
-    StringVar::from_leak_ascii(load_ascii_str(data, index)).into()
+pub fn load_ascii(data: &[u8], index: &mut usize) -> Result<Variable, Truncated> {
+    Result::Ok(StringVar::from_leak_ascii(load_ascii_str(data, index)?).into())
 }
 
-pub fn load_fmt_args(data: &[u8], index: &mut usize) -> Variable {
-    Rc::new(FormatArgs::parse(data, index)).into()
+pub fn load_fmt_args(data: &[u8], index: &mut usize) -> Result<Variable, Truncated> {
+    Result::Ok(Rc::new(FormatArgs::parse(data, index)?).into())
 }
 
-pub fn tuple_indices(data: &[u8], index: &mut usize) -> Vec<u16> {
-    let len = bytes_index::<u32>(data, index);
-    (0..len).map(|_| bytes_index(data, index)).collect()
+pub fn tuple_indices(data: &[u8], index: &mut usize) -> Result<Vec<u16>, Truncated> {
+    let len = try_bytes_index::<u32>(data, index)?;
+    (0..len).map(|_| try_bytes_index(data, index)).collect()
 }
 
-fn get_range_index(data: &[u8], index: &mut usize) -> Option<IntVar> {
-    *index += 1;
-    match data[*index - 1] {
+fn get_range_index(data: &[u8], index: &mut usize) -> Result<Option<IntVar>, Truncated> {
+    Result::Ok(match try_bytes_index::<u8>(data, index)? {
         0 => Option::None,
         1 => {
-            let value = bytes_index::<u32>(data, index);
+            let value = try_bytes_index::<u32>(data, index)?;
             Option::Some(IntVar::from(value))
         }
-        2 => Option::Some(inner_bigint(data, index).into()),
+        2 => Option::Some(inner_bigint(data, index)?.into()),
         _ => panic!(),
-    }
+    })
 }
 
-fn get_variables(data: &[u8], index: &mut usize) -> HashSet<Arc<str>> {
-    let byte_size = bytes_index::<u32>(data, index);
+fn get_variables(data: &[u8], index: &mut usize) -> Result<HashSet<Arc<str>>, Truncated> {
+    let byte_size = try_bytes_index::<u32>(data, index)?;
     let mut variables = HashSet::with_capacity(byte_size as usize);
     for _ in 0..byte_size {
-        let name = load_std_str(data, index);
-        bytes_index::<u16>(data, index); // TODO: Get classes properly
+        let name = load_std_str(data, index)?;
+        try_bytes_index::<u16>(data, index)?; // TODO: Get classes properly
         variables.insert(name.into());
     }
-    variables
+    Result::Ok(variables)
 }
 
 fn get_operators(
@@ -178,17 +177,14 @@ fn get_operators(
     file_no: usize,
     index: &mut usize,
     functions: &mut Vec<BaseFunction>,
-) -> HashMap<Operator, StdVarMethod> {
+) -> Result<HashMap<Operator, StdVarMethod>, Truncated> {
     let mut operators: HashMap<Operator, StdVarMethod> = HashMap::new();
-    let byte_size = bytes_index::<u32>(data, index);
+    let byte_size = try_bytes_index::<u32>(data, index)?;
     for _ in 0..byte_size {
-        let op: Operator = FromPrimitive::from_u8(data[*index]).expect("Invalid operator");
-        *index += 1;
-        let is_gen = data[*index];
-        *index += 1;
-        let method_size = bytes_index::<u32>(data, index);
-        let values = data[*index..*index + method_size as usize].to_vec();
-        *index += method_size as usize;
+        let op: Operator =
+            FromPrimitive::from_u8(try_bytes_index::<u8>(data, index)?).expect("Invalid operator");
+        let is_gen = try_bytes_index::<u8>(data, index)?;
+        let values = method_bytes(data, index)?;
         let full_name = format!("{}.{}", cls_name, op.name());
         operators.insert(op, StdVarMethod::Standard(file_no, functions.len() as u32));
         let base_fn = if is_gen != 0 {
@@ -198,7 +194,7 @@ fn get_operators(
         };
         functions.push(base_fn);
     }
-    operators
+    Result::Ok(operators)
 }
 
 fn get_methods(
@@ -207,16 +203,13 @@ fn get_methods(
     file_no: usize,
     index: &mut usize,
     functions: &mut Vec<BaseFunction>,
-) -> HashMap<String, StdVarMethod> {
+) -> Result<HashMap<String, StdVarMethod>, Truncated> {
     let mut methods: HashMap<String, StdVarMethod> = HashMap::new();
-    let byte_size = bytes_index::<u32>(data, index);
+    let byte_size = try_bytes_index::<u32>(data, index)?;
     for _ in 0..byte_size {
-        let name = load_std_str(data, index);
-        let is_gen = data[*index];
-        *index += 1;
-        let method_size = bytes_index::<u32>(data, index);
-        let values = data[*index..*index + method_size as usize].to_vec();
-        *index += method_size as usize;
+        let name = load_std_str(data, index)?;
+        let is_gen = try_bytes_index::<u8>(data, index)?;
+        let values = method_bytes(data, index)?;
         let full_name = format!("{}.{}", cls_name, name);
         methods.insert(
             name,
@@ -229,7 +222,13 @@ fn get_methods(
         };
         functions.push(base_fn);
     }
-    methods
+    Result::Ok(methods)
+}
+
+/// Loads the bytecode of a method, preceded by its length.
+fn method_bytes(data: &[u8], index: &mut usize) -> Result<Vec<u8>, Truncated> {
+    let method_size = try_bytes_index::<u32>(data, index)?;
+    Result::Ok(bytes_slice(data, index, method_size as usize)?.to_vec())
 }
 
 fn get_properties(
@@ -238,17 +237,14 @@ fn get_properties(
     file_no: usize,
     index: &mut usize,
     functions: &mut Vec<BaseFunction>,
-) -> HashMap<String, Property> {
+) -> Result<HashMap<String, Property>, Truncated> {
     let mut properties: HashMap<String, Property> = HashMap::new();
-    let byte_size = bytes_index::<u32>(data, index);
+    let byte_size = try_bytes_index::<u32>(data, index)?;
     for _ in 0..byte_size {
-        let name = load_std_str(data, index);
+        let name = load_std_str(data, index)?;
 
-        assert_eq!(data[*index], 0);
-        *index += 1;
-        let getter_size = bytes_index::<u32>(data, index);
-        let getter = data[*index..*index + getter_size as usize].to_vec();
-        *index += getter_size as usize;
+        assert_eq!(try_bytes_index::<u8>(data, index)?, 0);
+        let getter = method_bytes(data, index)?;
         let getter_index = functions.len();
         functions.push(BaseFunction::new(
             format!("{}.{}$get", cls_name, name),
@@ -256,11 +252,8 @@ fn get_properties(
             getter,
         ));
 
-        assert_eq!(data[*index], 0);
-        *index += 1;
-        let setter_size = bytes_index::<u32>(data, index);
-        let setter = data[*index..*index + setter_size as usize].to_vec();
-        *index += setter_size as usize;
+        assert_eq!(try_bytes_index::<u8>(data, index)?, 0);
+        let setter = method_bytes(data, index)?;
         let setter_index = functions.len();
         functions.push(BaseFunction::new(
             format!("{}.{}$set", cls_name, name),
@@ -277,7 +270,7 @@ fn get_properties(
             )),
         );
     }
-    properties
+    Result::Ok(properties)
 }
 
 fn merge_maps_union<T>(
@@ -303,14 +296,14 @@ fn std_to_union<T>(val: StdVarMethod) -> InnerMethod<T> {
     }
 }
 
-fn get_names(data: &[u8], index: &mut usize) -> Option<Vec<String>> {
-    let is_union = data[*index] != 0;
-    *index += 1;
+fn get_names(data: &[u8], index: &mut usize) -> Result<Option<Vec<String>>, Truncated> {
+    let is_union = try_bytes_index::<u8>(data, index)? != 0;
     if is_union {
-        let vec_size = bytes_index::<u32>(data, index);
-        Option::Some((0..vec_size).map(|_| load_std_str(data, index)).collect())
+        let vec_size = try_bytes_index::<u32>(data, index)?;
+        let names = (0..vec_size).map(|_| load_std_str(data, index));
+        Result::Ok(Option::Some(names.collect::<Result<_, _>>()?))
     } else {
-        Option::None
+        Result::Ok(Option::None)
     }
 }
 
@@ -319,23 +312,23 @@ pub fn load_class(
     data: &[u8],
     index: &mut usize,
     functions: &mut Vec<BaseFunction>,
-) -> Type {
-    let name = load_std_str(data, index);
-    let supers = (0..bytes_index::<u32>(data, index))
-        .map(|_| bytes_index::<u32>(data, index))
-        .collect();
-    let _generic_size = bytes_index::<u16>(data, index);
+) -> Result<Type, Truncated> {
+    let name = load_std_str(data, index)?;
+    let supers = (0..try_bytes_index::<u32>(data, index)?)
+        .map(|_| try_bytes_index::<u32>(data, index))
+        .collect::<Result<_, _>>()?;
+    let _generic_size = try_bytes_index::<u16>(data, index)?;
     // assert_eq!(_generic_size, 0);
-    let names = get_names(data, index);
-    let variables = get_variables(data, index);
-    get_variables(data, index);
-    let operators = get_operators(&*name, data, file_no, index, functions);
-    let static_operators = get_operators(&*name, data, file_no, index, functions);
-    let methods = get_methods(&*name, data, file_no, index, functions);
-    let static_methods = get_methods(&*name, data, file_no, index, functions);
-    let properties = get_properties(&*name, data, file_no, index, functions);
-
-    match names {
+    let names = get_names(data, index)?;
+    let variables = get_variables(data, index)?;
+    get_variables(data, index)?;
+    let operators = get_operators(&*name, data, file_no, index, functions)?;
+    let static_operators = get_operators(&*name, data, file_no, index, functions)?;
+    let methods = get_methods(&*name, data, file_no, index, functions)?;
+    let static_methods = get_methods(&*name, data, file_no, index, functions)?;
+    let properties = get_properties(&*name, data, file_no, index, functions)?;
+
+    Result::Ok(match names {
         Option::None => Type::new_std(
             StringVar::from_leak(name),
             file_no,
@@ -355,7 +348,7 @@ pub fn load_class(
             merge_maps_union(static_operators, static_methods),
             properties,
         ),
-    }
+    })
 }
 
 #[cfg(test)]
@@ -363,6 +356,7 @@ mod test {
     use crate::constant_loaders::{
         inner_bigint, load_ascii_str, load_bool, load_decimal, load_std_str,
     };
+    use crate::int_tools::Truncated;
     use crate::rational_var::RationalVar;
     use ascii::AsciiChar;
     use num::{BigInt, BigRational, One};
@@ -376,21 +370,27 @@ mod test {
     #[test]
     fn load_str() {
         let mut index = 0;
-        assert_eq!(&load_std_str(&A_STR_BYTES, &mut index), "a");
+        assert_eq!(&load_std_str(&A_STR_BYTES, &mut index).unwrap(), "a");
         assert_eq!(index, A_STR_BYTES.len());
     }
 
     #[test]
     fn load_ascii() {
         let mut index = 0;
-        assert_eq!(&*load_ascii_str(&A_STR_BYTES, &mut index), &[AsciiChar::a]);
+        assert_eq!(
+            &*load_ascii_str(&A_STR_BYTES, &mut index).unwrap(),
+            &[AsciiChar::a]
+        );
         assert_eq!(index, A_STR_BYTES.len());
     }
 
     #[test]
     fn bigint() {
         let mut index = 0;
-        assert_eq!(inner_bigint(&BIG_ONE_BYTES, &mut index), BigInt::one());
+        assert_eq!(
+            inner_bigint(&BIG_ONE_BYTES, &mut index),
+            Result::Ok(BigInt::one())
+        );
         assert_eq!(index, BIG_ONE_BYTES.len());
     }
 
@@ -399,7 +399,7 @@ mod test {
         let mut index = 0;
         assert_eq!(
             load_decimal(&TENTH_BYTES, &mut index),
-            RationalVar::new(BigRational::new(BigInt::one(), BigInt::from(10))).into()
+            Result::Ok(RationalVar::new(BigRational::new(BigInt::one(), BigInt::from(10))).into())
         );
         assert_eq!(index, TENTH_BYTES.len());
     }
@@ -407,10 +407,26 @@ mod test {
     #[test]
     fn parse_bool() {
         let mut index = 0;
-        assert_eq!(load_bool(&[1], &mut index), true.into());
+        assert_eq!(load_bool(&[1], &mut index), Result::Ok(true.into()));
         assert_eq!(index, 1);
         index = 0;
-        assert_eq!(load_bool(&[0], &mut index), false.into());
+        assert_eq!(load_bool(&[0], &mut index), Result::Ok(false.into()));
         assert_eq!(index, 1);
     }
+
+    #[test]
+    fn truncated() {
+        let mut index = 0;
+        assert_eq!(
+            load_std_str(&A_STR_BYTES[..4], &mut index),
+            Result::Err(Truncated)
+        );
+        index = 0;
+        assert_eq!(
+            inner_bigint(&BIG_ONE_BYTES[..6], &mut index),
+            Result::Err(Truncated)
+        );
+        index = 0;
+        assert_eq!(load_bool(&[], &mut index), Result::Err(Truncated));
+    }
 }
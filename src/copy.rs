@@ -0,0 +1,179 @@
+//! Deep copying of values.
+
+use crate::custom_types::dict::Dict;
+use crate::custom_types::list::List;
+use crate::custom_types::set::Set;
+use crate::custom_var::downcast_var;
+use crate::runtime::Runtime;
+use crate::tuple::LangTuple;
+use crate::variable::{InnerVar, OptionVar, Variable};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// The copies made so far during a deep copy, keyed by the identity of the
+/// original container.
+///
+/// Containers are registered before their contents are copied, so a value
+/// which contains itself is copied into a value which contains its copy.
+#[derive(Debug, Default)]
+pub struct CopyMap {
+    copies: HashMap<usize, Variable>,
+}
+
+impl CopyMap {
+    pub fn new() -> CopyMap {
+        CopyMap::default()
+    }
+
+    /// Records `copy` as the copy of `original`.
+    ///
+    /// If `original` has already been copied, the existing copy is returned
+    /// and `copy` is discarded.
+    pub fn register<T>(&mut self, original: &Rc<T>, copy: Variable) -> Option<Variable> {
+        let key = Rc::as_ptr(original) as *const () as usize;
+        match self.copies.get(&key) {
+            Option::Some(existing) => Option::Some(existing.clone()),
+            Option::None => {
+                self.copies.insert(key, copy);
+                Option::None
+            }
+        }
+    }
+
+    pub fn copy(&mut self, value: Variable, runtime: &mut Runtime) -> Result<Variable, ()> {
+        match value {
+            Variable::Normal(InnerVar::Tuple(tup)) => {
+                let mut values = Vec::with_capacity(tup.len());
+                for value in &tup {
+                    values.push(self.copy(value.clone(), runtime)?);
+                }
+                Result::Ok(LangTuple::from_vec(values).into())
+            }
+            Variable::Option(OptionVar {
+                depth,
+                value: Option::Some(value),
+            }) => match self.copy(Variable::Normal(value), runtime)? {
+                Variable::Normal(value) => Result::Ok(Variable::Option(OptionVar {
+                    depth,
+                    value: Option::Some(value),
+                })),
+                Variable::Option(_) => unreachable!(),
+            },
+            value => self.copy_container(value, runtime),
+        }
+    }
+
+    fn copy_container(&mut self, value: Variable, runtime: &mut Runtime) -> Result<Variable, ()> {
+        let value = match downcast_var::<List>(value) {
+            Result::Ok(list) => return list.deep_copy(self, runtime),
+            Result::Err(value) => value,
+        };
+        let value = match downcast_var::<Dict>(value) {
+            Result::Ok(dict) => return dict.deep_copy(self, runtime),
+            Result::Err(value) => value,
+        };
+        match downcast_var::<Set>(value) {
+            Result::Ok(set) => set.deep_copy(self, runtime),
+            Result::Err(value) => Result::Ok(value),
+        }
+    }
+}
+
+/// Copies `value`, recursively copying any lists, dicts, sets, tuples and
+/// options it contains.
+///
+/// All other values are immutable or have identity semantics, and so are
+/// shared between the original and the copy.
+pub fn deep_copy(value: Variable, runtime: &mut Runtime) -> Result<Variable, ()> {
+    CopyMap::new().copy(value, runtime)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::copy::deep_copy;
+    use crate::custom_types::dict::Dict;
+    use crate::custom_types::list::List;
+    use crate::custom_var::downcast_var;
+    use crate::runtime::Runtime;
+    use crate::std_type::Type;
+    use crate::variable::Variable;
+    use std::rc::Rc;
+
+    fn list_of(values: Vec<Variable>) -> Rc<List> {
+        List::from_values(Type::Object, values)
+    }
+
+    fn as_list(value: Variable) -> Rc<List> {
+        downcast_var::<List>(value).unwrap_or_else(|_| panic!("Expected a list"))
+    }
+
+    fn call_attr(value: Variable, name: &str, args: Vec<Variable>) -> Result<Variable, ()> {
+        Runtime::test(|runtime| {
+            runtime.call_attr(value, name, args)?;
+            let result = runtime.pop_return();
+            runtime.return_1(result)
+        })
+    }
+
+    fn copy_deep(value: Variable) -> Result<Variable, ()> {
+        Runtime::test(|runtime| {
+            let result = deep_copy(value, runtime)?;
+            runtime.return_1(result)
+        })
+    }
+
+    #[test]
+    fn shallow_copy_shares() -> Result<(), ()> {
+        let inner = list_of(vec![1.into()]);
+        let outer = list_of(vec![inner.clone().into()]);
+        let copied = as_list(call_attr(outer.clone().into(), "copy", Vec::new())?);
+        assert!(!Rc::ptr_eq(&copied, &outer));
+        assert!(Rc::ptr_eq(&as_list(copied.values()[0].clone()), &inner));
+        Result::Ok(())
+    }
+
+    #[test]
+    fn deep_copy_list() -> Result<(), ()> {
+        let inner = list_of(vec![1.into()]);
+        let outer = list_of(vec![inner.clone().into(), inner.clone().into()]);
+        let copied = as_list(copy_deep(outer.clone().into())?);
+        let first = as_list(copied.values()[0].clone());
+        let second = as_list(copied.values()[1].clone());
+        assert!(!Rc::ptr_eq(&first, &inner));
+        assert!(Rc::ptr_eq(&first, &second));
+        assert_eq!(first.values().to_vec(), vec![1.into()]);
+        Result::Ok(())
+    }
+
+    #[test]
+    fn deep_copy_cycle() -> Result<(), ()> {
+        let list = list_of(Vec::new());
+        let copied = Runtime::test(|runtime| {
+            runtime.call_attr(list.clone().into(), "add", vec![list.clone().into()])?;
+            let copied = deep_copy(list.clone().into(), runtime)?;
+            runtime.call_attr(list.clone().into(), "clear", Vec::new())?;
+            runtime.return_1(copied)
+        })?;
+        let copied = as_list(copied);
+        assert!(!Rc::ptr_eq(&copied, &list));
+        assert!(Rc::ptr_eq(&as_list(copied.values()[0].clone()), &copied));
+        call_attr(copied.into(), "pop", Vec::new())?;
+        Result::Ok(())
+    }
+
+    #[test]
+    fn deep_copy_dict() -> Result<(), ()> {
+        let inner = list_of(vec![1.into()]);
+        let copied = Runtime::test(|runtime| {
+            let dict = Dict::from_args(vec![1.into()], vec![inner.clone().into()], runtime)?;
+            let copied = deep_copy(dict.into(), runtime)?;
+            let copied = downcast_var::<Dict>(copied).unwrap_or_else(|_| panic!());
+            let value = copied.get_value(1.into(), runtime)?.unwrap();
+            runtime.return_1(value)
+        })?;
+        let copied = as_list(copied);
+        assert!(!Rc::ptr_eq(&copied, &inner));
+        assert_eq!(copied.values().to_vec(), vec![1.into()]);
+        Result::Ok(())
+    }
+}
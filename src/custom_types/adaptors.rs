@@ -0,0 +1,632 @@
+use crate::custom_types::list::List;
+use crate::looping::{self, IterAttrs, IterResult, NativeIterator};
+use crate::runtime::Runtime;
+use crate::std_type::Type;
+use crate::tuple::LangTuple;
+use crate::variable::{FnResult, Variable};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+/// A lazy iterator applying a function to each value of another iterator.
+#[derive(Debug)]
+pub struct Map {
+    func: Variable,
+    iterable: looping::Iterator,
+}
+
+/// A lazy iterator yielding only the values of another iterator for which a
+/// predicate is true.
+#[derive(Debug)]
+pub struct Filter {
+    predicate: Variable,
+    iterable: looping::Iterator,
+}
+
+/// A lazy iterator yielding the values of another iterator until a predicate
+/// first fails.
+#[derive(Debug)]
+pub struct TakeWhile {
+    predicate: Variable,
+    iterable: looping::Iterator,
+    done: Cell<bool>,
+}
+
+/// A lazy iterator skipping the leading values of another iterator for which
+/// a predicate holds, and yielding everything after.
+#[derive(Debug)]
+pub struct DropWhile {
+    predicate: Variable,
+    iterable: looping::Iterator,
+    dropping: Cell<bool>,
+}
+
+/// A lazy iterator yielding all the values of each of several iterators in
+/// turn.
+#[derive(Debug)]
+pub struct Chain {
+    iterables: Vec<looping::Iterator>,
+    index: Cell<usize>,
+}
+
+/// A lazy iterator yielding all the values of each iterable yielded by another
+/// iterator.
+#[derive(Debug)]
+pub struct Flatten {
+    iterable: looping::Iterator,
+    current: RefCell<Option<looping::Iterator>>,
+}
+
+/// A lazy iterator yielding a `(key, values)` tuple for each run of
+/// consecutive values of another iterator whose keys are equal.
+///
+/// Only consecutive values are grouped together, so the input should be
+/// sorted by key if each key should appear only once.
+#[derive(Debug)]
+pub struct GroupBy {
+    key_fn: Variable,
+    iterable: looping::Iterator,
+    next_start: RefCell<Option<(Variable, Variable)>>,
+}
+
+fn test_predicate(predicate: &Variable, val: Variable, runtime: &mut Runtime) -> Result<bool, ()> {
+    predicate.clone().call((vec![val], runtime))?;
+    runtime.pop_return().into_bool(runtime)
+}
+
+impl Map {
+    pub fn new(func: Variable, iterable: looping::Iterator) -> Rc<Map> {
+        Rc::new(Map { func, iterable })
+    }
+
+    fn inner_next(&self, runtime: &mut Runtime) -> Result<Option<Variable>, ()> {
+        match self.iterable.next(runtime)?.take_first() {
+            Option::Some(val) => {
+                self.func.clone().call((vec![val], runtime))?;
+                Result::Ok(Option::Some(runtime.pop_return()))
+            }
+            Option::None => Result::Ok(Option::None),
+        }
+    }
+
+    fn create(_args: Vec<Variable>, _runtime: &mut Runtime) -> FnResult {
+        unimplemented!()
+    }
+}
+
+impl Filter {
+    pub fn new(predicate: Variable, iterable: looping::Iterator) -> Rc<Filter> {
+        Rc::new(Filter {
+            predicate,
+            iterable,
+        })
+    }
+
+    fn inner_next(&self, runtime: &mut Runtime) -> Result<Option<Variable>, ()> {
+        while let Option::Some(val) = self.iterable.next(runtime)?.take_first() {
+            if test_predicate(&self.predicate, val.clone(), runtime)? {
+                return Result::Ok(Option::Some(val));
+            }
+        }
+        Result::Ok(Option::None)
+    }
+
+    fn create(_args: Vec<Variable>, _runtime: &mut Runtime) -> FnResult {
+        unimplemented!()
+    }
+}
+
+impl TakeWhile {
+    pub fn new(predicate: Variable, iterable: looping::Iterator) -> Rc<TakeWhile> {
+        Rc::new(TakeWhile {
+            predicate,
+            iterable,
+            done: Cell::new(false),
+        })
+    }
+
+    fn inner_next(&self, runtime: &mut Runtime) -> Result<Option<Variable>, ()> {
+        if self.done.get() {
+            return Result::Ok(Option::None);
+        }
+        match self.iterable.next(runtime)?.take_first() {
+            Option::Some(val) => {
+                if test_predicate(&self.predicate, val.clone(), runtime)? {
+                    Result::Ok(Option::Some(val))
+                } else {
+                    // The failing value is consumed and discarded
+                    self.done.set(true);
+                    Result::Ok(Option::None)
+                }
+            }
+            Option::None => {
+                self.done.set(true);
+                Result::Ok(Option::None)
+            }
+        }
+    }
+
+    fn create(_args: Vec<Variable>, _runtime: &mut Runtime) -> FnResult {
+        unimplemented!()
+    }
+}
+
+impl DropWhile {
+    pub fn new(predicate: Variable, iterable: looping::Iterator) -> Rc<DropWhile> {
+        Rc::new(DropWhile {
+            predicate,
+            iterable,
+            dropping: Cell::new(true),
+        })
+    }
+
+    fn inner_next(&self, runtime: &mut Runtime) -> Result<Option<Variable>, ()> {
+        if !self.dropping.get() {
+            return Result::Ok(self.iterable.next(runtime)?.take_first());
+        }
+        while let Option::Some(val) = self.iterable.next(runtime)?.take_first() {
+            if !test_predicate(&self.predicate, val.clone(), runtime)? {
+                self.dropping.set(false);
+                return Result::Ok(Option::Some(val));
+            }
+        }
+        Result::Ok(Option::None)
+    }
+
+    fn create(_args: Vec<Variable>, _runtime: &mut Runtime) -> FnResult {
+        unimplemented!()
+    }
+}
+
+impl Chain {
+    pub fn new(iterables: Vec<looping::Iterator>) -> Rc<Chain> {
+        Rc::new(Chain {
+            iterables,
+            index: Cell::new(0),
+        })
+    }
+
+    fn inner_next(&self, runtime: &mut Runtime) -> Result<Option<Variable>, ()> {
+        while let Option::Some(iter) = self.iterables.get(self.index.get()) {
+            match iter.next(runtime)?.take_first() {
+                x @ Option::Some(_) => return Result::Ok(x),
+                Option::None => self.index.set(self.index.get() + 1),
+            }
+        }
+        Result::Ok(Option::None)
+    }
+
+    fn create(_args: Vec<Variable>, _runtime: &mut Runtime) -> FnResult {
+        unimplemented!()
+    }
+}
+
+impl Flatten {
+    pub fn new(iterable: looping::Iterator) -> Rc<Flatten> {
+        Rc::new(Flatten {
+            iterable,
+            current: RefCell::new(Option::None),
+        })
+    }
+
+    fn inner_next(&self, runtime: &mut Runtime) -> Result<Option<Variable>, ()> {
+        loop {
+            // Cloned so the cell isn't borrowed while the runtime is running
+            let current = self.current.borrow().clone();
+            if let Option::Some(iter) = current {
+                if let x @ Option::Some(_) = iter.next(runtime)?.take_first() {
+                    return Result::Ok(x);
+                }
+            }
+            match self.iterable.next(runtime)?.take_first() {
+                Option::Some(val) => *self.current.borrow_mut() = Option::Some(val.iter(runtime)?),
+                Option::None => {
+                    *self.current.borrow_mut() = Option::None;
+                    return Result::Ok(Option::None);
+                }
+            }
+        }
+    }
+
+    fn create(_args: Vec<Variable>, _runtime: &mut Runtime) -> FnResult {
+        unimplemented!()
+    }
+}
+
+impl GroupBy {
+    pub fn new(key_fn: Variable, iterable: looping::Iterator) -> Rc<GroupBy> {
+        Rc::new(GroupBy {
+            key_fn,
+            iterable,
+            next_start: RefCell::new(Option::None),
+        })
+    }
+
+    fn key_of(&self, val: Variable, runtime: &mut Runtime) -> Result<Variable, ()> {
+        self.key_fn.clone().call((vec![val], runtime))?;
+        Result::Ok(runtime.pop_return())
+    }
+
+    fn inner_next(&self, runtime: &mut Runtime) -> Result<Option<Variable>, ()> {
+        // The first value of each group (after the first) is read while
+        // finishing the previous one, so it is saved along with its key
+        let start = self.next_start.borrow_mut().take();
+        let (key, first) = match start {
+            Option::Some(x) => x,
+            Option::None => match self.iterable.next(runtime)?.take_first() {
+                Option::Some(val) => (self.key_of(val.clone(), runtime)?, val),
+                Option::None => return Result::Ok(Option::None),
+            },
+        };
+        let mut values = vec![first];
+        while let Option::Some(val) = self.iterable.next(runtime)?.take_first() {
+            let val_key = self.key_of(val.clone(), runtime)?;
+            if key.clone().equals(val_key.clone(), runtime)? {
+                values.push(val);
+            } else {
+                *self.next_start.borrow_mut() = Option::Some((val_key, val));
+                break;
+            }
+        }
+        let group = List::from_values(Type::Object, values);
+        Result::Ok(Option::Some(
+            LangTuple::from_vec(vec![key, group.into()]).into(),
+        ))
+    }
+
+    fn create(_args: Vec<Variable>, _runtime: &mut Runtime) -> FnResult {
+        unimplemented!()
+    }
+}
+
+impl IterAttrs for Map {
+    fn next_fn(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert!(args.is_empty());
+        let result = self.inner_next(runtime)?;
+        runtime.return_1(result.into())
+    }
+
+    fn get_type() -> Type {
+        custom_class!(Map, create, "Map")
+    }
+}
+
+impl NativeIterator for Map {
+    fn next(self: Rc<Self>, runtime: &mut Runtime) -> IterResult {
+        Result::Ok(self.inner_next(runtime)?.into())
+    }
+}
+
+impl IterAttrs for Filter {
+    fn next_fn(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert!(args.is_empty());
+        let result = self.inner_next(runtime)?;
+        runtime.return_1(result.into())
+    }
+
+    fn get_type() -> Type {
+        custom_class!(Filter, create, "Filter")
+    }
+}
+
+impl NativeIterator for Filter {
+    fn next(self: Rc<Self>, runtime: &mut Runtime) -> IterResult {
+        Result::Ok(self.inner_next(runtime)?.into())
+    }
+}
+
+impl IterAttrs for TakeWhile {
+    fn next_fn(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert!(args.is_empty());
+        let result = self.inner_next(runtime)?;
+        runtime.return_1(result.into())
+    }
+
+    fn get_type() -> Type {
+        custom_class!(TakeWhile, create, "TakeWhile")
+    }
+}
+
+impl NativeIterator for TakeWhile {
+    fn next(self: Rc<Self>, runtime: &mut Runtime) -> IterResult {
+        Result::Ok(self.inner_next(runtime)?.into())
+    }
+}
+
+impl IterAttrs for DropWhile {
+    fn next_fn(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert!(args.is_empty());
+        let result = self.inner_next(runtime)?;
+        runtime.return_1(result.into())
+    }
+
+    fn get_type() -> Type {
+        custom_class!(DropWhile, create, "DropWhile")
+    }
+}
+
+impl NativeIterator for DropWhile {
+    fn next(self: Rc<Self>, runtime: &mut Runtime) -> IterResult {
+        Result::Ok(self.inner_next(runtime)?.into())
+    }
+}
+
+impl IterAttrs for Chain {
+    fn next_fn(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert!(args.is_empty());
+        let result = self.inner_next(runtime)?;
+        runtime.return_1(result.into())
+    }
+
+    fn get_type() -> Type {
+        custom_class!(Chain, create, "Chain")
+    }
+}
+
+impl NativeIterator for Chain {
+    fn next(self: Rc<Self>, runtime: &mut Runtime) -> IterResult {
+        Result::Ok(self.inner_next(runtime)?.into())
+    }
+}
+
+impl IterAttrs for Flatten {
+    fn next_fn(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert!(args.is_empty());
+        let result = self.inner_next(runtime)?;
+        runtime.return_1(result.into())
+    }
+
+    fn get_type() -> Type {
+        custom_class!(Flatten, create, "Flatten")
+    }
+}
+
+impl NativeIterator for Flatten {
+    fn next(self: Rc<Self>, runtime: &mut Runtime) -> IterResult {
+        Result::Ok(self.inner_next(runtime)?.into())
+    }
+}
+
+impl IterAttrs for GroupBy {
+    fn next_fn(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert!(args.is_empty());
+        let result = self.inner_next(runtime)?;
+        runtime.return_1(result.into())
+    }
+
+    fn get_type() -> Type {
+        custom_class!(GroupBy, create, "GroupBy")
+    }
+}
+
+impl NativeIterator for GroupBy {
+    fn next(self: Rc<Self>, runtime: &mut Runtime) -> IterResult {
+        Result::Ok(self.inner_next(runtime)?.into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::custom_types::adaptors::{
+        Chain, DropWhile, Filter, Flatten, GroupBy, Map, TakeWhile,
+    };
+    use crate::custom_types::exceptions::value_error;
+    use crate::custom_types::list::List;
+    use crate::custom_types::range::Range;
+    use crate::custom_var::downcast_var;
+    use crate::first;
+    use crate::function::Function;
+    use crate::int_var::IntVar;
+    use crate::looping::{self, NativeIterator};
+    use crate::runtime::Runtime;
+    use crate::std_type::Type;
+    use crate::tuple::LangTuple;
+    use crate::variable::{FnResult, Variable};
+    use num::{Signed, Zero};
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    thread_local! {
+        static CALL_COUNT: Cell<usize> = const { Cell::new(0) };
+    }
+
+    fn double(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        CALL_COUNT.with(|x| x.set(x.get() + 1));
+        let value = IntVar::from(first(args));
+        runtime.return_1((&value + &value).into())
+    }
+
+    fn is_multiple_of_4(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        let value = IntVar::from(first(args));
+        runtime.return_1((&value % &IntVar::from(4)).is_zero().into())
+    }
+
+    fn less_than_3(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        let value = IntVar::from(first(args));
+        runtime.return_1((value < IntVar::from(3)).into())
+    }
+
+    fn tens(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        let value = IntVar::from(first(args));
+        runtime.return_1((&value / &IntVar::from(10)).into())
+    }
+
+    fn no_negatives(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        let value = IntVar::from(first(args));
+        if value.is_negative() {
+            runtime.throw_quick(value_error(), "Negative value")
+        } else {
+            runtime.return_1(value.into())
+        }
+    }
+
+    fn range(start: i32, stop: i32, runtime: &mut Runtime) -> looping::Iterator {
+        let range = Range::new(start.into(), stop.into(), 1.into());
+        Variable::from(Rc::new(range)).iter(runtime).unwrap()
+    }
+
+    fn list(values: &[i32], runtime: &mut Runtime) -> looping::Iterator {
+        let values = values.iter().map(|&x| IntVar::from(x).into()).collect();
+        Variable::from(List::from_values(Type::Bigint, values))
+            .iter(runtime)
+            .unwrap()
+    }
+
+    fn ints(values: &[i32]) -> Vec<IntVar> {
+        values.iter().map(|&x| x.into()).collect()
+    }
+
+    /// Runs all of the values out of the iterator created by `make`, and then
+    /// calls `next` once more to check that the iterator stays exhausted.
+    fn collect<F, T>(make: F) -> Result<Vec<IntVar>, ()>
+    where
+        F: FnOnce(&mut Runtime) -> Rc<T>,
+        T: NativeIterator,
+    {
+        let mut values = Vec::new();
+        Runtime::test(|runtime| {
+            let iter = make(runtime);
+            while let Option::Some(x) = iter.clone().next(runtime)?.take_first() {
+                values.push(IntVar::from(x));
+            }
+            assert_eq!(iter.next(runtime)?.take_first(), Option::None);
+            runtime.return_1(Variable::null())
+        })?;
+        Result::Ok(values)
+    }
+
+    #[test]
+    fn map_filter() {
+        let result = collect(|runtime| {
+            let map = Map::new(Function::Native(double).into(), range(0, 6, runtime));
+            Filter::new(Function::Native(is_multiple_of_4).into(), map.into())
+        });
+        assert_eq!(result, Result::Ok(ints(&[0, 4, 8])));
+    }
+
+    #[test]
+    fn map_lazy() {
+        CALL_COUNT.with(|x| x.set(0));
+        let result = Runtime::test(|runtime| {
+            let map = Map::new(Function::Native(double).into(), range(0, 100, runtime));
+            let filter = Filter::new(Function::Native(is_multiple_of_4).into(), map.into());
+            assert_eq!(CALL_COUNT.with(Cell::get), 0);
+            let first = filter.clone().next(runtime)?.take_first();
+            assert_eq!(first, Option::Some(IntVar::from(0).into()));
+            assert_eq!(CALL_COUNT.with(Cell::get), 1);
+            let second = filter.next(runtime)?.take_first();
+            assert_eq!(second, Option::Some(IntVar::from(4).into()));
+            runtime.return_1(CALL_COUNT.with(Cell::get).into())
+        });
+        assert_eq!(result, Result::Ok(3.into()));
+    }
+
+    #[test]
+    fn map_throws() {
+        let result = Runtime::test(|runtime| {
+            let map = Map::new(Function::Native(no_negatives).into(), range(-1, 1, runtime));
+            assert_eq!(map.next(runtime), Result::Err(()));
+            let exc = runtime.pop_err()?;
+            runtime.return_1(exc.get_type().into())
+        });
+        assert_eq!(result, Result::Ok(value_error().into()));
+    }
+
+    #[test]
+    fn take_while() {
+        // The failing element (3) is consumed, but never yielded
+        let result = collect(|runtime| {
+            TakeWhile::new(Function::Native(less_than_3).into(), range(0, 6, runtime))
+        });
+        assert_eq!(result, Result::Ok(ints(&[0, 1, 2])));
+    }
+
+    #[test]
+    fn drop_while() {
+        // The first failing element (3) is the first one yielded
+        let result = collect(|runtime| {
+            DropWhile::new(Function::Native(less_than_3).into(), range(0, 6, runtime))
+        });
+        assert_eq!(result, Result::Ok(ints(&[3, 4, 5])));
+    }
+
+    #[test]
+    fn drop_while_stops_dropping() {
+        let result = collect(|runtime| {
+            DropWhile::new(
+                Function::Native(less_than_3).into(),
+                list(&[0, 5, 1], runtime),
+            )
+        });
+        assert_eq!(result, Result::Ok(ints(&[5, 1])));
+    }
+
+    #[test]
+    fn chain_list_range() {
+        let result = collect(|runtime| {
+            let iterables = vec![
+                list(&[5, 6], runtime),
+                list(&[], runtime),
+                range(0, 3, runtime),
+                range(0, 0, runtime),
+            ];
+            Chain::new(iterables)
+        });
+        assert_eq!(result, Result::Ok(ints(&[5, 6, 0, 1, 2])));
+        assert_eq!(collect(|_| Chain::new(vec![])), Result::Ok(vec![]));
+    }
+
+    #[test]
+    fn flatten_lists() {
+        let result = collect(|runtime| {
+            let lists = [&[1, 2][..], &[], &[3], &[], &[4, 5]]
+                .iter()
+                .map(|values| {
+                    let values = values.iter().map(|&x| IntVar::from(x).into()).collect();
+                    List::from_values(Type::Bigint, values).into()
+                })
+                .collect();
+            let outer = Variable::from(List::from_values(Type::Object, lists));
+            Flatten::new(outer.iter(runtime).unwrap())
+        });
+        assert_eq!(result, Result::Ok(ints(&[1, 2, 3, 4, 5])));
+    }
+
+    fn groups(values: &[i32]) -> Vec<(IntVar, Vec<IntVar>)> {
+        let mut result = Vec::new();
+        Runtime::test(|runtime| {
+            let group_by = GroupBy::new(Function::Native(tens).into(), list(values, runtime));
+            while let Option::Some(x) = group_by.clone().next(runtime)?.take_first() {
+                let pair = LangTuple::from(x);
+                let group = downcast_var::<List>(pair[1].clone()).unwrap();
+                let group = group.values().iter().cloned().map(IntVar::from).collect();
+                result.push((IntVar::from(pair[0].clone()), group));
+            }
+            assert_eq!(group_by.next(runtime)?.take_first(), Option::None);
+            runtime.return_1(Variable::null())
+        })
+        .unwrap();
+        result
+    }
+
+    #[test]
+    fn group_by_sorted() {
+        let expected = vec![
+            (IntVar::from(0), ints(&[1, 2])),
+            (IntVar::from(1), ints(&[11, 12, 13])),
+            (IntVar::from(2), ints(&[25])),
+        ];
+        assert_eq!(groups(&[1, 2, 11, 12, 13, 25]), expected);
+        assert_eq!(groups(&[]), vec![]);
+    }
+
+    #[test]
+    fn group_by_consecutive_runs() {
+        let expected = vec![
+            (IntVar::from(0), ints(&[1])),
+            (IntVar::from(1), ints(&[11])),
+            (IntVar::from(0), ints(&[2, 3])),
+            (IntVar::from(1), ints(&[12])),
+        ];
+        assert_eq!(groups(&[1, 11, 2, 3, 12]), expected);
+    }
+}
@@ -1,7 +1,7 @@
 use crate::custom_types::exceptions::{index_error, value_error};
-use crate::custom_types::join_values;
 use crate::custom_types::list::List;
 use crate::custom_types::range::Range;
+use crate::custom_types::{elements_equal, join_values};
 use crate::custom_var::{downcast_var, CustomVar};
 use crate::int_var::{normalize, IntVar};
 use crate::looping::{self, TypicalIterator};
@@ -12,7 +12,7 @@ use crate::runtime::Runtime;
 use crate::std_type::Type;
 use crate::string_var::{MaybeString, StringVar};
 use crate::variable::{FnResult, Variable};
-use crate::{first, first_n};
+use crate::{check_args, first, first_n};
 use ascii::{AsciiChar, AsciiStr};
 use num::ToPrimitive;
 use once_cell::sync::Lazy;
@@ -54,6 +54,13 @@ impl Array {
         }
     }
 
+    fn attr_fn(name: &str) -> Option<NativeMethod<Rc<Array>>> {
+        Option::Some(match name {
+            "elementsEqual" => Self::elements_equal,
+            _ => return Option::None,
+        })
+    }
+
     fn index(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
         let values = self.vars.borrow();
         match normalize(values.len(), first(args).into()) {
@@ -80,15 +87,10 @@ impl Array {
 
     fn str(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
         debug_assert!(args.is_empty());
-        let value = self.str_value(runtime)?;
+        let value = self.repr_value(runtime)?;
         runtime.return_1(value.into())
     }
 
-    fn str_value(&self, runtime: &mut Runtime) -> Result<StringVar, ()> {
-        let value = join_values(&**self.vars.borrow(), |x| x.str(runtime))?;
-        Result::Ok(Self::surround(value).into())
-    }
-
     fn repr(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
         debug_assert!(args.is_empty());
         let value = self.repr_value(runtime)?;
@@ -169,6 +171,13 @@ impl Array {
         runtime.return_1(Rc::new(ArrayIter::new(new_vec)).into())
     }
 
+    fn elements_equal(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        check_args("Array.elementsEqual", &args, 1, runtime)?;
+        let values = self.vars.borrow().clone();
+        let result = elements_equal(&values, first(args), runtime)?;
+        runtime.return_1(result.into())
+    }
+
     fn arr_eq(first: &[Variable], second: &[Variable], runtime: &mut Runtime) -> Result<bool, ()> {
         for (a, b) in first.iter().zip(second.iter()) {
             if !a.clone().equals(b.clone(), runtime)? {
@@ -243,8 +252,9 @@ impl CustomVar for Array {
         StdMethod::new_native(self, func).into()
     }
 
-    fn get_attribute(self: Rc<Self>, _name: &str) -> Variable {
-        unimplemented!()
+    fn get_attribute(self: Rc<Self>, name: &str) -> Option<Variable> {
+        let func = Self::attr_fn(name)?;
+        Option::Some(StdMethod::new_native(self, func).into())
     }
 
     fn call_op(
@@ -266,7 +276,7 @@ impl CustomVar for Array {
     }
 
     fn str(self: Rc<Self>, runtime: &mut Runtime) -> Result<StringVar, ()> {
-        self.str_value(runtime)
+        self.repr_value(runtime)
     }
 
     fn repr(self: Rc<Self>, runtime: &mut Runtime) -> Result<StringVar, ()> {
@@ -310,3 +320,70 @@ impl TypicalIterator for ArrayIter {
         custom_class!(ArrayIter, create, "ArrayIter")
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::custom_types::array::Array;
+    use crate::custom_types::list::List;
+    use crate::int_var::IntVar;
+    use crate::operator::Operator;
+    use crate::runtime::Runtime;
+    use crate::std_type::Type;
+    use crate::tuple::LangTuple;
+    use crate::variable::Variable;
+
+    fn values(values: &[i32]) -> Vec<Variable> {
+        values.iter().map(|&x| IntVar::from(x).into()).collect()
+    }
+
+    fn elements_equal(this: Variable, other: Variable) -> Result<Variable, ()> {
+        Runtime::test(|runtime| {
+            runtime.call_attr(this, "elementsEqual", vec![other])?;
+            let result = runtime.pop_return();
+            runtime.return_1(result)
+        })
+    }
+
+    #[test]
+    fn list_elements_equal() {
+        let list: Variable = List::from_values(Type::Bigint, values(&[1, 2, 3])).into();
+        let array: Variable = Array::new(values(&[1, 2, 3]).into_boxed_slice()).into();
+        let tuple: Variable = LangTuple::from_vec(values(&[1, 2, 3])).into();
+        assert_eq!(elements_equal(list.clone(), array), Result::Ok(true.into()));
+        assert_eq!(elements_equal(list, tuple), Result::Ok(true.into()));
+    }
+
+    #[test]
+    fn array_elements_equal() {
+        let array: Variable = Array::new(values(&[1, 2, 3]).into_boxed_slice()).into();
+        let list: Variable = List::from_values(Type::Bigint, values(&[1, 2, 3])).into();
+        let tuple: Variable = LangTuple::from_vec(values(&[1, 2, 3])).into();
+        assert_eq!(elements_equal(array.clone(), list), Result::Ok(true.into()));
+        assert_eq!(elements_equal(array, tuple), Result::Ok(true.into()));
+    }
+
+    #[test]
+    fn elements_not_equal() {
+        let list: Variable = List::from_values(Type::Bigint, values(&[1, 2, 3])).into();
+        let cases = [values(&[1, 2]), values(&[1, 2, 3, 4]), values(&[1, 2, 4])];
+        for case in &cases {
+            let array: Variable = Array::new(case.clone().into_boxed_slice()).into();
+            assert_eq!(
+                elements_equal(list.clone(), array),
+                Result::Ok(false.into())
+            );
+        }
+    }
+
+    #[test]
+    fn strict_equals() {
+        let result = Runtime::test(|runtime| {
+            let list: Variable = List::from_values(Type::Bigint, values(&[1, 2])).into();
+            let array: Variable = Array::new(values(&[1, 2]).into_boxed_slice()).into();
+            runtime.call_op(list, Operator::Equals, vec![array])?;
+            let result = runtime.pop_return();
+            runtime.return_1(result)
+        });
+        assert_eq!(result, Result::Ok(false.into()));
+    }
+}
@@ -12,7 +12,7 @@ use crate::runtime::Runtime;
 use crate::std_type::Type;
 use crate::string_var::StringVar;
 use crate::variable::{FnResult, InnerVar, Variable};
-use crate::{first, first_n};
+use crate::{check_args, first, first_n};
 use ascii::{AsciiChar, AsciiStr, AsciiString, IntoAsciiString};
 use num::{BigInt, One, ToPrimitive};
 use once_cell::sync::Lazy;
@@ -67,25 +67,34 @@ impl LangBytes {
             Operator::SetSlice => Self::set_slice,
             Operator::DelSlice => Self::del_slice,
             Operator::Reversed => Self::reversed,
+            Operator::Equals => Self::eq,
+            Operator::Hash => Self::hash,
+            Operator::In => Self::contains,
             _ => unimplemented!("bytes.{}", op.name()),
         }
     }
 
-    fn attr_fn(attr: &str) -> NativeMethod<Rc<LangBytes>> {
-        match attr {
+    fn attr_fn(attr: &str) -> Option<NativeMethod<Rc<LangBytes>>> {
+        Option::Some(match attr {
             "encode" => Self::encode,
             "join" => Self::join,
             "indexOf" => Self::index_of,
+            "indexOfByte" => Self::index_of_byte,
             "get" => Self::get,
             "add" => Self::add,
+            "append" => Self::add,
+            "extend" => Self::extend,
             "addChar" => Self::add_char,
             "startsWith" => Self::starts_with,
             "endsWith" => Self::ends_with,
             "lastIndexOf" => Self::last_index_of,
+            "find" => Self::find,
+            "rfind" => Self::rfind,
+            "split" => Self::split,
             "hex" => Self::hex,
             "reverse" => Self::reverse,
-            _ => unimplemented!("bytes.{}", attr),
-        }
+            _ => return Option::None,
+        })
     }
 
     fn index(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
@@ -116,19 +125,9 @@ impl LangBytes {
     fn get_slice(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
         debug_assert_eq!(args.len(), 1);
         let range = Range::from_slice(self.len(), runtime, first(args))?;
-        if range.get_step().is_one() {
-            let value = self.value.borrow();
-            let start = range.get_start().to_usize().unwrap();
-            let stop = range.get_stop().to_usize().unwrap_or(usize::MAX);
-            runtime.return_1(Rc::new(LangBytes::new(value[start..stop].to_vec())).into())
-        } else {
-            let mut raw_vec = Vec::new();
-            let self_val = self.value.borrow();
-            for i in range.values() {
-                raw_vec.push(self_val[i.to_usize().expect("Conversion error")]);
-            }
-            runtime.return_1(Rc::new(LangBytes::new(raw_vec)).into())
-        }
+        let value = self.value.borrow();
+        let result = range.slice_indices(value.len()).map(|i| value[i]).collect();
+        runtime.return_1(Rc::new(LangBytes::new(result)).into())
     }
 
     fn set_slice(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
@@ -377,6 +376,28 @@ impl LangBytes {
         }
     }
 
+    fn extend(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        check_args("bytes.extend", &args, 1, runtime)?;
+        let values = match downcast_var::<LangBytes>(first(args)) {
+            // Copy first, in case the bytes are extended with themselves
+            Result::Ok(other) => other.value.borrow().clone(),
+            Result::Err(other) => {
+                let mut values = Vec::new();
+                let iter = other.iter(runtime)?;
+                while let Option::Some(next) = iter.next(runtime)?.take_first() {
+                    let int_val = IntVar::from(next);
+                    match int_val.to_u8() {
+                        Option::Some(value) => values.push(value),
+                        Option::None => return Self::shrink_err(int_val, runtime),
+                    }
+                }
+                values
+            }
+        };
+        self.value.borrow_mut().extend(values);
+        runtime.return_0()
+    }
+
     fn add_char(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
         debug_assert_eq!(args.len(), 2);
         let [char_val, encoding] = first_n(args);
@@ -476,13 +497,33 @@ impl LangBytes {
         runtime.throw_quick_native(value_error(), "Invalid byte literal for utf-8 conversion")
     }
 
-    fn shrink_err(index: IntVar, runtime: &mut Runtime) -> FnResult {
-        runtime.throw_quick(
+    fn shrink_err<T>(index: IntVar, runtime: &mut Runtime) -> Result<T, ()> {
+        runtime.throw_quick_native(
             value_error(),
             format!("{} is too big to fit in a byte (must be in [0:256])", index),
         )
     }
 
+    fn eq(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        for arg in args {
+            match downcast_var::<LangBytes>(arg) {
+                Result::Ok(other) if *other.value.borrow() == *self.value.borrow() => {}
+                _ => return runtime.return_1(false.into()),
+            }
+        }
+        runtime.return_1(true.into())
+    }
+
+    /// The FNV-1a hash of the contents.
+    ///
+    /// This is unrelated to the hash of a string, so bytes and strings with
+    /// the same (ASCII) contents will not necessarily hash equal. Since they
+    /// never compare equal, this is fine.
+    fn hash(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert!(args.is_empty());
+        runtime.return_1((fnv1a(&self.value.borrow()) as usize).into())
+    }
+
     fn iter(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
         debug_assert!(args.is_empty());
         runtime.return_1(Rc::new(BytesIter::new(self)).into())
@@ -555,6 +596,19 @@ impl LangBytes {
         }))
     }
 
+    fn index_of_byte(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        check_args("bytes.indexOfByte", &args, 1, runtime)?;
+        let byte = Self::byte_arg(first(args), runtime)?;
+        let index = self.value.borrow().iter().position(|&x| x == byte);
+        runtime.return_1(index.map(Variable::from).into())
+    }
+
+    fn contains(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert_eq!(args.len(), 1);
+        let byte = Self::byte_arg(first(args), runtime)?;
+        runtime.return_1(self.value.borrow().contains(&byte).into())
+    }
+
     fn last_index_of(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
         debug_assert!(args.len() == 1);
         let search_int = IntVar::from(first(args));
@@ -573,6 +627,54 @@ impl LangBytes {
         }))
     }
 
+    fn find(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        check_args("bytes.find", &args, 1, runtime)?;
+        let needle = Self::needle(first(args), runtime)?;
+        let index = find_sub(&self.value.borrow(), &needle);
+        runtime.return_1(index.map(Variable::from).into())
+    }
+
+    fn rfind(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        check_args("bytes.rfind", &args, 1, runtime)?;
+        let needle = Self::needle(first(args), runtime)?;
+        let index = rfind_sub(&self.value.borrow(), &needle);
+        runtime.return_1(index.map(Variable::from).into())
+    }
+
+    fn split(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        check_args("bytes.split", &args, 1, runtime)?;
+        let sep = Self::needle(first(args), runtime)?;
+        if sep.is_empty() {
+            return runtime.throw_quick(value_error(), "bytes.split separator cannot be empty");
+        }
+        let value = self.value.borrow();
+        let mut result = Vec::new();
+        let mut rest = &value[..];
+        while let Option::Some(index) = find_sub(rest, &sep) {
+            result.push(Rc::new(LangBytes::new(rest[..index].to_vec())).into());
+            rest = &rest[index + sep.len()..];
+        }
+        result.push(Rc::new(LangBytes::new(rest.to_vec())).into());
+        runtime.return_1(List::from_values(Self::bytes_type(), result).into())
+    }
+
+    /// The byte sequence to search for, given either a bytes object or a
+    /// single byte as an int.
+    fn needle(value: Variable, runtime: &mut Runtime) -> Result<Vec<u8>, ()> {
+        match downcast_var::<LangBytes>(value) {
+            Result::Ok(bytes) => Result::Ok(bytes.value.borrow().clone()),
+            Result::Err(value) => Result::Ok(vec![Self::byte_arg(value, runtime)?]),
+        }
+    }
+
+    fn byte_arg(value: Variable, runtime: &mut Runtime) -> Result<u8, ()> {
+        let int_val = IntVar::from(value);
+        match int_val.to_u8() {
+            Option::Some(byte) => Result::Ok(byte),
+            Option::None => Self::shrink_err(int_val, runtime),
+        }
+    }
+
     fn hex(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
         debug_assert!(args.is_empty());
         let result = self
@@ -660,12 +762,12 @@ impl CustomVar for LangBytes {
         StdMethod::new_native(self, func).into()
     }
 
-    fn get_attribute(self: Rc<Self>, attr: &str) -> Variable {
+    fn get_attribute(self: Rc<Self>, attr: &str) -> Option<Variable> {
         let func = match attr {
-            "length" => return self.value.borrow().len().into(),
-            _ => Self::attr_fn(attr),
+            "length" => return Option::Some(self.value.borrow().len().into()),
+            _ => Self::attr_fn(attr)?,
         };
-        StdMethod::new_native(self, func).into()
+        Option::Some(StdMethod::new_native(self, func).into())
     }
 
     fn call_op(
@@ -755,6 +857,30 @@ impl TypicalIterator for BytesRevIter {
     }
 }
 
+fn fnv1a(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+    bytes.iter().fold(OFFSET_BASIS, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(PRIME)
+    })
+}
+
+fn find_sub(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if needle.is_empty() {
+        Option::Some(0)
+    } else {
+        haystack.windows(needle.len()).position(|x| x == needle)
+    }
+}
+
+fn rfind_sub(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if needle.is_empty() {
+        Option::Some(haystack.len())
+    } else {
+        haystack.windows(needle.len()).rposition(|x| x == needle)
+    }
+}
+
 fn overflow_exc(val: usize, len: usize) -> StringVar {
     format!(
         "Too many string repetitions: maximum bytes length is {}, \
@@ -772,3 +898,370 @@ fn from_hex_exc(len: usize) -> StringVar {
     )
     .into()
 }
+
+#[cfg(test)]
+mod test {
+    use crate::custom_types::bytes::LangBytes;
+    use crate::custom_types::dict::Dict;
+    use crate::custom_types::exceptions::{index_error, value_error};
+    use crate::custom_types::list::List;
+    use crate::custom_types::slice::Slice;
+    use crate::custom_var::downcast_var;
+    use crate::int_var::IntVar;
+    use crate::looping;
+    use crate::name::Name;
+    use crate::operator::Operator;
+    use crate::runtime::Runtime;
+    use crate::std_type::Type;
+    use crate::variable::Variable;
+    use std::rc::Rc;
+
+    fn bytes(values: &[u8]) -> Variable {
+        Rc::new(LangBytes::new(values.to_vec())).into()
+    }
+
+    fn call_op(value: Variable, op: Operator, args: Vec<Variable>) -> Result<Variable, ()> {
+        Runtime::test(|runtime| {
+            runtime.call_op(value, op, args)?;
+            let result = runtime.pop_return();
+            runtime.return_1(result)
+        })
+    }
+
+    fn bytes_value(value: Result<Variable, ()>) -> Vec<u8> {
+        let value = downcast_var::<LangBytes>(value.unwrap()).unwrap();
+        let result = value.get_value().clone();
+        result
+    }
+
+    fn slice(start: Option<i32>, stop: Option<i32>, step: Option<i32>) -> Variable {
+        Rc::new(Slice::new(
+            start.map(IntVar::from),
+            stop.map(IntVar::from),
+            step.map(IntVar::from),
+        ))
+        .into()
+    }
+
+    #[test]
+    fn index() {
+        let value = bytes(&[1, 2, 255]);
+        let result = call_op(
+            value.clone(),
+            Operator::GetAttr,
+            vec![IntVar::from(2).into()],
+        );
+        assert_eq!(result, Result::Ok(IntVar::from(255).into()));
+        let result = call_op(value, Operator::GetAttr, vec![IntVar::from(-3).into()]);
+        assert_eq!(result, Result::Ok(IntVar::from(1).into()));
+    }
+
+    #[test]
+    fn index_out_of_range() {
+        let result = Runtime::test(|runtime| {
+            let args = vec![IntVar::from(3).into()];
+            assert!(runtime
+                .call_op(bytes(&[1, 2, 3]), Operator::GetAttr, args)
+                .is_err());
+            let exc = runtime.pop_err()?;
+            runtime.return_1(exc.get_type().into())
+        });
+        assert_eq!(result, Result::Ok(index_error().into()));
+    }
+
+    #[test]
+    fn get_slice() {
+        let value = bytes(&[0, 1, 2, 3, 4]);
+        let cases = [
+            (
+                slice(Option::Some(1), Option::Some(3), Option::None),
+                vec![1, 2],
+            ),
+            (
+                slice(Option::None, Option::None, Option::Some(2)),
+                vec![0, 2, 4],
+            ),
+            (
+                slice(Option::Some(-2), Option::None, Option::None),
+                vec![3, 4],
+            ),
+            (
+                slice(Option::Some(3), Option::Some(100), Option::None),
+                vec![3, 4],
+            ),
+            (
+                slice(Option::Some(4), Option::Some(1), Option::None),
+                vec![],
+            ),
+        ];
+        for (slice, expected) in cases.iter().cloned() {
+            let result = call_op(value.clone(), Operator::GetSlice, vec![slice]);
+            assert_eq!(bytes_value(result), expected);
+        }
+    }
+
+    #[test]
+    fn get_slice_negative_step() {
+        let value = bytes(&[0, 1, 2, 3, 4]);
+        let cases = [
+            (
+                slice(Option::None, Option::None, Option::Some(-1)),
+                vec![4, 3, 2, 1, 0],
+            ),
+            (
+                slice(Option::Some(3), Option::Some(0), Option::Some(-1)),
+                vec![3, 2, 1],
+            ),
+            (
+                slice(Option::None, Option::None, Option::Some(-2)),
+                vec![4, 2, 0],
+            ),
+            (
+                slice(Option::Some(10), Option::Some(2), Option::Some(-1)),
+                vec![4, 3],
+            ),
+            (
+                slice(Option::Some(1), Option::Some(3), Option::Some(-1)),
+                vec![],
+            ),
+        ];
+        for (slice, expected) in cases.iter().cloned() {
+            let result = call_op(value.clone(), Operator::GetSlice, vec![slice]);
+            assert_eq!(bytes_value(result), expected);
+        }
+    }
+
+    #[test]
+    fn plus() {
+        let result = call_op(bytes(&[1, 2]), Operator::Add, vec![bytes(&[3])]);
+        assert_eq!(bytes_value(result), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn times() {
+        let result = call_op(
+            bytes(&[1, 2]),
+            Operator::Multiply,
+            vec![IntVar::from(3).into()],
+        );
+        assert_eq!(bytes_value(result), vec![1, 2, 1, 2, 1, 2]);
+        let result = call_op(
+            bytes(&[1, 2]),
+            Operator::Multiply,
+            vec![IntVar::from(0).into()],
+        );
+        assert_eq!(bytes_value(result), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn length() {
+        let result = Runtime::test(|runtime| {
+            let length = bytes(&[1, 2, 3]).index(Name::Attribute("length"), runtime)?;
+            runtime.return_1(length)
+        });
+        assert_eq!(result, Result::Ok(IntVar::from(3).into()));
+    }
+
+    #[test]
+    fn iterate() {
+        let mut values: Vec<Variable> = Vec::new();
+        Runtime::test(|runtime| {
+            values = looping::collect(bytes(&[0, 7, 255]), runtime)?;
+            runtime.return_1(true.into())
+        })
+        .unwrap();
+        let expected: Vec<Variable> = vec![
+            IntVar::from(0).into(),
+            IntVar::from(7).into(),
+            IntVar::from(255).into(),
+        ];
+        assert_eq!(values, expected);
+    }
+
+    fn call_attr(value: &Variable, name: &str, args: Vec<Variable>) -> Result<Variable, ()> {
+        Runtime::test(|runtime| {
+            runtime.call_attr(value.clone(), name, args)?;
+            runtime.return_1(true.into())
+        })
+    }
+
+    #[test]
+    fn append() {
+        let value = bytes(&[]);
+        for &byte in &[0, 100, 255] {
+            let result = call_attr(&value, "append", vec![IntVar::from(byte).into()]);
+            assert_eq!(result, Result::Ok(true.into()));
+        }
+        assert_eq!(bytes_value(Result::Ok(value)), vec![0, 100, 255]);
+    }
+
+    #[test]
+    fn extend() {
+        let value = bytes(&[1]);
+        let result = call_attr(&value, "extend", vec![bytes(&[2, 3])]);
+        assert_eq!(result, Result::Ok(true.into()));
+        let result = call_attr(&value, "extend", vec![value.clone()]);
+        assert_eq!(result, Result::Ok(true.into()));
+        let ints = List::from_values(Type::Bigint, vec![IntVar::from(4).into()]);
+        let result = call_attr(&value, "extend", vec![ints.into()]);
+        assert_eq!(result, Result::Ok(true.into()));
+        assert_eq!(bytes_value(Result::Ok(value)), vec![1, 2, 3, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn set_index() {
+        let value = bytes(&[1, 2, 3]);
+        let args = vec![IntVar::from(-1).into(), IntVar::from(200).into()];
+        let result = Runtime::test(|runtime| {
+            runtime.call_op(value.clone(), Operator::SetAttr, args)?;
+            runtime.return_1(true.into())
+        });
+        assert_eq!(result, Result::Ok(true.into()));
+        assert_eq!(bytes_value(Result::Ok(value)), vec![1, 2, 200]);
+    }
+
+    #[test]
+    fn invalid_byte() {
+        let value = bytes(&[1]);
+        let too_big = List::from_values(
+            Type::Bigint,
+            vec![IntVar::from(2).into(), IntVar::from(256).into()],
+        );
+        let cases = vec![
+            ("append", vec![IntVar::from(-1).into()]),
+            ("extend", vec![too_big.into()]),
+        ];
+        for (name, args) in cases {
+            let result = Runtime::test(|runtime| {
+                assert!(runtime.call_attr(value.clone(), name, args).is_err());
+                let exc = runtime.pop_err()?;
+                runtime.return_1(exc.get_type().into())
+            });
+            assert_eq!(result, Result::Ok(value_error().into()), "{}", name);
+        }
+        assert_eq!(bytes_value(Result::Ok(value)), vec![1]);
+    }
+
+    fn find(name: &str, value: &[u8], needle: Variable) -> Result<Variable, ()> {
+        Runtime::test(|runtime| {
+            runtime.call_attr(bytes(value), name, vec![needle])?;
+            let result = runtime.pop_return();
+            runtime.return_1(result)
+        })
+    }
+
+    fn found(index: usize) -> Result<Variable, ()> {
+        Result::Ok(Option::Some(Variable::from(index)).into())
+    }
+
+    #[test]
+    fn find_sub() {
+        let value = [1, 2, 3, 1, 2, 3];
+        assert_eq!(find("find", &value, bytes(&[2, 3])), found(1));
+        assert_eq!(find("rfind", &value, bytes(&[2, 3])), found(4));
+        assert_eq!(find("find", &value, IntVar::from(3).into()), found(2));
+        assert_eq!(find("find", &value, bytes(&[])), found(0));
+        assert_eq!(find("rfind", &value, bytes(&[])), found(6));
+        let missing = Result::Ok(Option::None.into());
+        assert_eq!(find("find", &value, bytes(&[3, 2])), missing);
+        assert_eq!(find("rfind", &value, bytes(&[1, 2, 3, 4])), missing);
+    }
+
+    fn split(value: &[u8], sep: Variable) -> Vec<Vec<u8>> {
+        let result = Runtime::test(|runtime| {
+            runtime.call_attr(bytes(value), "split", vec![sep])?;
+            let result = runtime.pop_return();
+            runtime.return_1(result)
+        });
+        let list = downcast_var::<List>(result.unwrap()).unwrap();
+        let values = list.values().to_vec();
+        values
+            .into_iter()
+            .map(|x| bytes_value(Result::Ok(x)))
+            .collect()
+    }
+
+    #[test]
+    fn split_byte() {
+        let result = split(&[1, 0, 2, 3, 0, 0, 4], IntVar::from(0).into());
+        assert_eq!(result, vec![vec![1], vec![2, 3], vec![], vec![4]]);
+        let result = split(&[0, 1], IntVar::from(0).into());
+        assert_eq!(result, vec![vec![], vec![1]]);
+    }
+
+    #[test]
+    fn split_bytes() {
+        let result = split(&[1, 13, 10, 2, 13, 3], bytes(&[13, 10]));
+        assert_eq!(result, vec![vec![1], vec![2, 13, 3]]);
+        let result = split(&[], bytes(&[13, 10]));
+        assert_eq!(result, vec![Vec::<u8>::new()]);
+    }
+
+    #[test]
+    fn fnv1a_known() {
+        assert_eq!(super::fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(super::fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(super::fnv1a(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn equals() {
+        let result = call_op(bytes(&[1, 2]), Operator::Equals, vec![bytes(&[1, 2])]);
+        assert_eq!(result, Result::Ok(true.into()));
+        let result = call_op(bytes(&[1, 2]), Operator::Equals, vec![bytes(&[1, 3])]);
+        assert_eq!(result, Result::Ok(false.into()));
+        let result = call_op(bytes(&[1]), Operator::Equals, vec![IntVar::from(1).into()]);
+        assert_eq!(result, Result::Ok(false.into()));
+    }
+
+    #[test]
+    fn dict_keys() {
+        let result = Runtime::test(|runtime| {
+            let keys = vec![bytes(b"abc"), bytes(b"def")];
+            let values = vec![IntVar::from(1).into(), IntVar::from(2).into()];
+            let dict = Dict::from_args(keys, values, runtime)?;
+            let found = dict.get_value(bytes(b"def"), runtime)?;
+            assert_eq!(found, Option::Some(IntVar::from(2).into()));
+            let missing = dict.get_value(bytes(b"ab"), runtime)?;
+            assert_eq!(missing, Option::None);
+            runtime.return_1(true.into())
+        });
+        assert_eq!(result, Result::Ok(true.into()));
+    }
+
+    #[test]
+    fn contains_byte() {
+        let value = bytes(&[1, 2, 255]);
+        let result = call_op(value.clone(), Operator::In, vec![IntVar::from(255).into()]);
+        assert_eq!(result, Result::Ok(true.into()));
+        let result = call_op(value, Operator::In, vec![IntVar::from(3).into()]);
+        assert_eq!(result, Result::Ok(false.into()));
+    }
+
+    fn index_of_byte(value: Variable, byte: i32) -> Result<Variable, ()> {
+        Runtime::test(|runtime| {
+            runtime.call_attr(value, "indexOfByte", vec![IntVar::from(byte).into()])?;
+            let result = runtime.pop_return();
+            runtime.return_1(result)
+        })
+    }
+
+    #[test]
+    fn index_of_byte_found() {
+        let value = bytes(&[1, 2, 255, 2]);
+        let expected = Option::Some(Variable::from(IntVar::from(1))).into();
+        assert_eq!(index_of_byte(value.clone(), 2), Result::Ok(expected));
+        assert_eq!(index_of_byte(value, 3), Result::Ok(Option::None.into()));
+    }
+
+    #[test]
+    fn index_of_byte_range() {
+        let result = Runtime::test(|runtime| {
+            let args = vec![IntVar::from(256).into()];
+            assert!(runtime.call_attr(bytes(&[0]), "indexOfByte", args).is_err());
+            let exc = runtime.pop_err()?;
+            runtime.return_1(exc.get_type().into())
+        });
+        assert_eq!(result, Result::Ok(value_error().into()));
+    }
+}
@@ -81,10 +81,10 @@ impl CustomVar for Generator {
         }
     }
 
-    fn get_attribute(self: Rc<Self>, name: &str) -> Variable {
+    fn get_attribute(self: Rc<Self>, name: &str) -> Option<Variable> {
         match name {
-            "next" => StdMethod::new_native(self, Self::next_fn).into(),
-            _ => unimplemented!("Generator.{}", name),
+            "next" => Option::Some(StdMethod::new_native(self, Self::next_fn).into()),
+            _ => Option::None,
         }
     }
 
@@ -0,0 +1,284 @@
+use crate::custom_types::exceptions::key_error;
+use crate::custom_types::inner_dict::{DictIter, DictLike, InnerDict};
+use crate::custom_var::CustomVar;
+use crate::looping;
+use crate::method::{NativeMethod, StdMethod};
+use crate::name::Name;
+use crate::operator::Operator;
+use crate::runtime::Runtime;
+use crate::std_type::Type;
+use crate::string_var::StringVar;
+use crate::variable::{FnResult, Variable};
+use crate::{check_args, first, first_n};
+use std::cell::{Ref, RefCell};
+use std::rc::Rc;
+
+/// A dict which fills in missing keys with the result of calling a factory.
+///
+/// Only subscripting (`d[k]`) inserts missing keys; `get` and `in` leave the
+/// dict unchanged.
+#[derive(Debug)]
+pub struct DefaultDict {
+    factory: Variable,
+    value: RefCell<InnerDict>,
+}
+
+impl DefaultDict {
+    pub fn new(factory: Variable) -> Rc<DefaultDict> {
+        Rc::new(DefaultDict {
+            factory,
+            value: RefCell::new(InnerDict::new()),
+        })
+    }
+
+    pub fn get_value(&self, key: Variable, runtime: &mut Runtime) -> Result<Option<Variable>, ()> {
+        self.value.borrow().get(key, runtime)
+    }
+
+    /// Gets the value for `key`, inserting the result of the factory first if
+    /// it is not present.
+    pub fn get_or_insert(&self, key: Variable, runtime: &mut Runtime) -> Result<Variable, ()> {
+        if let Option::Some(value) = self.get_value(key.clone(), runtime)? {
+            return Result::Ok(value);
+        }
+        // The dict must not be borrowed here, as the factory may access it
+        self.factory.clone().call((Vec::new(), runtime))?;
+        let value = runtime.pop_return();
+        self.value.borrow_mut().set(key, value.clone(), runtime)?;
+        Result::Ok(value)
+    }
+
+    fn op_fn(o: Operator) -> NativeMethod<Rc<DefaultDict>> {
+        match o {
+            Operator::GetAttr => Self::index,
+            Operator::SetAttr => Self::set,
+            Operator::DelAttr => Self::del,
+            Operator::In => Self::contains,
+            Operator::Bool => Self::bool,
+            Operator::Iter => Self::iter,
+            Operator::Repr => Self::repr,
+            Operator::Str => Self::repr,
+            _ => unimplemented!("DefaultDict.{}", o.name()),
+        }
+    }
+
+    fn attr_fn(s: &str) -> Option<NativeMethod<Rc<DefaultDict>>> {
+        Option::Some(match s {
+            "get" => Self::get,
+            "remove" => Self::remove,
+            "clear" => Self::clear,
+            _ => return Option::None,
+        })
+    }
+
+    fn index(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert_eq!(args.len(), 1);
+        let result = self.get_or_insert(first(args), runtime)?;
+        runtime.return_1(result)
+    }
+
+    fn set(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert_eq!(args.len(), 2);
+        let [key, val] = first_n(args);
+        self.value.borrow_mut().set(key, val, runtime)?;
+        runtime.return_0()
+    }
+
+    fn del(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert_eq!(args.len(), 1);
+        match self.value.borrow_mut().del(first(args), runtime)? {
+            Option::Some(_) => runtime.return_0(),
+            Option::None => runtime.throw_quick(key_error(), "Value not found"),
+        }
+    }
+
+    fn contains(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert_eq!(args.len(), 1);
+        let is_in = self.get_value(first(args), runtime)?.is_some();
+        runtime.return_1(is_in.into())
+    }
+
+    fn bool(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert!(args.is_empty());
+        runtime.return_1((!self.value.borrow().is_empty()).into())
+    }
+
+    fn iter(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert!(args.is_empty());
+        runtime.return_1(Rc::new(DictIter::new(self)).into())
+    }
+
+    fn repr(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert!(args.is_empty());
+        let result = self.value.borrow().true_repr(runtime)?;
+        runtime.return_1(result.into())
+    }
+
+    fn get(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        if args.len() == 1 {
+            let val = self.get_value(first(args), runtime)?.into();
+            runtime.return_1(val)
+        } else {
+            check_args("DefaultDict.get", &args, 2, runtime)?;
+            let [key, default] = first_n(args);
+            let val = self.get_value(key, runtime)?.unwrap_or(default);
+            runtime.return_1(val)
+        }
+    }
+
+    fn remove(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        check_args("DefaultDict.remove", &args, 1, runtime)?;
+        let removed = self.value.borrow_mut().del(first(args), runtime)?.into();
+        runtime.return_1(removed)
+    }
+
+    fn clear(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        check_args("DefaultDict.clear", &args, 0, runtime)?;
+        self.value.borrow_mut().clear();
+        runtime.return_0()
+    }
+
+    fn create(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        check_args("DefaultDict", &args, 1, runtime)?;
+        runtime.return_1(DefaultDict::new(first(args)).into())
+    }
+
+    pub fn default_dict_type() -> Type {
+        custom_class!(DefaultDict, create, "DefaultDict")
+    }
+
+    fn len(&self) -> usize {
+        self.value.borrow().size()
+    }
+}
+
+impl CustomVar for DefaultDict {
+    fn set(self: Rc<Self>, _name: Name, _object: Variable) {
+        unimplemented!()
+    }
+
+    fn get_type(&self) -> Type {
+        DefaultDict::default_dict_type()
+    }
+
+    fn get_operator(self: Rc<Self>, o: Operator) -> Variable {
+        let func = DefaultDict::op_fn(o);
+        StdMethod::new_native(self, func).into()
+    }
+
+    fn get_attribute(self: Rc<Self>, s: &str) -> Option<Variable> {
+        let func = match s {
+            "length" => return Option::Some(self.len().into()),
+            "factory" => return Option::Some(self.factory.clone()),
+            _ => Self::attr_fn(s)?,
+        };
+        Option::Some(StdMethod::new_native(self, func).into())
+    }
+
+    fn call_op(
+        self: Rc<Self>,
+        operator: Operator,
+        args: Vec<Variable>,
+        runtime: &mut Runtime,
+    ) -> FnResult {
+        runtime.call_native_method(DefaultDict::op_fn(operator), self, args)
+    }
+
+    fn call_op_or_goto(
+        self: Rc<Self>,
+        operator: Operator,
+        args: Vec<Variable>,
+        runtime: &mut Runtime,
+    ) -> FnResult {
+        runtime.call_native_method(DefaultDict::op_fn(operator), self, args)
+    }
+
+    fn str(self: Rc<Self>, runtime: &mut Runtime) -> Result<StringVar, ()> {
+        self.value.borrow().true_repr(runtime)
+    }
+
+    fn repr(self: Rc<Self>, runtime: &mut Runtime) -> Result<StringVar, ()> {
+        self.value.borrow().true_repr(runtime)
+    }
+
+    fn bool(self: Rc<Self>, _runtime: &mut Runtime) -> Result<bool, ()> {
+        Result::Ok(!self.value.borrow().is_empty())
+    }
+
+    fn iter(self: Rc<Self>, _runtime: &mut Runtime) -> Result<looping::Iterator, ()> {
+        Result::Ok(Rc::new(DictIter::new(self)).into())
+    }
+}
+
+impl DictLike for DefaultDict {
+    fn borrow(&self) -> Ref<'_, InnerDict> {
+        self.value.borrow()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::custom_types::default_dict::DefaultDict;
+    use crate::custom_types::list::List;
+    use crate::int_var::IntVar;
+    use crate::name::Name;
+    use crate::operator::Operator;
+    use crate::runtime::Runtime;
+    use crate::string_var::StringVar;
+    use crate::variable::Variable;
+
+    #[test]
+    fn index_inserts_default() {
+        let result = Runtime::test(|runtime| {
+            let dict: Variable = DefaultDict::new(List::list_type().into()).into();
+            let keys = ["a", "b", "a", "a"];
+            for (i, &key) in keys.iter().enumerate() {
+                dict.clone().call_op(
+                    Operator::GetAttr,
+                    vec![StringVar::from(key).into()],
+                    runtime,
+                )?;
+                let list = runtime.pop_return();
+                list.index(Name::Attribute("add"), runtime)?
+                    .call((vec![IntVar::from(i).into()], runtime))?;
+            }
+            dict.clone().call_op(
+                Operator::GetAttr,
+                vec![StringVar::from("a").into()],
+                runtime,
+            )?;
+            let a = runtime.pop_return().repr(runtime)?;
+            dict.clone().call_op(
+                Operator::GetAttr,
+                vec![StringVar::from("b").into()],
+                runtime,
+            )?;
+            let b = runtime.pop_return().repr(runtime)?;
+            assert_eq!(a.as_str(), "[0, 2, 3]");
+            assert_eq!(b.as_str(), "[1]");
+            let length = dict.index(Name::Attribute("length"), runtime)?;
+            runtime.return_1(length)
+        });
+        assert_eq!(result, Result::Ok(IntVar::from(2).into()));
+    }
+
+    #[test]
+    fn get_does_not_insert() {
+        let result = Runtime::test(|runtime| {
+            let dict = DefaultDict::new(List::list_type().into());
+            let var: Variable = dict.clone().into();
+            var.clone()
+                .index(Name::Attribute("get"), runtime)?
+                .call((vec![StringVar::from("missing").into()], runtime))?;
+            assert_eq!(runtime.pop_return(), Option::<Variable>::None.into());
+            var.call_op(
+                Operator::In,
+                vec![StringVar::from("missing").into()],
+                runtime,
+            )?;
+            assert_eq!(runtime.pop_return(), false.into());
+            runtime.return_1(dict.len().into())
+        });
+        assert_eq!(result, Result::Ok(IntVar::from(0).into()));
+    }
+}
@@ -1,7 +1,8 @@
-use crate::custom_types::exceptions::key_error;
+use crate::copy::CopyMap;
+use crate::custom_types::exceptions::{key_error, value_error};
 use crate::custom_types::inner_dict::{DictIter, DictLike, Entry, InnerDict};
 use crate::custom_var::{downcast_var, CustomVar};
-use crate::looping;
+use crate::looping::{self, IterOk};
 use crate::method::{NativeMethod, StdMethod};
 use crate::name::Name;
 use crate::operator::Operator;
@@ -9,8 +10,8 @@ use crate::runtime::Runtime;
 use crate::std_type::Type;
 use crate::string_var::StringVar;
 use crate::tuple::LangTuple;
-use crate::variable::{FnResult, Variable};
-use crate::{first, first_n};
+use crate::variable::{FnResult, InnerVar, Variable};
+use crate::{check_args, first, first_n};
 use std::cell::{Ref, RefCell};
 use std::fmt::Debug;
 use std::mem::replace;
@@ -38,6 +39,28 @@ impl Dict {
         }))
     }
 
+    pub fn get_value(&self, key: Variable, runtime: &mut Runtime) -> Result<Option<Variable>, ()> {
+        self.value.borrow().get(key, runtime)
+    }
+
+    pub(crate) fn deep_copy(
+        self: &Rc<Self>,
+        copies: &mut CopyMap,
+        runtime: &mut Runtime,
+    ) -> Result<Variable, ()> {
+        let result = Dict::new();
+        if let Option::Some(copy) = copies.register(self, result.clone().into()) {
+            return Result::Ok(copy);
+        }
+        let pairs = self.value.borrow().pairs();
+        for (key, value) in pairs {
+            let key = copies.copy(key, runtime)?;
+            let value = copies.copy(value, runtime)?;
+            result.value.borrow_mut().set(key, value, runtime)?;
+        }
+        Result::Ok(result.into())
+    }
+
     fn from_inner(value: InnerDict) -> Rc<Dict> {
         Rc::new(Dict {
             value: RefCell::new(value),
@@ -59,8 +82,8 @@ impl Dict {
         }
     }
 
-    fn attr_fn(s: &str) -> NativeMethod<Rc<Dict>> {
-        match s {
+    fn attr_fn(s: &str) -> Option<NativeMethod<Rc<Dict>>> {
+        Option::Some(match s {
             "clear" => Dict::clear,
             "get" => Dict::get,
             "getPair" => Dict::get_pair,
@@ -68,8 +91,15 @@ impl Dict {
             "remove" => Dict::remove,
             "setDefault" => Dict::set_default,
             "retain" => Dict::retain,
-            _ => unimplemented!("dict.{}", s),
-        }
+            "copy" => Dict::copy,
+            _ => return Option::None,
+        })
+    }
+
+    fn copy(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        check_args("dict.copy", &args, 0, runtime)?;
+        let value = self.value.borrow().clone();
+        runtime.return_1(Dict::from_inner(value).into())
     }
 
     fn index(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
@@ -219,23 +249,66 @@ impl Dict {
                 let value = first(args);
                 match downcast_var::<Dict>(value) {
                     Result::Ok(x) => Dict::from_inner(x.value.borrow().clone()),
-                    Result::Err(x) => {
-                        let iter = x.iter(runtime)?;
-                        let mut inner = InnerDict::new();
-                        while let Option::Some([key, val]) = iter.next(runtime)?.take_n() {
-                            inner.set(key, val, runtime)?;
-                        }
-                        Dict::from_inner(inner)
-                    }
+                    Result::Err(x) => Dict::from_inner(Self::fill(InnerDict::new(), x, runtime)?),
                 }
             }
-            x => panic!("Expected 0 or 1 arguments for dict.operator new, got {}", x),
+            2 => {
+                let [value, cap] = first_n(args);
+                let cap = InnerDict::capacity_arg(cap, "dict", runtime)?;
+                Dict::from_inner(Self::fill(InnerDict::with_capacity(cap), value, runtime)?)
+            }
+            x => panic!(
+                "Expected 0, 1, or 2 arguments for dict.operator new, got {}",
+                x
+            ),
         };
         runtime.return_1(dict.into())
     }
 
+    fn fill(mut inner: InnerDict, value: Variable, runtime: &mut Runtime) -> Result<InnerDict, ()> {
+        let iter = value.iter(runtime)?;
+        while let Option::Some([key, val]) = iter.next(runtime)?.take_n() {
+            inner.set(key, val, runtime)?;
+        }
+        Result::Ok(inner)
+    }
+
+    /// Creates a dict from an iterable of key-value pairs.
+    ///
+    /// Pairs are inserted in iteration order, so if a key occurs more than
+    /// once, the last value given for it wins.
+    fn from_pairs(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        check_args("dict.fromPairs", &args, 1, runtime)?;
+        let iter = first(args).iter(runtime)?;
+        let mut inner = InnerDict::new();
+        loop {
+            let (key, val) = match iter.next(runtime)? {
+                IterOk::None => break,
+                IterOk::Many(v) if v.len() == 2 => {
+                    let [key, val] = first_n(v);
+                    (key, val)
+                }
+                IterOk::One(Variable::Normal(InnerVar::Tuple(t))) if t.len() == 2 => {
+                    (t[0].clone(), t[1].clone())
+                }
+                IterOk::One(x) => return Self::pair_error(x, runtime),
+                IterOk::Many(v) => return Self::pair_error(LangTuple::from_vec(v).into(), runtime),
+            };
+            inner.set(key, val, runtime)?;
+        }
+        runtime.return_1(Dict::from_inner(inner).into())
+    }
+
+    fn pair_error(value: Variable, runtime: &mut Runtime) -> FnResult {
+        let repr = value.repr(runtime)?;
+        runtime.throw_quick(
+            value_error(),
+            format!("dict.fromPairs requires an iterable of pairs, got {}", repr),
+        )
+    }
+
     pub fn dict_type() -> Type {
-        custom_class!(Dict, create, "dict")
+        custom_class!(Dict, create, "dict", "fromPairs" => from_pairs)
     }
 
     fn is_empty(&self) -> bool {
@@ -261,12 +334,12 @@ impl CustomVar for Dict {
         StdMethod::new_native(self, func).into()
     }
 
-    fn get_attribute(self: Rc<Self>, s: &str) -> Variable {
+    fn get_attribute(self: Rc<Self>, s: &str) -> Option<Variable> {
         let func = match s {
-            "length" => return self.len().into(),
-            _ => Self::attr_fn(s),
+            "length" => return Option::Some(self.len().into()),
+            _ => Self::attr_fn(s)?,
         };
-        StdMethod::new_native(self, func).into()
+        Option::Some(StdMethod::new_native(self, func).into())
     }
 
     fn call_op(
@@ -309,3 +382,65 @@ impl DictLike for Dict {
         self.value.borrow()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::custom_types::dict::Dict;
+    use crate::custom_types::list::List;
+    use crate::custom_types::range::Range;
+    use crate::custom_var::downcast_var;
+    use crate::int_var::IntVar;
+    use crate::operator::Operator;
+    use crate::runtime::Runtime;
+    use crate::std_type::Type;
+    use crate::variable::Variable;
+
+    #[test]
+    fn type_keys() {
+        let types = [
+            Type::Bigint,
+            Type::String,
+            Type::Object,
+            Type::Bigint.make_option(),
+            Type::Object.make_option(),
+            Range::range_type(),
+            List::list_type(),
+        ];
+        let keys: Vec<Variable> = types.iter().map(|&t| t.into()).collect();
+        let values: Vec<Variable> = (0..keys.len()).map(|i| IntVar::from(i).into()).collect();
+        let result = Runtime::test(|runtime| {
+            let dict: Variable = Dict::from_args(keys.clone(), values.clone(), runtime)?.into();
+            let mut found = Vec::new();
+            for key in &keys {
+                dict.clone()
+                    .call_op(Operator::GetAttr, vec![key.clone()], runtime)?;
+                found.push(runtime.pop_return());
+            }
+            assert_eq!(found, values);
+            dict.call_op(Operator::In, vec![Type::Char.into()], runtime)?;
+            let contains_char = runtime.pop_return();
+            runtime.return_1(contains_char)
+        });
+        assert_eq!(result, Result::Ok(false.into()));
+    }
+
+    #[test]
+    fn create_with_capacity() {
+        let keys: Vec<Variable> = (0..1000).map(|i| IntVar::from(i).into()).collect();
+        let values: Vec<Variable> = (0..1000).map(|i| IntVar::from(-i).into()).collect();
+        let result = Runtime::test(|runtime| {
+            let source = Dict::from_args(keys.clone(), values.clone(), runtime)?;
+            Dict::create(vec![source.into(), IntVar::from(1000).into()], runtime)?;
+            let dict = downcast_var::<Dict>(runtime.pop_return()).unwrap();
+            assert_eq!(dict.len(), 1000);
+            for (key, value) in keys.iter().zip(&values) {
+                assert_eq!(
+                    dict.get_value(key.clone(), runtime)?.as_ref(),
+                    Option::Some(value)
+                );
+            }
+            runtime.return_1(Variable::null())
+        });
+        assert_eq!(result, Result::Ok(Variable::null()));
+    }
+}
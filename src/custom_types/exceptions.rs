@@ -1,3 +1,4 @@
+use crate::custom_types::interfaces::Throwable;
 use crate::custom_types::types::CustomType;
 use crate::custom_var::CustomVar;
 use crate::first;
@@ -52,12 +53,12 @@ impl CustomVar for StdException {
         StdMethod::new_native(self, func).into()
     }
 
-    fn get_attribute(self: Rc<Self>, name: &str) -> Variable {
-        match name {
+    fn get_attribute(self: Rc<Self>, name: &str) -> Option<Variable> {
+        Option::Some(match name {
             "message" => self.msg.clone().into(),
             "msg" => StdMethod::new_native(self, Self::msg).into(),
-            _ => unimplemented!("{}{}", self.exc_type.str(), name),
-        }
+            _ => return Option::None,
+        })
     }
 
     fn str(self: Rc<Self>, _runtime: &mut Runtime) -> Result<StringVar, ()> {
@@ -86,13 +87,13 @@ macro_rules! create_exc {
     ($fn_name:ident, $type_name:tt) => {
         pub fn $fn_name() -> Type {
             fn create(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
-                let msg = get_message(stringify!($type_name), args, runtime).into();
+                let msg = get_message($type_name, args, runtime).into();
                 runtime.return_1(Rc::new(StdException::new(msg, $fn_name())).into())
             }
             static TYPE: Lazy<CustomType> = Lazy::new(|| {
                 CustomType::new(
                     $type_name.into(),
-                    Vec::new(),
+                    vec![Throwable::cls()],
                     Function::Native(create),
                     NameMap::new(),
                 )
@@ -1,7 +1,7 @@
 use crate::custom_types::exceptions::{invalid_state, io_error};
 use crate::custom_types::list::List;
 use crate::custom_var::CustomVar;
-use crate::first;
+use crate::int_var::IntVar;
 use crate::method::StdMethod;
 use crate::name::Name;
 use crate::operator::Operator;
@@ -9,25 +9,75 @@ use crate::runtime::Runtime;
 use crate::std_type::Type;
 use crate::string_var::StringVar;
 use crate::variable::{FnResult, Variable};
+use crate::{check_args, first};
+use num::ToPrimitive;
 use std::cell::RefCell;
+use std::fmt::{self, Debug, Formatter};
 use std::fs::File;
-use std::io::Read;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
 use std::path::PathBuf;
 use std::rc::Rc;
 
 #[derive(Debug)]
 pub struct FileObj {
     path: PathBuf,
-    file: RefCell<Option<File>>,
+    file: RefCell<Option<Handle>>,
+}
+
+/// The underlying stream of an open [`FileObj`].
+///
+/// The standard streams are re-acquired on every use, rather than locked for
+/// the life of the object, so that other users of them (e.g. `input`) still
+/// work.
+enum Handle {
+    File(BufReader<File>),
+    Stdin,
+    Stdout,
+    Stderr,
+    Reader(Box<dyn BufRead>),
+    Writer(Box<dyn Write>),
 }
 
 impl FileObj {
+    /// The object `sys.stdin` initially refers to.
+    pub fn stdin() -> Rc<FileObj> {
+        Self::from_handle("<stdin>", Handle::Stdin)
+    }
+
+    /// The object `sys.stdout` initially refers to.
+    pub fn stdout() -> Rc<FileObj> {
+        Self::from_handle("<stdout>", Handle::Stdout)
+    }
+
+    /// The object `sys.stderr` initially refers to.
+    pub fn stderr() -> Rc<FileObj> {
+        Self::from_handle("<stderr>", Handle::Stderr)
+    }
+
+    /// An already-open, read-only file reading from `reader`.
+    pub fn from_reader(name: &str, reader: Box<dyn BufRead>) -> Rc<FileObj> {
+        Self::from_handle(name, Handle::Reader(reader))
+    }
+
+    /// An already-open, write-only file writing to `writer`.
+    pub fn from_writer(name: &str, writer: Box<dyn Write>) -> Rc<FileObj> {
+        Self::from_handle(name, Handle::Writer(writer))
+    }
+
+    fn from_handle(name: &str, handle: Handle) -> Rc<FileObj> {
+        Rc::new(FileObj {
+            path: name.into(),
+            file: RefCell::new(Option::Some(handle)),
+        })
+    }
+
     fn open(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
         debug_assert!(args.is_empty());
         if self.file.borrow().is_none() {
             match File::open(&self.path) {
                 Result::Ok(file) => {
-                    self.file.replace(Option::Some(file));
+                    self.file
+                        .replace(Option::Some(Handle::File(BufReader::new(file))));
                 }
                 Result::Err(err) => runtime.throw_quick(io_error(), format!("{}", err))?,
             }
@@ -67,6 +117,65 @@ impl FileObj {
         }
     }
 
+    /// Reads a single line, without its line ending, returning null at the end
+    /// of the file.
+    pub fn read_line(&self, runtime: &mut Runtime) -> Result<Option<StringVar>, ()> {
+        let mut result = String::new();
+        match self.file_do(|f| f.read_line(&mut result)) {
+            Result::Ok(0) => Result::Ok(Option::None),
+            Result::Ok(_) => {
+                if result.ends_with('\n') {
+                    result.pop();
+                    if result.ends_with('\r') {
+                        result.pop();
+                    }
+                }
+                Result::Ok(Option::Some(result.into()))
+            }
+            Result::Err(e) => self.io_err(e, runtime),
+        }
+    }
+
+    /// Writes `text` to the file and flushes it.
+    pub fn write_str(&self, text: &str, runtime: &mut Runtime) -> Result<(), ()> {
+        match self.file_do(|f| f.write(text.as_bytes())) {
+            Result::Ok(()) => Result::Ok(()),
+            Result::Err(e) => self.io_err(e, runtime),
+        }
+    }
+
+    fn read_line_fn(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        check_args("File.readLine", &args, 0, runtime)?;
+        let line = self.read_line(runtime)?;
+        runtime.return_1(line.map(Variable::from).into())
+    }
+
+    fn write(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        check_args("File.write", &args, 1, runtime)?;
+        self.write_str(&StringVar::from(first(args)), runtime)?;
+        runtime.return_0()
+    }
+
+    fn seek(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        check_args("File.seek", &args, 1, runtime)?;
+        let position = IntVar::from(first(args));
+        let position = match position.to_u64() {
+            Option::Some(x) => x,
+            Option::None => {
+                return runtime
+                    .throw_quick(io_error(), format!("Invalid seek position {}", position))
+            }
+        };
+        match self.file_do(|f| f.seek(position)) {
+            Result::Ok(_) => runtime.return_0(),
+            Result::Err(e) => self.io_err(e, runtime),
+        }
+    }
+
+    fn io_err<T>(&self, err: io::Error, runtime: &mut Runtime) -> Result<T, ()> {
+        runtime.throw_quick_native(io_error(), format!("{}: {}", self.path.display(), err))
+    }
+
     fn create(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
         debug_assert!(args.len() == 1);
         let path = StringVar::from(first(args));
@@ -79,7 +188,7 @@ impl FileObj {
         )
     }
 
-    fn file_do<T>(&self, func: impl FnOnce(&mut File) -> T) -> T {
+    fn file_do<T>(&self, func: impl FnOnce(&mut Handle) -> T) -> T {
         match &mut *self.file.borrow_mut() {
             Option::Some(f) => func(f),
             Option::None => panic!("File is not open"),
@@ -109,12 +218,173 @@ impl CustomVar for FileObj {
         StdMethod::new_native(self, func).into()
     }
 
-    fn get_attribute(self: Rc<Self>, attr: &str) -> Variable {
+    fn get_attribute(self: Rc<Self>, attr: &str) -> Option<Variable> {
         let func = match attr {
             "readLines" => Self::read_lines,
             "read" => Self::read,
-            _ => unimplemented!(),
+            "readLine" => Self::read_line_fn,
+            "write" => Self::write,
+            "seek" => Self::seek,
+            _ => return Option::None,
         };
-        StdMethod::new_native(self, func).into()
+        Option::Some(StdMethod::new_native(self, func).into())
+    }
+}
+
+impl Handle {
+    fn read_to_string(&mut self, buf: &mut String) -> io::Result<usize> {
+        match self {
+            Handle::File(f) => f.read_to_string(buf),
+            Handle::Stdin => io::stdin().read_to_string(buf),
+            Handle::Reader(r) => r.read_to_string(buf),
+            _ => Result::Err(unsupported("reading")),
+        }
+    }
+
+    fn read_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        match self {
+            Handle::File(f) => f.read_line(buf),
+            Handle::Stdin => io::stdin().read_line(buf),
+            Handle::Reader(r) => r.read_line(buf),
+            _ => Result::Err(unsupported("reading")),
+        }
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> io::Result<()> {
+        match self {
+            Handle::File(f) => {
+                let file = f.get_mut();
+                file.write_all(bytes)?;
+                file.flush()
+            }
+            Handle::Stdout => {
+                let mut stdout = io::stdout();
+                stdout.write_all(bytes)?;
+                stdout.flush()
+            }
+            Handle::Stderr => io::stderr().write_all(bytes),
+            Handle::Writer(w) => {
+                w.write_all(bytes)?;
+                w.flush()
+            }
+            _ => Result::Err(unsupported("writing")),
+        }
+    }
+
+    fn seek(&mut self, position: u64) -> io::Result<u64> {
+        match self {
+            Handle::File(f) => f.seek(SeekFrom::Start(position)),
+            _ => Result::Err(unsupported("seeking")),
+        }
+    }
+}
+
+fn unsupported(action: &str) -> io::Error {
+    io::Error::other(format!("Stream does not support {}", action))
+}
+
+impl Debug for Handle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Handle::File(file) => f.debug_tuple("File").field(file.get_ref()).finish(),
+            Handle::Stdin => f.write_str("Stdin"),
+            Handle::Stdout => f.write_str("Stdout"),
+            Handle::Stderr => f.write_str("Stderr"),
+            Handle::Reader(_) => f.write_str("Reader"),
+            Handle::Writer(_) => f.write_str("Writer"),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use crate::custom_types::exceptions::io_error;
+    use crate::custom_types::file::FileObj;
+    use crate::runtime::Runtime;
+    use crate::string_var::StringVar;
+    use crate::variable::Variable;
+    use std::cell::RefCell;
+    use std::io::{self, Cursor, Write};
+    use std::rc::Rc;
+
+    /// A writer whose output can still be read after it has been boxed.
+    #[derive(Debug, Clone, Default)]
+    pub struct SharedBuffer(pub Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Result::Ok(())
+        }
+    }
+
+    fn call_file(file: &Rc<FileObj>, name: &str, args: Vec<Variable>) -> Result<Variable, ()> {
+        Runtime::test(|runtime| {
+            runtime.call_attr(file.clone().into(), name, args)?;
+            let result = runtime.pop_return();
+            runtime.return_1(result)
+        })
+    }
+
+    fn some_str(value: &str) -> Result<Variable, ()> {
+        Result::Ok(Option::Some(StringVar::from(value.to_owned()).into()).into())
+    }
+
+    #[test]
+    fn write_captured() {
+        let buffer = SharedBuffer::default();
+        let file = FileObj::from_writer("<test>", Box::new(buffer.clone()));
+        let result = Runtime::test(|runtime| {
+            for text in &["Hello, ", "world!\n"] {
+                let args = vec![StringVar::from(*text).into()];
+                runtime.call_attr(file.clone().into(), "write", args)?;
+            }
+            runtime.return_1(true.into())
+        });
+        assert_eq!(result, Result::Ok(true.into()));
+        assert_eq!(&*buffer.0.borrow(), b"Hello, world!\n");
+    }
+
+    #[test]
+    fn read_line() {
+        let input = Cursor::new(b"first\nsecond\r\n\nlast".to_vec());
+        let file = FileObj::from_reader("<test>", Box::new(input));
+        assert_eq!(call_file(&file, "readLine", vec![]), some_str("first"));
+        assert_eq!(call_file(&file, "readLine", vec![]), some_str("second"));
+        assert_eq!(call_file(&file, "readLine", vec![]), some_str(""));
+        assert_eq!(call_file(&file, "readLine", vec![]), some_str("last"));
+        let eof: Variable = Option::<Variable>::None.into();
+        assert_eq!(call_file(&file, "readLine", vec![]), Result::Ok(eof));
+    }
+
+    fn error_type(file: &Rc<FileObj>, name: &str, args: Vec<Variable>) -> Result<Variable, ()> {
+        Runtime::test(|runtime| {
+            assert!(runtime.call_attr(file.clone().into(), name, args).is_err());
+            let exc = runtime.pop_err()?;
+            runtime.return_1(exc.get_type().into())
+        })
+    }
+
+    #[test]
+    fn std_streams_not_seekable() {
+        for file in &[FileObj::stdin(), FileObj::stdout(), FileObj::stderr()] {
+            let result = error_type(file, "seek", vec![0.into()]);
+            assert_eq!(result, Result::Ok(io_error().into()));
+        }
+    }
+
+    #[test]
+    fn wrong_direction() {
+        let reader = FileObj::from_reader("<test>", Box::new(Cursor::new(Vec::new())));
+        let result = error_type(&reader, "write", vec![StringVar::from("a").into()]);
+        assert_eq!(result, Result::Ok(io_error().into()));
+        let writer = FileObj::from_writer("<test>", Box::new(SharedBuffer::default()));
+        assert_eq!(
+            error_type(&writer, "readLine", vec![]),
+            Result::Ok(io_error().into())
+        );
     }
 }
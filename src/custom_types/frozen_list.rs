@@ -0,0 +1,346 @@
+use crate::custom_types::exceptions::index_error;
+use crate::custom_types::range::Range;
+use crate::custom_types::{elements_equal, join_values};
+use crate::custom_var::{downcast_var, CustomVar};
+use crate::int_var::{normalize, IntVar};
+use crate::looping::{self, TypicalIterator};
+use crate::method::{NativeMethod, StdMethod};
+use crate::name::Name;
+use crate::operator::Operator;
+use crate::runtime::Runtime;
+use crate::std_type::Type;
+use crate::string_var::{MaybeString, StringVar};
+use crate::tuple::hash_values;
+use crate::variable::{FnResult, Variable};
+use crate::{check_args, first};
+use ascii::{AsciiChar, AsciiStr};
+use once_cell::sync::Lazy;
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// An immutable list.
+///
+/// Unlike [`List`](crate::custom_types::list::List), this has no mutating
+/// methods, and so it can be hashed and used as a set element or dict key.
+#[derive(Debug)]
+pub struct FrozenList {
+    generic: Type,
+    values: Box<[Variable]>,
+}
+
+#[derive(Debug)]
+struct FrozenListIter {
+    current: Cell<usize>,
+    value: Rc<FrozenList>,
+}
+
+impl FrozenList {
+    pub fn new(generic: Type, values: Box<[Variable]>) -> Rc<FrozenList> {
+        Rc::new(FrozenList { generic, values })
+    }
+
+    fn op_fn(o: Operator) -> NativeMethod<Rc<FrozenList>> {
+        match o {
+            Operator::GetAttr => Self::index,
+            Operator::Bool => Self::bool,
+            Operator::Str => Self::str,
+            Operator::Repr => Self::repr,
+            Operator::Equals => Self::eq,
+            Operator::Hash => Self::hash,
+            Operator::In => Self::contains,
+            Operator::GetSlice => Self::get_slice,
+            Operator::Iter => Self::iter,
+            _ => unimplemented!("FrozenList.{}", o.name()),
+        }
+    }
+
+    fn attr_fn(name: &str) -> Option<NativeMethod<Rc<FrozenList>>> {
+        Option::Some(match name {
+            "elementsEqual" => Self::elements_equal,
+            _ => return Option::None,
+        })
+    }
+
+    fn index(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert_eq!(args.len(), 1);
+        match normalize(self.values.len(), first(args).into()) {
+            Result::Ok(i) => runtime.return_1(self.values[i].clone()),
+            Result::Err(index) => Self::index_err(runtime, self.values.len(), &index),
+        }
+    }
+
+    fn bool(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert!(args.is_empty());
+        runtime.return_1((!self.values.is_empty()).into())
+    }
+
+    fn str(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert!(args.is_empty());
+        let value = self.repr_value(runtime)?;
+        runtime.return_1(value.into())
+    }
+
+    fn repr(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert!(args.is_empty());
+        let value = self.repr_value(runtime)?;
+        runtime.return_1(value.into())
+    }
+
+    fn repr_value(&self, runtime: &mut Runtime) -> Result<StringVar, ()> {
+        let value = join_values(&self.values, |x| x.repr(runtime))?;
+        Result::Ok(Self::surround(value).into())
+    }
+
+    fn surround(mut str: MaybeString) -> MaybeString {
+        static FROZEN: Lazy<&AsciiStr> = Lazy::new(|| AsciiStr::from_ascii("FrozenList[").unwrap());
+        str.insert_ascii_str(0, *FROZEN);
+        str.push_ascii(AsciiChar::BracketClose);
+        str
+    }
+
+    fn eq(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        for arg in args {
+            let is_eq = match downcast_var::<FrozenList>(arg) {
+                Result::Err(_) => false,
+                Result::Ok(other) => {
+                    self.values.len() == other.values.len()
+                        && elements_equal(&self.values, other.into(), runtime)?
+                }
+            };
+            if !is_eq {
+                return runtime.return_1(false.into());
+            }
+        }
+        runtime.return_1(true.into())
+    }
+
+    fn hash(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert!(args.is_empty());
+        let result = hash_values(&self.values, runtime)?;
+        runtime.return_1(IntVar::from(result).into())
+    }
+
+    fn contains(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert_eq!(args.len(), 1);
+        let arg = first(args);
+        for val in self.values.iter() {
+            if arg.clone().equals(val.clone(), runtime)? {
+                return runtime.return_1(true.into());
+            }
+        }
+        runtime.return_1(false.into())
+    }
+
+    fn get_slice(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert_eq!(args.len(), 1);
+        let range = Range::from_slice(self.values.len(), runtime, first(args))?;
+        let values = range
+            .slice_indices(self.values.len())
+            .map(|i| self.values[i].clone())
+            .collect();
+        runtime.return_1(Self::new(self.generic, values).into())
+    }
+
+    fn iter(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert!(args.is_empty());
+        runtime.return_1(Rc::new(FrozenListIter::new(self)).into())
+    }
+
+    fn elements_equal(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        check_args("FrozenList.elementsEqual", &args, 1, runtime)?;
+        let result = elements_equal(&self.values, first(args), runtime)?;
+        runtime.return_1(result.into())
+    }
+
+    fn create(_args: Vec<Variable>, _runtime: &mut Runtime) -> FnResult {
+        unimplemented!()
+    }
+
+    pub fn frozen_list_type() -> Type {
+        custom_class!(FrozenList, create, "FrozenList")
+    }
+
+    fn index_err(runtime: &mut Runtime, len: usize, index: &IntVar) -> FnResult {
+        runtime.throw_quick(
+            index_error(),
+            format!("Index {} out of bounds for list of length {}", index, len),
+        )
+    }
+}
+
+impl CustomVar for FrozenList {
+    fn set(self: Rc<Self>, _name: Name, _object: Variable) {
+        unimplemented!()
+    }
+
+    fn get_type(&self) -> Type {
+        Self::frozen_list_type()
+    }
+
+    fn get_operator(self: Rc<Self>, op: Operator) -> Variable {
+        StdMethod::new_native(self, Self::op_fn(op)).into()
+    }
+
+    fn get_attribute(self: Rc<Self>, name: &str) -> Option<Variable> {
+        match name {
+            "length" => Option::Some(IntVar::from(self.values.len()).into()),
+            _ => Option::Some(StdMethod::new_native(self, Self::attr_fn(name)?).into()),
+        }
+    }
+
+    fn call_op(
+        self: Rc<Self>,
+        operator: Operator,
+        args: Vec<Variable>,
+        runtime: &mut Runtime,
+    ) -> FnResult {
+        runtime.call_native_method(Self::op_fn(operator), self, args)
+    }
+
+    fn call_op_or_goto(
+        self: Rc<Self>,
+        operator: Operator,
+        args: Vec<Variable>,
+        runtime: &mut Runtime,
+    ) -> FnResult {
+        runtime.call_native_method(Self::op_fn(operator), self, args)
+    }
+
+    fn str(self: Rc<Self>, runtime: &mut Runtime) -> Result<StringVar, ()> {
+        self.repr_value(runtime)
+    }
+
+    fn repr(self: Rc<Self>, runtime: &mut Runtime) -> Result<StringVar, ()> {
+        self.repr_value(runtime)
+    }
+
+    fn bool(self: Rc<Self>, _runtime: &mut Runtime) -> Result<bool, ()> {
+        Result::Ok(!self.values.is_empty())
+    }
+
+    fn iter(self: Rc<Self>, _runtime: &mut Runtime) -> Result<looping::Iterator, ()> {
+        Result::Ok(Rc::new(FrozenListIter::new(self)).into())
+    }
+}
+
+impl FrozenListIter {
+    fn new(value: Rc<FrozenList>) -> FrozenListIter {
+        FrozenListIter {
+            value,
+            current: Cell::new(0),
+        }
+    }
+
+    fn create(_args: Vec<Variable>, _runtime: &mut Runtime) -> FnResult {
+        unimplemented!()
+    }
+}
+
+impl TypicalIterator for FrozenListIter {
+    fn inner_next(&self) -> Option<Variable> {
+        let result = self.value.values.get(self.current.get()).cloned();
+        if result.is_some() {
+            self.current.set(self.current.get() + 1);
+        }
+        result
+    }
+
+    fn get_type() -> Type {
+        custom_class!(FrozenListIter, create, "FrozenListIter")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::custom_types::dict::Dict;
+    use crate::custom_types::frozen_list::FrozenList;
+    use crate::custom_types::list::List;
+    use crate::custom_types::slice::Slice;
+    use crate::custom_var::downcast_var;
+    use crate::int_var::IntVar;
+    use crate::operator::Operator;
+    use crate::runtime::Runtime;
+    use crate::std_type::Type;
+    use crate::variable::Variable;
+    use std::rc::Rc;
+
+    fn ints(values: &[i32]) -> Vec<Variable> {
+        values.iter().map(|&x| IntVar::from(x).into()).collect()
+    }
+
+    fn frozen(values: &[i32]) -> Result<Rc<FrozenList>, ()> {
+        let list = List::from_values(Type::Bigint, ints(values));
+        let result = Runtime::test(|runtime| {
+            runtime.call_attr(list.into(), "immutable", Vec::new())?;
+            let result = runtime.pop_return();
+            runtime.return_1(result)
+        })?;
+        Result::Ok(downcast_var(result).unwrap_or_else(|_| panic!("Expected a FrozenList")))
+    }
+
+    #[test]
+    fn immutable_index() -> Result<(), ()> {
+        let list = frozen(&[1, 2, 3])?;
+        assert_eq!(&*list.values, &*ints(&[1, 2, 3]));
+        let result = Runtime::test(|runtime| {
+            let mut found = Vec::new();
+            for i in [0, 2, -1] {
+                runtime.call_op(list.clone().into(), Operator::GetAttr, ints(&[i]))?;
+                found.push(runtime.pop_return());
+            }
+            runtime.return_1(List::from_values(Type::Bigint, found).into())
+        })?;
+        let result = downcast_var::<List>(result).unwrap();
+        assert_eq!(result.values().to_vec(), ints(&[1, 3, 3]));
+        Result::Ok(())
+    }
+
+    fn get_slice(list: Rc<FrozenList>, slice: Slice) -> Result<Vec<Variable>, ()> {
+        let result = Runtime::test(|runtime| {
+            runtime.call_op(list.into(), Operator::GetSlice, vec![Rc::new(slice).into()])
+        })?;
+        let result = downcast_var::<FrozenList>(result).unwrap();
+        Result::Ok(result.values.to_vec())
+    }
+
+    #[test]
+    fn slice_past_end() -> Result<(), ()> {
+        let list = frozen(&[1, 2, 3])?;
+        let slice = Slice::new(
+            Option::Some(0.into()),
+            Option::Some(100.into()),
+            Option::None,
+        );
+        assert_eq!(get_slice(list, slice)?, ints(&[1, 2, 3]));
+        Result::Ok(())
+    }
+
+    #[test]
+    fn slice_negative_step() -> Result<(), ()> {
+        let list = frozen(&[1, 2, 3, 4])?;
+        let slice = Slice::new(Option::None, Option::None, Option::Some((-1).into()));
+        assert_eq!(get_slice(list.clone(), slice)?, ints(&[4, 3, 2, 1]));
+        let slice = Slice::new(
+            Option::Some(2.into()),
+            Option::None,
+            Option::Some((-2).into()),
+        );
+        assert_eq!(get_slice(list, slice)?, ints(&[3, 1]));
+        Result::Ok(())
+    }
+
+    #[test]
+    fn immutable_dict_key() -> Result<(), ()> {
+        let key = frozen(&[1, 2])?;
+        let lookup = frozen(&[1, 2])?;
+        let other = frozen(&[2, 1])?;
+        let result = Runtime::test(|runtime| {
+            let dict = Dict::from_args(vec![key.into()], ints(&[5]), runtime)?;
+            assert_eq!(dict.get_value(other.into(), runtime)?, Option::None);
+            let value = dict.get_value(lookup.into(), runtime)?;
+            runtime.return_1(value.unwrap())
+        });
+        assert_eq!(result, Result::Ok(IntVar::from(5).into()));
+        Result::Ok(())
+    }
+}
@@ -0,0 +1,397 @@
+use crate::custom_types::exceptions::key_error;
+use crate::custom_var::{CustomVar, CustomVarWrapper};
+use crate::method::{NativeMethod, StdMethod};
+use crate::name::Name;
+use crate::operator::Operator;
+use crate::runtime::Runtime;
+use crate::std_type::Type;
+use crate::std_variable::WeakStdVariable;
+use crate::string_var::StringVar;
+use crate::variable::{FnResult, InnerVar, OptionVar, Variable};
+use crate::{check_args, first, first_n};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::{Rc, Weak};
+
+/// A map keyed by object identity (see [`Variable::id`]) rather than by
+/// value equality.
+///
+/// Keys which are heap objects are held weakly, so an entry disappears once
+/// nothing else refers to its key. Keys without a lifetime of their own (such
+/// as small integers or strings) are held strongly instead. An option is
+/// identified by its value together with its depth, so `x`, `Some(x)` and
+/// `Some(Some(x))` are all different keys, each of which holds `x` weakly.
+#[derive(Debug)]
+pub struct IdentityMap {
+    values: RefCell<HashMap<(usize, usize), Entry>>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    key: WeakKey,
+    value: Variable,
+}
+
+#[derive(Debug)]
+struct WeakKey {
+    /// The option depth of the key, or 0 if it is not an option.
+    depth: usize,
+    value: WeakValue,
+}
+
+#[derive(Debug)]
+enum WeakValue {
+    Standard(WeakStdVariable),
+    Custom(Weak<dyn CustomVar>),
+    Strong(Variable),
+}
+
+impl IdentityMap {
+    pub fn new() -> Rc<IdentityMap> {
+        Rc::new(IdentityMap {
+            values: RefCell::new(HashMap::new()),
+        })
+    }
+
+    pub fn get_value(&self, key: &Variable) -> Option<Variable> {
+        let values = self.values.borrow();
+        // While an entry exists its weak key keeps the allocation around, so
+        // the id cannot have been reused by a different object
+        values
+            .get(&map_key(key))
+            .filter(|entry| entry.key.is_alive())
+            .map(|entry| entry.value.clone())
+    }
+
+    pub fn set_value(&self, key: Variable, value: Variable) {
+        self.purge();
+        let entry = Entry {
+            key: WeakKey::new(&key),
+            value,
+        };
+        self.values.borrow_mut().insert(map_key(&key), entry);
+    }
+
+    pub fn remove_value(&self, key: &Variable) -> Option<Variable> {
+        self.purge();
+        self.values
+            .borrow_mut()
+            .remove(&map_key(key))
+            .map(|entry| entry.value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.purge();
+        self.values.borrow().len()
+    }
+
+    fn purge(&self) {
+        self.values
+            .borrow_mut()
+            .retain(|_, entry| entry.key.is_alive());
+    }
+
+    fn op_fn(o: Operator) -> NativeMethod<Rc<IdentityMap>> {
+        match o {
+            Operator::GetAttr => Self::index,
+            Operator::SetAttr => Self::set,
+            Operator::DelAttr => Self::del,
+            Operator::In => Self::contains,
+            Operator::Bool => Self::bool,
+            Operator::Str => Self::repr,
+            Operator::Repr => Self::repr,
+            _ => unimplemented!("IdentityMap.{}", o.name()),
+        }
+    }
+
+    fn attr_fn(s: &str) -> Option<NativeMethod<Rc<IdentityMap>>> {
+        Option::Some(match s {
+            "get" => Self::get,
+            "remove" => Self::remove,
+            "clear" => Self::clear,
+            _ => return Option::None,
+        })
+    }
+
+    fn index(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert_eq!(args.len(), 1);
+        match self.get_value(&first(args)) {
+            Option::Some(result) => runtime.return_1(result),
+            Option::None => runtime.throw_quick(key_error(), "Value not found"),
+        }
+    }
+
+    fn set(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert_eq!(args.len(), 2);
+        let [key, value] = first_n(args);
+        self.set_value(key, value);
+        runtime.return_0()
+    }
+
+    fn del(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert_eq!(args.len(), 1);
+        match self.remove_value(&first(args)) {
+            Option::Some(_) => runtime.return_0(),
+            Option::None => runtime.throw_quick(key_error(), "Value not found"),
+        }
+    }
+
+    fn contains(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert_eq!(args.len(), 1);
+        let is_in = self.get_value(&first(args)).is_some();
+        runtime.return_1(is_in.into())
+    }
+
+    fn bool(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert!(args.is_empty());
+        runtime.return_1((self.len() != 0).into())
+    }
+
+    fn repr(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert!(args.is_empty());
+        let result = self.repr_str(runtime)?;
+        runtime.return_1(result.into())
+    }
+
+    fn get(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        if args.len() == 1 {
+            let val = self.get_value(&first(args)).into();
+            runtime.return_1(val)
+        } else {
+            check_args("IdentityMap.get", &args, 2, runtime)?;
+            let [key, default] = first_n(args);
+            let val = self.get_value(&key).unwrap_or(default);
+            runtime.return_1(val)
+        }
+    }
+
+    fn remove(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        check_args("IdentityMap.remove", &args, 1, runtime)?;
+        let removed = self.remove_value(&first(args)).into();
+        runtime.return_1(removed)
+    }
+
+    fn clear(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        check_args("IdentityMap.clear", &args, 0, runtime)?;
+        self.values.borrow_mut().clear();
+        runtime.return_0()
+    }
+
+    fn repr_str(&self, runtime: &mut Runtime) -> Result<StringVar, ()> {
+        // Collect the pairs first so the map is not borrowed while calling
+        // back into user code
+        let pairs: Vec<_> = self
+            .values
+            .borrow()
+            .values()
+            .filter_map(|entry| Option::Some((entry.key.upgrade()?, entry.value.clone())))
+            .collect();
+        let mut result = String::from("IdentityMap({");
+        for (i, (key, value)) in pairs.into_iter().enumerate() {
+            if i != 0 {
+                result += ", ";
+            }
+            result += key.repr(runtime)?.as_str();
+            result += ": ";
+            result += value.repr(runtime)?.as_str();
+        }
+        result += "})";
+        Result::Ok(result.into())
+    }
+
+    fn create(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        check_args("IdentityMap", &args, 0, runtime)?;
+        runtime.return_1(IdentityMap::new().into())
+    }
+
+    pub fn identity_map_type() -> Type {
+        custom_class!(IdentityMap, create, "IdentityMap")
+    }
+}
+
+/// The key of `key` in the map: its id, together with its option depth since
+/// options share the id of their value.
+fn map_key(key: &Variable) -> (usize, usize) {
+    match key {
+        Variable::Normal(x) => (x.id(), 0),
+        Variable::Option(x) => (x.id(), x.depth),
+    }
+}
+
+impl WeakKey {
+    fn new(key: &Variable) -> WeakKey {
+        // Options are unwrapped so that their value is held weakly as well
+        let (depth, inner) = match key {
+            Variable::Normal(x) => (0, Option::Some(x)),
+            Variable::Option(x) => (x.depth, x.value.as_ref()),
+        };
+        let value = match inner {
+            Option::Some(InnerVar::Standard(s)) => WeakValue::Standard(s.downgrade()),
+            Option::Some(InnerVar::Custom(c)) => WeakValue::Custom(c.downgrade()),
+            _ => WeakValue::Strong(key.clone()),
+        };
+        WeakKey { depth, value }
+    }
+
+    fn upgrade(&self) -> Option<Variable> {
+        let value = match &self.value {
+            WeakValue::Standard(s) => InnerVar::Standard(s.upgrade()?),
+            WeakValue::Custom(c) => InnerVar::Custom(CustomVarWrapper::new(c.upgrade()?)),
+            WeakValue::Strong(x) => return Option::Some(x.clone()),
+        };
+        Option::Some(if self.depth == 0 {
+            value.into()
+        } else {
+            OptionVar::new(self.depth, Option::Some(value)).into()
+        })
+    }
+
+    fn is_alive(&self) -> bool {
+        match &self.value {
+            WeakValue::Standard(s) => s.upgrade().is_some(),
+            WeakValue::Custom(c) => c.strong_count() != 0,
+            WeakValue::Strong(_) => true,
+        }
+    }
+}
+
+impl CustomVar for IdentityMap {
+    fn set(self: Rc<Self>, _name: Name, _object: Variable) {
+        unimplemented!()
+    }
+
+    fn get_type(&self) -> Type {
+        IdentityMap::identity_map_type()
+    }
+
+    fn get_operator(self: Rc<Self>, o: Operator) -> Variable {
+        let func = IdentityMap::op_fn(o);
+        StdMethod::new_native(self, func).into()
+    }
+
+    fn get_attribute(self: Rc<Self>, s: &str) -> Option<Variable> {
+        let func = match s {
+            "length" => return Option::Some(self.len().into()),
+            _ => Self::attr_fn(s)?,
+        };
+        Option::Some(StdMethod::new_native(self, func).into())
+    }
+
+    fn call_op(
+        self: Rc<Self>,
+        operator: Operator,
+        args: Vec<Variable>,
+        runtime: &mut Runtime,
+    ) -> FnResult {
+        runtime.call_native_method(IdentityMap::op_fn(operator), self, args)
+    }
+
+    fn call_op_or_goto(
+        self: Rc<Self>,
+        operator: Operator,
+        args: Vec<Variable>,
+        runtime: &mut Runtime,
+    ) -> FnResult {
+        runtime.call_native_method(IdentityMap::op_fn(operator), self, args)
+    }
+
+    fn str(self: Rc<Self>, runtime: &mut Runtime) -> Result<StringVar, ()> {
+        self.repr_str(runtime)
+    }
+
+    fn repr(self: Rc<Self>, runtime: &mut Runtime) -> Result<StringVar, ()> {
+        self.repr_str(runtime)
+    }
+
+    fn bool(self: Rc<Self>, _runtime: &mut Runtime) -> Result<bool, ()> {
+        Result::Ok(self.len() != 0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::custom_types::identity_map::IdentityMap;
+    use crate::custom_types::list::List;
+    use crate::int_var::IntVar;
+    use crate::operator::Operator;
+    use crate::runtime::Runtime;
+    use crate::std_type::Type;
+    use crate::variable::Variable;
+
+    fn list_of_one() -> Variable {
+        List::from_values(Type::Bigint, vec![IntVar::from(1).into()]).into()
+    }
+
+    #[test]
+    fn equal_keys_kept_separate() {
+        let result = Runtime::test(|runtime| {
+            let first = list_of_one();
+            let second = list_of_one();
+            assert!(first.clone().equals(second.clone(), runtime)?);
+            let map: Variable = IdentityMap::new().into();
+            map.clone().call_op(
+                Operator::SetAttr,
+                vec![first.clone(), IntVar::from(1).into()],
+                runtime,
+            )?;
+            map.clone().call_op(
+                Operator::SetAttr,
+                vec![second.clone(), IntVar::from(2).into()],
+                runtime,
+            )?;
+            map.clone()
+                .call_op(Operator::GetAttr, vec![first], runtime)?;
+            assert_eq!(runtime.pop_return(), IntVar::from(1).into());
+            map.clone()
+                .call_op(Operator::GetAttr, vec![second], runtime)?;
+            assert_eq!(runtime.pop_return(), IntVar::from(2).into());
+            map.call_op(Operator::In, vec![list_of_one()], runtime)?;
+            let contains_other = runtime.pop_return();
+            runtime.return_1(contains_other)
+        });
+        assert_eq!(result, Result::Ok(false.into()));
+    }
+
+    #[test]
+    fn collected_keys_removed() {
+        let map = IdentityMap::new();
+        let kept = list_of_one();
+        let dropped = list_of_one();
+        map.set_value(kept.clone(), IntVar::from(1).into());
+        map.set_value(dropped.clone(), IntVar::from(2).into());
+        assert_eq!(map.len(), 2);
+        drop(dropped);
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get_value(&kept), Option::Some(IntVar::from(1).into()));
+    }
+
+    #[test]
+    fn option_keys_separate() {
+        let map = IdentityMap::new();
+        let value = list_of_one();
+        let some: Variable = Option::Some(value.clone()).into();
+        let some_some: Variable = Option::Some(some.clone()).into();
+        map.set_value(value.clone(), IntVar::from(0).into());
+        map.set_value(some.clone(), IntVar::from(1).into());
+        map.set_value(some_some.clone(), IntVar::from(2).into());
+        assert_eq!(map.get_value(&value), Option::Some(IntVar::from(0).into()));
+        assert_eq!(map.get_value(&some), Option::Some(IntVar::from(1).into()));
+        assert_eq!(
+            map.get_value(&some_some),
+            Option::Some(IntVar::from(2).into())
+        );
+        let none: Variable = Option::<Variable>::None.into();
+        assert_eq!(map.get_value(&none), Option::None);
+        assert_eq!(map.get_value(&Variable::null()), Option::None);
+    }
+
+    #[test]
+    fn option_keys_weak() {
+        let map = IdentityMap::new();
+        let value = list_of_one();
+        map.set_value(Option::Some(value.clone()).into(), IntVar::from(1).into());
+        assert_eq!(map.len(), 1);
+        drop(value);
+        assert_eq!(map.len(), 0);
+    }
+}
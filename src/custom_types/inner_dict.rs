@@ -1,10 +1,13 @@
+use crate::custom_types::exceptions::value_error;
 use crate::custom_types::ASCII_COMMA;
+use crate::int_var::IntVar;
 use crate::looping::{IterAttrs, IterResult, NativeIterator};
 use crate::runtime::Runtime;
 use crate::std_type::Type;
 use crate::string_var::{MaybeString, StringVar};
 use crate::variable::{FnResult, Variable};
 use ascii::{AsciiChar, AsciiStr};
+use num::ToPrimitive;
 use once_cell::sync::Lazy;
 use std::cell::{Cell, Ref};
 use std::cmp::{max, min};
@@ -48,6 +51,9 @@ pub(super) struct EntryMut<'a> {
 impl InnerDict {
     const PERTURB_SHIFT: u32 = 5;
     const MIN_SIZE: usize = 8;
+    /// The largest capacity hint honoured by `with_capacity`; larger hints
+    /// are clamped so an oversized hint cannot exhaust memory up front.
+    const MAX_CAPACITY_HINT: usize = 1 << 16;
 
     pub fn new() -> InnerDict {
         InnerDict {
@@ -57,6 +63,36 @@ impl InnerDict {
         }
     }
 
+    /// Creates an empty dict with room for at least `capacity` entries
+    /// before it needs to be resized.
+    ///
+    /// The capacity is only a hint, and is clamped to `MAX_CAPACITY_HINT`;
+    /// the dict still grows as usual past that.
+    pub fn with_capacity(capacity: usize) -> InnerDict {
+        let capacity = min(capacity, Self::MAX_CAPACITY_HINT);
+        InnerDict {
+            size: 0,
+            size_w_deleted: 0,
+            entries: vec![Entry::None; Self::new_cap(0, capacity)],
+        }
+    }
+
+    /// Converts the capacity argument of a `dict` or `set` constructor.
+    pub(super) fn capacity_arg(
+        cap: Variable,
+        type_name: &str,
+        runtime: &mut Runtime,
+    ) -> Result<usize, ()> {
+        let cap = IntVar::from(cap);
+        match cap.to_usize() {
+            Option::Some(x) => Result::Ok(x),
+            Option::None => runtime.throw_quick_native(
+                value_error(),
+                format!("Value {} too big to create {}", cap, type_name),
+            ),
+        }
+    }
+
     pub(super) fn entries_raw_mut(&mut self) -> &mut [Entry] {
         &mut self.entries
     }
@@ -74,12 +110,7 @@ impl InnerDict {
         if keys.is_empty() {
             Result::Ok(InnerDict::new())
         } else {
-            let vec_capacity = Self::new_cap(0, keys.len());
-            let mut value = InnerDict {
-                size: 0,
-                size_w_deleted: 0,
-                entries: vec![Entry::None; vec_capacity],
-            };
+            let mut value = InnerDict::with_capacity(keys.len());
             for (x, y) in keys.into_iter().zip(values) {
                 value.set(x, y, runtime)?;
             }
@@ -91,6 +122,17 @@ impl InnerDict {
         self.size
     }
 
+    /// The key-value pairs of the dict, in iteration order.
+    pub fn pairs(&self) -> Vec<(Variable, Variable)> {
+        self.entries
+            .iter()
+            .filter_map(|x| match x {
+                Entry::Some(e) => Option::Some((e.key.clone(), e.value.clone())),
+                _ => Option::None,
+            })
+            .collect()
+    }
+
     pub fn get(&self, key: Variable, runtime: &mut Runtime) -> Result<Option<Variable>, ()> {
         if self.entries.is_empty() {
             Result::Ok(Option::None)
@@ -173,9 +215,9 @@ impl InnerDict {
                     result += *ASCII_COMMA;
                 }
                 first = false;
-                result += e.key.clone().str(runtime)?;
+                result += e.key.clone().repr(runtime)?;
                 result += *ASCII_COLON;
-                result += e.value.clone().str(runtime)?;
+                result += e.value.clone().repr(runtime)?;
             }
         }
         result.push_ascii(AsciiChar::CurlyBraceClose);
@@ -196,7 +238,7 @@ impl InnerDict {
                     result += *ASCII_COMMA;
                 }
                 first = false;
-                result += e.key.clone().str(runtime)?;
+                result += e.key.clone().repr(runtime)?;
             }
         }
         result.push_ascii(AsciiChar::CurlyBraceClose);
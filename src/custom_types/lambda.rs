@@ -62,8 +62,8 @@ impl CustomVar for Lambda {
         StdMethod::new_native(self, func).into()
     }
 
-    fn get_attribute(self: Rc<Self>, _name: &str) -> Variable {
-        unimplemented!()
+    fn get_attribute(self: Rc<Self>, _name: &str) -> Option<Variable> {
+        Option::None
     }
 
     fn call(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
@@ -1,17 +1,21 @@
-use crate::custom_types::exceptions::{index_error, value_error};
-use crate::custom_types::join_values;
+use crate::builtins::is_callable;
+use crate::copy::CopyMap;
+use crate::custom_types::exceptions::{index_error, invalid_state, value_error};
+use crate::custom_types::frozen_list::FrozenList;
 use crate::custom_types::range::Range;
+use crate::custom_types::{elements_equal, join_values};
 use crate::custom_var::{downcast_var, CustomVar};
 use crate::int_var::{normalize, IntVar};
 use crate::looping::{self, TypicalIterator};
 use crate::method::{NativeMethod, StdMethod};
 use crate::name::Name;
 use crate::operator::Operator;
+use crate::random::Rng;
 use crate::runtime::Runtime;
 use crate::std_type::Type;
 use crate::string_var::{MaybeString, StringVar};
 use crate::variable::{FnResult, Variable};
-use crate::{first, first_n};
+use crate::{check_args, first, first_n};
 use ascii::AsciiChar;
 use num::{One, Signed, ToPrimitive, Zero};
 use std::cell::{Cell, Ref, RefCell};
@@ -44,6 +48,28 @@ impl List {
         }
     }
 
+    pub(crate) fn deep_copy(
+        self: &Rc<Self>,
+        copies: &mut CopyMap,
+        runtime: &mut Runtime,
+    ) -> Result<Variable, ()> {
+        let result = List::from_values(self.generic, Vec::new());
+        if let Option::Some(copy) = copies.register(self, result.clone().into()) {
+            return Result::Ok(copy);
+        }
+        let values = self.values().to_vec();
+        let mut copied = Vec::with_capacity(values.len());
+        for value in values {
+            copied.push(copies.copy(value, runtime)?);
+        }
+        *result.value.borrow_mut() = copied;
+        Result::Ok(result.into())
+    }
+
+    pub fn shuffle(&self, rng: &mut Rng) {
+        rng.shuffle(&mut self.value.borrow_mut())
+    }
+
     fn op_fn(name: Operator) -> NativeMethod<Rc<List>> {
         match name {
             Operator::Bool => List::list_bool,
@@ -65,8 +91,8 @@ impl List {
         }
     }
 
-    fn attr_fn(name: &str) -> NativeMethod<Rc<List>> {
-        match name {
+    fn attr_fn(name: &str) -> Option<NativeMethod<Rc<List>>> {
+        Option::Some(match name {
             "containsAll" => Self::contains_all,
             "get" => Self::list_get,
             "reverse" => Self::reverse,
@@ -75,6 +101,7 @@ impl List {
             "add" => Self::add,
             "addAll" => Self::add_all,
             "insert" => Self::insert,
+            "insertAll" => Self::insert_all,
             "indexOf" => Self::index_of,
             "pop" => Self::pop,
             "popFirst" => Self::pop_first,
@@ -82,8 +109,16 @@ impl List {
             "remove" => Self::remove,
             "fill" => Self::fill,
             "fillWith" => Self::fill_with,
-            x => unimplemented!("List.{}", x),
-        }
+            "removeAll" => Self::remove_all,
+            "retainAll" => Self::retain_all,
+            "rotate" => Self::rotate,
+            "shuffle" => Self::shuffle_values,
+            "sample" => Self::sample,
+            "elementsEqual" => Self::elements_equal,
+            "copy" => Self::copy,
+            "immutable" => Self::immutable,
+            _ => return Option::None,
+        })
     }
 
     fn list_bool(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
@@ -202,12 +237,12 @@ impl List {
     }
 
     fn pop(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
-        debug_assert!(args.is_empty());
+        check_args("list.pop", &args, 0, runtime)?;
         runtime.return_1(self.value.borrow_mut().pop().into())
     }
 
     fn pop_first(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
-        debug_assert!(args.is_empty());
+        check_args("list.popFirst", &args, 0, runtime)?;
         if self.value.borrow().is_empty() {
             runtime.return_1(Option::None.into())
         } else {
@@ -216,7 +251,7 @@ impl List {
     }
 
     fn swap(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
-        debug_assert_eq!(args.len(), 2);
+        check_args("list.swap", &args, 2, runtime)?;
         let mut value = self.value.borrow_mut();
         let len = value.len();
         let [index_1, index_2] = first_n(args);
@@ -233,7 +268,7 @@ impl List {
     }
 
     fn remove(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
-        debug_assert_eq!(args.len(), 1);
+        check_args("list.remove", &args, 1, runtime)?;
         let mut value = self.value.borrow_mut();
         match normalize(value.len(), first(args).into()) {
             Result::Ok(i) => runtime.return_1(value.remove(i)),
@@ -242,14 +277,14 @@ impl List {
     }
 
     fn fill(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
-        debug_assert_eq!(args.len(), 1);
+        check_args("list.fill", &args, 1, runtime)?;
         let value = first(args);
         self.value.borrow_mut().fill(value);
         runtime.return_0()
     }
 
     fn fill_with(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
-        debug_assert_eq!(args.len(), 1);
+        check_args("list.fillWith", &args, 1, runtime)?;
         let value = first(args);
         for val in &mut *self.value.borrow_mut() {
             value.clone().call((Vec::new(), runtime))?;
@@ -281,7 +316,7 @@ impl List {
     }
 
     fn index_of(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
-        debug_assert!(args.len() == 1);
+        check_args("list.indexOf", &args, 1, runtime)?;
         let searcher = first(args);
         for (i, var) in self.value.borrow().iter().enumerate() {
             if searcher.clone().equals(var.clone(), runtime)? {
@@ -297,13 +332,117 @@ impl List {
     }
 
     fn reverse(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
-        debug_assert!(args.is_empty());
+        check_args("list.reverse", &args, 0, runtime)?;
         self.value.borrow_mut().reverse();
         runtime.return_0()
     }
 
+    fn remove_all(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        check_args("list.removeAll", &args, 1, runtime)?;
+        let arg = first(args);
+        let len = self.value.borrow().len();
+        let kept = if is_callable(&arg) {
+            self.retain_where(runtime, |x, runtime| {
+                arg.clone().call((vec![x], runtime))?;
+                Result::Ok(!runtime.pop_return().into_bool(runtime)?)
+            })?
+        } else {
+            self.retain_where(runtime, |x, runtime| {
+                Result::Ok(!x.equals(arg.clone(), runtime)?)
+            })?
+        };
+        runtime.return_1((len - kept).into())
+    }
+
+    fn retain_all(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        check_args("list.retainAll", &args, 1, runtime)?;
+        let other = first(args);
+        let kept = self.retain_where(runtime, |x, runtime| {
+            other.clone().call_op(Operator::In, vec![x], runtime)?;
+            runtime.pop_return().into_bool(runtime)
+        })?;
+        runtime.return_1(kept.into())
+    }
+
+    /// Removes every element for which `predicate` returns `false`, returning
+    /// the number of elements kept.
+    ///
+    /// The predicate is run over a snapshot of the list, so it may freely
+    /// read the list itself. If it modifies the list, an `InvalidState` is
+    /// thrown and the list is left as the predicate left it.
+    fn retain_where(
+        &self,
+        runtime: &mut Runtime,
+        mut predicate: impl FnMut(Variable, &mut Runtime) -> Result<bool, ()>,
+    ) -> Result<usize, ()> {
+        let values = self.value.borrow().clone();
+        let mut kept = Vec::with_capacity(values.len());
+        for value in &values {
+            if predicate(value.clone(), runtime)? {
+                kept.push(value.clone());
+            }
+        }
+        if !Self::same_values(&values, &self.value.borrow()) {
+            return runtime
+                .throw_quick_native(invalid_state(), "List was modified while filtering it");
+        }
+        let len = kept.len();
+        *self.value.borrow_mut() = kept;
+        Result::Ok(len)
+    }
+
+    fn same_values(first: &[Variable], second: &[Variable]) -> bool {
+        first.len() == second.len() && first.iter().zip(second).all(|(x, y)| x.id() == y.id())
+    }
+
+    fn rotate(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        check_args("list.rotate", &args, 1, runtime)?;
+        let count = IntVar::from(first(args));
+        let mut value = self.value.borrow_mut();
+        let len = value.len();
+        if len != 0 {
+            // The remainder is smaller in magnitude than len, so this can't overflow
+            let shift = (count % IntVar::from(len)).to_isize().unwrap();
+            value.rotate_right(shift.rem_euclid(len as isize) as usize);
+        }
+        runtime.return_0()
+    }
+
+    fn shuffle_values(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        check_args("list.shuffle", &args, 0, runtime)?;
+        self.shuffle(runtime.rng());
+        runtime.return_0()
+    }
+
+    fn sample(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        check_args("list.sample", &args, 1, runtime)?;
+        let count = IntVar::from(first(args));
+        let mut values = self.value.borrow().clone();
+        let count = match count.to_usize().filter(|&x| x <= values.len()) {
+            Option::Some(x) => x,
+            Option::None => {
+                return runtime.throw_quick(
+                    value_error(),
+                    format!(
+                        "Cannot take a sample of {} from a list of length {}",
+                        count,
+                        values.len()
+                    ),
+                )
+            }
+        };
+        // A Fisher-Yates shuffle stopped after the first `count` elements
+        let rng = runtime.rng();
+        for i in 0..count {
+            let j = i + rng.below(values.len() - i);
+            values.swap(i, j);
+        }
+        values.truncate(count);
+        runtime.return_1(List::from_values(self.generic, values).into())
+    }
+
     fn count(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
-        debug_assert!(args.len() == 1);
+        check_args("list.count", &args, 1, runtime)?;
         let mut count: usize = 0;
         for x in &*self.value.borrow() {
             if x.clone().equals(args[0].clone(), runtime)? {
@@ -314,13 +453,13 @@ impl List {
     }
 
     fn clear(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
-        debug_assert!(args.is_empty());
+        check_args("list.clear", &args, 0, runtime)?;
         self.value.borrow_mut().clear();
         runtime.return_0()
     }
 
     fn add(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
-        debug_assert_eq!(args.len(), 1);
+        check_args("list.add", &args, 1, runtime)?;
         if !args[0].get_type().is_subclass(&self.generic, runtime) {
             panic!(
                 "Bad type for list.add: got {}, expected {}\n{}",
@@ -334,7 +473,7 @@ impl List {
     }
 
     fn add_all(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
-        debug_assert_eq!(args.len(), 1);
+        check_args("list.addAll", &args, 1, runtime)?;
         let iterator = first(args).iter(runtime)?;
         let mut value = self.value.borrow_mut();
         while let Option::Some(val) = iterator.next(runtime)?.take_first() {
@@ -368,6 +507,25 @@ impl List {
         runtime.return_1(true.into())
     }
 
+    fn copy(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        check_args("list.copy", &args, 0, runtime)?;
+        let values = self.value.borrow().clone();
+        runtime.return_1(List::from_values(self.generic, values).into())
+    }
+
+    fn immutable(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        check_args("list.immutable", &args, 0, runtime)?;
+        let values = self.value.borrow().clone().into_boxed_slice();
+        runtime.return_1(FrozenList::new(self.generic, values).into())
+    }
+
+    fn elements_equal(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        check_args("list.elementsEqual", &args, 1, runtime)?;
+        let values = self.value.borrow().clone();
+        let result = elements_equal(&values, first(args), runtime)?;
+        runtime.return_1(result.into())
+    }
+
     fn vec_eq(first: &[Variable], second: &[Variable], runtime: &mut Runtime) -> Result<bool, ()> {
         let mut is_eq = true;
         for (a, b) in first.iter().zip(second.iter()) {
@@ -404,13 +562,7 @@ impl List {
         let [range, values] = first_n(args);
         let range = self.slice_to_range(runtime, range)?;
         if !range.get_step().is_one() {
-            return runtime.throw_quick(
-                value_error(),
-                format!(
-                    "list.operator [:]= is only valid with a slice step of 1, not {}",
-                    range.get_step()
-                ),
-            );
+            return self.set_extended_slice(&range, values, runtime);
         }
         let range_end = range.get_stop().to_usize().unwrap_or(usize::MAX);
         let value_iter = values.iter(runtime)?;
@@ -449,18 +601,48 @@ impl List {
         runtime.return_0()
     }
 
-    fn del_slice(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
-        debug_assert_eq!(args.len(), 1);
-        let range = self.slice_to_range(runtime, first(args))?;
-        if !range.get_step().is_one() {
+    /// Assigns to a slice with a step other than 1.
+    ///
+    /// Unlike with a step of 1, this cannot change the length of the list, so
+    /// there must be exactly one value per selected index.
+    fn set_extended_slice(
+        &self,
+        range: &Range,
+        values: Variable,
+        runtime: &mut Runtime,
+    ) -> FnResult {
+        let values: Vec<Variable> = looping::collect(values, runtime)?;
+        let mut array = self.value.borrow_mut();
+        let indices: Vec<usize> = range.slice_indices(array.len()).collect();
+        if indices.len() != values.len() {
             return runtime.throw_quick(
                 value_error(),
                 format!(
-                    "list.operator del[:] is only valid with a slice step of 1, not {}",
-                    range.get_step()
+                    "Cannot assign {} values to an extended slice of length {}",
+                    values.len(),
+                    indices.len()
                 ),
             );
         }
+        for (index, value) in indices.into_iter().zip(values) {
+            array[index] = value;
+        }
+        runtime.return_0()
+    }
+
+    fn del_slice(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert_eq!(args.len(), 1);
+        let range = self.slice_to_range(runtime, first(args))?;
+        if !range.get_step().is_one() {
+            let len = self.len();
+            let mut removed = vec![false; len];
+            for index in range.slice_indices(len) {
+                removed[index] = true;
+            }
+            let mut removed = removed.into_iter();
+            self.value.borrow_mut().retain(|_| !removed.next().unwrap());
+            return runtime.return_0();
+        }
         let range_start = range.get_start().to_usize().unwrap();
         let range_end = range.get_stop().to_usize().unwrap_or(usize::MAX);
         let len = self.len();
@@ -498,7 +680,7 @@ impl List {
     }
 
     pub fn insert(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
-        debug_assert_eq!(args.len(), 2);
+        check_args("list.insert", &args, 2, runtime)?;
         let [index, param] = first_n(args);
         let index = index.int(runtime)?;
         let mut value = self.value.borrow_mut();
@@ -534,6 +716,47 @@ impl List {
         }
     }
 
+    fn insert_all(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        check_args("list.insertAll", &args, 2, runtime)?;
+        let [index, values] = first_n(args);
+        let index = index.int(runtime)?;
+        let len = self.value.borrow().len();
+        let index = match normalize(len, index) {
+            Result::Ok(i) => i,
+            Result::Err(i) if i == len.into() => len,
+            Result::Err(i) => return Self::insert_error(len, i, runtime),
+        };
+        let mut new_values = Vec::new();
+        let iterator = values.iter(runtime)?;
+        while let Option::Some(val) = iterator.next(runtime)?.take_first() {
+            if !val.get_type().is_subclass(&self.generic, runtime) {
+                panic!(
+                    "Bad type for list[{}].insertAll: {}\n{}",
+                    self.generic.str(),
+                    val.get_type().str(),
+                    runtime.frame_strings(),
+                )
+            }
+            new_values.push(val);
+        }
+        let mut value = self.value.borrow_mut();
+        if index > value.len() {
+            return Self::insert_error(value.len(), index.into(), runtime);
+        }
+        value.splice(index..index, new_values);
+        runtime.return_0()
+    }
+
+    fn insert_error(len: usize, index: IntVar, runtime: &mut Runtime) -> FnResult {
+        runtime.throw_quick(
+            index_error(),
+            format!(
+                "Index {} out of bounds for insert on list of length {}",
+                index, len
+            ),
+        )
+    }
+
     pub fn create(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
         let result = match args.len() {
             0 => vec![],
@@ -603,10 +826,10 @@ impl CustomVar for List {
         StdMethod::new_native(self, value).into()
     }
 
-    fn get_attribute(self: Rc<Self>, name: &str) -> Variable {
+    fn get_attribute(self: Rc<Self>, name: &str) -> Option<Variable> {
         match name {
-            "length" => self.len().into(),
-            x => StdMethod::new_native(self, Self::attr_fn(x)).into(),
+            "length" => Option::Some(self.len().into()),
+            x => Option::Some(StdMethod::new_native(self, Self::attr_fn(x)?).into()),
         }
     }
 
@@ -629,8 +852,7 @@ impl CustomVar for List {
     }
 
     fn str(self: Rc<Self>, runtime: &mut Runtime) -> Result<StringVar, ()> {
-        let value = join_values(&**self.value.borrow(), |x| x.str(runtime))?;
-        Result::Ok(Self::surround(value).into())
+        self.repr(runtime)
     }
 
     fn repr(self: Rc<Self>, runtime: &mut Runtime) -> Result<StringVar, ()> {
@@ -728,3 +950,326 @@ impl<'a> Deref for SliceRef<'a> {
         &self.value
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::custom_types::exceptions::{index_error, invalid_state, value_error};
+    use crate::custom_types::list::List;
+    use crate::custom_types::set::Set;
+    use crate::custom_types::slice::Slice;
+    use crate::custom_var::downcast_var;
+    use crate::first;
+    use crate::function::Function;
+    use crate::int_var::IntVar;
+    use crate::operator::Operator;
+    use crate::random::Rng;
+    use crate::runtime::Runtime;
+    use crate::std_type::Type;
+    use crate::variable::{FnResult, Variable};
+    use num::Zero;
+    use std::rc::Rc;
+
+    fn int_list(values: &[i32]) -> Rc<List> {
+        List::from_values(
+            Type::Bigint,
+            values.iter().map(|&x| IntVar::from(x).into()).collect(),
+        )
+    }
+
+    fn insert_all(values: &[i32], index: i32, new: &[i32]) -> Result<Vec<Variable>, ()> {
+        let list = int_list(values);
+        Runtime::test(|runtime| {
+            let args = vec![IntVar::from(index).into(), int_list(new).into()];
+            runtime.call_attr(list.clone().into(), "insertAll", args)?;
+            runtime.return_1(true.into())
+        })?;
+        let result = list.values().to_vec();
+        Result::Ok(result)
+    }
+
+    fn ints(values: &[i32]) -> Vec<Variable> {
+        values.iter().map(|&x| IntVar::from(x).into()).collect()
+    }
+
+    #[test]
+    fn insert_all_middle() {
+        let result = insert_all(&[1, 2, 3], 1, &[4, 5]);
+        assert_eq!(result, Result::Ok(ints(&[1, 4, 5, 2, 3])));
+        let result = insert_all(&[1, 2, 3], -1, &[4, 5]);
+        assert_eq!(result, Result::Ok(ints(&[1, 2, 4, 5, 3])));
+    }
+
+    #[test]
+    fn insert_all_front() {
+        let result = insert_all(&[1, 2, 3], 0, &[4, 5]);
+        assert_eq!(result, Result::Ok(ints(&[4, 5, 1, 2, 3])));
+    }
+
+    #[test]
+    fn insert_all_end() {
+        let result = insert_all(&[1, 2, 3], 3, &[4, 5]);
+        assert_eq!(result, Result::Ok(ints(&[1, 2, 3, 4, 5])));
+        let result = insert_all(&[], 0, &[4]);
+        assert_eq!(result, Result::Ok(ints(&[4])));
+    }
+
+    #[test]
+    fn insert_all_out_of_range() {
+        let list = int_list(&[1, 2, 3]);
+        let result = Runtime::test(|runtime| {
+            let args = vec![IntVar::from(4).into(), int_list(&[4]).into()];
+            assert!(runtime
+                .call_attr(list.clone().into(), "insertAll", args)
+                .is_err());
+            let exc = runtime.pop_err()?;
+            runtime.return_1(exc.get_type().into())
+        });
+        assert_eq!(result, Result::Ok(index_error().into()));
+        assert_eq!(list.values().to_vec(), ints(&[1, 2, 3]));
+    }
+
+    fn call_list(list: &Rc<List>, name: &str, arg: Variable) -> Result<Variable, ()> {
+        Runtime::test(|runtime| {
+            runtime.call_attr(list.clone().into(), name, vec![arg])?;
+            let result = runtime.pop_return();
+            runtime.return_1(result)
+        })
+    }
+
+    #[test]
+    fn remove_all_value() {
+        let list = int_list(&[1, 2, 1, 3, 1]);
+        let result = call_list(&list, "removeAll", IntVar::from(1).into());
+        assert_eq!(result, Result::Ok(IntVar::from(3).into()));
+        assert_eq!(list.values().to_vec(), ints(&[2, 3]));
+    }
+
+    fn is_even(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        let value = IntVar::from(first(args));
+        runtime.return_1((value % 2.into()).is_zero().into())
+    }
+
+    #[test]
+    fn remove_all_predicate() {
+        let list = int_list(&[1, 2, 3, 4, 5]);
+        let result = call_list(&list, "removeAll", Function::Native(is_even).into());
+        assert_eq!(result, Result::Ok(IntVar::from(2).into()));
+        assert_eq!(list.values().to_vec(), ints(&[1, 3, 5]));
+    }
+
+    #[test]
+    fn retain_all_set() {
+        let list = int_list(&[1, 2, 3, 2, 4]);
+        let result = Runtime::test(|runtime| {
+            let set = Set::new(Type::Bigint, ints(&[2, 4, 6]), runtime)?;
+            runtime.call_attr(list.clone().into(), "retainAll", vec![set.into()])?;
+            let result = runtime.pop_return();
+            runtime.return_1(result)
+        });
+        assert_eq!(result, Result::Ok(IntVar::from(3).into()));
+        assert_eq!(list.values().to_vec(), ints(&[2, 2, 4]));
+    }
+
+    #[test]
+    fn retain_where_modified() {
+        let list = int_list(&[1, 2, 3]);
+        let result = Runtime::test(|runtime| {
+            let filtered = list.retain_where(runtime, |x, runtime| {
+                let value: Variable = list.clone().into();
+                runtime.call_attr(value, "add", vec![x])?;
+                Result::Ok(true)
+            });
+            assert!(filtered.is_err());
+            let exc = runtime.pop_err()?;
+            runtime.return_1(exc.get_type().into())
+        });
+        assert_eq!(result, Result::Ok(invalid_state().into()));
+        assert_eq!(list.values().len(), 6);
+    }
+
+    fn rotate(values: &[i32], count: i32) -> Vec<Variable> {
+        let list = int_list(values);
+        let result = Runtime::test(|runtime| {
+            let args = vec![IntVar::from(count).into()];
+            runtime.call_attr(list.clone().into(), "rotate", args)?;
+            runtime.return_1(true.into())
+        });
+        assert_eq!(result, Result::Ok(true.into()));
+        let result = list.values().to_vec();
+        result
+    }
+
+    #[test]
+    fn rotate_positive() {
+        assert_eq!(rotate(&[1, 2, 3, 4], 1), ints(&[4, 1, 2, 3]));
+        assert_eq!(rotate(&[1, 2, 3, 4], 4), ints(&[1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn rotate_negative() {
+        assert_eq!(rotate(&[1, 2, 3, 4], -1), ints(&[2, 3, 4, 1]));
+        assert_eq!(rotate(&[1, 2, 3, 4], -3), ints(&[4, 1, 2, 3]));
+    }
+
+    #[test]
+    fn rotate_large() {
+        assert_eq!(rotate(&[1, 2, 3, 4], 10), ints(&[3, 4, 1, 2]));
+        assert_eq!(rotate(&[1, 2, 3, 4], -9), ints(&[2, 3, 4, 1]));
+        assert_eq!(rotate(&[], 5), ints(&[]));
+    }
+
+    #[test]
+    fn wrong_arg_count() {
+        let list = int_list(&[1, 2, 3]);
+        let cases: Vec<(&str, Vec<Variable>)> = vec![
+            ("insert", ints(&[0])),
+            ("insert", ints(&[0, 1, 2])),
+            ("rotate", Vec::new()),
+            ("clear", ints(&[1])),
+        ];
+        for (name, args) in cases {
+            let result = Runtime::test(|runtime| {
+                assert!(runtime.call_attr(list.clone().into(), name, args).is_err());
+                let exc = runtime.pop_err()?;
+                runtime.return_1(exc.get_type().into())
+            });
+            assert_eq!(result, Result::Ok(value_error().into()), "{}", name);
+        }
+        assert_eq!(list.values().to_vec(), ints(&[1, 2, 3]));
+    }
+
+    fn set_slice(values: &[i32], slice: Variable, new: &[i32]) -> Result<Vec<Variable>, ()> {
+        let list = int_list(values);
+        Runtime::test(|runtime| {
+            let args = vec![slice, int_list(new).into()];
+            runtime.call_op(list.clone().into(), Operator::SetSlice, args)?;
+            runtime.return_1(true.into())
+        })?;
+        let result = list.values().to_vec();
+        Result::Ok(result)
+    }
+
+    fn slice(start: Option<i32>, stop: Option<i32>, step: Option<i32>) -> Variable {
+        Rc::new(Slice::new(
+            start.map(IntVar::from),
+            stop.map(IntVar::from),
+            step.map(IntVar::from),
+        ))
+        .into()
+    }
+
+    #[test]
+    fn set_extended_slice() {
+        let every_other = slice(Option::None, Option::None, Option::Some(2));
+        let result = set_slice(&[1, 2, 3, 4, 5], every_other, &[0, 0, 0]);
+        assert_eq!(result, Result::Ok(ints(&[0, 2, 0, 4, 0])));
+        let backwards = slice(Option::Some(3), Option::None, Option::Some(-2));
+        let result = set_slice(&[1, 2, 3, 4, 5], backwards, &[7, 8]);
+        assert_eq!(result, Result::Ok(ints(&[1, 8, 3, 7, 5])));
+    }
+
+    #[test]
+    fn set_extended_slice_mismatch() {
+        let list = int_list(&[1, 2, 3, 4, 5]);
+        for new in &[&[0, 0][..], &[0, 0, 0, 0][..]] {
+            let result = Runtime::test(|runtime| {
+                let every_other = slice(Option::None, Option::None, Option::Some(2));
+                let args = vec![every_other, int_list(new).into()];
+                assert!(runtime
+                    .call_op(list.clone().into(), Operator::SetSlice, args)
+                    .is_err());
+                let exc = runtime.pop_err()?;
+                runtime.return_1(exc.get_type().into())
+            });
+            assert_eq!(result, Result::Ok(value_error().into()));
+        }
+        assert_eq!(list.values().to_vec(), ints(&[1, 2, 3, 4, 5]));
+    }
+
+    #[test]
+    fn del_extended_slice() {
+        let cases = [
+            (
+                slice(Option::Some(1), Option::None, Option::Some(2)),
+                ints(&[1, 3, 5]),
+            ),
+            (
+                slice(Option::None, Option::None, Option::Some(-2)),
+                ints(&[2, 4]),
+            ),
+            (
+                slice(Option::Some(0), Option::Some(4), Option::Some(3)),
+                ints(&[2, 3, 5]),
+            ),
+        ];
+        for (slice, expected) in cases.iter().cloned() {
+            let list = int_list(&[1, 2, 3, 4, 5]);
+            let result = Runtime::test(|runtime| {
+                runtime.call_op(list.clone().into(), Operator::DelSlice, vec![slice])?;
+                runtime.return_1(true.into())
+            });
+            assert_eq!(result, Result::Ok(true.into()));
+            assert_eq!(list.values().to_vec(), expected);
+        }
+    }
+
+    fn seeded_shuffle(seed: u64) -> Vec<Variable> {
+        let list = int_list(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+        let result = Runtime::test(|runtime| {
+            *runtime.rng() = Rng::new(seed);
+            runtime.call_attr(list.clone().into(), "shuffle", vec![])?;
+            runtime.return_1(true.into())
+        });
+        assert_eq!(result, Result::Ok(true.into()));
+        let values = list.values().to_vec();
+        values
+    }
+
+    #[test]
+    fn shuffle_reproducible() {
+        let shuffled = seeded_shuffle(1234);
+        assert_eq!(shuffled, seeded_shuffle(1234));
+        assert_ne!(shuffled, seeded_shuffle(4321));
+        let mut sorted: Vec<_> = shuffled.into_iter().map(IntVar::from).collect();
+        sorted.sort();
+        let expected: Vec<_> = (1..=10).map(IntVar::from).collect();
+        assert_eq!(sorted, expected);
+    }
+
+    #[test]
+    fn sample_distinct() {
+        let list = int_list(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+        for count in 0..=10 {
+            let result = call_list(&list, "sample", IntVar::from(count).into());
+            let sample = downcast_var::<List>(result.unwrap()).unwrap();
+            let mut values: Vec<_> = sample.values().iter().cloned().map(IntVar::from).collect();
+            assert_eq!(values.len(), count as usize);
+            values.sort();
+            values.dedup();
+            assert_eq!(values.len(), count as usize);
+            assert!(values
+                .iter()
+                .all(|x| (&IntVar::from(1)..=&IntVar::from(10)).contains(&x)));
+        }
+        assert_eq!(
+            list.values().to_vec(),
+            ints(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10])
+        );
+    }
+
+    #[test]
+    fn sample_too_large() {
+        let list = int_list(&[1, 2, 3]);
+        for &count in &[4, -1] {
+            let result = Runtime::test(|runtime| {
+                let args = vec![IntVar::from(count).into()];
+                assert!(runtime
+                    .call_attr(list.clone().into(), "sample", args)
+                    .is_err());
+                let exc = runtime.pop_err()?;
+                runtime.return_1(exc.get_type().into())
+            });
+            assert_eq!(result, Result::Ok(value_error().into()));
+        }
+    }
+}
@@ -0,0 +1,322 @@
+use crate::custom_types::exceptions::value_error;
+use crate::custom_types::inner_dict::InnerDict;
+use crate::custom_var::CustomVar;
+use crate::int_var::IntVar;
+use crate::method::{NativeMethod, StdMethod};
+use crate::name::Name;
+use crate::operator::Operator;
+use crate::runtime::Runtime;
+use crate::std_type::Type;
+use crate::string_var::StringVar;
+use crate::variable::{FnResult, Variable};
+use crate::{check_args, first, first_n};
+use num::{Signed, ToPrimitive};
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+/// A map holding at most `capacity` entries, which evicts the least recently
+/// used entry when full.
+///
+/// Both `get` and `put` count as a use of the key.
+#[derive(Debug)]
+pub struct LruCache {
+    capacity: usize,
+    /// The stamp of the last use of each key.
+    stamps: RefCell<InnerDict>,
+    /// The key-value pairs, ordered from least to most recently used.
+    entries: RefCell<BTreeMap<usize, (Variable, Variable)>>,
+    next_stamp: Cell<usize>,
+}
+
+impl LruCache {
+    pub fn new(capacity: usize) -> Rc<LruCache> {
+        debug_assert_ne!(capacity, 0);
+        Rc::new(LruCache {
+            capacity,
+            stamps: RefCell::new(InnerDict::new()),
+            entries: RefCell::new(BTreeMap::new()),
+            next_stamp: Cell::new(0),
+        })
+    }
+
+    /// Gets the value for `key`, marking it as the most recently used.
+    pub fn get_value(&self, key: Variable, runtime: &mut Runtime) -> Result<Option<Variable>, ()> {
+        let stamp = match self.stamps.borrow().get(key.clone(), runtime)? {
+            Option::Some(stamp) => Self::stamp_of(stamp),
+            Option::None => return Result::Ok(Option::None),
+        };
+        let pair = self.entries.borrow_mut().remove(&stamp).unwrap();
+        let value = pair.1.clone();
+        self.insert_new(pair, runtime)?;
+        Result::Ok(Option::Some(value))
+    }
+
+    /// Sets the value for `key`, evicting the least recently used entry if
+    /// the cache is over capacity.
+    pub fn put_value(
+        &self,
+        key: Variable,
+        value: Variable,
+        runtime: &mut Runtime,
+    ) -> Result<(), ()> {
+        if let Option::Some(stamp) = self.stamps.borrow().get(key.clone(), runtime)? {
+            self.entries.borrow_mut().remove(&Self::stamp_of(stamp));
+        }
+        self.insert_new((key, value), runtime)?;
+        if self.len() > self.capacity {
+            let (_, (oldest, _)) = self.entries.borrow_mut().pop_first().unwrap();
+            self.stamps.borrow_mut().del(oldest, runtime)?;
+        }
+        Result::Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.borrow().len()
+    }
+
+    fn insert_new(&self, pair: (Variable, Variable), runtime: &mut Runtime) -> Result<(), ()> {
+        let stamp = self.next_stamp.get();
+        self.next_stamp.set(stamp + 1);
+        self.stamps
+            .borrow_mut()
+            .set(pair.0.clone(), IntVar::from(stamp).into(), runtime)?;
+        self.entries.borrow_mut().insert(stamp, pair);
+        Result::Ok(())
+    }
+
+    fn stamp_of(value: Variable) -> usize {
+        IntVar::from(value).to_usize().unwrap()
+    }
+
+    fn op_fn(o: Operator) -> NativeMethod<Rc<LruCache>> {
+        match o {
+            Operator::Bool => Self::bool,
+            Operator::Str => Self::repr,
+            Operator::Repr => Self::repr,
+            _ => unimplemented!("LruCache.{}", o.name()),
+        }
+    }
+
+    fn attr_fn(s: &str) -> Option<NativeMethod<Rc<LruCache>>> {
+        Option::Some(match s {
+            "get" => Self::get,
+            "put" => Self::put,
+            _ => return Option::None,
+        })
+    }
+
+    fn bool(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert!(args.is_empty());
+        runtime.return_1((self.len() != 0).into())
+    }
+
+    fn repr(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert!(args.is_empty());
+        let result = self.repr_str(runtime)?;
+        runtime.return_1(result.into())
+    }
+
+    fn get(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        if args.len() == 1 {
+            let val = self.get_value(first(args), runtime)?.into();
+            runtime.return_1(val)
+        } else {
+            check_args("LruCache.get", &args, 2, runtime)?;
+            let [key, default] = first_n(args);
+            let val = self.get_value(key, runtime)?.unwrap_or(default);
+            runtime.return_1(val)
+        }
+    }
+
+    fn put(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        check_args("LruCache.put", &args, 2, runtime)?;
+        let [key, value] = first_n(args);
+        self.put_value(key, value, runtime)?;
+        runtime.return_0()
+    }
+
+    fn repr_str(&self, runtime: &mut Runtime) -> Result<StringVar, ()> {
+        // Collect the pairs first so the cache is not borrowed while calling
+        // back into user code
+        let pairs: Vec<_> = self.entries.borrow().values().cloned().collect();
+        let mut result = String::from("LruCache({");
+        for (i, (key, value)) in pairs.into_iter().enumerate() {
+            if i != 0 {
+                result += ", ";
+            }
+            result += key.repr(runtime)?.as_str();
+            result += ": ";
+            result += value.repr(runtime)?.as_str();
+        }
+        result += "})";
+        Result::Ok(result.into())
+    }
+
+    fn create(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        check_args("LruCache", &args, 1, runtime)?;
+        let capacity = IntVar::from(first(args));
+        if !capacity.is_positive() {
+            return runtime.throw_quick(
+                value_error(),
+                format!("LruCache capacity must be positive, not {}", capacity),
+            );
+        }
+        // Capacities too large for a usize are never reached in practice
+        let capacity = capacity.to_usize().unwrap_or(usize::MAX);
+        runtime.return_1(LruCache::new(capacity).into())
+    }
+
+    pub fn lru_cache_type() -> Type {
+        custom_class!(LruCache, create, "LruCache")
+    }
+}
+
+impl CustomVar for LruCache {
+    fn set(self: Rc<Self>, _name: Name, _object: Variable) {
+        unimplemented!()
+    }
+
+    fn get_type(&self) -> Type {
+        LruCache::lru_cache_type()
+    }
+
+    fn get_operator(self: Rc<Self>, o: Operator) -> Variable {
+        let func = LruCache::op_fn(o);
+        StdMethod::new_native(self, func).into()
+    }
+
+    fn get_attribute(self: Rc<Self>, s: &str) -> Option<Variable> {
+        let func = match s {
+            "length" => return Option::Some(self.len().into()),
+            "capacity" => return Option::Some(self.capacity.into()),
+            _ => Self::attr_fn(s)?,
+        };
+        Option::Some(StdMethod::new_native(self, func).into())
+    }
+
+    fn call_op(
+        self: Rc<Self>,
+        operator: Operator,
+        args: Vec<Variable>,
+        runtime: &mut Runtime,
+    ) -> FnResult {
+        runtime.call_native_method(LruCache::op_fn(operator), self, args)
+    }
+
+    fn call_op_or_goto(
+        self: Rc<Self>,
+        operator: Operator,
+        args: Vec<Variable>,
+        runtime: &mut Runtime,
+    ) -> FnResult {
+        runtime.call_native_method(LruCache::op_fn(operator), self, args)
+    }
+
+    fn str(self: Rc<Self>, runtime: &mut Runtime) -> Result<StringVar, ()> {
+        self.repr_str(runtime)
+    }
+
+    fn repr(self: Rc<Self>, runtime: &mut Runtime) -> Result<StringVar, ()> {
+        self.repr_str(runtime)
+    }
+
+    fn bool(self: Rc<Self>, _runtime: &mut Runtime) -> Result<bool, ()> {
+        Result::Ok(self.len() != 0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::custom_types::exceptions::value_error;
+    use crate::custom_types::lru_cache::LruCache;
+    use crate::int_var::IntVar;
+    use crate::operator::Operator;
+    use crate::runtime::Runtime;
+    use crate::string_var::StringVar;
+    use crate::variable::Variable;
+
+    fn int(i: usize) -> Variable {
+        IntVar::from(i).into()
+    }
+
+    #[test]
+    fn evicts_least_recent() {
+        let result = Runtime::test(|runtime| {
+            let cache = LruCache::new(2);
+            cache.put_value(int(1), int(10), runtime)?;
+            cache.put_value(int(2), int(20), runtime)?;
+            cache.put_value(int(3), int(30), runtime)?;
+            assert_eq!(cache.len(), 2);
+            assert_eq!(cache.get_value(int(1), runtime)?, Option::None);
+            assert_eq!(cache.get_value(int(2), runtime)?, Option::Some(int(20)));
+            let third = cache.get_value(int(3), runtime)?.unwrap();
+            runtime.return_1(third)
+        });
+        assert_eq!(result, Result::Ok(int(30)));
+    }
+
+    #[test]
+    fn get_refreshes() {
+        let result = Runtime::test(|runtime| {
+            let cache = LruCache::new(2);
+            cache.put_value(int(1), int(10), runtime)?;
+            cache.put_value(int(2), int(20), runtime)?;
+            cache.get_value(int(1), runtime)?;
+            cache.put_value(int(3), int(30), runtime)?;
+            assert_eq!(cache.get_value(int(2), runtime)?, Option::None);
+            assert_eq!(cache.get_value(int(3), runtime)?, Option::Some(int(30)));
+            let first = cache.get_value(int(1), runtime)?.unwrap();
+            runtime.return_1(first)
+        });
+        assert_eq!(result, Result::Ok(int(10)));
+    }
+
+    #[test]
+    fn put_existing_refreshes() {
+        let result = Runtime::test(|runtime| {
+            let cache = LruCache::new(2);
+            cache.put_value(int(1), int(10), runtime)?;
+            cache.put_value(int(2), int(20), runtime)?;
+            cache.put_value(int(1), int(11), runtime)?;
+            cache.put_value(int(3), int(30), runtime)?;
+            assert_eq!(cache.len(), 2);
+            assert_eq!(cache.get_value(int(2), runtime)?, Option::None);
+            let first = cache.get_value(int(1), runtime)?.unwrap();
+            runtime.return_1(first)
+        });
+        assert_eq!(result, Result::Ok(int(11)));
+    }
+
+    #[test]
+    fn repr_and_bool() {
+        let result = Runtime::test(|runtime| {
+            let cache = LruCache::new(2);
+            let var: Variable = cache.clone().into();
+            assert!(!var.clone().into_bool(runtime)?);
+            cache.put_value(int(1), int(10), runtime)?;
+            cache.put_value(int(2), int(20), runtime)?;
+            cache.get_value(int(1), runtime)?;
+            assert!(var.clone().into_bool(runtime)?);
+            assert_eq!(var.clone().str(runtime)?, var.clone().repr(runtime)?);
+            var.call_op(Operator::Repr, Vec::new(), runtime)
+        });
+        assert_eq!(
+            result,
+            Result::Ok(StringVar::from("LruCache({2: 20, 1: 10})").into())
+        );
+    }
+
+    #[test]
+    fn non_positive_capacity() {
+        for capacity in &[0, -1] {
+            let result = Runtime::test(|runtime| {
+                let args = vec![IntVar::from(*capacity).into()];
+                assert!(LruCache::create(args, runtime).is_err());
+                let exc = runtime.pop_err()?;
+                runtime.return_1(exc.get_type().into())
+            });
+            assert_eq!(result, Result::Ok(value_error().into()));
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use crate::check_args;
+use crate::custom_types::inner_dict::InnerDict;
+use crate::custom_var::CustomVar;
+use crate::method::{NativeMethod, StdMethod};
+use crate::name::Name;
+use crate::operator::Operator;
+use crate::runtime::Runtime;
+use crate::std_type::Type;
+use crate::tuple::LangTuple;
+use crate::variable::{FnResult, Variable};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A callable wrapper which caches the results of a function by its
+/// arguments, as returned by the `memoize` builtin.
+///
+/// The arguments are stored as a tuple in a dict, so they must all be
+/// hashable; errors from hashing or comparing them propagate to the caller
+/// rather than bypassing the cache.
+#[derive(Debug)]
+pub struct Memoized {
+    function: Variable,
+    cache: RefCell<InnerDict>,
+}
+
+impl Memoized {
+    pub fn new(function: Variable) -> Rc<Memoized> {
+        Rc::new(Memoized {
+            function,
+            cache: RefCell::new(InnerDict::new()),
+        })
+    }
+
+    fn call_now(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        let key: Variable = LangTuple::from_vec(args.clone()).into();
+        if let Option::Some(result) = self.cache.borrow().get(key.clone(), runtime)? {
+            return runtime.return_1(result);
+        }
+        // The cache must not be borrowed here, as the function may recurse
+        self.function.clone().call((args, runtime))?;
+        let result = runtime.pop_return();
+        self.cache.borrow_mut().set(key, result.clone(), runtime)?;
+        runtime.return_1(result)
+    }
+
+    fn clear(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        check_args("Memoized.clear", &args, 0, runtime)?;
+        self.cache.borrow_mut().clear();
+        runtime.return_0()
+    }
+
+    fn create(_args: Vec<Variable>, _runtime: &mut Runtime) -> FnResult {
+        unimplemented!("Memoized objects should only be created through memoize")
+    }
+
+    pub fn memoized_type() -> Type {
+        custom_class!(Memoized, create, "Memoized")
+    }
+
+    fn op_fn(o: Operator) -> NativeMethod<Rc<Memoized>> {
+        match o {
+            Operator::Call => Self::call_now,
+            _ => unimplemented!("Memoized.{}", o.name()),
+        }
+    }
+}
+
+impl CustomVar for Memoized {
+    fn set(self: Rc<Self>, _name: Name, _object: Variable) {
+        unimplemented!()
+    }
+
+    fn get_type(&self) -> Type {
+        Self::memoized_type()
+    }
+
+    fn get_operator(self: Rc<Self>, op: Operator) -> Variable {
+        StdMethod::new_native(self, Self::op_fn(op)).into()
+    }
+
+    fn get_attribute(self: Rc<Self>, name: &str) -> Option<Variable> {
+        Option::Some(match name {
+            "function" => self.function.clone(),
+            "cacheSize" => self.cache.borrow().size().into(),
+            "clear" => StdMethod::new_native(self, Self::clear).into(),
+            _ => return Option::None,
+        })
+    }
+
+    fn call_op(
+        self: Rc<Self>,
+        operator: Operator,
+        args: Vec<Variable>,
+        runtime: &mut Runtime,
+    ) -> FnResult {
+        runtime.call_native_method(Self::op_fn(operator), self, args)
+    }
+
+    fn call_op_or_goto(
+        self: Rc<Self>,
+        operator: Operator,
+        args: Vec<Variable>,
+        runtime: &mut Runtime,
+    ) -> FnResult {
+        runtime.call_native_method(Self::op_fn(operator), self, args)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::custom_types::memoized::Memoized;
+    use crate::first;
+    use crate::function::Function;
+    use crate::int_var::IntVar;
+    use crate::runtime::Runtime;
+    use crate::variable::{FnResult, Variable};
+    use num::ToPrimitive;
+    use std::cell::{Cell, RefCell};
+
+    thread_local! {
+        static FIB_CALLS: Cell<usize> = const { Cell::new(0) };
+        static MEMO_FIB: RefCell<Option<Variable>> = const { RefCell::new(Option::None) };
+    }
+
+    fn fib(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        FIB_CALLS.with(|calls| calls.set(calls.get() + 1));
+        let n = IntVar::from(first(args)).to_usize().unwrap();
+        if n < 2 {
+            return runtime.return_1(IntVar::from(n).into());
+        }
+        let memo = MEMO_FIB.with(|memo| memo.borrow().clone().unwrap());
+        memo.clone()
+            .call((vec![IntVar::from(n - 1).into()], runtime))?;
+        let a = IntVar::from(runtime.pop_return());
+        memo.call((vec![IntVar::from(n - 2).into()], runtime))?;
+        let b = IntVar::from(runtime.pop_return());
+        runtime.return_1((a + b).into())
+    }
+
+    #[test]
+    fn memoized_fib() {
+        let memo: Variable = Memoized::new(Function::Native(fib).into()).into();
+        MEMO_FIB.with(|x| *x.borrow_mut() = Option::Some(memo.clone()));
+        let result =
+            Runtime::test(|runtime| memo.clone().call((vec![IntVar::from(30).into()], runtime)));
+        MEMO_FIB.with(|x| *x.borrow_mut() = Option::None);
+        assert_eq!(result, Result::Ok(IntVar::from(832040).into()));
+        // Without the cache, this would take over a million calls
+        assert_eq!(FIB_CALLS.with(Cell::get), 31);
+    }
+}
@@ -1,5 +1,6 @@
+use crate::runtime::Runtime;
 use crate::string_var::{MaybeString, StringVar};
-use crate::variable::Variable;
+use crate::variable::{InnerVar, Variable};
 use ascii::AsciiStr;
 use once_cell::sync::Lazy;
 
@@ -28,20 +29,30 @@ macro_rules! custom_class {
     }};
 }
 
+pub mod adaptors;
 pub mod array;
 pub mod bytes;
 pub mod coroutine;
+pub mod default_dict;
 pub mod dict;
 pub mod enumerate;
 pub mod exceptions;
 pub mod file;
+pub mod frozen_list;
+pub mod identity_map;
 pub mod interfaces;
 pub mod lambda;
 pub mod list;
+pub mod lru_cache;
+pub mod memoized;
+pub mod module;
+pub mod partial;
 pub mod range;
+pub mod repeat;
 pub mod set;
 pub mod slice;
 pub mod types;
+pub mod zip;
 
 mod inner_dict;
 
@@ -50,6 +61,10 @@ mod inner_dict;
 /// It'd be nice if this was `const`, but it's not yet
 static ASCII_COMMA: Lazy<&AsciiStr> = Lazy::new(|| AsciiStr::from_ascii(", ").unwrap());
 
+/// Joins the string forms of `values` with commas.
+///
+/// Containers pass `repr` here for both their `str` and their `repr`, so
+/// that elements are unambiguous: `["a", "b"]` rather than `[a, b]`.
 pub fn join_values(
     values: &[Variable],
     mut func: impl FnMut(Variable) -> Result<StringVar, ()>,
@@ -66,3 +81,39 @@ pub fn join_values(
         Result::Ok(MaybeString::new())
     }
 }
+
+/// Compares `values` element-wise against the values of a tuple or any
+/// iterable.
+///
+/// Unlike `operator ==` on the containers themselves, this does not care about
+/// the concrete type of `other`.
+pub fn elements_equal(
+    values: &[Variable],
+    other: Variable,
+    runtime: &mut Runtime,
+) -> Result<bool, ()> {
+    if let Variable::Normal(InnerVar::Tuple(tup)) = other {
+        if tup.len() != values.len() {
+            return Result::Ok(false);
+        }
+        for (value, next) in values.iter().zip(&tup) {
+            if !value.clone().equals(next.clone(), runtime)? {
+                return Result::Ok(false);
+            }
+        }
+        return Result::Ok(true);
+    }
+    let iter = other.iter(runtime)?;
+    let mut values = values.iter();
+    while let Option::Some(next) = iter.next(runtime)?.take_first() {
+        match values.next() {
+            Option::Some(value) => {
+                if !value.clone().equals(next, runtime)? {
+                    return Result::Ok(false);
+                }
+            }
+            Option::None => return Result::Ok(false),
+        }
+    }
+    Result::Ok(values.next().is_none())
+}
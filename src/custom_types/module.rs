@@ -0,0 +1,104 @@
+use crate::custom_var::CustomVar;
+use crate::name::Name;
+use crate::operator::Operator;
+use crate::runtime::Runtime;
+use crate::std_type::Type;
+use crate::string_var::StringVar;
+use crate::variable::{FnResult, Variable};
+use std::rc::Rc;
+
+/// A native module, such as `math`, which the builtin of the same name refers
+/// to.
+///
+/// Attribute lookups are delegated to the module's `get_value` function.
+#[derive(Debug)]
+pub struct Module {
+    name: &'static str,
+    get_value: fn(&str) -> Option<Variable>,
+}
+
+impl Module {
+    pub fn new(name: &'static str, get_value: fn(&str) -> Option<Variable>) -> Rc<Module> {
+        Rc::new(Module { name, get_value })
+    }
+
+    fn create(_args: Vec<Variable>, _runtime: &mut Runtime) -> FnResult {
+        unimplemented!("Modules should only be created through their builtins")
+    }
+
+    pub fn module_type() -> Type {
+        custom_class!(Module, create, "module")
+    }
+
+    fn repr_str(&self) -> StringVar {
+        format!("<module '{}'>", self.name).into()
+    }
+}
+
+impl CustomVar for Module {
+    fn set(self: Rc<Self>, _name: Name, _object: Variable) {
+        unimplemented!()
+    }
+
+    fn get_type(&self) -> Type {
+        Self::module_type()
+    }
+
+    fn get_operator(self: Rc<Self>, op: Operator) -> Variable {
+        unimplemented!("{}.{}", self.name, op.name())
+    }
+
+    fn get_attribute(self: Rc<Self>, name: &str) -> Option<Variable> {
+        (self.get_value)(name)
+    }
+
+    fn str(self: Rc<Self>, _runtime: &mut Runtime) -> Result<StringVar, ()> {
+        Result::Ok(self.repr_str())
+    }
+
+    fn repr(self: Rc<Self>, _runtime: &mut Runtime) -> Result<StringVar, ()> {
+        Result::Ok(self.repr_str())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::custom_types::module::Module;
+    use crate::int_var::IntVar;
+    use crate::name::Name;
+    use crate::runtime::Runtime;
+    use crate::string_var::StringVar;
+    use crate::variable::Variable;
+
+    fn get_value(name: &str) -> Option<Variable> {
+        match name {
+            "answer" => Option::Some(IntVar::from(42).into()),
+            _ => Option::None,
+        }
+    }
+
+    #[test]
+    fn attributes_delegated() {
+        let result = Runtime::test(|runtime| {
+            let module: Variable = Module::new("test", get_value).into();
+            assert!(module.clone().try_index_attr("missing", runtime)?.is_none());
+            let answer = module.index(Name::Attribute("answer"), runtime)?;
+            runtime.return_1(answer)
+        });
+        assert_eq!(result, Result::Ok(IntVar::from(42).into()));
+    }
+
+    #[test]
+    fn module_str() {
+        let result = Runtime::test(|runtime| {
+            let module: Variable = Module::new("test", get_value).into();
+            let repr = module.clone().repr(runtime)?;
+            assert_eq!(module.str(runtime)?, repr);
+            runtime.return_1(repr.into())
+        });
+        assert_eq!(
+            result,
+            Result::Ok(StringVar::from("<module 'test'>").into())
+        );
+    }
+}
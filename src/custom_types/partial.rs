@@ -0,0 +1,113 @@
+use crate::custom_var::CustomVar;
+use crate::method::StdMethod;
+use crate::name::Name;
+use crate::operator::Operator;
+use crate::runtime::Runtime;
+use crate::std_type::Type;
+use crate::tuple::LangTuple;
+use crate::variable::{FnResult, Variable};
+use std::rc::Rc;
+
+/// A function with some of its leading arguments already supplied, as
+/// returned by the `partial` builtin.
+#[derive(Debug)]
+pub struct Partial {
+    function: Variable,
+    args: Vec<Variable>,
+}
+
+impl Partial {
+    pub fn new(function: Variable, args: Vec<Variable>) -> Rc<Partial> {
+        Rc::new(Partial { function, args })
+    }
+
+    fn full_args(&self, args: Vec<Variable>) -> Vec<Variable> {
+        let mut result = Vec::with_capacity(self.args.len() + args.len());
+        result.extend_from_slice(&self.args);
+        result.extend(args);
+        result
+    }
+
+    fn call_now(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        let args = self.full_args(args);
+        self.function.clone().call((args, runtime))
+    }
+
+    fn create(_args: Vec<Variable>, _runtime: &mut Runtime) -> FnResult {
+        unimplemented!("Partial objects should only be created through partial")
+    }
+
+    pub fn partial_type() -> Type {
+        custom_class!(Partial, create, "Partial")
+    }
+}
+
+impl CustomVar for Partial {
+    fn set(self: Rc<Self>, _name: Name, _object: Variable) {
+        unimplemented!()
+    }
+
+    fn get_type(&self) -> Type {
+        Self::partial_type()
+    }
+
+    fn get_operator(self: Rc<Self>, op: Operator) -> Variable {
+        let func = match op {
+            Operator::Call => Self::call_now,
+            _ => unimplemented!("Partial.{}", op.name()),
+        };
+        StdMethod::new_native(self, func).into()
+    }
+
+    fn get_attribute(self: Rc<Self>, name: &str) -> Option<Variable> {
+        Option::Some(match name {
+            "function" => self.function.clone(),
+            "args" => LangTuple::from_vec(self.args.clone()).into(),
+            _ => return Option::None,
+        })
+    }
+
+    fn call(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        self.call_now(args, runtime)
+    }
+
+    fn call_or_goto(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        let args = self.full_args(args);
+        self.function.clone().call_or_goto((args, runtime))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::custom_types::partial::Partial;
+    use crate::first_n;
+    use crate::function::Function;
+    use crate::int_var::IntVar;
+    use crate::operator::Operator;
+    use crate::runtime::Runtime;
+    use crate::variable::{FnResult, Variable};
+
+    fn subtract(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        let [a, b] = first_n(args);
+        runtime.return_1((IntVar::from(a) - IntVar::from(b)).into())
+    }
+
+    #[test]
+    fn prepends_args() {
+        let function: Variable = Function::Native(subtract).into();
+        let partial: Variable = Partial::new(function, vec![IntVar::from(10).into()]).into();
+        let result = Runtime::test(|runtime| {
+            partial.call_op(Operator::Call, vec![IntVar::from(3).into()], runtime)
+        });
+        assert_eq!(result, Result::Ok(IntVar::from(7).into()));
+    }
+
+    #[test]
+    fn all_args_captured() {
+        let function: Variable = Function::Native(subtract).into();
+        let args = vec![IntVar::from(1).into(), IntVar::from(4).into()];
+        let partial: Variable = Partial::new(function, args).into();
+        let result = Runtime::test(|runtime| partial.call((Vec::new(), runtime)));
+        assert_eq!(result, Result::Ok(IntVar::from(-3).into()));
+    }
+}
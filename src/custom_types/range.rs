@@ -1,4 +1,5 @@
 use crate::custom_types::exceptions::{index_error, value_error};
+use crate::custom_types::list::List;
 use crate::custom_var::{downcast_var, CustomVar};
 use crate::int_var::IntVar;
 use crate::looping::{self, TypicalIterator};
@@ -8,9 +9,10 @@ use crate::operator::Operator;
 use crate::runtime::Runtime;
 use crate::std_type::Type;
 use crate::string_var::StringVar;
+use crate::tuple::hash_values;
 use crate::variable::{FnResult, Variable};
-use crate::{first, first_n};
-use num::{One, Signed, Zero};
+use crate::{check_args, first, first_n};
+use num::{One, Signed, ToPrimitive, Zero};
 use std::cell::RefCell;
 use std::mem::replace;
 use std::ops::Neg;
@@ -47,13 +49,50 @@ impl Range {
         }
     }
 
+    /// The indices of a sequence of length `len` selected by this range (as
+    /// produced by [`Range::from_slice`]), clipped to the bounds of the
+    /// sequence.
+    pub fn slice_indices(&self, len: usize) -> impl Iterator<Item = usize> {
+        let len = len as isize;
+        let step = self.step.abs().to_usize().unwrap_or(usize::MAX);
+        let (start, stop, rev) = if self.step.is_negative() {
+            let start = clamp_index(&self.start, -1, len - 1);
+            let stop = clamp_index(&self.stop, -1, len - 1);
+            // Iterate over (stop, start] backwards
+            (stop + 1, (start + 1).max(stop + 1), true)
+        } else {
+            let start = clamp_index(&self.start, 0, len);
+            let stop = clamp_index(&self.stop, 0, len);
+            (start, stop.max(start), false)
+        };
+        let (start, stop) = (start as usize, stop as usize);
+        let indices: Box<dyn Iterator<Item = usize>> = if rev {
+            Box::new((start..stop).rev().step_by(step))
+        } else {
+            Box::new((start..stop).step_by(step))
+        };
+        indices
+    }
+
     pub fn from_slice(len: usize, runtime: &mut Runtime, arg: Variable) -> Result<Rc<Range>, ()> {
         runtime.call_attr(arg, "toRange", vec![len.into()])?;
-        Result::Ok(downcast_var(runtime.pop_return()).expect("Expected a range"))
+        let range: Rc<Range> = downcast_var(runtime.pop_return()).expect("Expected a range");
+        if range.step.is_zero() {
+            runtime.throw_quick_native(value_error(), "Slice step cannot be 0")
+        } else {
+            Result::Ok(range)
+        }
     }
 
+    /// Whether `value` comes before the end of the range.
+    ///
+    /// Ranges with a step of zero can only be created from constants (the
+    /// constructor rejects them), and are treated as empty so that iterating
+    /// over them terminates.
     fn before_end(&self, value: &IntVar) -> bool {
-        if self.step.is_positive() {
+        if self.step.is_zero() {
+            false
+        } else if self.step.is_positive() {
             value < &self.stop
         } else {
             value > &self.stop
@@ -65,6 +104,7 @@ impl Range {
             Operator::Str => Self::str,
             Operator::Repr => Self::str,
             Operator::Equals => Self::eq,
+            Operator::Hash => Self::hash,
             Operator::Iter => Self::iter,
             Operator::GetAttr => Self::index,
             Operator::In => Self::contains,
@@ -87,6 +127,13 @@ impl Range {
         runtime.return_1(is_eq.into())
     }
 
+    fn hash(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert!(args.is_empty());
+        let values = [&self.start, &self.stop, &self.step].map(|x| x.clone().into());
+        let result = hash_values(&values, runtime)?;
+        runtime.return_1(IntVar::from(result).into())
+    }
+
     fn iter(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
         debug_assert!(args.is_empty());
         runtime.return_1(Rc::new(RangeIter::new(self)).into())
@@ -96,7 +143,12 @@ impl Range {
         debug_assert!(args.len() == 1);
         let index = IntVar::from(first(args));
         let result = &self.start + &(&index * &self.step);
-        if !self.before_end(&result) {
+        if self.step.is_zero() {
+            runtime.throw_quick(
+                index_error(),
+                format!("Index {} out of bounds for empty range", index),
+            )
+        } else if !self.before_end(&result) {
             let max_index = (&self.stop - &self.start) / self.step.clone();
             runtime.throw_quick(
                 index_error(),
@@ -151,12 +203,54 @@ impl Range {
     }
 
     fn len(&self) -> IntVar {
+        if self.step.is_zero() {
+            return Zero::zero();
+        }
         let (start, stop) = if self.step.is_negative() {
             (&self.stop, &self.start)
         } else {
             (&self.start, &self.stop)
         };
-        (stop - start) / self.step.abs()
+        if stop <= start {
+            return Zero::zero();
+        }
+        // Round up, as a partial step at the end still includes one more value
+        let step = self.step.abs();
+        (&(stop - start) + &(&step - &IntVar::one())) / step
+    }
+
+    fn to_list(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        check_args("range.toList", &args, 0, runtime)?;
+        let len = self.len();
+        let capacity = match len.to_usize() {
+            Option::Some(x) => x,
+            Option::None => {
+                return runtime.throw_quick(
+                    value_error(),
+                    format!("Range of length {} too large to create list", len),
+                )
+            }
+        };
+        let mut values = Vec::with_capacity(capacity);
+        values.extend(self.values().map(Variable::from));
+        runtime.return_1(List::from_values(Type::Bigint, values).into())
+    }
+
+    fn sum(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        check_args("range.sum", &args, 0, runtime)?;
+        runtime.return_1(self.sum_value().into())
+    }
+
+    /// The sum of the values in the range, calculated using the formula for
+    /// an arithmetic series rather than by iteration.
+    fn sum_value(&self) -> IntVar {
+        let len = self.len();
+        if len.is_zero() {
+            return Zero::zero();
+        }
+        // n * start + step * n * (n - 1) / 2, where the division is always exact
+        let triangle = &(&len * &(&len - &IntVar::one())) / &IntVar::from(2);
+        &(&len * &self.start) + &(&self.step * &triangle)
     }
 
     fn create(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
@@ -175,6 +269,16 @@ impl Range {
     }
 }
 
+fn clamp_index(value: &IntVar, min: isize, max: isize) -> isize {
+    if value < &min.into() {
+        min
+    } else if value > &max.into() {
+        max
+    } else {
+        value.to_isize().unwrap()
+    }
+}
+
 impl CustomVar for Range {
     fn set(self: Rc<Self>, _name: Name, _object: Variable) {
         unimplemented!()
@@ -189,13 +293,15 @@ impl CustomVar for Range {
         StdMethod::new_native(self, func).into()
     }
 
-    fn get_attribute(self: Rc<Self>, attr: &str) -> Variable {
+    fn get_attribute(self: Rc<Self>, attr: &str) -> Option<Variable> {
         let func = match attr {
-            "length" => return self.len().into(),
+            "length" => return Option::Some(self.len().into()),
             "get" => Self::get,
-            x => unimplemented!("Range.{}", x),
+            "toList" => Self::to_list,
+            "sum" => Self::sum,
+            _ => return Option::None,
         };
-        StdMethod::new_native(self, func).into()
+        Option::Some(StdMethod::new_native(self, func).into())
     }
 
     fn call_op(
@@ -288,3 +394,180 @@ impl Iterator for RangeValueIter<'_> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::custom_types::dict::Dict;
+    use crate::custom_types::exceptions::value_error;
+    use crate::custom_types::list::List;
+    use crate::custom_types::range::Range;
+    use crate::custom_types::slice::Slice;
+    use crate::custom_var::downcast_var;
+    use crate::int_var::IntVar;
+    use crate::name::Name;
+    use crate::runtime::Runtime;
+    use crate::std_type::Type;
+    use crate::variable::Variable;
+    use std::rc::Rc;
+
+    fn create(args: [i32; 3]) -> Result<Variable, ()> {
+        Runtime::test(|runtime| {
+            let args = args.iter().map(|&x| IntVar::from(x).into()).collect();
+            let result = Range::range_type().create_inst(args, runtime)?;
+            runtime.return_1(result)
+        })
+    }
+
+    fn values(range: Variable) -> Result<Vec<Variable>, ()> {
+        let mut result = Vec::new();
+        Runtime::test(|runtime| {
+            let iter = range.iter(runtime)?;
+            while let Option::Some(v) = iter.next(runtime)?.take_first() {
+                result.push(v);
+            }
+            runtime.return_1(true.into())
+        })?;
+        Result::Ok(result)
+    }
+
+    fn ints(values: &[i32]) -> Vec<Variable> {
+        values.iter().map(|&x| IntVar::from(x).into()).collect()
+    }
+
+    #[test]
+    fn zero_step_error() {
+        let result = Runtime::test(|runtime| {
+            let args = vec![0.into(), 10.into(), 0.into()];
+            assert!(Range::range_type().create_inst(args, runtime).is_err());
+            let exc = runtime.pop_err()?;
+            runtime.return_1(exc.get_type().into())
+        });
+        assert_eq!(result, Result::Ok(value_error().into()));
+    }
+
+    #[test]
+    fn normal_step() {
+        let range = create([0, 10, 3]).unwrap();
+        assert_eq!(values(range), Result::Ok(ints(&[0, 3, 6, 9])));
+        let range = create([5, 0, -2]).unwrap();
+        assert_eq!(values(range), Result::Ok(ints(&[5, 3, 1])));
+    }
+
+    #[test]
+    fn zero_step_constant() {
+        let range = Range::new(10.into(), 0.into(), 0.into());
+        assert!(range.values().next().is_none());
+        assert_eq!(values(Rc::new(range).into()), Result::Ok(Vec::new()));
+    }
+
+    #[test]
+    fn zero_step_slice() {
+        let result = Runtime::test(|runtime| {
+            let slice = Slice::new(Option::None, Option::None, Option::Some(0.into()));
+            assert!(Range::from_slice(5, runtime, Rc::new(slice).into()).is_err());
+            let exc = runtime.pop_err()?;
+            runtime.return_1(exc.get_type().into())
+        });
+        assert_eq!(result, Result::Ok(value_error().into()));
+    }
+
+    #[test]
+    fn sum_matches_iteration() -> Result<(), ()> {
+        let ranges = [
+            [0, 10, 1],
+            [0, 10, 3],
+            [-7, 20, 4],
+            [5, 0, -2],
+            [10, -10, -3],
+            [0, 0, 1],
+            [5, 0, 1],
+            [0, 5, -1],
+        ];
+        for args in ranges {
+            let range = create(args)?;
+            let expected = values(range.clone())?
+                .into_iter()
+                .map(IntVar::from)
+                .fold(IntVar::from(0), |acc, x| &acc + &x);
+            let result = Runtime::test(|runtime| {
+                runtime.call_attr(range.clone(), "sum", Vec::new())?;
+                let result = runtime.pop_return();
+                runtime.return_1(result)
+            })?;
+            assert_eq!(result, expected.into(), "sum of range {:?}", args);
+        }
+        Result::Ok(())
+    }
+
+    #[test]
+    fn to_list() -> Result<(), ()> {
+        for args in [[0, 10, 3], [5, 0, -2], [5, 0, 1]] {
+            let range = create(args)?;
+            let expected = values(range.clone())?;
+            let result = Runtime::test(|runtime| {
+                runtime.call_attr(range.clone(), "toList", Vec::new())?;
+                let result = runtime.pop_return();
+                runtime.return_1(result)
+            })?;
+            let list = downcast_var::<List>(result).unwrap();
+            assert_eq!(list.values().to_vec(), expected);
+            let length = Runtime::test(|runtime| {
+                let length = range.clone().index(Name::Attribute("length"), runtime)?;
+                runtime.return_1(length)
+            })?;
+            assert_eq!(length, expected.len().into());
+        }
+        Result::Ok(())
+    }
+
+    fn range(args: [i32; 3]) -> Variable {
+        let [start, stop, step] = args;
+        Rc::new(Range::new(start.into(), stop.into(), step.into())).into()
+    }
+
+    #[test]
+    fn range_dict_keys() -> Result<(), ()> {
+        let keys = [[0, 10, 1], [0, 10, 2], [0, 5, 1], [1, 10, 1], [10, 0, -1]];
+        let result = Runtime::test(|runtime| {
+            let dict = Dict::from_args(
+                keys.iter().map(|&x| range(x)).collect(),
+                ints(&[0, 1, 2, 3, 4]),
+                runtime,
+            )?;
+            let mut found = Vec::new();
+            for key in keys {
+                found.push(dict.get_value(range(key), runtime)?.unwrap());
+            }
+            assert_eq!(dict.get_value(range([0, 10, 3]), runtime)?, Option::None);
+            runtime.return_1(List::from_values(Type::Bigint, found).into())
+        })?;
+        let list = downcast_var::<List>(result).unwrap();
+        assert_eq!(list.values().to_vec(), ints(&[0, 1, 2, 3, 4]));
+        Result::Ok(())
+    }
+
+    #[test]
+    fn slice_dict_keys() -> Result<(), ()> {
+        let slice = |start: Option<i32>, stop: Option<i32>| -> Variable {
+            Rc::new(Slice::new(
+                start.map(Into::into),
+                stop.map(Into::into),
+                Option::None,
+            ))
+            .into()
+        };
+        let result = Runtime::test(|runtime| {
+            let keys = vec![
+                slice(Option::None, Option::Some(2)),
+                slice(Option::Some(2), Option::None),
+            ];
+            let dict = Dict::from_args(keys, ints(&[0, 1]), runtime)?;
+            let missing = dict.get_value(slice(Option::Some(2), Option::Some(2)), runtime)?;
+            assert_eq!(missing, Option::None);
+            let value = dict.get_value(slice(Option::Some(2), Option::None), runtime)?;
+            runtime.return_1(value.unwrap())
+        });
+        assert_eq!(result, Result::Ok(1.into()));
+        Result::Ok(())
+    }
+}
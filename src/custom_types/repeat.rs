@@ -0,0 +1,94 @@
+use crate::int_var::IntVar;
+use crate::looping::TypicalIterator;
+use crate::runtime::Runtime;
+use crate::std_type::Type;
+use crate::variable::{FnResult, Variable};
+use num::{Signed, ToPrimitive};
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// An iterator yielding the same value, either a fixed number of times or
+/// forever.
+#[derive(Debug)]
+pub struct Repeat {
+    value: Variable,
+    remaining: Option<Cell<usize>>,
+}
+
+impl Repeat {
+    pub fn new(value: Variable, count: Option<usize>) -> Rc<Repeat> {
+        Rc::new(Repeat {
+            value,
+            remaining: count.map(Cell::new),
+        })
+    }
+
+    pub fn from_count(value: Variable, count: &IntVar) -> Rc<Repeat> {
+        // Negative counts yield nothing; counts too large to fit in a usize
+        // are indistinguishable from infinity in practice
+        if count.is_negative() {
+            Self::new(value, Option::Some(0))
+        } else {
+            Self::new(value, count.to_usize())
+        }
+    }
+
+    fn create(_args: Vec<Variable>, _runtime: &mut Runtime) -> FnResult {
+        unimplemented!()
+    }
+}
+
+impl TypicalIterator for Repeat {
+    fn inner_next(&self) -> Option<Variable> {
+        match &self.remaining {
+            Option::None => Option::Some(self.value.clone()),
+            Option::Some(remaining) => {
+                let count = remaining.get();
+                if count == 0 {
+                    Option::None
+                } else {
+                    remaining.set(count - 1);
+                    Option::Some(self.value.clone())
+                }
+            }
+        }
+    }
+
+    fn get_type() -> Type {
+        custom_class!(Repeat, create, "Repeat")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::custom_types::repeat::Repeat;
+    use crate::int_var::IntVar;
+    use crate::looping::TypicalIterator;
+    use crate::string_var::StringVar;
+    use crate::variable::Variable;
+    use std::iter::from_fn;
+
+    #[test]
+    fn bounded() {
+        let value: Variable = StringVar::from("abc").into();
+        let repeat = Repeat::from_count(value.clone(), &IntVar::from(3));
+        let values: Vec<_> = from_fn(|| repeat.inner_next()).collect();
+        assert_eq!(values, vec![value.clone(), value.clone(), value]);
+        assert_eq!(repeat.inner_next(), Option::None);
+    }
+
+    #[test]
+    fn negative() {
+        let repeat = Repeat::from_count(Variable::null(), &IntVar::from(-1));
+        assert_eq!(repeat.inner_next(), Option::None);
+    }
+
+    #[test]
+    fn infinite() {
+        let value: Variable = IntVar::from(1).into();
+        let repeat = Repeat::new(value.clone(), Option::None);
+        let values: Vec<_> = from_fn(|| repeat.inner_next()).take(100).collect();
+        assert_eq!(values, vec![value.clone(); 100]);
+        assert_eq!(repeat.inner_next(), Option::Some(value));
+    }
+}
@@ -1,6 +1,6 @@
+use crate::copy::CopyMap;
 use crate::custom_types::inner_dict::{DictIter, DictLike, InnerDict};
 use crate::custom_var::{downcast_var, CustomVar};
-use crate::first;
 use crate::looping;
 use crate::method::{NativeMethod, StdMethod};
 use crate::name::Name;
@@ -9,6 +9,7 @@ use crate::runtime::Runtime;
 use crate::std_type::Type;
 use crate::string_var::StringVar;
 use crate::variable::{FnResult, Variable};
+use crate::{check_args, first, first_n};
 use std::cell::{Ref, RefCell};
 use std::rc::Rc;
 
@@ -31,6 +32,26 @@ impl Set {
         }))
     }
 
+    pub(crate) fn deep_copy(
+        self: &Rc<Self>,
+        copies: &mut CopyMap,
+        runtime: &mut Runtime,
+    ) -> Result<Variable, ()> {
+        let result = Set::from_inner(self.generic, InnerDict::new());
+        if let Option::Some(copy) = copies.register(self, result.clone().into()) {
+            return Result::Ok(copy);
+        }
+        let pairs = self.value.borrow().pairs();
+        for (value, _) in pairs {
+            let value = copies.copy(value, runtime)?;
+            result
+                .value
+                .borrow_mut()
+                .set(value, Variable::null(), runtime)?;
+        }
+        Result::Ok(result.into())
+    }
+
     fn from_inner(generic: Type, value: InnerDict) -> Rc<Set> {
         Rc::new(Set {
             generic,
@@ -54,8 +75,8 @@ impl Set {
         }
     }
 
-    fn attr_fn(s: &str) -> NativeMethod<Rc<Set>> {
-        match s {
+    fn attr_fn(s: &str) -> Option<NativeMethod<Rc<Set>>> {
+        Option::Some(match s {
             "add" => Self::add,
             "addAll" => Self::add_all,
             "remove" => Self::remove,
@@ -64,8 +85,15 @@ impl Set {
             "isSuperset" => Self::superset,
             "isDisjoint" => Self::disjoint,
             "containsAll" => Self::contains_all,
-            _ => unimplemented!(),
-        }
+            "copy" => Self::copy,
+            _ => return Option::None,
+        })
+    }
+
+    fn copy(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        check_args("set.copy", &args, 0, runtime)?;
+        let value = self.value.borrow().clone();
+        runtime.return_1(Set::from_inner(self.generic, value).into())
     }
 
     fn intersection(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
@@ -253,17 +281,18 @@ impl Set {
             1 => match downcast_var::<Set>(first(args)) {
                 Result::Ok(x) => Set::from_inner(x.generic, x.value.borrow().clone()),
                 Result::Err(arg) => {
-                    let mut inner = InnerDict::new();
-                    let iter = arg.iter(runtime)?;
-                    while let Option::Some(val) = iter.next(runtime)?.take_first() {
-                        inner.set(val, Variable::null(), runtime)?;
-                    }
                     // TODO: Generic value
-                    Set::from_inner(Type::Object, inner)
+                    Set::from_inner(Type::Object, Self::fill(InnerDict::new(), arg, runtime)?)
                 }
             },
+            2 => {
+                let [value, cap] = first_n(args);
+                let cap = InnerDict::capacity_arg(cap, "set", runtime)?;
+                let inner = Self::fill(InnerDict::with_capacity(cap), value, runtime)?;
+                Set::from_inner(Type::Object, inner)
+            }
             x => unimplemented!(
-                "set.operator new expected 0 or 1 args, got {}\n{}",
+                "set.operator new expected 0, 1, or 2 args, got {}\n{}",
                 x,
                 runtime.frame_strings()
             ),
@@ -271,8 +300,25 @@ impl Set {
         runtime.return_1(set.into())
     }
 
+    fn fill(mut inner: InnerDict, value: Variable, runtime: &mut Runtime) -> Result<InnerDict, ()> {
+        let iter = value.iter(runtime)?;
+        while let Option::Some(val) = iter.next(runtime)?.take_first() {
+            inner.set(val, Variable::null(), runtime)?;
+        }
+        Result::Ok(inner)
+    }
+
+    /// Creates a set of the given type from the values of an iterable.
+    fn from_iterable(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        check_args("set.fromIterable", &args, 2, runtime)?;
+        let [iterable, generic] = first_n(args);
+        let generic = Type::from(generic);
+        let inner = Self::fill(InnerDict::new(), iterable, runtime)?;
+        runtime.return_1(Set::from_inner(generic, inner).into())
+    }
+
     pub fn set_type() -> Type {
-        custom_class!(Set, create, "set")
+        custom_class!(Set, create, "set", "fromIterable" => from_iterable)
     }
 
     pub fn is_empty(&self) -> bool {
@@ -298,10 +344,10 @@ impl CustomVar for Set {
         StdMethod::new_native(self, func).into()
     }
 
-    fn get_attribute(self: Rc<Self>, name: &str) -> Variable {
+    fn get_attribute(self: Rc<Self>, name: &str) -> Option<Variable> {
         match name {
-            "length" => self.len().into(),
-            _ => StdMethod::new_native(self, Self::attr_fn(name)).into(),
+            "length" => Option::Some(self.len().into()),
+            _ => Option::Some(StdMethod::new_native(self, Self::attr_fn(name)?).into()),
         }
     }
 
@@ -345,3 +391,59 @@ impl DictLike for Set {
         self.value.borrow()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::custom_types::range::Range;
+    use crate::custom_types::set::Set;
+    use crate::custom_var::downcast_var;
+    use crate::int_var::IntVar;
+    use crate::name::Name;
+    use crate::runtime::Runtime;
+    use crate::std_type::Type;
+    use crate::variable::Variable;
+    use std::rc::Rc;
+
+    #[test]
+    fn from_iterable_range() -> Result<(), ()> {
+        let range: Variable = Rc::new(Range::new(0.into(), 10.into(), 1.into())).into();
+        let result = Runtime::test(|runtime| {
+            let func = Set::set_type().index(Name::Attribute("fromIterable"), runtime);
+            func.call((vec![range, Type::Bigint.into()], runtime))?;
+            let result = runtime.pop_return();
+            runtime.return_1(result)
+        })?;
+        let set = downcast_var::<Set>(result).unwrap_or_else(|_| panic!("Expected a set"));
+        assert_eq!(set.len(), 10);
+        assert_eq!(set.generic, Type::Bigint);
+        Runtime::test(|runtime| {
+            for i in 0..10 {
+                assert!(set.value.borrow().get(i.into(), runtime)?.is_some());
+            }
+            runtime.return_1(Variable::null())
+        })?;
+        Result::Ok(())
+    }
+
+    #[test]
+    fn create_with_capacity() -> Result<(), ()> {
+        // The second hint is far too large to allocate, so it must be clamped
+        for cap in [IntVar::from(100), IntVar::from(usize::MAX)] {
+            let range: Variable = Rc::new(Range::new(0.into(), 100.into(), 1.into())).into();
+            let result = Runtime::test(|runtime| {
+                Set::create(vec![range, cap.into()], runtime)?;
+                let result = runtime.pop_return();
+                runtime.return_1(result)
+            })?;
+            let set = downcast_var::<Set>(result).unwrap_or_else(|_| panic!("Expected a set"));
+            assert_eq!(set.len(), 100);
+            Runtime::test(|runtime| {
+                for i in 0..100 {
+                    assert!(set.value.borrow().get(i.into(), runtime)?.is_some());
+                }
+                runtime.return_1(Variable::null())
+            })?;
+        }
+        Result::Ok(())
+    }
+}
@@ -1,6 +1,6 @@
 use crate::custom_types::exceptions::value_error;
 use crate::custom_types::range::Range;
-use crate::custom_var::CustomVar;
+use crate::custom_var::{downcast_var, CustomVar};
 use crate::int_var::IntVar;
 use crate::method::StdMethod;
 use crate::name::Name;
@@ -8,13 +8,14 @@ use crate::operator::Operator;
 use crate::runtime::Runtime;
 use crate::std_type::Type;
 use crate::string_var::StringVar;
+use crate::tuple::hash_values;
 use crate::variable::{FnResult, InnerVar, Variable};
 use crate::{first, first_n};
 use num::{One, Signed, Zero};
 use std::borrow::Cow;
 use std::rc::Rc;
 
-#[derive(Debug)]
+#[derive(Debug, Eq, PartialEq)]
 pub struct Slice {
     start: Option<IntVar>,
     stop: Option<IntVar>,
@@ -49,6 +50,22 @@ impl Slice {
         .into()
     }
 
+    fn eq(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert_eq!(args.len(), 1);
+        let is_eq = match downcast_var::<Slice>(first(args)) {
+            Result::Err(_) => false,
+            Result::Ok(other) => self == other,
+        };
+        runtime.return_1(is_eq.into())
+    }
+
+    fn hash(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert!(args.is_empty());
+        let values = [&self.start, &self.stop, &self.step].map(|x| int_to_var(x.clone()));
+        let result = hash_values(&values, runtime)?;
+        runtime.return_1(IntVar::from(result).into())
+    }
+
     fn make_range(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
         debug_assert_eq!(args.len(), 1);
         /*
@@ -86,7 +103,7 @@ impl Slice {
                 .map(|x| if x.is_negative() { &len + x } else { x.clone() })
                 .unwrap_or_else(|| &len - &1.into());
             let stop = self
-                .start
+                .stop
                 .as_ref()
                 .map(|x| if x.is_negative() { &len + x } else { x.clone() })
                 .unwrap_or_else(|| (-1).into());
@@ -122,18 +139,20 @@ impl CustomVar for Slice {
     fn get_operator(self: Rc<Self>, op: Operator) -> Variable {
         match op {
             Operator::Str | Operator::Repr => StdMethod::new_native(self, Self::str).into(),
+            Operator::Equals => StdMethod::new_native(self, Self::eq).into(),
+            Operator::Hash => StdMethod::new_native(self, Self::hash).into(),
             _ => unimplemented!(),
         }
     }
 
-    fn get_attribute(self: Rc<Self>, name: &str) -> Variable {
-        match name {
+    fn get_attribute(self: Rc<Self>, name: &str) -> Option<Variable> {
+        Option::Some(match name {
             "start" => int_to_var(self.start.clone()),
             "stop" => int_to_var(self.stop.clone()),
             "step" => int_to_var(self.step.clone()),
             "toRange" => StdMethod::new_native(self, Self::make_range).into(),
-            _ => unimplemented!(),
-        }
+            _ => return Option::None,
+        })
     }
 
     fn str(self: Rc<Self>, _runtime: &mut Runtime) -> Result<StringVar, ()> {
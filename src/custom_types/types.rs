@@ -8,6 +8,7 @@ use crate::std_type::Type;
 use crate::string_var::StringVar;
 use crate::variable::{FnResult, Variable};
 use std::fmt::Debug;
+use std::ptr;
 use std::rc::Rc;
 
 #[derive(Debug)]
@@ -39,12 +40,21 @@ impl CustomType {
         &self.name
     }
 
+    pub fn get_supers(&self) -> &[Type] {
+        &self.supers
+    }
+
     pub fn create(&self, args: Vec<Variable>, runtime: &mut Runtime) -> Result<Variable, ()> {
         self.constructor.call((args, runtime))?;
         Result::Ok(runtime.pop_return())
     }
 
     pub fn is_subclass(&self, other: &Type, runtime: &Runtime) -> bool {
+        if let Type::Custom(o) = other {
+            if ptr::eq(self, *o) {
+                return true;
+            }
+        }
         for s in &self.supers {
             if s.is_subclass(other, runtime) {
                 return true;
@@ -53,6 +63,10 @@ impl CustomType {
         false
     }
 
+    pub fn has_static(&self, attr: &str) -> bool {
+        self.static_methods.get(Name::Attribute(attr)).is_some()
+    }
+
     pub fn index(&self, name: Name) -> Variable {
         self.static_methods
             .get(name)
@@ -90,8 +104,8 @@ impl CustomVar for TypeIdentity {
         }
     }
 
-    fn get_attribute(self: Rc<Self>, _name: &str) -> Variable {
-        unimplemented!()
+    fn get_attribute(self: Rc<Self>, _name: &str) -> Option<Variable> {
+        Option::None
     }
 
     fn call(self: Rc<Self>, _args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
@@ -0,0 +1,200 @@
+use crate::looping::{self, IterAttrs, IterResult, NativeIterator};
+use crate::runtime::Runtime;
+use crate::std_type::Type;
+use crate::tuple::LangTuple;
+use crate::variable::{FnResult, Variable};
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// An iterator yielding tuples of corresponding elements of its sources,
+/// stopping as soon as the shortest one is exhausted.
+#[derive(Debug)]
+pub struct Zip {
+    iterators: Vec<looping::Iterator>,
+    done: Cell<bool>,
+}
+
+/// An iterator yielding tuples of corresponding elements of its sources,
+/// continuing until all are exhausted and padding the shorter ones with a
+/// fill value.
+#[derive(Debug)]
+pub struct ZipLongest {
+    fill: Variable,
+    iterators: Vec<(looping::Iterator, Cell<bool>)>,
+}
+
+impl Zip {
+    pub fn new(iterators: Vec<looping::Iterator>) -> Rc<Zip> {
+        Rc::new(Zip {
+            iterators,
+            done: Cell::new(false),
+        })
+    }
+
+    fn inner_next(&self, runtime: &mut Runtime) -> Result<Option<Variable>, ()> {
+        if self.done.get() || self.iterators.is_empty() {
+            return Result::Ok(Option::None);
+        }
+        let mut results = Vec::with_capacity(self.iterators.len());
+        for iterator in &self.iterators {
+            match iterator.next(runtime)?.take_first() {
+                Option::Some(val) => results.push(val),
+                Option::None => {
+                    // Don't advance the remaining iterators any further, so
+                    // the shortest input stops things immediately
+                    self.done.set(true);
+                    return Result::Ok(Option::None);
+                }
+            }
+        }
+        Result::Ok(Option::Some(LangTuple::from_vec(results).into()))
+    }
+
+    fn create(_args: Vec<Variable>, _runtime: &mut Runtime) -> FnResult {
+        unimplemented!()
+    }
+}
+
+impl ZipLongest {
+    pub fn new(fill: Variable, iterators: Vec<looping::Iterator>) -> Rc<ZipLongest> {
+        Rc::new(ZipLongest {
+            fill,
+            iterators: iterators
+                .into_iter()
+                .map(|x| (x, Cell::new(false)))
+                .collect(),
+        })
+    }
+
+    fn inner_next(&self, runtime: &mut Runtime) -> Result<Option<Variable>, ()> {
+        let mut results = Vec::with_capacity(self.iterators.len());
+        let mut any_left = false;
+        for (iterator, done) in &self.iterators {
+            if done.get() {
+                results.push(self.fill.clone());
+                continue;
+            }
+            match iterator.next(runtime)?.take_first() {
+                Option::Some(val) => {
+                    any_left = true;
+                    results.push(val);
+                }
+                Option::None => {
+                    done.set(true);
+                    results.push(self.fill.clone());
+                }
+            }
+        }
+        if any_left {
+            Result::Ok(Option::Some(LangTuple::from_vec(results).into()))
+        } else {
+            Result::Ok(Option::None)
+        }
+    }
+
+    fn create(_args: Vec<Variable>, _runtime: &mut Runtime) -> FnResult {
+        unimplemented!()
+    }
+}
+
+impl IterAttrs for Zip {
+    fn next_fn(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert!(args.is_empty());
+        let result = self.inner_next(runtime)?;
+        runtime.return_1(result.into())
+    }
+
+    fn get_type() -> Type {
+        custom_class!(Zip, create, "Zip")
+    }
+}
+
+impl NativeIterator for Zip {
+    fn next(self: Rc<Self>, runtime: &mut Runtime) -> IterResult {
+        Result::Ok(self.inner_next(runtime)?.into())
+    }
+}
+
+impl IterAttrs for ZipLongest {
+    fn next_fn(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert!(args.is_empty());
+        let result = self.inner_next(runtime)?;
+        runtime.return_1(result.into())
+    }
+
+    fn get_type() -> Type {
+        custom_class!(ZipLongest, create, "ZipLongest")
+    }
+}
+
+impl NativeIterator for ZipLongest {
+    fn next(self: Rc<Self>, runtime: &mut Runtime) -> IterResult {
+        Result::Ok(self.inner_next(runtime)?.into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::builtin_functions::string_fn;
+    use crate::custom_types::zip::{Zip, ZipLongest};
+    use crate::looping::{self, NativeIterator};
+    use crate::runtime::Runtime;
+    use crate::string_var::StringVar;
+    use crate::variable::{InnerVar, Variable};
+    use std::rc::Rc;
+
+    fn iter_of(value: &'static str) -> looping::Iterator {
+        string_fn::iter(StringVar::from(value)).into()
+    }
+
+    fn tuple_of(values: &[char]) -> Vec<Variable> {
+        values.iter().map(|&x| x.into()).collect()
+    }
+
+    // Tuples compare by identity, so they need to be unpacked to be compared
+    fn collect(iter: Rc<dyn NativeIterator>) -> Vec<Vec<Variable>> {
+        let mut runtime = Runtime::new(vec![], 0);
+        let mut result = Vec::new();
+        while let Option::Some(val) = iter.clone().next(&mut runtime).unwrap().take_first() {
+            match val {
+                Variable::Normal(InnerVar::Tuple(x)) => result.push(x.iter().cloned().collect()),
+                x => panic!("Expected a tuple, got {:?}", x),
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn equal_length() {
+        let zip = Zip::new(vec![iter_of("abc"), iter_of("def")]);
+        let expected = vec![
+            tuple_of(&['a', 'd']),
+            tuple_of(&['b', 'e']),
+            tuple_of(&['c', 'f']),
+        ];
+        assert_eq!(collect(zip), expected);
+    }
+
+    #[test]
+    fn ragged() {
+        let zip = Zip::new(vec![iter_of("abc"), iter_of("d"), iter_of("ef")]);
+        assert_eq!(collect(zip), vec![tuple_of(&['a', 'd', 'e'])]);
+    }
+
+    #[test]
+    fn empty() {
+        let zip = Zip::new(vec![]);
+        assert_eq!(collect(zip), Vec::<Vec<_>>::new());
+    }
+
+    #[test]
+    fn longest() {
+        let zip = ZipLongest::new('-'.into(), vec![iter_of("abc"), iter_of("d")]);
+        let expected = vec![
+            tuple_of(&['a', 'd']),
+            tuple_of(&['b', '-']),
+            tuple_of(&['c', '-']),
+        ];
+        assert_eq!(collect(zip), expected);
+    }
+}
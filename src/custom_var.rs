@@ -12,18 +12,23 @@ use std::fmt::Debug;
 use std::hash::{Hash, Hasher};
 use std::ops::Deref;
 use std::ptr;
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
 
 pub trait CustomVar: Debug + Any + Downcast {
     fn set(self: Rc<Self>, name: Name, object: Variable);
     fn get_type(&self) -> Type;
 
     fn get_operator(self: Rc<Self>, op: Operator) -> Variable;
-    fn get_attribute(self: Rc<Self>, name: &str) -> Variable;
+    /// Gets the attribute with the given name, or [`None`] if there is no
+    /// such attribute.
+    fn get_attribute(self: Rc<Self>, name: &str) -> Option<Variable>;
 
     fn get_attr(self: Rc<Self>, name: Name) -> Variable {
         match name {
-            Name::Attribute(a) => self.get_attribute(a),
+            Name::Attribute(a) => match self.clone().get_attribute(a) {
+                Option::Some(attr) => attr,
+                Option::None => unimplemented!("{}.{}", self.get_type().str(), a),
+            },
             Name::Operator(o) => self.get_operator(o),
         }
     }
@@ -104,6 +109,10 @@ impl CustomVarWrapper {
     pub fn into_inner(self) -> Rc<dyn CustomVar> {
         self.value
     }
+
+    pub fn downgrade(&self) -> Weak<dyn CustomVar> {
+        Rc::downgrade(&self.value)
+    }
 }
 
 impl Deref for CustomVarWrapper {
@@ -12,6 +12,14 @@ pub struct FileInfo {
     functions: Vec<BaseFunction>,
     exports: HashMap<String, u32>,
     jump_tables: Vec<JumpTable>,
+    line_table: LineTable,
+}
+
+/// Maps the bytecode positions of each function in a file to the source lines
+/// they were compiled from.
+#[derive(Debug, Default)]
+pub struct LineTable {
+    functions: HashMap<u16, Vec<(u32, usize)>>,
 }
 
 impl FileInfo {
@@ -28,6 +36,7 @@ impl FileInfo {
             functions,
             exports,
             jump_tables,
+            line_table: LineTable::default(),
         }
     }
 
@@ -38,6 +47,7 @@ impl FileInfo {
             functions: vec![],
             exports: HashMap::new(),
             jump_tables: Vec::new(),
+            line_table: LineTable::default(),
         }
     }
 
@@ -60,4 +70,60 @@ impl FileInfo {
     pub fn jump_table(&self, val: usize) -> &JumpTable {
         &self.jump_tables[val]
     }
+
+    pub fn set_line_table(&mut self, table: LineTable) {
+        self.line_table = table;
+    }
+
+    /// The source line containing the instruction at `pos` in the given
+    /// function, if the file has line information for it.
+    pub fn line_for(&self, fn_no: u16, pos: u32) -> Option<usize> {
+        self.line_table.line_for(fn_no, pos)
+    }
+}
+
+impl LineTable {
+    pub fn new() -> LineTable {
+        LineTable::default()
+    }
+
+    /// Records that the instructions of function `fn_no` starting at `start`
+    /// come from `line`, up until the next recorded position.
+    ///
+    /// Positions must be added in increasing order for each function.
+    pub fn add_line(&mut self, fn_no: u16, start: u32, line: usize) {
+        let entries = self.functions.entry(fn_no).or_default();
+        debug_assert!(entries.last().is_none_or(|&(x, _)| x < start));
+        entries.push((start, line));
+    }
+
+    pub fn line_for(&self, fn_no: u16, pos: u32) -> Option<usize> {
+        let entries = self.functions.get(&fn_no)?;
+        match entries.binary_search_by_key(&pos, |&(start, _)| start) {
+            Result::Ok(i) => Option::Some(entries[i].1),
+            Result::Err(0) => Option::None,
+            Result::Err(i) => Option::Some(entries[i - 1].1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::file_info::LineTable;
+
+    #[test]
+    fn line_lookup() {
+        let mut table = LineTable::new();
+        table.add_line(0, 0, 1);
+        table.add_line(0, 6, 2);
+        table.add_line(0, 10, 5);
+        table.add_line(2, 4, 10);
+        assert_eq!(table.line_for(0, 0), Option::Some(1));
+        assert_eq!(table.line_for(0, 5), Option::Some(1));
+        assert_eq!(table.line_for(0, 6), Option::Some(2));
+        assert_eq!(table.line_for(0, 100), Option::Some(5));
+        assert_eq!(table.line_for(1, 0), Option::None);
+        assert_eq!(table.line_for(2, 3), Option::None);
+        assert_eq!(table.line_for(2, 4), Option::Some(10));
+    }
 }
@@ -1,21 +1,29 @@
 use crate::base_fn::BaseFunction;
+use crate::bytecode::validate;
 use crate::constant_loaders::{
     class_index, function_index, load_ascii, load_bigint, load_bool, load_builtin, load_bytes,
     load_char, load_class, load_decimal, load_fmt_args, load_int, load_range, load_std_str,
     load_str, option_index, tuple_indices,
 };
-use crate::file_info::FileInfo;
+use crate::file_info::{FileInfo, LineTable};
 use crate::function::Function;
-use crate::int_tools::bytes_index;
+use crate::int_tools::{try_bytes_index, Truncated};
 use crate::jump_table::JumpTable;
 use crate::std_type::Type;
 use crate::tuple::LangTuple;
 use crate::variable::Variable;
 use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
 use std::fs::read;
 use std::path::Path;
 
 const FILE_EXTENSION: &str = ".nbyte";
+#[allow(clippy::unusual_byte_groupings)]
+const MAGIC_NUMBER: u32 = 0x0A_BAD_E66;
+/// The version of the file format, immediately following the magic number.
+///
+/// This should be incremented whenever the format changes incompatibly.
+const FILE_VERSION: u32 = 1;
 
 #[derive(Debug, Clone)]
 enum Constant {
@@ -32,50 +40,114 @@ enum LoadType {
     OptionType(u32),
 }
 
-fn load_constant(data: &[u8], index: &mut usize, imports: &[Variable]) -> Constant {
-    *index += 1;
-    match data[*index - 1] {
+fn load_constant(
+    data: &[u8],
+    index: &mut usize,
+    imports: &[Variable],
+) -> Result<Constant, Truncated> {
+    Result::Ok(match try_bytes_index::<u8>(data, index)? {
         0 => Variable::null().into(),
-        1 => load_str(data, index).into(),
-        2 => load_int(data, index).into(),
-        3 => load_bigint(data, index).into(),
-        4 => load_decimal(data, index).into(),
-        5 => imports[bytes_index::<u32>(data, index) as usize]
+        1 => load_str(data, index)?.into(),
+        2 => load_int(data, index)?.into(),
+        3 => load_bigint(data, index)?.into(),
+        4 => load_decimal(data, index)?.into(),
+        5 => imports[try_bytes_index::<u32>(data, index)? as usize]
             .clone()
             .into(),
-        6 => load_builtin(data, index).into(),
-        7 => LoadType::Function(function_index(data, index)).into(),
-        8 => load_bool(data, index).into(),
-        9 => LoadType::Class(class_index(data, index)).into(),
-        10 => LoadType::Option(option_index(data, index)).into(),
-        11 => load_bytes(data, index).into(),
-        12 => load_range(data, index).into(),
-        13 => LoadType::Tuple(tuple_indices(data, index)).into(),
-        14 => LoadType::OptionType(class_index(data, index)).into(),
-        15 => load_char(data, index).into(),
-        16 => load_ascii(data, index).into(),
-        17 => load_fmt_args(data, index).into(),
+        6 => load_builtin(data, index)?.into(),
+        7 => LoadType::Function(function_index(data, index)?).into(),
+        8 => load_bool(data, index)?.into(),
+        9 => LoadType::Class(class_index(data, index)?).into(),
+        10 => LoadType::Option(option_index(data, index)?).into(),
+        11 => load_bytes(data, index)?.into(),
+        12 => load_range(data, index)?.into(),
+        13 => LoadType::Tuple(tuple_indices(data, index)?).into(),
+        14 => LoadType::OptionType(class_index(data, index)?).into(),
+        15 => load_char(data, index)?.into(),
+        16 => load_ascii(data, index)?.into(),
+        17 => load_fmt_args(data, index)?.into(),
         x => panic!("Invalid value for constant: {}", x),
+    })
+}
+
+/// An error which prevented a file from being loaded.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct LoadError {
+    file: String,
+    message: String,
+}
+
+impl LoadError {
+    fn new(file: &str, message: String) -> LoadError {
+        LoadError {
+            file: file.to_owned(),
+            message,
+        }
+    }
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Could not load {}: {}", self.file, self.message)
+    }
+}
+
+pub fn parse_file(name: String, files: &mut Vec<FileInfo>) -> Result<usize, LoadError> {
+    match read(Path::new(&name)) {
+        Result::Ok(data) => parse_bytes(name, &data, files),
+        Result::Err(e) => Result::Err(LoadError::new(&name, format!("{}", e))),
     }
 }
 
-pub fn parse_file(name: String, files: &mut Vec<FileInfo>) -> usize {
-    let data = read(Path::new(&name)).unwrap_or_else(|_| panic!("File {} not found", &name));
+fn parse_bytes(name: String, data: &[u8], files: &mut Vec<FileInfo>) -> Result<usize, LoadError> {
     let file_no = files.len();
     files.push(FileInfo::temp());
+    let result = parse_into(name, data, file_no, files);
+    if result.is_err() {
+        // Remove the placeholder, along with any files it imported
+        files.truncate(file_no);
+    }
+    result
+}
+
+/// Parses `data` into `files[file_no]`, which must already exist.
+fn parse_into(
+    name: String,
+    data: &[u8],
+    file_no: usize,
+    files: &mut Vec<FileInfo>,
+) -> Result<usize, LoadError> {
+    let truncated = |_: Truncated| LoadError::new(&name, "File ended unexpectedly".to_owned());
     let mut index: usize = 0;
 
-    let magic_number = bytes_index::<u32>(&data, &mut index);
-    #[allow(clippy::unusual_byte_groupings)]
-    if magic_number != 0x0A_BAD_E66 {
-        panic!("File does not start with the magic number")
+    if data.len() < 8 {
+        let message = "File is too short to contain a header".to_owned();
+        return Result::Err(LoadError::new(&name, message));
+    }
+    let magic_number = try_bytes_index::<u32>(data, &mut index).map_err(truncated)?;
+    if magic_number != MAGIC_NUMBER {
+        let message = "File does not start with the magic number".to_owned();
+        return Result::Err(LoadError::new(&name, message));
+    }
+    let version = try_bytes_index::<u32>(data, &mut index).map_err(truncated)?;
+    if version != FILE_VERSION {
+        let age = if version < FILE_VERSION {
+            "an older"
+        } else {
+            "a newer"
+        };
+        let message = format!(
+            "File was compiled for {} format (version {}) than this runtime supports (version {})",
+            age, version, FILE_VERSION
+        );
+        return Result::Err(LoadError::new(&name, message));
     }
 
-    let import_count = bytes_index::<u32>(&data, &mut index);
+    let import_count = try_bytes_index::<u32>(data, &mut index).map_err(truncated)?;
     let mut imports: Vec<Variable> = Vec::with_capacity(import_count as usize);
     for _ in 0..import_count {
-        let _used_name = load_std_str(&data, &mut index);
-        let full_name = load_std_str(&data, &mut index);
+        let _used_name = load_std_str(data, &mut index).map_err(truncated)?;
+        let full_name = load_std_str(data, &mut index).map_err(truncated)?;
         let names: Vec<&str> = full_name.split('.').collect();
         let folder_split: Vec<&str> = name.rsplitn(2, '/').collect();
         let parent_folder = folder_split[1];
@@ -83,43 +155,72 @@ pub fn parse_file(name: String, files: &mut Vec<FileInfo>) -> usize {
         let file_index = files
             .iter()
             .position(|a| a.get_name() == file_name)
-            .unwrap_or_else(|| parse_file(file_name, files));
+            .map_or_else(|| parse_file(file_name, files), Result::Ok)?;
         // FIXME: Recursion fails
         let other_file = &files[file_index];
         // TODO: Get nested dots
         imports.push(other_file.get_export(names[1]).clone());
     }
 
-    let export_count = bytes_index::<u32>(&data, &mut index);
+    let export_count = try_bytes_index::<u32>(data, &mut index).map_err(truncated)?;
     let mut exports: HashMap<String, u32> = HashMap::with_capacity(export_count as usize);
     for _ in 0..export_count {
-        let export_name = load_std_str(&data, &mut index);
-        let const_no = bytes_index::<u32>(&data, &mut index);
+        let export_name = load_std_str(data, &mut index).map_err(truncated)?;
+        let const_no = try_bytes_index::<u32>(data, &mut index).map_err(truncated)?;
         exports.insert(export_name, const_no);
     }
 
-    let constant_count = bytes_index::<u32>(&data, &mut index);
+    let constant_count = try_bytes_index::<u32>(data, &mut index).map_err(truncated)?;
     let mut constants = Vec::with_capacity(constant_count as usize);
     for _ in 0..constant_count {
-        constants.push(load_constant(&data, &mut index, &imports));
+        constants.push(load_constant(data, &mut index, &imports).map_err(truncated)?);
     }
 
-    let fn_count = bytes_index::<u32>(&data, &mut index);
+    let fn_count = try_bytes_index::<u32>(data, &mut index).map_err(truncated)?;
     let mut functions: Vec<BaseFunction> = Vec::with_capacity(fn_count as usize);
     for _ in 0..fn_count {
-        functions.push(BaseFunction::parse(&data, &mut index))
+        functions.push(BaseFunction::parse(data, &mut index).map_err(truncated)?);
     }
 
-    let class_count = bytes_index::<u32>(&data, &mut index);
+    let class_count = try_bytes_index::<u32>(data, &mut index).map_err(truncated)?;
     let mut classes: Vec<Type> = Vec::with_capacity(fn_count as usize);
     for _ in 0..class_count {
-        classes.push(load_class(file_no, &data, &mut index, &mut functions));
+        let class = load_class(file_no, data, &mut index, &mut functions);
+        classes.push(class.map_err(truncated)?);
     }
 
-    let table_count = bytes_index::<u32>(&data, &mut index);
+    let table_count = try_bytes_index::<u32>(data, &mut index).map_err(truncated)?;
     let mut jump_tables: Vec<JumpTable> = Vec::with_capacity(table_count as usize);
     for _ in 0..table_count {
-        jump_tables.push(JumpTable::parse(&data, &mut index));
+        jump_tables.push(JumpTable::parse(data, &mut index).map_err(truncated)?);
+    }
+
+    // The line table is optional, since it is only used for tracebacks
+    let mut line_table = LineTable::new();
+    if index < data.len() {
+        let line_count = try_bytes_index::<u32>(data, &mut index).map_err(truncated)?;
+        let mut lines = Vec::with_capacity(line_count as usize);
+        for _ in 0..line_count {
+            let fn_no = try_bytes_index::<u16>(data, &mut index).map_err(truncated)?;
+            let start = try_bytes_index::<u32>(data, &mut index).map_err(truncated)?;
+            let line = try_bytes_index::<u32>(data, &mut index).map_err(truncated)?;
+            lines.push((fn_no, start, line));
+        }
+        // LineTable requires the positions of each function in order
+        lines.sort_unstable();
+        lines.dedup_by_key(|&mut (fn_no, start, _)| (fn_no, start));
+        for (fn_no, start, line) in lines {
+            line_table.add_line(fn_no, start, line as usize);
+        }
+    }
+
+    // Checked here, rather than as each instruction is executed, so that a
+    // corrupt file fails before running anything
+    for function in &functions {
+        if let Result::Err(e) = validate(function.get_bytes()) {
+            let message = format!("function {}: {}", function.get_name(), e);
+            return Result::Err(LoadError::new(&name, message));
+        }
     }
 
     debug_assert_eq!(data.len(), index);
@@ -146,8 +247,10 @@ pub fn parse_file(name: String, files: &mut Vec<FileInfo>) -> usize {
         }
     }
 
-    files[file_no] = FileInfo::new(name, new_constants, functions, exports, jump_tables);
-    file_no
+    let mut file = FileInfo::new(name, new_constants, functions, exports, jump_tables);
+    file.set_line_table(line_table);
+    files[file_no] = file;
+    Result::Ok(file_no)
 }
 
 impl From<Variable> for Constant {
@@ -161,3 +264,112 @@ impl From<LoadType> for Constant {
         Constant::Later(x)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::bytecode::Bytecode;
+    use crate::file_parsing::{parse_bytes, LoadError, FILE_VERSION, MAGIC_NUMBER};
+
+    fn file_with_function(body: &[u8]) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&MAGIC_NUMBER.to_be_bytes());
+        data.extend_from_slice(&FILE_VERSION.to_be_bytes());
+        // Imports, exports, and constants
+        data.extend_from_slice(&[0; 12]);
+        data.extend_from_slice(&1u32.to_be_bytes());
+        data.extend_from_slice(&4u32.to_be_bytes());
+        data.extend_from_slice(b"test");
+        data.push(0);
+        data.extend_from_slice(&0u16.to_be_bytes());
+        data.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        data.extend_from_slice(body);
+        // Classes and jump tables
+        data.extend_from_slice(&[0; 8]);
+        data
+    }
+
+    #[test]
+    fn load_valid() {
+        let body = [Bytecode::LoadNull as u8, Bytecode::Return as u8, 0, 0];
+        let mut files = Vec::new();
+        let result = parse_bytes("test".to_owned(), &file_with_function(&body), &mut files);
+        assert_eq!(result, Result::Ok(0));
+        assert_eq!(files.len(), 1);
+    }
+
+    #[test]
+    fn load_line_table() {
+        let body = [Bytecode::LoadNull as u8, Bytecode::Return as u8, 0, 0];
+        let mut data = file_with_function(&body);
+        data.extend_from_slice(&2u32.to_be_bytes());
+        for &(start, line) in &[(1u32, 4u32), (0, 3)] {
+            data.extend_from_slice(&0u16.to_be_bytes());
+            data.extend_from_slice(&start.to_be_bytes());
+            data.extend_from_slice(&line.to_be_bytes());
+        }
+        let mut files = Vec::new();
+        let result = parse_bytes("test".to_owned(), &data, &mut files);
+        assert_eq!(result, Result::Ok(0));
+        assert_eq!(files[0].line_for(0, 0), Option::Some(3));
+        assert_eq!(files[0].line_for(0, 3), Option::Some(4));
+        assert_eq!(files[0].line_for(1, 0), Option::None);
+    }
+
+    #[test]
+    fn load_invalid_opcode() {
+        let body = [Bytecode::LoadNull as u8, 0xFF];
+        let data = file_with_function(&body);
+        let result = parse_bytes("test".to_owned(), &data, &mut Vec::new());
+        let message = "function test: invalid bytecode 0xff at position 1".to_owned();
+        assert_eq!(result, Result::Err(LoadError::new("test", message)));
+    }
+
+    #[test]
+    fn load_truncated() {
+        let body = [Bytecode::LoadNull as u8, Bytecode::LoadConst as u8, 0];
+        let data = file_with_function(&body);
+        let result = parse_bytes("test".to_owned(), &data, &mut Vec::new());
+        assert!(result.unwrap_err().to_string().contains("position 1"));
+    }
+
+    fn load_error(data: &[u8]) -> String {
+        let result = parse_bytes("test".to_owned(), data, &mut Vec::new());
+        result.unwrap_err().to_string()
+    }
+
+    #[test]
+    fn load_truncated_file() {
+        let body = [Bytecode::LoadNull as u8, Bytecode::Return as u8, 0, 0];
+        let data = file_with_function(&body);
+        // Every section is required, so any prefix after the header is truncated
+        for len in 8..data.len() {
+            let mut files = Vec::new();
+            let result = parse_bytes("test".to_owned(), &data[..len], &mut files);
+            let message = "File ended unexpectedly".to_owned();
+            assert_eq!(result, Result::Err(LoadError::new("test", message)));
+            assert!(files.is_empty());
+        }
+    }
+
+    #[test]
+    fn load_bad_magic() {
+        let mut data = file_with_function(&[]);
+        data[0] = 0;
+        assert!(load_error(&data).contains("magic number"));
+        assert!(load_error(&data[..3]).contains("too short"));
+    }
+
+    #[test]
+    fn load_old_version() {
+        let mut data = file_with_function(&[]);
+        data[4..8].copy_from_slice(&(FILE_VERSION - 1).to_be_bytes());
+        assert!(load_error(&data).contains("an older format"));
+    }
+
+    #[test]
+    fn load_new_version() {
+        let mut data = file_with_function(&[]);
+        data[4..8].copy_from_slice(&(FILE_VERSION + 1).to_be_bytes());
+        assert!(load_error(&data).contains("a newer format"));
+    }
+}
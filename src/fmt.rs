@@ -1,12 +1,13 @@
-use crate::custom_types::exceptions::value_error;
+use crate::character;
+use crate::custom_types::dict::Dict;
+use crate::custom_types::exceptions::{index_error, key_error, value_error};
 use crate::custom_var::CustomVar;
-use crate::first_n;
 use crate::fmt_num::{
     format_int_exp, format_int_upper_exp, format_rational_unsigned, format_u_exp,
     format_upper_u_exp,
 };
 use crate::from_bool::FromBool;
-use crate::int_tools::bytes_index;
+use crate::int_tools::{try_bytes_index, Truncated};
 use crate::int_var::IntVar;
 use crate::name::Name;
 use crate::operator::Operator;
@@ -15,15 +16,19 @@ use crate::std_type::Type;
 use crate::string_var::{OwnedStringVar, StringVar};
 use crate::variable::{FnResult, InnerVar, Variable};
 use ascii::{AsAsciiStr, AsciiChar, AsciiStr};
-use num::{bigint, BigInt, BigRational, BigUint, One, ToPrimitive, Zero};
+use num::{bigint, BigInt, BigRational, BigUint, One, Zero};
 use once_cell::sync::Lazy;
 use std::borrow::Cow;
 use std::fmt::{Display, Formatter, Write};
+use std::iter::Peekable;
 use std::rc::Rc;
+use std::str::Chars;
 
-const DEFAULT_FLOAT_DECIMALS: usize = 6;
+/// The number of decimal places used by float formats with no explicit
+/// precision, unless changed with [`Runtime::set_default_float_decimals`].
+pub const DEFAULT_FLOAT_DECIMALS: u32 = 6;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Copy)]
 pub struct FormatArgs {
     fill: char,
     align: Align,
@@ -31,15 +36,254 @@ pub struct FormatArgs {
     hash: bool,
     zero: bool,
     min_width: u32,
-    precision: u32,
+    precision: Option<u32>,
     fmt_type: FmtType,
 }
 
+/// Formats a value with a compiled format specifier.
+///
+/// An optional third argument gives the source text of the formatted
+/// expression, which is prepended as `name=`, as in a debugging f-string
+/// (`f"{x=:>4}"` gives `x=   1`).
 pub fn format_internal(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
-    let [arg, formatter] = first_n(args);
-    let format = get_formatter(formatter);
+    debug_assert!((2..=3).contains(&args.len()));
+    let mut args = args.into_iter();
+    let arg = args.next().unwrap();
+    let format = get_formatter(args.next().unwrap());
     let result = format.format(arg, runtime)?;
-    runtime.return_1(result.into())
+    runtime.return_1(with_name(args.next(), result).into())
+}
+
+/// Formats a value with a textual format specifier, optionally prefixed with
+/// `name=` as in [`format_internal`].
+pub fn format_text(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    debug_assert!((2..=3).contains(&args.len()));
+    let mut args = args.into_iter();
+    let arg = args.next().unwrap();
+    let spec = StringVar::from(args.next().unwrap());
+    match FormatArgs::parse_str(spec.as_str()) {
+        Result::Ok(format) => {
+            let result = format.format(arg, runtime)?;
+            runtime.return_1(with_name(args.next(), result).into())
+        }
+        Result::Err(msg) => runtime.throw_quick(value_error(), msg),
+    }
+}
+
+fn with_name(name: Option<Variable>, value: StringVar) -> StringVar {
+    match name {
+        Option::Some(name) => format!("{}={}", StringVar::from(name), value).into(),
+        Option::None => value,
+    }
+}
+
+/// Substitutes the arguments into the replacement fields of a template, as in
+/// `str.format`.
+///
+/// Replacement fields are of the form `{name:spec}`, where both parts are
+/// optional. The name is either empty, for the next positional argument; an
+/// integer, for the positional argument with that index; or an identifier,
+/// which is looked up in `names`. The spec is the same as that of an
+/// f-string, and may itself contain replacement fields (e.g. `{:>{}}`), which
+/// are substituted before the spec is parsed. Literal braces are written as
+/// `{{` and `}}`.
+pub fn format_template(
+    template: &str,
+    args: &[Variable],
+    names: Option<&Dict>,
+    runtime: &mut Runtime,
+) -> Result<StringVar, ()> {
+    let formatter = TemplateFormatter {
+        template,
+        args,
+        names,
+        numbering: Numbering::Unknown,
+        missing_name: key_error(),
+    };
+    substitute_fields(formatter, runtime)
+}
+
+/// Substitutes the values of `names` into the replacement fields of a
+/// template, as in `str.formatMap`.
+///
+/// This uses the same syntax as [`format_template`], but there are no
+/// positional arguments, so every field must be named. Since the names are
+/// the only argument, a name missing from the dict is a malformed template
+/// and throws a `ValueError`, rather than the `KeyError` of `str.format`.
+pub fn format_map(template: &str, names: &Dict, runtime: &mut Runtime) -> Result<StringVar, ()> {
+    let formatter = TemplateFormatter {
+        template,
+        args: &[],
+        names: Option::Some(names),
+        numbering: Numbering::Unknown,
+        missing_name: value_error(),
+    };
+    substitute_fields(formatter, runtime)
+}
+
+fn substitute_fields(
+    mut formatter: TemplateFormatter<'_>,
+    runtime: &mut Runtime,
+) -> Result<StringVar, ()> {
+    let template = formatter.template;
+    let mut result = String::with_capacity(template.len());
+    let mut chars = template.chars();
+    while let Option::Some(c) = chars.next() {
+        match c {
+            '{' | '}' if chars.as_str().starts_with(c) => {
+                chars.next();
+                result.push(c);
+            }
+            '{' => {
+                let field = match take_field(&mut chars) {
+                    Option::Some(field) => field,
+                    Option::None => return formatter.error("Unterminated '{'", runtime),
+                };
+                let value = formatter.format_field(field, runtime)?;
+                result.push_str(value.as_str());
+            }
+            '}' => return formatter.error("Single '}' not part of a replacement field", runtime),
+            c => result.push(c),
+        }
+    }
+    Result::Ok(result.into())
+}
+
+/// Takes the contents of a replacement field, up to (and consuming) the
+/// matching close brace, or returns [`None`] if there is no such brace.
+fn take_field<'a>(chars: &mut Chars<'a>) -> Option<&'a str> {
+    let text = chars.as_str();
+    let mut depth = 0usize;
+    for (i, c) in text.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' if depth == 0 => {
+                *chars = text[i + 1..].chars();
+                return Option::Some(&text[..i]);
+            }
+            '}' => depth -= 1,
+            _ => {}
+        }
+    }
+    Option::None
+}
+
+#[derive(Debug, Copy, Clone)]
+enum Numbering {
+    Unknown,
+    Auto(usize),
+    Manual,
+}
+
+#[derive(Debug)]
+struct TemplateFormatter<'a> {
+    template: &'a str,
+    args: &'a [Variable],
+    names: Option<&'a Dict>,
+    numbering: Numbering,
+    missing_name: Type,
+}
+
+impl TemplateFormatter<'_> {
+    fn format_field(&mut self, field: &str, runtime: &mut Runtime) -> Result<StringVar, ()> {
+        let (name, spec) = match field.find(':') {
+            Option::Some(i) => (&field[..i], &field[i + 1..]),
+            Option::None => (field, ""),
+        };
+        let value = self.get_arg(name, runtime)?;
+        if spec.is_empty() {
+            return value.str(runtime);
+        }
+        let spec = self.substitute_spec(spec, runtime)?;
+        match FormatArgs::parse_str(&spec) {
+            Result::Ok(format) => format.format(value, runtime),
+            Result::Err(msg) => runtime.throw_quick_native(value_error(), msg),
+        }
+    }
+
+    fn substitute_spec(&mut self, spec: &str, runtime: &mut Runtime) -> Result<String, ()> {
+        let mut result = String::with_capacity(spec.len());
+        let mut chars = spec.chars();
+        while let Option::Some(c) = chars.next() {
+            match c {
+                '{' => match take_field(&mut chars) {
+                    Option::Some(name) if !name.contains(['{', ':']) => {
+                        let value = self.get_arg(name, runtime)?.str(runtime)?;
+                        result.push_str(value.as_str());
+                    }
+                    _ => return self.error("Invalid nested replacement field", runtime),
+                },
+                '}' => return self.error("Single '}' in format spec", runtime),
+                c => result.push(c),
+            }
+        }
+        Result::Ok(result)
+    }
+
+    fn get_arg(&mut self, name: &str, runtime: &mut Runtime) -> Result<Variable, ()> {
+        if name.is_empty() {
+            let index = match self.numbering {
+                Numbering::Unknown => 0,
+                Numbering::Auto(i) => i,
+                Numbering::Manual => {
+                    return self.error(
+                        "Cannot switch from manual field numbering to automatic",
+                        runtime,
+                    )
+                }
+            };
+            self.numbering = Numbering::Auto(index + 1);
+            self.positional(index, runtime)
+        } else if let Result::Ok(index) = name.parse::<usize>() {
+            if let Numbering::Auto(_) = self.numbering {
+                return self.error(
+                    "Cannot switch from automatic field numbering to manual",
+                    runtime,
+                );
+            }
+            self.numbering = Numbering::Manual;
+            self.positional(index, runtime)
+        } else {
+            let value = match self.names {
+                Option::Some(names) => {
+                    names.get_value(StringVar::from(name.to_owned()).into(), runtime)?
+                }
+                Option::None => Option::None,
+            };
+            match value {
+                Option::Some(value) => Result::Ok(value),
+                Option::None => runtime.throw_quick_native(
+                    self.missing_name,
+                    format!(
+                        "No argument named {} in format string '{}'",
+                        name, self.template
+                    ),
+                ),
+            }
+        }
+    }
+
+    fn positional(&self, index: usize, runtime: &mut Runtime) -> Result<Variable, ()> {
+        match self.args.get(index) {
+            Option::Some(value) => Result::Ok(value.clone()),
+            Option::None => runtime.throw_quick_native(
+                index_error(),
+                format!(
+                    "Format string '{}' needs argument {}, but only {} were given",
+                    self.template,
+                    index,
+                    self.args.len()
+                ),
+            ),
+        }
+    }
+
+    fn error<T>(&self, msg: &str, runtime: &mut Runtime) -> Result<T, ()> {
+        runtime.throw_quick_native(
+            value_error(),
+            format!("{} in format string '{}'", msg, self.template),
+        )
+    }
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -77,28 +321,102 @@ enum FmtType {
     String,
 }
 
+/// The fractional part of a whole number with `precision` decimal places,
+/// which is empty if there are none.
+fn decimal_zeros(precision: usize) -> String {
+    if precision == 0 {
+        String::new()
+    } else {
+        format!(".{:0width$}", 0, width = precision)
+    }
+}
+
 impl FormatArgs {
-    pub fn parse(bytes: &[u8], index: &mut usize) -> FormatArgs {
-        let fill = bytes_index::<char>(bytes, index);
-        let align = bytes_index::<u8>(bytes, index);
-        let sign = bytes_index::<u8>(bytes, index);
-        let hash_zero = bytes_index::<u8>(bytes, index);
-        let min_width = bytes_index::<u32>(bytes, index);
-        let precision = bytes_index::<u32>(bytes, index);
-        let fmt_type = bytes_index::<u8>(bytes, index);
-        FormatArgs {
+    pub fn parse(bytes: &[u8], index: &mut usize) -> Result<FormatArgs, Truncated> {
+        let fill = try_bytes_index::<char>(bytes, index)?;
+        let align = try_bytes_index::<u8>(bytes, index)?;
+        let sign = try_bytes_index::<u8>(bytes, index)?;
+        let hash_zero = try_bytes_index::<u8>(bytes, index)?;
+        let min_width = try_bytes_index::<u32>(bytes, index)?;
+        let precision = try_bytes_index::<u32>(bytes, index)?;
+        let fmt_type = try_bytes_index::<u8>(bytes, index)?;
+        Result::Ok(FormatArgs {
             fill,
             align: Align::from_u8(align),
             sign: Sign::from_u8(sign),
             hash: (hash_zero & 0b01) != 0,
             zero: (hash_zero & 0b10) != 0,
             min_width,
-            precision,
+            // Compiled format specifiers use a precision of 0 for the default
+            precision: Option::Some(precision).filter(|&x| x != 0),
             fmt_type: FmtType::from_u8(fmt_type),
+        })
+    }
+
+    /// Parses a textual format specifier, with the same syntax as the format
+    /// specifiers in an f-string:
+    ///
+    /// `[[fill]align][sign]["#"]["0"][width]["." precision][type]`
+    pub fn parse_str(spec: &str) -> Result<FormatArgs, String> {
+        let mut result = FormatArgs::default();
+        let mut chars = spec.chars().peekable();
+        let mut lookahead = spec.chars().skip(1);
+        if let Option::Some(align) = lookahead.next().and_then(Align::from_char) {
+            result.fill = chars.next().unwrap();
+            result.align = align;
+            chars.next();
+        } else if let Option::Some(align) = chars.peek().and_then(|&c| Align::from_char(c)) {
+            result.align = align;
+            chars.next();
+        }
+        if let Option::Some(sign) = chars.peek().and_then(|&c| Sign::from_char(c)) {
+            result.sign = sign;
+            chars.next();
+        }
+        result.hash = chars.next_if_eq(&'#').is_some();
+        result.zero = chars.next_if_eq(&'0').is_some();
+        result.min_width = parse_spec_int(&mut chars, spec)?.unwrap_or(0);
+        if chars.next_if_eq(&'.').is_some() {
+            result.precision = match parse_spec_int(&mut chars, spec)? {
+                Option::Some(x) => Option::Some(x),
+                Option::None => {
+                    return Result::Err(format!("Expected a precision in format spec '{}'", spec))
+                }
+            };
+        }
+        if let Option::Some(c) = chars.next() {
+            result.fmt_type = FmtType::from_char(c)
+                .ok_or_else(|| format!("Invalid format type '{}' in format spec '{}'", c, spec))?;
         }
+        if let Option::Some(c) = chars.next() {
+            return Result::Err(format!(
+                "Unexpected character '{}' in format spec '{}'",
+                c, spec
+            ));
+        }
+        if result.precision.is_some() && result.fmt_type.is_integer() {
+            return Result::Err(format!(
+                "Precision not allowed in integer format specifier '{}'",
+                spec
+            ));
+        }
+        Result::Ok(result)
     }
 
     pub fn format(&self, arg: Variable, runtime: &mut Runtime) -> Result<StringVar, ()> {
+        let decimals = runtime.default_float_decimals();
+        if self.precision.is_none()
+            && decimals != DEFAULT_FLOAT_DECIMALS
+            && self.fmt_type.uses_float_decimals()
+        {
+            // The default precision has fast paths of its own, so only pay
+            // for an explicit precision when the default has been changed
+            let args = FormatArgs {
+                precision: Option::Some(decimals),
+                ..*self
+            };
+            return args.format(arg, runtime);
+        }
         match self.fmt_type {
             FmtType::Binary => Result::Ok(self.fmt_binary(arg).into()),
             FmtType::Character => self.fmt_character(arg, runtime).map(From::from),
@@ -126,19 +444,15 @@ impl FormatArgs {
             && !self.hash
             && !self.zero
             && self.min_width == 0
-            && self.precision == 0
+            && self.precision.is_none()
     }
 
     fn is_default_float(&self) -> bool {
-        self.precision == 0 || self.precision == DEFAULT_FLOAT_DECIMALS as u32
+        self.float_decimals() == DEFAULT_FLOAT_DECIMALS
     }
 
     fn float_decimals(&self) -> u32 {
-        if self.precision == 0 {
-            DEFAULT_FLOAT_DECIMALS as u32
-        } else {
-            self.precision
-        }
+        self.precision.unwrap_or(DEFAULT_FLOAT_DECIMALS)
     }
 
     fn pad_integer(
@@ -150,7 +464,7 @@ impl FormatArgs {
         if self.is_simple_format() {
             return value;
         }
-        if self.precision != 0 {
+        if self.precision.is_some() {
             panic!("Precision not allowed in integer format specifier");
         }
         if self.hash {
@@ -295,7 +609,7 @@ impl FormatArgs {
     }
 
     fn char_from_int(i: &IntVar) -> Option<OwnedStringVar> {
-        i.to_u32().and_then(char::from_u32).map(From::from)
+        character::from_int(i).map(From::from)
     }
 
     fn char_from_bool(i: bool) -> OwnedStringVar {
@@ -373,7 +687,7 @@ impl FormatArgs {
             let e = if uppercase { 'E' } else { 'e' };
             let precision = self.float_decimals() as usize;
             let sign = self.sign_char(bigint::Sign::NoSign);
-            let str = format!("0.{:0width$}{}+00", 0, e, width = precision);
+            let str = format!("0{}{}+00", decimal_zeros(precision), e);
             self.pad_str_simple(OwnedStringVar::from_str_checked(str), sign, "")
         }
     }
@@ -391,7 +705,7 @@ impl FormatArgs {
             let e = if uppercase { 'E' } else { 'e' };
             let precision = self.float_decimals() as usize;
             let sign = self.sign_char(bigint::Sign::NoSign);
-            let str = format!("1.{:0width$}{}+00", 0, e, width = precision);
+            let str = format!("1{}{}+00", decimal_zeros(precision), e);
             self.pad_str_simple(OwnedStringVar::from_str_checked(str), sign, "")
         }
     }
@@ -415,8 +729,7 @@ impl FormatArgs {
                 } else {
                     let precision = self.float_decimals() as usize;
                     let sign_char = self.sign_char(i.sign());
-                    let value =
-                        format!("{}.{:precision$}", i.magnitude(), 0, precision = precision);
+                    let value = format!("{}{}", i.magnitude(), decimal_zeros(precision));
                     self.pad_str_simple(OwnedStringVar::from_str_checked(value), sign_char, "")
                 }
             }
@@ -493,30 +806,62 @@ impl FormatArgs {
 
 impl Align {
     pub fn from_u8(x: u8) -> Align {
-        match x as char {
+        Self::from_char(x as char)
+            .unwrap_or_else(|| panic!("Invalid align type: {} (hex value {:x})", x as char, x))
+    }
+
+    pub fn from_char(x: char) -> Option<Align> {
+        Option::Some(match x {
             '>' => Align::Left,
             '<' => Align::Right,
             '^' => Align::Center,
             '=' => Align::AfterSign,
-            x => panic!("Invalid align type: {} (hex value {:x})", x, x as u32),
+            _ => return Option::None,
+        })
+    }
+
+    pub fn to_char(self) -> char {
+        // Inverse of from_u8: Left pads on the left, and is thus written '>'
+        match self {
+            Align::Left => '>',
+            Align::Right => '<',
+            Align::AfterSign => '=',
+            Align::Center => '^',
         }
     }
 }
 
 impl Sign {
     pub fn from_u8(x: u8) -> Sign {
-        match x as char {
+        Self::from_char(x as char)
+            .unwrap_or_else(|| panic!("Invalid sign type: {} (hex value {:x})", x as char, x))
+    }
+
+    pub fn from_char(x: char) -> Option<Sign> {
+        Option::Some(match x {
             '-' => Sign::NegativeOnly,
             '+' => Sign::Both,
             ' ' => Sign::LeadingSpace,
-            x => panic!("Invalid sign type: {} (hex value {:x})", x, x as u32),
+            _ => return Option::None,
+        })
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            Sign::Both => '+',
+            Sign::NegativeOnly => '-',
+            Sign::LeadingSpace => ' ',
         }
     }
 }
 
 impl FmtType {
     pub fn from_u8(x: u8) -> FmtType {
-        match x as char {
+        Self::from_char(x as char).unwrap_or_else(|| panic!("Invalid format type: {}", x as char))
+    }
+
+    pub fn from_char(x: char) -> Option<FmtType> {
+        Option::Some(match x {
             'b' => FmtType::Binary,
             'c' => FmtType::Character,
             'd' => FmtType::Decimal,
@@ -533,7 +878,47 @@ impl FmtType {
             '%' => FmtType::Percentage,
             'r' => FmtType::Repr,
             's' => FmtType::String,
-            x => panic!("Invalid format type: {}", x),
+            _ => return Option::None,
+        })
+    }
+
+    fn uses_float_decimals(self) -> bool {
+        matches!(
+            self,
+            FmtType::Exponent | FmtType::UpperExp | FmtType::Fixed | FmtType::UpperFixed
+        )
+    }
+
+    fn is_integer(self) -> bool {
+        matches!(
+            self,
+            FmtType::Binary
+                | FmtType::Character
+                | FmtType::Decimal
+                | FmtType::Octal
+                | FmtType::Hex
+                | FmtType::UpperHex
+        )
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            FmtType::Binary => 'b',
+            FmtType::Character => 'c',
+            FmtType::Decimal => 'd',
+            FmtType::Octal => 'o',
+            FmtType::Hex => 'x',
+            FmtType::UpperHex => 'X',
+            FmtType::Number => 'n',
+            FmtType::Exponent => 'e',
+            FmtType::UpperExp => 'E',
+            FmtType::Fixed => 'f',
+            FmtType::UpperFixed => 'F',
+            FmtType::General => 'g',
+            FmtType::UpperGeneral => 'G',
+            FmtType::Percentage => '%',
+            FmtType::Repr => 'r',
+            FmtType::String => 's',
         }
     }
 }
@@ -557,11 +942,7 @@ impl Default for Align {
 
 impl Display for Sign {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        f.write_char(match self {
-            Sign::Both => '+',
-            Sign::NegativeOnly => '-',
-            Sign::LeadingSpace => ' ',
-        })
+        f.write_char(self.to_char())
     }
 }
 
@@ -573,24 +954,7 @@ impl Default for Sign {
 
 impl Display for FmtType {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        f.write_char(match self {
-            FmtType::Binary => 'b',
-            FmtType::Character => 'c',
-            FmtType::Decimal => 'd',
-            FmtType::Octal => 'o',
-            FmtType::Hex => 'x',
-            FmtType::UpperHex => 'X',
-            FmtType::Number => 'n',
-            FmtType::Exponent => 'e',
-            FmtType::UpperExp => 'E',
-            FmtType::Fixed => 'f',
-            FmtType::UpperFixed => 'F',
-            FmtType::General => 'g',
-            FmtType::UpperGeneral => 'G',
-            FmtType::Percentage => '%',
-            FmtType::Repr => 'r',
-            FmtType::String => 's',
-        })
+        f.write_char(self.to_char())
     }
 }
 
@@ -600,6 +964,20 @@ impl Default for FmtType {
     }
 }
 
+fn parse_spec_int(chars: &mut Peekable<Chars<'_>>, spec: &str) -> Result<Option<u32>, String> {
+    let mut result = Option::None;
+    while let Option::Some(digit) = chars.peek().and_then(|c| c.to_digit(10)) {
+        let value = result
+            .unwrap_or(0u32)
+            .checked_mul(10)
+            .and_then(|x| x.checked_add(digit))
+            .ok_or_else(|| format!("Number too large in format spec '{}'", spec))?;
+        result = Option::Some(value);
+        chars.next();
+    }
+    Result::Ok(result)
+}
+
 fn get_formatter(var: Variable) -> Rc<FormatArgs> {
     match var {
         Variable::Normal(InnerVar::Custom(c)) => c.into_inner().downcast_rc().unwrap(),
@@ -622,29 +1000,59 @@ fn int_var_as_magnitude(x: &IntVar) -> Cow<'_, BigUint> {
     }
 }
 
+impl FormatArgs {
+    fn create(_args: Vec<Variable>, _runtime: &mut Runtime) -> FnResult {
+        unimplemented!()
+    }
+
+    pub fn format_args_type() -> Type {
+        custom_class!(FormatArgs, create, "FormatArgs")
+    }
+}
+
 impl CustomVar for FormatArgs {
     fn set(self: Rc<Self>, _name: Name, _object: Variable) {
         unimplemented!()
     }
 
     fn get_type(&self) -> Type {
-        todo!()
+        Self::format_args_type()
     }
 
-    fn get_operator(self: Rc<Self>, _op: Operator) -> Variable {
-        unimplemented!()
+    fn get_operator(self: Rc<Self>, op: Operator) -> Variable {
+        unimplemented!("FormatArgs.{}", op.name())
     }
 
-    fn get_attribute(self: Rc<Self>, _name: &str) -> Variable {
-        unimplemented!()
+    fn get_attribute(self: Rc<Self>, name: &str) -> Option<Variable> {
+        Option::Some(match name {
+            "fill" => self.fill_char().into(),
+            "align" => self.align.to_char().into(),
+            "sign" => self.sign.to_char().into(),
+            "width" => IntVar::from(self.min_width).into(),
+            "precision" => self.precision.map(|x| IntVar::from(x).into()).into(),
+            "type" => self.fmt_type.to_char().into(),
+            "hash" => self.hash.into(),
+            "zero" => self.zero.into(),
+            _ => return Option::None,
+        })
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::fmt::{Align, FmtType, FormatArgs, Sign};
+    use crate::custom_types::dict::Dict;
+    use crate::custom_types::exceptions::{index_error, key_error, value_error};
+    use crate::custom_var::CustomVar;
+    use crate::fmt::{
+        format_internal, format_map, format_template, format_text, Align, FmtType, FormatArgs, Sign,
+    };
+    use crate::int_var::IntVar;
     use crate::rational_var::RationalVar;
+    use crate::runtime::Runtime;
+    use crate::string_var::StringVar;
+    use crate::variable::Variable;
     use num::{BigInt, BigRational, One};
+    use std::rc::Rc;
 
     #[test]
     fn simple_binary() {
@@ -861,4 +1269,327 @@ mod test {
             "33%"
         );
     }
+
+    #[test]
+    fn attributes() {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&('*' as u32).to_be_bytes());
+        bytes.extend_from_slice(&[b'^', b'+', 0b11]);
+        bytes.extend_from_slice(&10u32.to_be_bytes());
+        bytes.extend_from_slice(&3u32.to_be_bytes());
+        bytes.push(b'x');
+        let mut index = 0;
+        let formatter = Rc::new(FormatArgs::parse(&bytes, &mut index).unwrap());
+        assert_eq!(index, bytes.len());
+        let attr = |name| formatter.clone().get_attribute(name).unwrap();
+        assert_eq!(attr("fill"), Variable::from('*'));
+        assert_eq!(attr("align"), Variable::from('^'));
+        assert_eq!(attr("sign"), Variable::from('+'));
+        assert_eq!(attr("width"), Variable::from(IntVar::from(10)));
+        assert_eq!(
+            attr("precision"),
+            Variable::from(Option::Some(IntVar::from(3).into()))
+        );
+        assert_eq!(attr("type"), Variable::from('x'));
+        assert_eq!(attr("hash"), Variable::from(true));
+        assert_eq!(attr("zero"), Variable::from(true));
+    }
+
+    #[test]
+    fn default_attributes() {
+        let formatter = Rc::new(FormatArgs::default());
+        let attr = |name| formatter.clone().get_attribute(name).unwrap();
+        assert_eq!(attr("fill"), Variable::from(' '));
+        assert_eq!(attr("align"), Variable::from('>'));
+        assert_eq!(attr("sign"), Variable::from('-'));
+        assert_eq!(attr("width"), Variable::from(IntVar::from(0)));
+        assert_eq!(attr("type"), Variable::from('s'));
+        assert_eq!(attr("hash"), Variable::from(false));
+    }
+
+    fn format_text_str(value: Variable, spec: &'static str) -> Result<Variable, ()> {
+        Runtime::test(|runtime| format_text(vec![value, StringVar::from(spec).into()], runtime))
+    }
+
+    #[test]
+    fn parse_str() {
+        let formatter = FormatArgs::parse_str("*^+#012.3e").unwrap();
+        assert_eq!(formatter.fill, '*');
+        assert_eq!(formatter.align, Align::Center);
+        assert_eq!(formatter.sign, Sign::Both);
+        assert!(formatter.hash);
+        assert!(formatter.zero);
+        assert_eq!(formatter.min_width, 12);
+        assert_eq!(formatter.precision, Option::Some(3));
+        assert_eq!(formatter.fmt_type, FmtType::Exponent);
+    }
+
+    #[test]
+    fn parse_str_empty() {
+        let formatter = FormatArgs::parse_str("").unwrap();
+        assert!(formatter.is_simple_format());
+        assert_eq!(formatter.fmt_type, FmtType::String);
+    }
+
+    #[test]
+    fn parse_str_align_only() {
+        let formatter = FormatArgs::parse_str("<").unwrap();
+        assert_eq!(formatter.fill, '\0');
+        assert_eq!(formatter.align, Align::Right);
+        // A fill character that could also be an align character
+        let formatter = FormatArgs::parse_str("<>5").unwrap();
+        assert_eq!(formatter.fill, '<');
+        assert_eq!(formatter.align, Align::Left);
+        assert_eq!(formatter.min_width, 5);
+    }
+
+    #[test]
+    fn parse_str_invalid() {
+        for spec in &["abc", "5.", ".2d", "5q", "99999999999"] {
+            assert!(FormatArgs::parse_str(spec).is_err(), "{}", spec);
+        }
+    }
+
+    #[test]
+    fn format_text_specs() {
+        let cases: Vec<(Variable, &'static str, &'static str)> = vec![
+            (StringVar::from("abc").into(), ">8", "     abc"),
+            (StringVar::from("abc").into(), "", "abc"),
+            (IntVar::from(12).into(), "<5", "12   "),
+            (StringVar::from("ab").into(), "*^7", "**ab***"),
+            (IntVar::from(5).into(), "+d", "+5"),
+            (IntVar::from(255).into(), "#x", "0xff"),
+            (IntVar::from(5).into(), "08b", "00000101"),
+            (IntVar::from(-5).into(), "=6d", "-    5"),
+            (IntVar::from(1).into(), ">8.2f", "    1.00"),
+        ];
+        for (value, spec, expected) in cases {
+            let result = format_text_str(value, spec);
+            assert_eq!(
+                result,
+                Result::Ok(StringVar::from(expected).into()),
+                "{}",
+                spec
+            );
+        }
+    }
+
+    #[test]
+    fn format_text_invalid() {
+        let result = Runtime::test(|runtime| {
+            let args = vec![IntVar::from(1).into(), StringVar::from(".2d").into()];
+            assert!(format_text(args, runtime).is_err());
+            let exc = runtime.pop_err()?;
+            runtime.return_1(exc.get_type().into())
+        });
+        assert_eq!(result, Result::Ok(value_error().into()));
+    }
+
+    #[test]
+    fn format_with_name() {
+        let result = Runtime::test(|runtime| {
+            let args = vec![
+                IntVar::from(1).into(),
+                StringVar::from(">4").into(),
+                StringVar::from("x").into(),
+            ];
+            format_text(args, runtime)
+        });
+        assert_eq!(result, Result::Ok(StringVar::from("x=   1").into()));
+        let result = Runtime::test(|runtime| {
+            let format = Rc::new(FormatArgs::parse_str("<5").unwrap());
+            let args = vec![
+                StringVar::from("ab").into(),
+                format.into(),
+                StringVar::from("a + b").into(),
+            ];
+            format_internal(args, runtime)
+        });
+        assert_eq!(result, Result::Ok(StringVar::from("a + b=ab   ").into()));
+    }
+
+    #[test]
+    fn default_float_decimals() {
+        let format = |value: Variable, spec: &'static str, decimals: Option<u32>| {
+            Runtime::test(|runtime| {
+                if let Option::Some(decimals) = decimals {
+                    runtime.set_default_float_decimals(decimals);
+                }
+                format_text(vec![value, StringVar::from(spec).into()], runtime)
+            })
+        };
+        let half: Variable = RationalVar::from(BigRational::new(1.into(), 2.into())).into();
+        let cases: Vec<(Variable, &'static str, &'static str, &'static str)> = vec![
+            (IntVar::from(0).into(), "f", "0.000000", "0.00"),
+            (half.clone(), "f", "0.500000", "0.50"),
+            (half.clone(), ".3f", "0.500", "0.500"),
+            (IntVar::from(0).into(), "e", "0.000000e+00", "0.00e+00"),
+            (half, "e", "5.000000e-01", "5.00e-01"),
+        ];
+        for (value, spec, default, changed) in cases {
+            let result = format(value.clone(), spec, Option::None);
+            assert_eq!(result, Result::Ok(StringVar::from(default).into()));
+            let result = format(value, spec, Option::Some(2));
+            assert_eq!(result, Result::Ok(StringVar::from(changed).into()));
+        }
+    }
+
+    #[test]
+    fn zero_default_float_decimals() {
+        let result = Runtime::test(|runtime| {
+            runtime.set_default_float_decimals(0);
+            format_text(
+                vec![IntVar::from(3).into(), StringVar::from("f").into()],
+                runtime,
+            )
+        });
+        assert_eq!(result, Result::Ok(StringVar::from("3").into()));
+    }
+
+    #[test]
+    fn zero_precision() {
+        let five_quarters: Variable =
+            RationalVar::from(BigRational::new(5.into(), 4.into())).into();
+        let cases: Vec<(Variable, &'static str, &'static str)> = vec![
+            (IntVar::from(3).into(), ".0f", "3"),
+            (IntVar::from(0).into(), ".0f", "0"),
+            (five_quarters.clone(), ".0f", "1"),
+            (IntVar::from(0).into(), ".0e", "0e+00"),
+            (five_quarters, ".0e", "1e+00"),
+        ];
+        for (value, spec, expected) in cases {
+            let result = format_text_str(value, spec);
+            assert_eq!(
+                result,
+                Result::Ok(StringVar::from(expected).into()),
+                "{}",
+                spec
+            );
+        }
+    }
+
+    fn template_str(template: &'static str, args: Vec<Variable>) -> Result<Variable, ()> {
+        Runtime::test(|runtime| {
+            let result = format_template(template, &args, Option::None, runtime)?;
+            runtime.return_1(result.into())
+        })
+    }
+
+    fn template_err(template: &'static str, args: Vec<Variable>) -> Result<Variable, ()> {
+        Runtime::test(|runtime| {
+            assert!(format_template(template, &args, Option::None, runtime).is_err());
+            let exc = runtime.pop_err()?;
+            runtime.return_1(exc.get_type().into())
+        })
+    }
+
+    #[test]
+    fn template_positional() {
+        let args: Vec<Variable> = vec![IntVar::from(1).into(), StringVar::from("b").into()];
+        let cases = [
+            ("{} and {}", "1 and b"),
+            ("{1} and {0} and {1}", "b and 1 and b"),
+            ("no fields", "no fields"),
+            ("", ""),
+        ];
+        for (template, expected) in &cases {
+            assert_eq!(
+                template_str(template, args.clone()),
+                Result::Ok(StringVar::from(*expected).into()),
+                "{}",
+                template
+            );
+        }
+    }
+
+    #[test]
+    fn template_spec() {
+        let args: Vec<Variable> = vec![IntVar::from(255).into(), IntVar::from(6).into()];
+        let cases = [
+            ("[{:#x}]", "[0xff]"),
+            ("[{0:>{1}}]", "[   255]"),
+            ("[{:*^{}}]", "[*255**]"),
+            ("[{0:0{1}b}]", "[11111111]"),
+        ];
+        for (template, expected) in &cases {
+            assert_eq!(
+                template_str(template, args.clone()),
+                Result::Ok(StringVar::from(*expected).into()),
+                "{}",
+                template
+            );
+        }
+    }
+
+    #[test]
+    fn template_escaped() {
+        let args: Vec<Variable> = vec![IntVar::from(3).into()];
+        assert_eq!(
+            template_str("{{{}}} }}{{", args),
+            Result::Ok(StringVar::from("{3} }{").into())
+        );
+    }
+
+    #[test]
+    fn template_named() {
+        let result = Runtime::test(|runtime| {
+            let names = Dict::from_args(
+                vec![StringVar::from("x").into(), StringVar::from("y").into()],
+                vec![IntVar::from(4).into(), StringVar::from("z").into()],
+                runtime,
+            )?;
+            let args = vec![IntVar::from(7).into()];
+            let result = format_template("{y}{x:>3}{}", &args, Option::Some(&names), runtime)?;
+            runtime.return_1(result.into())
+        });
+        assert_eq!(result, Result::Ok(StringVar::from("z  47").into()));
+    }
+
+    fn names_dict(runtime: &mut Runtime) -> Result<Rc<Dict>, ()> {
+        Dict::from_args(
+            vec![StringVar::from("x").into(), StringVar::from("y").into()],
+            vec![IntVar::from(4).into(), StringVar::from("z").into()],
+            runtime,
+        )
+    }
+
+    #[test]
+    fn template_map() {
+        let result = Runtime::test(|runtime| {
+            let names = names_dict(runtime)?;
+            let result = format_map("{y}{x:>3}{{{x}}}", &names, runtime)?;
+            runtime.return_1(result.into())
+        });
+        assert_eq!(result, Result::Ok(StringVar::from("z  4{4}").into()));
+    }
+
+    #[test]
+    fn template_map_missing() {
+        let result = Runtime::test(|runtime| {
+            let names = names_dict(runtime)?;
+            assert!(format_map("{x}{w}", &names, runtime).is_err());
+            let exc = runtime.pop_err()?;
+            runtime.return_1(exc.get_type().into())
+        });
+        assert_eq!(result, Result::Ok(value_error().into()));
+    }
+
+    #[test]
+    fn template_invalid() {
+        let args: Vec<Variable> = vec![IntVar::from(1).into()];
+        let cases = ["{", "}", "{0}{}", "{}{0}", "{:{:}}", "{:q}", "a}b"];
+        for template in &cases {
+            assert_eq!(
+                template_err(template, args.clone()),
+                Result::Ok(value_error().into()),
+                "{}",
+                template
+            );
+        }
+        assert_eq!(
+            template_err("{1}", args.clone()),
+            Result::Ok(index_error().into())
+        );
+        assert_eq!(template_err("{x}", args), Result::Ok(key_error().into()));
+    }
 }
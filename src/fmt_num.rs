@@ -390,9 +390,6 @@ fn fmt_display_abs(value: &BigUint, scale: i64, f: &mut Formatter<'_>) -> std::f
 }
 
 fn fmt_exp(value: &BigInt, scale: i64, f: &mut Formatter<'_>, e: char) -> std::fmt::Result {
-    if scale == 0 {
-        return value.fmt(f);
-    }
     if value.is_negative() {
         f.write_char('-')?;
     }
@@ -419,7 +416,9 @@ fn fmt_exp_abs(value: &BigUint, scale: i64, f: &mut Formatter<'_>, e: char) -> s
                 } else {
                     let carry = round_chars(&mut digits, x + 1);
                     f.write_char(if carry { '1' } else { digits[0] })?;
-                    f.write_char('.')?;
+                    if x != 0 {
+                        f.write_char('.')?;
+                    }
                     let c = usize::from_bool(!carry);
                     for ch in &digits[c..x + c] {
                         f.write_char(*ch)?;
@@ -438,7 +437,7 @@ fn fmt_exp_abs(value: &BigUint, scale: i64, f: &mut Formatter<'_>, e: char) -> s
         }
     } else {
         f.write_char(digits[0])?;
-        if let Option::Some(prec) = precision {
+        if let Option::Some(prec) = precision.filter(|&x| x != 0) {
             f.write_char('.')?;
             for _ in 0..prec {
                 f.write_char('0')?;
@@ -1,6 +1,6 @@
 use crate::runtime::Runtime;
 use crate::string_var::StringVar;
-use crate::variable::{FnResult, Variable};
+use crate::variable::{value_id, FnResult, IdTag, Variable};
 use std::fmt::{Debug, Formatter};
 use std::hash::{Hash, Hasher};
 
@@ -41,8 +41,14 @@ impl Function {
 
     pub fn id(&self) -> usize {
         match self {
-            Function::Standard(_file_no, _fn_no) => todo!("Unique ids for standard functions"),
-            Function::Native(func) => func as *const _ as usize,
+            Function::Standard(file_no, fn_no) => {
+                // Each number gets half of the bits, so ids only collide once
+                // there are more than 2^32 files or functions in a file (2^16
+                // on 32-bit targets)
+                let half = usize::BITS / 2;
+                value_id(IdTag::Function, (*file_no << half) ^ *fn_no as usize)
+            }
+            Function::Native(func) => *func as usize,
         }
     }
 }
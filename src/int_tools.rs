@@ -78,6 +78,32 @@ where
     result
 }
 
+/// The error returned when a file ends in the middle of a value.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Truncated;
+
+/// Like [`bytes_index`], but returns an error if there are too few bytes left
+/// rather than panicking.
+pub fn try_bytes_index<T>(bytes: &[u8], index: &mut usize) -> Result<T, Truncated>
+where
+    T: FromBytes,
+{
+    let value = bytes_slice(bytes, index, size_of::<T>())?;
+    Result::Ok(T::from_be(value))
+}
+
+/// The next `len` bytes, beginning at the index specified.
+pub fn bytes_slice<'a>(
+    bytes: &'a [u8],
+    index: &mut usize,
+    len: usize,
+) -> Result<&'a [u8], Truncated> {
+    let end = index.checked_add(len).ok_or(Truncated)?;
+    let result = bytes.get(*index..end).ok_or(Truncated)?;
+    *index = end;
+    Result::Ok(result)
+}
+
 fn bytes_error<T>(len: usize) -> ! {
     panic!(
         "Could not convert byte slice: expected {} bytes, got {}",
@@ -27,13 +27,18 @@ pub enum IntMag<'a> {
 impl IntVar {
     pub fn to_str_radix(&self, radix: u32) -> String {
         match self {
-            IntVar::Small(s) => match radix {
-                10 => format!("{}", s),
-                16 => format!("{:x}", s),
-                8 => format!("{:o}", s),
-                2 => format!("{:b}", s),
-                x => BigInt::from(x).to_str_radix(radix),
-            },
+            IntVar::Small(s) => {
+                // Formatting a negative isize directly in hex, octal, or binary
+                // gives its two's complement, so format the magnitude instead
+                let sign = if s.is_negative() { "-" } else { "" };
+                match radix {
+                    10 => format!("{}", s),
+                    16 => format!("{}{:x}", sign, s.unsigned_abs()),
+                    8 => format!("{}{:o}", sign, s.unsigned_abs()),
+                    2 => format!("{}{:b}", sign, s.unsigned_abs()),
+                    _ => BigInt::from(*s).to_str_radix(radix),
+                }
+            }
             IntVar::Big(b) => b.to_str_radix(radix),
         }
     }
@@ -774,8 +779,33 @@ macro_rules! integer_fn {
 impl Integer for IntVar {
     integer_fn!(div_floor);
     integer_fn!(mod_floor);
-    integer_fn!(gcd);
-    integer_fn!(lcm);
+
+    fn gcd(&self, other: &Self) -> Self {
+        match (self, other) {
+            // The gcd of isize::MIN and 0 (or itself) doesn't fit in an isize
+            (IntVar::Small(s1), IntVar::Small(s2)) if *s1 != isize::MIN && *s2 != isize::MIN => {
+                s1.gcd(s2).into()
+            }
+            _ => self
+                .to_bigint()
+                .unwrap()
+                .gcd(&other.to_bigint().unwrap())
+                .into(),
+        }
+    }
+
+    fn lcm(&self, other: &Self) -> Self {
+        if self.is_zero() || other.is_zero() {
+            IntVar::zero()
+        } else {
+            let lcm = self.clone() / self.gcd(other) * other.clone();
+            if lcm.is_negative() {
+                (-BigInt::from(lcm)).into()
+            } else {
+                lcm
+            }
+        }
+    }
 
     fn divides(&self, other: &Self) -> bool {
         self.is_multiple_of(other)
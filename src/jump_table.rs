@@ -1,5 +1,5 @@
 use crate::constant_loaders::{load_bigint, load_std_str};
-use crate::int_tools::bytes_index;
+use crate::int_tools::{try_bytes_index, Truncated};
 use crate::int_var::IntVar;
 use crate::string_var::StringVar;
 use num::ToPrimitive;
@@ -42,75 +42,74 @@ pub struct CharJumpTbl {
 }
 
 impl JumpTable {
-    pub fn parse(data: &[u8], index: &mut usize) -> JumpTable {
-        let tbl_type = data[*index];
-        *index += 1;
-        match tbl_type {
-            0 => JumpTable::Compact(CompactJumpTbl::parse(data, index)),
-            1 => JumpTable::Big(BigJumpTbl::parse(data, index)),
-            2 => JumpTable::String(StrJumpTbl::parse(data, index)),
-            3 => JumpTable::Char(CharJumpTbl::parse(data, index)),
+    pub fn parse(data: &[u8], index: &mut usize) -> Result<JumpTable, Truncated> {
+        let tbl_type = try_bytes_index::<u8>(data, index)?;
+        Result::Ok(match tbl_type {
+            0 => JumpTable::Compact(CompactJumpTbl::parse(data, index)?),
+            1 => JumpTable::Big(BigJumpTbl::parse(data, index)?),
+            2 => JumpTable::String(StrJumpTbl::parse(data, index)?),
+            3 => JumpTable::Char(CharJumpTbl::parse(data, index)?),
             _ => panic!("{} is an invalid table-type number", tbl_type),
-        }
+        })
     }
 }
 
 impl CompactJumpTbl {
-    pub fn parse(data: &[u8], index: &mut usize) -> CompactJumpTbl {
-        let size = bytes_index::<u32>(data, index);
+    pub fn parse(data: &[u8], index: &mut usize) -> Result<CompactJumpTbl, Truncated> {
+        let size = try_bytes_index::<u32>(data, index)?;
         let values = (0..size)
-            .map(|_| bytes_index::<u32>(data, index) as usize)
-            .collect();
-        let default = bytes_index::<u32>(data, index) as usize;
-        CompactJumpTbl { values, default }
+            .map(|_| Result::Ok(try_bytes_index::<u32>(data, index)? as usize))
+            .collect::<Result<_, _>>()?;
+        let default = try_bytes_index::<u32>(data, index)? as usize;
+        Result::Ok(CompactJumpTbl { values, default })
     }
 }
 
 impl BigJumpTbl {
-    pub fn parse(data: &[u8], index: &mut usize) -> BigJumpTbl {
-        let size = bytes_index::<u32>(data, index);
+    pub fn parse(data: &[u8], index: &mut usize) -> Result<BigJumpTbl, Truncated> {
+        let size = try_bytes_index::<u32>(data, index)?;
         let values = (0..size)
             .map(|_| {
-                (
-                    load_bigint(data, index).into(),
-                    bytes_index::<u32>(data, index) as usize,
-                )
+                Result::Ok((
+                    load_bigint(data, index)?.into(),
+                    try_bytes_index::<u32>(data, index)? as usize,
+                ))
             })
-            .collect();
-        let default = bytes_index::<u32>(data, index) as usize;
-        BigJumpTbl { values, default }
+            .collect::<Result<_, _>>()?;
+        let default = try_bytes_index::<u32>(data, index)? as usize;
+        Result::Ok(BigJumpTbl { values, default })
     }
 }
 
 impl StrJumpTbl {
-    pub fn parse(data: &[u8], index: &mut usize) -> StrJumpTbl {
-        let size = bytes_index::<u32>(data, index);
+    pub fn parse(data: &[u8], index: &mut usize) -> Result<StrJumpTbl, Truncated> {
+        let size = try_bytes_index::<u32>(data, index)?;
         let values = (0..size)
             .map(|_| {
-                (
-                    load_std_str(data, index),
-                    bytes_index::<u32>(data, index) as usize,
-                )
+                Result::Ok((
+                    load_std_str(data, index)?,
+                    try_bytes_index::<u32>(data, index)? as usize,
+                ))
             })
-            .collect();
-        let default = bytes_index::<u32>(data, index) as usize;
-        StrJumpTbl { values, default }
+            .collect::<Result<_, _>>()?;
+        let default = try_bytes_index::<u32>(data, index)? as usize;
+        Result::Ok(StrJumpTbl { values, default })
     }
 }
 
 impl CharJumpTbl {
-    pub fn parse(data: &[u8], index: &mut usize) -> CharJumpTbl {
-        let size = bytes_index::<u32>(data, index);
+    pub fn parse(data: &[u8], index: &mut usize) -> Result<CharJumpTbl, Truncated> {
+        let size = try_bytes_index::<u32>(data, index)?;
         let values = (0..size)
             .map(|_| {
-                (
-                    bytes_index::<char>(data, index),
-                    bytes_index::<u32>(data, index) as usize,
-                )
+                Result::Ok((
+                    try_bytes_index::<char>(data, index)?,
+                    try_bytes_index::<u32>(data, index)? as usize,
+                ))
             })
-            .collect();
-        let default = bytes_index::<u32>(data, index) as usize;
-        CharJumpTbl { values, default }
+            .collect::<Result<_, _>>()?;
+        let default = try_bytes_index::<u32>(data, index)? as usize;
+        Result::Ok(CharJumpTbl { values, default })
     }
 }
 
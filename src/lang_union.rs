@@ -102,6 +102,19 @@ impl LangUnion {
         }
     }
 
+    /// Gets the given attribute, or [`None`] if it does not exist.
+    pub fn try_index(&self, index: &str, runtime: &mut Runtime) -> Result<Option<Variable>, ()> {
+        let name = Name::Attribute(index);
+        if self.cls.variant_pos(name).is_some()
+            || self.cls.get_property(name).is_some()
+            || self.cls.methods.get(name).is_some()
+        {
+            self.index(name, runtime).map(Option::Some)
+        } else {
+            Result::Ok(Option::None)
+        }
+    }
+
     pub fn get_type(&self) -> Type {
         Type::Union(self.cls)
     }
@@ -185,6 +198,11 @@ impl UnionType {
         }
     }
 
+    pub fn has_static(&self, attr: &str) -> bool {
+        self.variants.iter().any(|x| *x == attr)
+            || self.static_methods.get(Name::Attribute(attr)).is_some()
+    }
+
     fn variant_pos(&self, index: Name) -> Option<usize> {
         if let Name::Attribute(name) = index {
             self.variants.iter().position(|x| *x == name)
@@ -197,6 +215,14 @@ impl UnionType {
         &self.name
     }
 
+    pub(crate) fn bases(&self, runtime: &Runtime) -> Vec<Type> {
+        let constants = runtime.file_no(self.file_no).get_constants();
+        self.supers
+            .iter()
+            .map(|&sup| constants[sup as usize].clone().into())
+            .collect()
+    }
+
     pub fn get_property(&self, name: Name) -> Option<&Property> {
         name.do_each(|_| Option::None, |str| self.properties.get(str))
     }
@@ -359,8 +385,8 @@ impl CustomVar for UnionMaker {
         }
     }
 
-    fn get_attribute(self: Rc<Self>, _name: &str) -> Variable {
-        unimplemented!()
+    fn get_attribute(self: Rc<Self>, _name: &str) -> Option<Variable> {
+        Option::None
     }
 
     fn call(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
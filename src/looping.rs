@@ -34,12 +34,12 @@ pub trait IterAttrs: NativeIterator + Sized {
     fn next_fn(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult;
     fn get_type() -> Type;
 
-    fn get_name(self: Rc<Self>, val: &str) -> Variable {
+    fn get_name(self: Rc<Self>, val: &str) -> Option<Variable> {
         let func = match val {
             "next" => Self::next_fn,
-            _ => unimplemented!("{}.{}", self.get_type().str(), val),
+            _ => return Option::None,
         };
-        StdMethod::new_native(self, func).into()
+        Option::Some(StdMethod::new_native(self, func).into())
     }
 
     fn get_op(self: Rc<Self>, val: Operator) -> Variable {
@@ -191,7 +191,7 @@ where
         self.get_op(op)
     }
 
-    fn get_attribute(self: Rc<Self>, name: &str) -> Variable {
+    fn get_attribute(self: Rc<Self>, name: &str) -> Option<Variable> {
         self.get_name(name)
     }
 
@@ -4,11 +4,15 @@ extern crate num_derive;
 #[macro_use]
 extern crate downcast_rs;
 
-use crate::executor::execute;
+use crate::custom_types::exceptions::value_error;
+use crate::executor::run;
 use crate::file_info::FileInfo;
 use crate::file_parsing::parse_file;
 use crate::runtime::Runtime;
+use crate::string_var::StringVar;
+use crate::variable::Variable;
 use std::convert::TryInto;
+use std::ops::{Bound, RangeBounds};
 
 #[macro_use]
 mod macros;
@@ -19,6 +23,8 @@ mod builtins;
 mod bytecode;
 mod character;
 mod constant_loaders;
+mod copy;
+#[macro_use]
 mod custom_types;
 mod custom_var;
 mod executor;
@@ -33,6 +39,7 @@ mod int_var;
 mod jump_table;
 mod lang_union;
 mod looping;
+mod math;
 mod method;
 mod name;
 mod name_map;
@@ -40,14 +47,17 @@ mod operator;
 mod primitive;
 mod property;
 mod quick_functions;
+mod random;
 mod rational_var;
 mod runtime;
+mod sort;
 mod stack_frame;
 mod std_type;
 mod std_variable;
 mod string_var;
 mod sys;
 mod test_fn;
+mod time;
 mod tuple;
 mod var_impls;
 mod variable;
@@ -55,11 +65,18 @@ mod variable;
 fn main() {
     let args: Vec<String> = std::env::args().collect();
     let mut files: Vec<FileInfo> = Vec::new();
-    let index = parse_file(args[1].clone(), &mut files);
-    let mut runtime = Runtime::new(files, index);
-    let result = execute(&mut runtime);
-    if result.is_err() {
-        panic!("Too many errors!")
+    let index = match parse_file(args[1].clone(), &mut files) {
+        Result::Ok(index) => index,
+        Result::Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1)
+        }
+    };
+    let program_args = args[2..].iter().cloned().map(StringVar::from).collect();
+    let mut runtime = Runtime::with_args(files, index, program_args);
+    if let Result::Err(e) = run(&mut runtime) {
+        eprintln!("{}", e);
+        std::process::exit(1)
     }
 }
 
@@ -75,3 +92,80 @@ fn first_n<T, const N: usize>(args: Vec<T>) -> [T; N] {
     args.try_into()
         .unwrap_or_else(|x: Vec<T>| panic!("Value had length {}, expected length {}", x.len(), N))
 }
+
+/// Checks that a native function was called with exactly `count` arguments,
+/// throwing a `value_error` if it was not.
+///
+/// Unlike operators, named methods can be called from user code with any
+/// number of arguments, so they should use this instead of a `debug_assert`.
+///
+/// `name` is spelled the way user code sees it: a builtin is named alone
+/// (`repr`), and a method is prefixed with the name of its type as given by
+/// `str(type)` (`list.pop`, `Array.elementsEqual`, `math.gcd`). Option
+/// methods, whose type names vary, use `Option`.
+fn check_args(
+    name: &str,
+    args: &[Variable],
+    count: usize,
+    runtime: &mut Runtime,
+) -> Result<(), ()> {
+    if args.len() == count {
+        Result::Ok(())
+    } else {
+        runtime.throw_quick_native(
+            value_error(),
+            format!(
+                "{}() takes {} argument{} but {} were given",
+                name,
+                count,
+                if count == 1 { "" } else { "s" },
+                args.len()
+            ),
+        )
+    }
+}
+
+/// Checks that a native function was called with a number of arguments
+/// within `range`, throwing a `value_error` if it was not.
+///
+/// This is the counterpart of [`check_args`] for functions with optional or
+/// variadic arguments.
+fn check_arg_range(
+    name: &str,
+    args: &[Variable],
+    range: impl RangeBounds<usize>,
+    runtime: &mut Runtime,
+) -> Result<(), ()> {
+    if range.contains(&args.len()) {
+        return Result::Ok(());
+    }
+    let min = match range.start_bound() {
+        Bound::Included(&x) => x,
+        Bound::Excluded(&x) => x + 1,
+        Bound::Unbounded => 0,
+    };
+    let max = match range.end_bound() {
+        Bound::Included(&x) => Option::Some(x),
+        Bound::Excluded(&x) => Option::Some(x - 1),
+        Bound::Unbounded => Option::None,
+    };
+    let expected = match max {
+        Option::Some(max) if max == min => format!("{}", min),
+        Option::Some(max) => format!("{} to {}", min, max),
+        Option::None => format!("at least {}", min),
+    };
+    runtime.throw_quick_native(
+        value_error(),
+        format!(
+            "{}() takes {} argument{} but {} were given",
+            name,
+            expected,
+            if min == 1 && max.is_none_or(|max| max == 1) {
+                ""
+            } else {
+                "s"
+            },
+            args.len()
+        ),
+    )
+}
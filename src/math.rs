@@ -0,0 +1,378 @@
+//! The `math` module.
+//!
+//! Since the only non-integral number type is the exact `dec`, these functions
+//! are computed using `f64` and return the exact rational value of the
+//! resulting float. This means results are only accurate to about 15
+//! significant digits, and that the constants `pi` and `e` are the nearest
+//! `f64` to their true values, not the values themselves.
+
+use crate::custom_types::exceptions::value_error;
+use crate::custom_types::module::Module;
+use crate::function::{Function, NativeFunction};
+use crate::int_var::IntVar;
+use crate::rational_var::RationalVar;
+use crate::runtime::Runtime;
+use crate::variable::{FnResult, InnerVar, Variable};
+use crate::{check_args, first, first_n};
+use num::{BigInt, BigRational, Integer, One, Signed, ToPrimitive, Zero};
+use std::f64::consts::{E, PI};
+use std::rc::Rc;
+
+pub fn get_value(x: &str) -> Option<Variable> {
+    match x {
+        "pi" => Option::Some(RationalVar::from(BigRational::from_float(PI).unwrap()).into()),
+        "e" => Option::Some(RationalVar::from(BigRational::from_float(E).unwrap()).into()),
+        _ => get_function(x).map(|f| Function::Native(f).into()),
+    }
+}
+
+#[inline]
+pub fn get_function(x: &str) -> Option<NativeFunction> {
+    Option::Some(match x {
+        "sqrt" => sqrt,
+        "exp" => exp,
+        "ln" => ln,
+        "log" => log,
+        "sin" => sin,
+        "cos" => cos,
+        "tan" => tan,
+        "gcd" => gcd,
+        "lcm" => lcm,
+        "factorial" => factorial,
+        "comb" => comb,
+        "perm" => perm,
+        _ => return Option::None,
+    })
+}
+
+/// The object the `math` builtin refers to.
+pub fn module() -> Rc<Module> {
+    Module::new("math", get_value)
+}
+
+fn to_float(name: &str, value: Variable, runtime: &mut Runtime) -> Result<Option<f64>, ()> {
+    Result::Ok(match value {
+        Variable::Normal(InnerVar::Bigint(i)) => i.to_f64(),
+        Variable::Normal(InnerVar::Decimal(d)) => d.to_f64(),
+        Variable::Normal(InnerVar::Bool(b)) => Option::Some(if b { 1.0 } else { 0.0 }),
+        x => {
+            let type_name = x.get_type().str();
+            return runtime.throw_quick_native(
+                value_error(),
+                format!("math.{} requires a number, not {}", name, type_name),
+            );
+        }
+    })
+}
+
+fn float_result(name: &str, value: Option<f64>, runtime: &mut Runtime) -> FnResult {
+    match value.and_then(BigRational::from_float) {
+        Option::Some(x) => runtime.return_1(RationalVar::from(x).into()),
+        Option::None => runtime.throw_quick(
+            value_error(),
+            format!("math.{}: result is not a finite number", name),
+        ),
+    }
+}
+
+fn unary(name: &str, args: Vec<Variable>, func: fn(f64) -> f64, runtime: &mut Runtime) -> FnResult {
+    check_args(&format!("math.{}", name), &args, 1, runtime)?;
+    let value = to_float(name, first(args), runtime)?.map(func);
+    float_result(name, value, runtime)
+}
+
+fn sqrt(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    unary("sqrt", args, f64::sqrt, runtime)
+}
+
+fn exp(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    unary("exp", args, f64::exp, runtime)
+}
+
+fn ln(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    unary("ln", args, f64::ln, runtime)
+}
+
+fn log(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("math.log", &args, 2, runtime)?;
+    let [value, base] = first_n(args);
+    let value = to_float("log", value, runtime)?
+        .zip(to_float("log", base, runtime)?)
+        .map(|(x, base)| x.log(base));
+    float_result("log", value, runtime)
+}
+
+fn sin(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    unary("sin", args, f64::sin, runtime)
+}
+
+fn cos(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    unary("cos", args, f64::cos, runtime)
+}
+
+fn tan(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    unary("tan", args, f64::tan, runtime)
+}
+
+fn gcd(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("math.gcd", &args, 2, runtime)?;
+    let [a, b] = first_n(args);
+    runtime.return_1(IntVar::from(a).gcd(&IntVar::from(b)).into())
+}
+
+fn lcm(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("math.lcm", &args, 2, runtime)?;
+    let [a, b] = first_n(args);
+    runtime.return_1(IntVar::from(a).lcm(&IntVar::from(b)).into())
+}
+
+fn factorial(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("math.factorial", &args, 1, runtime)?;
+    let n = count_arg("factorial", first(args), runtime)?;
+    runtime.return_1(product(BigInt::one(), n).into())
+}
+
+fn comb(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("math.comb", &args, 2, runtime)?;
+    let [n, k] = first_n(args);
+    let (n, k) = (
+        non_negative("comb", n, runtime)?,
+        count_arg("comb", k, runtime)?,
+    );
+    let result = match n.to_usize() {
+        Option::Some(n) if k > n => BigInt::zero(),
+        // comb(n, k) == comb(n, n - k), and the smaller product is cheaper
+        Option::Some(n) => {
+            let k = k.min(n - k);
+            product(BigInt::from(n - k + 1), k) / product(BigInt::one(), k)
+        }
+        Option::None => product(BigInt::from(n) - k + 1, k) / product(BigInt::one(), k),
+    };
+    runtime.return_1(result.into())
+}
+
+fn perm(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("math.perm", &args, 2, runtime)?;
+    let [n, k] = first_n(args);
+    let (n, k) = (
+        non_negative("perm", n, runtime)?,
+        count_arg("perm", k, runtime)?,
+    );
+    let n = BigInt::from(n);
+    let result = if BigInt::from(k) > n {
+        BigInt::zero()
+    } else {
+        product(n - k + 1, k)
+    };
+    runtime.return_1(result.into())
+}
+
+fn non_negative(name: &str, value: Variable, runtime: &mut Runtime) -> Result<IntVar, ()> {
+    let value = IntVar::from(value);
+    if value.is_negative() {
+        runtime.throw_quick_native(
+            value_error(),
+            format!(
+                "math.{} requires a non-negative argument, not {}",
+                name, value
+            ),
+        )
+    } else {
+        Result::Ok(value)
+    }
+}
+
+fn count_arg(name: &str, value: Variable, runtime: &mut Runtime) -> Result<usize, ()> {
+    let value = non_negative(name, value, runtime)?;
+    match value.to_usize() {
+        Option::Some(x) => Result::Ok(x),
+        Option::None => runtime.throw_quick_native(
+            value_error(),
+            format!("math.{} argument too large: {}", name, value),
+        ),
+    }
+}
+
+/// The product of the `count` consecutive integers starting at `start`.
+///
+/// This splits the range in half recursively, so that the multiplications are
+/// done between numbers of similar size, rather than multiplying a
+/// continually-growing product by small numbers.
+fn product(start: BigInt, count: usize) -> BigInt {
+    match count {
+        0 => BigInt::one(),
+        1 => start,
+        2 => &start * (&start + 1),
+        _ => {
+            let half = count / 2;
+            let middle = &start + half;
+            product(start, half) * product(middle, count - half)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::custom_types::exceptions::value_error;
+    use crate::int_var::IntVar;
+    use crate::math::{
+        comb, factorial, gcd, get_value, lcm, log, module, perm, product, sin, sqrt,
+    };
+    use crate::rational_var::RationalVar;
+    use crate::runtime::Runtime;
+    use crate::string_var::StringVar;
+    use crate::variable::{FnResult, InnerVar, Variable};
+    use num::{BigInt, BigRational, One, Signed, ToPrimitive, Zero};
+    use std::str::FromStr;
+
+    fn decimal(value: Result<Variable, ()>) -> BigRational {
+        match value {
+            Result::Ok(Variable::Normal(InnerVar::Decimal(d))) => d.into_inner(),
+            x => panic!("Expected a decimal, got {:?}", x),
+        }
+    }
+
+    #[test]
+    fn sqrt_two() {
+        let result = Runtime::test(|runtime| sqrt(vec![IntVar::from(2).into()], runtime));
+        let result = decimal(result);
+        assert_eq!(result.to_f64(), Option::Some(2f64.sqrt()));
+        let error = &result * &result - BigRational::from_integer(2.into());
+        assert!(error.abs() < BigRational::new(1.into(), 1_000_000_000_000_000i64.into()));
+    }
+
+    #[test]
+    fn sqrt_exact() {
+        let quarter = RationalVar::from(BigRational::new(1.into(), 4.into()));
+        let result = Runtime::test(|runtime| sqrt(vec![quarter.into()], runtime));
+        assert_eq!(decimal(result), BigRational::new(1.into(), 2.into()));
+    }
+
+    #[test]
+    fn sin_zero() {
+        let result = Runtime::test(|runtime| sin(vec![IntVar::from(0).into()], runtime));
+        assert!(decimal(result).is_zero());
+    }
+
+    #[test]
+    fn log_base() {
+        let args = vec![IntVar::from(1000).into(), IntVar::from(10).into()];
+        let result = Runtime::test(|runtime| log(args, runtime));
+        let result = decimal(result).to_f64().unwrap();
+        assert!((result - 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn domain_error() {
+        let result = Runtime::test(|runtime| {
+            assert!(sqrt(vec![IntVar::from(-1).into()], runtime).is_err());
+            let exc = runtime.pop_err()?;
+            runtime.return_1(exc.get_type().into())
+        });
+        assert_eq!(result, Result::Ok(value_error().into()));
+    }
+
+    #[test]
+    fn module_attrs() {
+        let result = Runtime::test(|runtime| {
+            let args = vec![IntVar::from(9).into()];
+            runtime.call_attr(module().into(), "sqrt", args)?;
+            let result = runtime.pop_return();
+            runtime.return_1(result)
+        });
+        assert_eq!(decimal(result), BigRational::from_integer(3.into()));
+        let pi = decimal(Result::Ok(get_value("pi").unwrap()));
+        assert_eq!(pi.to_f64(), Option::Some(std::f64::consts::PI));
+    }
+
+    #[test]
+    fn non_numeric() {
+        let result = Runtime::test(|runtime| {
+            let args = vec![StringVar::from("a").into()];
+            assert!(runtime.call_attr(module().into(), "sqrt", args).is_err());
+            let exc = runtime.pop_err()?;
+            runtime.return_1(exc.get_type().into())
+        });
+        assert_eq!(result, Result::Ok(value_error().into()));
+    }
+
+    fn call_ints(
+        func: fn(Vec<Variable>, &mut Runtime) -> FnResult,
+        args: &[IntVar],
+    ) -> Result<Variable, ()> {
+        let args = args.iter().cloned().map(Variable::from).collect();
+        Runtime::test(|runtime| func(args, runtime))
+    }
+
+    fn int(value: &str) -> Result<Variable, ()> {
+        Result::Ok(IntVar::from_str(value).unwrap().into())
+    }
+
+    #[test]
+    fn gcd_lcm() {
+        let ints = |a: isize, b: isize| [IntVar::from(a), IntVar::from(b)];
+        assert_eq!(call_ints(gcd, &ints(12, 18)), int("6"));
+        assert_eq!(call_ints(gcd, &ints(-4, 6)), int("2"));
+        assert_eq!(call_ints(gcd, &ints(0, 0)), int("0"));
+        assert_eq!(
+            call_ints(gcd, &ints(isize::MIN, 0)),
+            int("9223372036854775808")
+        );
+        assert_eq!(call_ints(lcm, &ints(4, 6)), int("12"));
+        assert_eq!(call_ints(lcm, &ints(-4, 6)), int("12"));
+        assert_eq!(call_ints(lcm, &ints(0, 5)), int("0"));
+        let large = ints(1 << 40, 3_486_784_401);
+        assert_eq!(call_ints(lcm, &large), int("3833759992447475122176"));
+    }
+
+    #[test]
+    fn factorials() {
+        assert_eq!(call_ints(factorial, &[IntVar::from(0)]), int("1"));
+        assert_eq!(call_ints(factorial, &[IntVar::from(5)]), int("120"));
+        let expected = "93326215443944152681699238856266700490715968264381621468592963895217\
+                        599993229915608941463976156518286253697920827223758251185210916864000000\
+                        000000000000000000";
+        assert_eq!(call_ints(factorial, &[IntVar::from(100)]), int(expected));
+    }
+
+    #[test]
+    fn factorial_negative() {
+        let result = Runtime::test(|runtime| {
+            assert!(factorial(vec![IntVar::from(-1).into()], runtime).is_err());
+            let exc = runtime.pop_err()?;
+            runtime.return_1(exc.get_type().into())
+        });
+        assert_eq!(result, Result::Ok(value_error().into()));
+    }
+
+    #[test]
+    fn comb_perm() {
+        let ints = |a: isize, b: isize| [IntVar::from(a), IntVar::from(b)];
+        assert_eq!(call_ints(comb, &ints(5, 2)), int("10"));
+        assert_eq!(call_ints(comb, &ints(52, 5)), int("2598960"));
+        assert_eq!(call_ints(comb, &ints(5, 7)), int("0"));
+        assert_eq!(
+            call_ints(comb, &ints(100, 50)),
+            int("100891344545564193334812497256")
+        );
+        assert_eq!(call_ints(perm, &ints(5, 2)), int("20"));
+        assert_eq!(call_ints(perm, &ints(5, 0)), int("1"));
+        assert_eq!(call_ints(perm, &ints(5, 6)), int("0"));
+        let big = [
+            IntVar::from_str("100000000000000000000").unwrap(),
+            IntVar::from(2),
+        ];
+        assert_eq!(
+            call_ints(comb, &big),
+            int("4999999999999999999950000000000000000000")
+        );
+    }
+
+    #[test]
+    fn product_matches_naive() {
+        for count in 0..20 {
+            let naive = (0..count).fold(BigInt::one(), |acc, i| acc * (i + 3));
+            assert_eq!(product(BigInt::from(3), count), naive);
+        }
+    }
+}
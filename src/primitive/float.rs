@@ -45,10 +45,10 @@ macro_rules! float_impl {
                 }
             }
 
-            fn attr_fn(s: &str) -> NativeMethod<Rc<Self>> {
+            fn attr_fn(s: &str) -> Option<NativeMethod<Rc<Self>>> {
                 match s {
-                    "mulAdd" => Self::mul_add,
-                    _ => unimplemented!("{}.{}", stringify!($name), s),
+                    "mulAdd" => Option::Some(Self::mul_add),
+                    _ => Option::None,
                 }
             }
 
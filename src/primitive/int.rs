@@ -52,8 +52,8 @@ macro_rules! int_impl {
                 }
             }
 
-            fn attr_fn(s: &str) -> NativeMethod<Rc<Self>> {
-                unimplemented!("{}.{}", stringify!($name), s)
+            fn attr_fn(_s: &str) -> Option<NativeMethod<Rc<Self>>> {
+                Option::None
             }
 
             fn to_int(self: Rc<Self>, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
@@ -112,8 +112,9 @@ macro_rules! primitive_custom {
                 StdMethod::new_native(self, Self::op_fn(op)).into()
             }
 
-            fn get_attribute(self: Rc<Self>, name: &str) -> Variable {
-                StdMethod::new_native(self, Self::attr_fn(name)).into()
+            fn get_attribute(self: Rc<Self>, name: &str) -> Option<Variable> {
+                let func = Self::attr_fn(name)?;
+                Option::Some(StdMethod::new_native(self, func).into())
             }
         }
     };
@@ -1,4 +1,4 @@
-use crate::custom_types::exceptions::{arithmetic_error, index_error};
+use crate::custom_types::exceptions::{arithmetic_error, index_error, value_error};
 use crate::from_bool::FromBool;
 use crate::int_var::IntVar;
 use crate::operator::Operator;
@@ -7,28 +7,21 @@ use crate::runtime::Runtime;
 use crate::string_var::StringVar;
 use crate::variable::{InnerVar, Variable};
 use num::traits::Pow;
-use num::{BigRational, One, ToPrimitive, Zero};
+use num::{BigRational, Integer, One, ToPrimitive, Zero};
+use std::cmp::Ordering;
 
 pub type QuickResult = Result<Variable, ()>;
 
 pub fn quick_add(this: Variable, other: Variable, runtime: &mut Runtime) -> QuickResult {
     match this {
         Variable::Normal(InnerVar::Null()) => unimplemented!(),
-        Variable::Normal(InnerVar::Bool(b)) => {
-            Result::Ok((IntVar::from(other) + u8::from_bool(b)).into())
+        x @ Variable::Normal(InnerVar::Bool(_) | InnerVar::Bigint(_) | InnerVar::Decimal(_)) => {
+            numeric_add(x, other, runtime)
         }
-        Variable::Normal(InnerVar::Bigint(i)) => Result::Ok((i + IntVar::from(other)).into()),
         Variable::Normal(InnerVar::String(s)) => {
             let result = s.as_owned() + &other.str(runtime)?;
             QuickResult::Ok(StringVar::from(result).into())
         }
-        Variable::Normal(InnerVar::Decimal(d1)) => {
-            if let Variable::Normal(InnerVar::Decimal(d2)) = other {
-                QuickResult::Ok((d1 + d2).into())
-            } else {
-                unimplemented!()
-            }
-        }
         Variable::Normal(InnerVar::Char(_)) => unimplemented!(),
         Variable::Normal(InnerVar::Type(_)) => unimplemented!(),
         Variable::Normal(InnerVar::Standard(v)) => {
@@ -51,6 +44,30 @@ pub fn quick_add(this: Variable, other: Variable, runtime: &mut Runtime) -> Quic
     }
 }
 
+/// Adds two numeric (bool, int, or dec) values, treating bools as ints and
+/// promoting both values to decimals if either one is.
+fn numeric_add(this: Variable, other: Variable, runtime: &mut Runtime) -> QuickResult {
+    let is_dec = |x: &Variable| matches!(x, Variable::Normal(InnerVar::Decimal(_)));
+    match other {
+        Variable::Normal(InnerVar::Bool(_) | InnerVar::Bigint(_) | InnerVar::Decimal(_)) => {
+            if is_dec(&this) || is_dec(&other) {
+                let sum = to_rational(this) + to_rational(other);
+                QuickResult::Ok(RationalVar::from(sum).into())
+            } else {
+                QuickResult::Ok((IntVar::from(this) + IntVar::from(other)).into())
+            }
+        }
+        x => runtime.throw_quick_native(
+            value_error(),
+            format!(
+                "Cannot add {} to {}",
+                x.get_type().str(),
+                this.get_type().str()
+            ),
+        ),
+    }
+}
+
 pub fn quick_sub(this: Variable, other: Variable, runtime: &mut Runtime) -> QuickResult {
     match this {
         Variable::Normal(InnerVar::Null()) => unimplemented!(),
@@ -340,6 +357,44 @@ fn mod_zero_error<T>(runtime: &mut Runtime) -> Result<T, ()> {
     runtime.throw_quick_native(arithmetic_error(), "Cannot modulo by zero")
 }
 
+/// Computes `this // other` and `this % other` together.
+///
+/// Numeric values are divided only once, with the same rounding as the
+/// separate operators; a decimal on either side promotes both values to
+/// decimals. Anything else falls back to calling both operators.
+pub fn div_mod(
+    this: Variable,
+    other: Variable,
+    runtime: &mut Runtime,
+) -> Result<(Variable, Variable), ()> {
+    let is_num = |x: &Variable| {
+        matches!(
+            x,
+            Variable::Normal(InnerVar::Bool(_) | InnerVar::Bigint(_) | InnerVar::Decimal(_))
+        )
+    };
+    let is_dec = |x: &Variable| matches!(x, Variable::Normal(InnerVar::Decimal(_)));
+    if !is_num(&this) || !is_num(&other) {
+        let div = quick_floor_div(this.clone(), other.clone(), runtime)?;
+        let rem = quick_mod(this, other, runtime)?;
+        Result::Ok((div, rem))
+    } else if is_dec(&this) || is_dec(&other) {
+        let (this, other) = (to_rational(this), to_rational(other));
+        if other.is_zero() {
+            return div_zero_error(runtime);
+        }
+        let div = IntVar::from((&this / &other).to_integer());
+        Result::Ok((div.into(), RationalVar::from(this % other).into()))
+    } else {
+        let other = IntVar::from(other);
+        if other.is_zero() {
+            return div_zero_error(runtime);
+        }
+        let (div, rem) = IntVar::from(this).div_rem(&other);
+        Result::Ok((div.into(), rem.into()))
+    }
+}
+
 pub fn quick_subscript(this: Variable, other: Variable, runtime: &mut Runtime) -> QuickResult {
     match this {
         Variable::Normal(InnerVar::Null()) => unimplemented!(),
@@ -665,22 +720,37 @@ pub fn quick_equals(this: Variable, other: Variable, runtime: &mut Runtime) -> Q
     }
 }
 
+fn to_rational(value: Variable) -> BigRational {
+    match value {
+        Variable::Normal(InnerVar::Decimal(d)) => d.into_inner(),
+        x => BigRational::from_integer(IntVar::from(x).into()),
+    }
+}
+
+/// Compares two numeric (bool, int, or dec) values exactly, treating bools as
+/// ints and promoting both values to decimals if either one is.
+fn numeric_cmp(this: Variable, other: Variable) -> Ordering {
+    let is_dec = |x: &Variable| matches!(x, Variable::Normal(InnerVar::Decimal(_)));
+    if is_dec(&this) || is_dec(&other) {
+        to_rational(this).cmp(&to_rational(other))
+    } else {
+        IntVar::from(this).cmp(&IntVar::from(other))
+    }
+}
+
 pub fn quick_less_than(this: Variable, other: Variable, runtime: &mut Runtime) -> QuickResult {
     match this {
         Variable::Normal(InnerVar::Null()) => unimplemented!(),
-        Variable::Normal(InnerVar::Bool(b)) => {
-            Result::Ok((IntVar::from(if b { 1 } else { 0 }) < IntVar::from(other)).into())
-        }
-        Variable::Normal(InnerVar::Bigint(i)) => Result::Ok((i < IntVar::from(other)).into()),
-        Variable::Normal(InnerVar::String(_)) => unimplemented!(),
-        Variable::Normal(InnerVar::Decimal(d1)) => {
-            if let Variable::Normal(InnerVar::Decimal(d2)) = other {
-                QuickResult::Ok((d1 < d2).into())
-            } else {
-                unimplemented!()
-            }
-        }
-        Variable::Normal(InnerVar::Char(_)) => unimplemented!(),
+        x @ Variable::Normal(InnerVar::Bool(_) | InnerVar::Bigint(_) | InnerVar::Decimal(_)) => {
+            QuickResult::Ok(numeric_cmp(x, other).is_lt().into())
+        }
+        Variable::Normal(InnerVar::String(s)) => QuickResult::Ok(
+            s.as_str()
+                .cmp(StringVar::from(other).as_str())
+                .is_lt()
+                .into(),
+        ),
+        Variable::Normal(InnerVar::Char(c)) => QuickResult::Ok(c.cmp(&other.into()).is_lt().into()),
         Variable::Normal(InnerVar::Type(_)) => unimplemented!(),
         Variable::Normal(InnerVar::Standard(v)) => {
             v.call_operator(Operator::LessThan, vec![other], runtime)?;
@@ -705,19 +775,16 @@ pub fn quick_less_than(this: Variable, other: Variable, runtime: &mut Runtime) -
 pub fn quick_greater_than(this: Variable, other: Variable, runtime: &mut Runtime) -> QuickResult {
     match this {
         Variable::Normal(InnerVar::Null()) => unimplemented!(),
-        Variable::Normal(InnerVar::Bool(b)) => {
-            Result::Ok((IntVar::from(if b { 1 } else { 0 }) > IntVar::from(other)).into())
-        }
-        Variable::Normal(InnerVar::Bigint(i)) => Result::Ok((i > IntVar::from(other)).into()),
-        Variable::Normal(InnerVar::String(_)) => unimplemented!(),
-        Variable::Normal(InnerVar::Decimal(d1)) => {
-            if let Variable::Normal(InnerVar::Decimal(d2)) = other {
-                QuickResult::Ok((d1 > d2).into())
-            } else {
-                unimplemented!()
-            }
-        }
-        Variable::Normal(InnerVar::Char(_)) => unimplemented!(),
+        x @ Variable::Normal(InnerVar::Bool(_) | InnerVar::Bigint(_) | InnerVar::Decimal(_)) => {
+            QuickResult::Ok(numeric_cmp(x, other).is_gt().into())
+        }
+        Variable::Normal(InnerVar::String(s)) => QuickResult::Ok(
+            s.as_str()
+                .cmp(StringVar::from(other).as_str())
+                .is_gt()
+                .into(),
+        ),
+        Variable::Normal(InnerVar::Char(c)) => QuickResult::Ok(c.cmp(&other.into()).is_gt().into()),
         Variable::Normal(InnerVar::Type(_)) => unimplemented!(),
         Variable::Normal(InnerVar::Standard(v)) => {
             v.call_operator(Operator::GreaterThan, vec![other], runtime)?;
@@ -742,19 +809,16 @@ pub fn quick_greater_than(this: Variable, other: Variable, runtime: &mut Runtime
 pub fn quick_less_equal(this: Variable, other: Variable, runtime: &mut Runtime) -> QuickResult {
     match this {
         Variable::Normal(InnerVar::Null()) => unimplemented!(),
-        Variable::Normal(InnerVar::Bool(b)) => {
-            Result::Ok((IntVar::from(if b { 1 } else { 0 }) <= IntVar::from(other)).into())
-        }
-        Variable::Normal(InnerVar::Bigint(i)) => Result::Ok((i <= IntVar::from(other)).into()),
-        Variable::Normal(InnerVar::String(_)) => unimplemented!(),
-        Variable::Normal(InnerVar::Decimal(d1)) => {
-            if let Variable::Normal(InnerVar::Decimal(d2)) = other {
-                QuickResult::Ok((d1 <= d2).into())
-            } else {
-                unimplemented!()
-            }
-        }
-        Variable::Normal(InnerVar::Char(_)) => unimplemented!(),
+        x @ Variable::Normal(InnerVar::Bool(_) | InnerVar::Bigint(_) | InnerVar::Decimal(_)) => {
+            QuickResult::Ok(numeric_cmp(x, other).is_le().into())
+        }
+        Variable::Normal(InnerVar::String(s)) => QuickResult::Ok(
+            s.as_str()
+                .cmp(StringVar::from(other).as_str())
+                .is_le()
+                .into(),
+        ),
+        Variable::Normal(InnerVar::Char(c)) => QuickResult::Ok(c.cmp(&other.into()).is_le().into()),
         Variable::Normal(InnerVar::Type(_)) => unimplemented!(),
         Variable::Normal(InnerVar::Standard(v)) => {
             v.call_operator(Operator::LessEqual, vec![other], runtime)?;
@@ -779,19 +843,16 @@ pub fn quick_less_equal(this: Variable, other: Variable, runtime: &mut Runtime)
 pub fn quick_greater_equal(this: Variable, other: Variable, runtime: &mut Runtime) -> QuickResult {
     match this {
         Variable::Normal(InnerVar::Null()) => unimplemented!(),
-        Variable::Normal(InnerVar::Bool(b)) => {
-            Result::Ok((IntVar::from(if b { 1 } else { 0 }) >= IntVar::from(other)).into())
-        }
-        Variable::Normal(InnerVar::Bigint(i)) => Result::Ok((i >= IntVar::from(other)).into()),
-        Variable::Normal(InnerVar::String(_)) => unimplemented!(),
-        Variable::Normal(InnerVar::Decimal(d1)) => {
-            if let Variable::Normal(InnerVar::Decimal(d2)) = other {
-                QuickResult::Ok((d1 >= d2).into())
-            } else {
-                unimplemented!()
-            }
-        }
-        Variable::Normal(InnerVar::Char(_)) => unimplemented!(),
+        x @ Variable::Normal(InnerVar::Bool(_) | InnerVar::Bigint(_) | InnerVar::Decimal(_)) => {
+            QuickResult::Ok(numeric_cmp(x, other).is_ge().into())
+        }
+        Variable::Normal(InnerVar::String(s)) => QuickResult::Ok(
+            s.as_str()
+                .cmp(StringVar::from(other).as_str())
+                .is_ge()
+                .into(),
+        ),
+        Variable::Normal(InnerVar::Char(c)) => QuickResult::Ok(c.cmp(&other.into()).is_ge().into()),
         Variable::Normal(InnerVar::Type(_)) => unimplemented!(),
         Variable::Normal(InnerVar::Standard(v)) => {
             v.call_operator(Operator::GreaterEqual, vec![other], runtime)?;
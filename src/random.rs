@@ -0,0 +1,289 @@
+//! The `random` module.
+//!
+//! The generator is [SplitMix64], which is small, fast, and accepts any seed;
+//! it is *not* suitable for anything security-related. Each runtime has its
+//! own generator, seeded from the system clock until `random.seed` is called.
+//!
+//! [SplitMix64]: https://prng.di.unimi.it/splitmix64.c
+
+use crate::custom_types::exceptions::{index_error, value_error};
+use crate::custom_types::list::List;
+use crate::custom_types::module::Module;
+use crate::custom_var::downcast_var;
+use crate::function::{Function, NativeFunction};
+use crate::int_var::IntVar;
+use crate::looping;
+use crate::rational_var::RationalVar;
+use crate::runtime::Runtime;
+use crate::variable::{FnResult, InnerVar, Variable};
+use crate::{check_args, first, first_n};
+use num::{BigInt, BigRational, BigUint, Integer, One, Signed, ToPrimitive};
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub fn from_time() -> Rng {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH);
+        Rng::new(now.map_or(0, |x| x.as_nanos() as u64))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A uniformly-distributed value in `[0, bound)`.
+    pub fn below(&mut self, bound: usize) -> usize {
+        debug_assert!(bound > 0);
+        let bound = bound as u64;
+        // Reject the values that would make the lowest residues more likely
+        let zone = u64::MAX - (u64::MAX - bound + 1) % bound;
+        loop {
+            let value = self.next_u64();
+            if value <= zone {
+                return (value % bound) as usize;
+            }
+        }
+    }
+
+    /// A uniformly-distributed value in `[0, bound)`, for arbitrarily-large
+    /// bounds.
+    pub fn below_big(&mut self, bound: &BigInt) -> BigInt {
+        debug_assert!(bound.is_positive());
+        let bits = bound.bits();
+        let words = bits.div_ceil(64);
+        let excess = words * 64 - bits;
+        loop {
+            let mut digits: Vec<u64> = (0..words).map(|_| self.next_u64()).collect();
+            if let Option::Some(last) = digits.last_mut() {
+                *last >>= excess;
+            }
+            let value = BigInt::from(BigUint::new(to_u32_digits(&digits)));
+            if &value < bound {
+                return value;
+            }
+        }
+    }
+
+    /// A uniformly-distributed value in `[0, 1)` with 53 bits of precision,
+    /// the same as an `f64`.
+    pub fn next_fraction(&mut self) -> BigRational {
+        let numerator = BigInt::from(self.next_u64() >> 11);
+        BigRational::new(numerator, BigInt::one() << 53)
+    }
+
+    /// Shuffles `values` in place using the Fisher-Yates algorithm.
+    pub fn shuffle<T>(&mut self, values: &mut [T]) {
+        for i in (1..values.len()).rev() {
+            values.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+fn to_u32_digits(digits: &[u64]) -> Vec<u32> {
+    digits
+        .iter()
+        .flat_map(|&x| vec![x as u32, (x >> 32) as u32])
+        .collect()
+}
+
+pub fn get_value(x: &str) -> Option<Variable> {
+    get_function(x).map(|f| Function::Native(f).into())
+}
+
+#[inline]
+pub fn get_function(x: &str) -> Option<NativeFunction> {
+    Option::Some(match x {
+        "seed" => seed,
+        "randint" => randint,
+        "random" => random,
+        "choice" => choice,
+        "shuffle" => shuffle,
+        _ => return Option::None,
+    })
+}
+
+/// The object the `random` builtin refers to.
+pub fn module() -> Rc<Module> {
+    Module::new("random", get_value)
+}
+
+fn seed(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("random.seed", &args, 1, runtime)?;
+    // Only the lowest 64 bits matter, so any int is a valid seed
+    let value = BigInt::from(IntVar::from(first(args))).mod_floor(&(BigInt::one() << 64));
+    *runtime.rng() = Rng::new(value.to_u64().unwrap());
+    runtime.return_0()
+}
+
+fn randint(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("random.randint", &args, 2, runtime)?;
+    let [lo, hi] = first_n(args);
+    let (lo, hi) = (IntVar::from(lo), IntVar::from(hi));
+    if lo > hi {
+        return runtime.throw_quick(
+            value_error(),
+            format!("random.randint: empty range [{}, {}]", lo, hi),
+        );
+    }
+    let span = BigInt::from(hi) - BigInt::from(lo.clone()) + 1;
+    let offset = runtime.rng().below_big(&span);
+    runtime.return_1((lo + IntVar::from(offset)).into())
+}
+
+fn random(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("random.random", &args, 0, runtime)?;
+    let value = runtime.rng().next_fraction();
+    runtime.return_1(RationalVar::from(value).into())
+}
+
+fn choice(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("random.choice", &args, 1, runtime)?;
+    let values: Vec<Variable> = match first(args) {
+        Variable::Normal(InnerVar::Tuple(t)) => t.iter().cloned().collect(),
+        x => looping::collect(x, runtime)?,
+    };
+    if values.is_empty() {
+        return runtime.throw_quick(index_error(), "random.choice: sequence is empty");
+    }
+    let index = runtime.rng().below(values.len());
+    runtime.return_1(values[index].clone())
+}
+
+fn shuffle(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("random.shuffle", &args, 1, runtime)?;
+    let list = match downcast_var::<List>(first(args)) {
+        Result::Ok(list) => list,
+        Result::Err(_) => {
+            return runtime.throw_quick(value_error(), "random.shuffle requires a list")
+        }
+    };
+    list.shuffle(runtime.rng());
+    runtime.return_0()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::custom_types::exceptions::value_error;
+    use crate::custom_types::list::List;
+    use crate::custom_var::downcast_var;
+    use crate::int_var::IntVar;
+    use crate::random::{randint, random, seed, shuffle, Rng};
+    use crate::runtime::Runtime;
+    use crate::std_type::Type;
+    use crate::variable::{InnerVar, Variable};
+    use num::{BigInt, One};
+    use std::str::FromStr;
+
+    fn seeded_values(seed_value: isize) -> Vec<Variable> {
+        let result = Runtime::test(|runtime| {
+            seed(vec![IntVar::from(seed_value).into()], runtime)?;
+            let mut values = Vec::new();
+            for _ in 0..10 {
+                randint(
+                    vec![IntVar::from(0).into(), IntVar::from(1000).into()],
+                    runtime,
+                )?;
+                values.push(runtime.pop_return());
+                random(vec![], runtime)?;
+                values.push(runtime.pop_return());
+            }
+            runtime.return_1(List::from_values(Type::Object, values).into())
+        });
+        let list = downcast_var::<List>(result.unwrap()).unwrap();
+        let values = list.values().to_vec();
+        values
+    }
+
+    #[test]
+    fn seed_reproducible() {
+        let first = seeded_values(42);
+        assert_eq!(first, seeded_values(42));
+        assert_ne!(first, seeded_values(43));
+    }
+
+    #[test]
+    fn randint_bounds() {
+        for &(lo, hi) in &[(0, 0), (-5, 5), (1, 6), (isize::MIN, isize::MAX)] {
+            let result = Runtime::test(|runtime| {
+                let mut seen = Vec::new();
+                for _ in 0..200 {
+                    randint(
+                        vec![IntVar::from(lo).into(), IntVar::from(hi).into()],
+                        runtime,
+                    )?;
+                    seen.push(runtime.pop_return());
+                }
+                runtime.return_1(List::from_values(Type::Object, seen).into())
+            });
+            let list = downcast_var::<List>(result.unwrap()).unwrap();
+            for value in list.values().iter() {
+                let value = IntVar::from(value.clone());
+                assert!(IntVar::from(lo) <= value && value <= IntVar::from(hi));
+            }
+        }
+    }
+
+    #[test]
+    fn below_big_bounds() {
+        let mut rng = Rng::new(7);
+        let bound = BigInt::from_str("100000000000000000000000000001").unwrap();
+        for _ in 0..100 {
+            let value = rng.below_big(&bound);
+            assert!(value >= BigInt::from(0) && value < bound);
+        }
+        assert_eq!(rng.below_big(&BigInt::one()), BigInt::from(0));
+    }
+
+    #[test]
+    fn random_range() {
+        let result = Runtime::test(|runtime| random(vec![], runtime));
+        match result {
+            Result::Ok(Variable::Normal(InnerVar::Decimal(d))) => {
+                assert!(d.numer() >= &BigInt::from(0) && d.numer() < d.denom())
+            }
+            x => panic!("Expected a decimal, got {:?}", x),
+        }
+    }
+
+    #[test]
+    fn shuffle_permutes() {
+        let values: Vec<Variable> = (0..20).map(|x| IntVar::from(x).into()).collect();
+        let list = List::from_values(Type::Object, values.clone());
+        let result = Runtime::test(|runtime| {
+            shuffle(vec![list.clone().into()], runtime)?;
+            runtime.return_1(true.into())
+        });
+        assert_eq!(result, Result::Ok(true.into()));
+        let mut shuffled: Vec<_> = list
+            .values()
+            .iter()
+            .map(|x| IntVar::from(x.clone()))
+            .collect();
+        shuffled.sort();
+        let expected: Vec<_> = (0..20).map(IntVar::from).collect();
+        assert_eq!(shuffled, expected);
+    }
+
+    #[test]
+    fn shuffle_not_list() {
+        let result = Runtime::test(|runtime| {
+            assert!(shuffle(vec![IntVar::from(1).into()], runtime).is_err());
+            let exc = runtime.pop_err()?;
+            runtime.return_1(exc.get_type().into())
+        });
+        assert_eq!(result, Result::Ok(value_error().into()));
+    }
+}
@@ -5,6 +5,7 @@ use std::ops::{
     Add, AddAssign, Deref, Div, DivAssign, Mul, MulAssign, Neg, Rem, RemAssign, Sub, SubAssign,
 };
 use std::rc::Rc;
+use std::str::FromStr;
 
 #[derive(Clone, Debug, Hash, Ord, PartialOrd, PartialEq, Eq)]
 pub struct RationalVar {
@@ -102,7 +103,7 @@ impl One for RationalVar {
     fn one() -> Self {
         RationalVar::new(One::one())
     }
-    
+
     fn is_one(&self) -> bool {
         self.value.is_one()
     }
@@ -114,6 +115,35 @@ impl From<BigRational> for RationalVar {
     }
 }
 
+impl FromStr for RationalVar {
+    type Err = ();
+
+    /// Parses either a fraction (e.g. `3/4`) or a decimal literal (e.g.
+    /// `-0.25`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.contains('/') {
+            return BigRational::from_str(s).map(Into::into).map_err(|_| ());
+        }
+        let (negative, digits) = match s.as_bytes().first() {
+            Option::Some(b'-') => (true, &s[1..]),
+            Option::Some(b'+') => (false, &s[1..]),
+            _ => (false, s),
+        };
+        let (whole, frac) = match digits.find('.') {
+            Option::Some(dot) => (&digits[..dot], &digits[dot + 1..]),
+            Option::None => (digits, ""),
+        };
+        let is_digits = |x: &str| x.bytes().all(|b| b.is_ascii_digit());
+        if whole.is_empty() && frac.is_empty() || !is_digits(whole) || !is_digits(frac) {
+            return Result::Err(());
+        }
+        let numerator = BigInt::from_str(&format!("0{}{}", whole, frac)).map_err(|_| ())?;
+        let denominator = num::pow(BigInt::from(10), frac.len());
+        let value = BigRational::new(numerator, denominator);
+        Result::Ok(Self::new(if negative { -value } else { value }))
+    }
+}
+
 impl Sum for RationalVar {
     fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
         iter.fold(BigRational::zero(), |sum, num| sum + &*num)
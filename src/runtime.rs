@@ -1,20 +1,26 @@
+use crate::bytecode::{Bytecode, Instruction};
 use crate::custom_types::coroutine::Generator;
 use crate::custom_types::exceptions::invalid_state;
+use crate::custom_types::file::FileObj;
 use crate::custom_types::lambda::Lambda;
 use crate::executor;
 use crate::file_info::FileInfo;
+use crate::fmt::DEFAULT_FLOAT_DECIMALS;
 use crate::function::NativeFunction;
 use crate::jump_table::JumpTable;
 use crate::method::NativeMethod;
 use crate::name::Name;
 use crate::name_map::NameMap;
 use crate::operator::Operator;
+use crate::random::Rng;
 use crate::stack_frame::{frame_strings, SFInfo, StackFrame};
 use crate::std_type::Type;
+use crate::std_variable::StdVarMethod;
 use crate::string_var::StringVar;
 use crate::variable::{FnResult, Variable};
 use std::cmp::{max, min, Ordering};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{self, Debug, Display, Formatter};
 use std::mem::{replace, take};
 use std::rc::Rc;
 use std::vec::Vec;
@@ -24,17 +30,69 @@ pub struct Runtime {
     variables: Vec<Variable>,
     frames: Vec<StackFrame>,
     exception_frames: HashMap<Variable, Vec<(u32, usize)>>,
-    exception_stack: Vec<Variable>,
+    /// The type of each handler, along with the index in the stack of the
+    /// first except handler of its try.
+    exception_stack: Vec<(Variable, usize)>,
     completed_statics: HashSet<(usize, u16, u32)>,
     static_vars: Vec<Variable>,
     type_vars: HashMap<Type, NameMap<Variable>>,
     ret_count: usize,
     borrowed_iterators: Vec<Rc<Generator>>,
     thrown_exception: Option<InnerException>,
+    pending_finally: Vec<(usize, Option<InnerException>)>,
+    handled_exceptions: Vec<(usize, InnerException)>,
+    interned_strings: HashSet<StringVar>,
+    repr_stack: HashSet<usize>,
+    float_decimals: u32,
+    operator_cache: HashMap<(Type, Operator), StdVarMethod>,
+    rng: Rng,
+    args: Vec<StringVar>,
+    stdin: Rc<FileObj>,
+    stdout: Rc<FileObj>,
+    stderr: Rc<FileObj>,
+    trace_hook: Option<TraceHook>,
+    opcode_counts: Option<BTreeMap<Bytecode, usize>>,
 
     files: Vec<FileInfo>,
 }
 
+/// The instruction about to be executed, as given to a trace hook.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct TracePoint {
+    pub file_no: usize,
+    pub fn_no: u16,
+    pub pos: usize,
+    pub bytecode: Bytecode,
+}
+
+/// A callback run before every instruction; see [`Runtime::with_trace_hook`].
+pub struct TraceHook(Box<dyn FnMut(TracePoint)>);
+
+impl Debug for TraceHook {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("TraceHook")
+    }
+}
+
+/// An exception which was not caught anywhere in the program.
+#[derive(Debug)]
+pub struct UncaughtException {
+    exc_type: Type,
+    message: StringVar,
+}
+
+impl UncaughtException {
+    pub fn get_type(&self) -> Type {
+        self.exc_type
+    }
+}
+
+impl Display for UncaughtException {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
 #[derive(Debug)]
 enum InnerException {
     Std(Variable, Vec<SFInfo>),
@@ -50,6 +108,12 @@ struct DeconstructedExc {
 
 impl Runtime {
     pub fn new(files: Vec<FileInfo>, starting_no: usize) -> Runtime {
+        Self::with_args(files, starting_no, Vec::new())
+    }
+
+    /// Creates a runtime whose program was run with the given command-line
+    /// arguments, as exposed through `sys.argv`.
+    pub fn with_args(files: Vec<FileInfo>, starting_no: usize, args: Vec<StringVar>) -> Runtime {
         Runtime {
             variables: vec![],
             frames: vec![StackFrame::new(0, 0, starting_no, vec![], 0)],
@@ -61,10 +125,123 @@ impl Runtime {
             ret_count: 0,
             borrowed_iterators: Vec::new(),
             thrown_exception: Option::None,
+            pending_finally: Vec::new(),
+            handled_exceptions: Vec::new(),
+            interned_strings: HashSet::new(),
+            repr_stack: HashSet::new(),
+            float_decimals: DEFAULT_FLOAT_DECIMALS,
+            operator_cache: HashMap::new(),
+            rng: Rng::from_time(),
+            args,
+            stdin: FileObj::stdin(),
+            stdout: FileObj::stdout(),
+            stderr: FileObj::stderr(),
+            trace_hook: Option::None,
+            opcode_counts: Option::None,
             files,
         }
     }
 
+    pub(crate) fn interned_strings(&mut self) -> &mut HashSet<StringVar> {
+        &mut self.interned_strings
+    }
+
+    /// The identities of the values whose default `repr` is currently being
+    /// computed, used to break cycles.
+    pub(crate) fn repr_stack(&mut self) -> &mut HashSet<usize> {
+        &mut self.repr_stack
+    }
+
+    /// Installs a callback to be run before every instruction is executed,
+    /// e.g. for debuggers or profilers.
+    pub fn with_trace_hook(mut self, hook: impl FnMut(TracePoint) + 'static) -> Runtime {
+        self.trace_hook = Option::Some(TraceHook(Box::new(hook)));
+        self
+    }
+
+    /// Sets the number of decimal places used by float formats (such as
+    /// `{:f}` and `{:e}`) which do not give a precision of their own.
+    pub fn set_default_float_decimals(&mut self, decimals: u32) {
+        self.float_decimals = decimals;
+    }
+
+    pub fn default_float_decimals(&self) -> u32 {
+        self.float_decimals
+    }
+
+    /// Enables counting how many times each opcode is executed; the counts
+    /// can be read with [`Runtime::opcode_counts`] after the program runs.
+    pub fn with_opcode_counts(mut self) -> Runtime {
+        self.opcode_counts = Option::Some(BTreeMap::new());
+        self
+    }
+
+    /// The number of times each opcode was executed, or `None` if counting
+    /// was not enabled.
+    pub fn opcode_counts(&self) -> Option<&BTreeMap<Bytecode, usize>> {
+        self.opcode_counts.as_ref()
+    }
+
+    pub(crate) fn is_instrumented(&self) -> bool {
+        self.trace_hook.is_some() || self.opcode_counts.is_some()
+    }
+
+    pub(crate) fn instrument(&mut self, bytecode: Bytecode) {
+        if let Option::Some(counts) = &mut self.opcode_counts {
+            *counts.entry(bytecode).or_insert(0) += 1;
+        }
+        if self.trace_hook.is_some() {
+            let frame = self.last_frame();
+            let point = TracePoint {
+                file_no: frame.file_no(),
+                fn_no: frame.get_fn_number(),
+                pos: frame.current_pos() as usize,
+                bytecode,
+            };
+            if let Option::Some(hook) = &mut self.trace_hook {
+                (hook.0)(point)
+            }
+        }
+    }
+
+    pub fn args(&self) -> &[StringVar] {
+        &self.args
+    }
+
+    pub fn stdin(&self) -> Rc<FileObj> {
+        self.stdin.clone()
+    }
+
+    pub fn stdout(&self) -> Rc<FileObj> {
+        self.stdout.clone()
+    }
+
+    pub fn stderr(&self) -> Rc<FileObj> {
+        self.stderr.clone()
+    }
+
+    /// Replaces the object `sys.stdin` refers to, e.g. to feed input in tests.
+    pub fn set_stdin(&mut self, stdin: Rc<FileObj>) {
+        self.stdin = stdin;
+    }
+
+    /// Replaces the object `sys.stdout` refers to, e.g. to capture output.
+    pub fn set_stdout(&mut self, stdout: Rc<FileObj>) {
+        self.stdout = stdout;
+    }
+
+    pub(crate) fn rng(&mut self) -> &mut Rng {
+        &mut self.rng
+    }
+
+    pub(crate) fn cached_operator(&self, cls: Type, op: Operator) -> Option<StdVarMethod> {
+        self.operator_cache.get(&(cls, op)).copied()
+    }
+
+    pub(crate) fn cache_operator(&mut self, cls: Type, op: Operator, method: StdVarMethod) {
+        self.operator_cache.insert((cls, op), method);
+    }
+
     pub fn push(&mut self, var: Variable) {
         self.variables.push(var)
     }
@@ -207,6 +384,11 @@ impl Runtime {
         self.current_file().get_functions()[self.last_frame().get_fn_number() as usize].get_bytes()
     }
 
+    pub fn current_instruction(&self) -> Instruction {
+        self.current_file().get_functions()[self.last_frame().get_fn_number() as usize]
+            .get_instruction(self.current_pos())
+    }
+
     pub fn current_pos(&self) -> usize {
         self.last_frame().current_pos() as usize
     }
@@ -311,7 +493,8 @@ impl Runtime {
     }
 
     pub fn pop_stack(&mut self) {
-        if self.is_generator() {
+        // Native frames have no bytecode function backing them, so they can never be generators
+        if !self.is_native() && self.is_generator() {
             self.borrowed_iterators.pop();
         }
         let last_stack_frame = self
@@ -323,10 +506,13 @@ impl Runtime {
                 "In pop_stack(): popped frame has exception \
                     not covered in runtime's exception frames",
             );
-            assert_eq!(last_frames.last().unwrap().1, self.frames.len() - 1);
+            assert_eq!(last_frames.last().unwrap().1, self.frames.len() + 1);
             last_frames.pop();
             self.exception_stack.pop();
         }
+        let height = self.frames.len();
+        self.pending_finally.retain(|(h, _)| *h <= height);
+        self.handled_exceptions.retain(|(h, _)| *h <= height);
         let stack_h = last_stack_frame.original_stack_height();
         if stack_h != 0 {
             let drain_end = self.variables.len() - self.ret_count;
@@ -399,15 +585,29 @@ impl Runtime {
     }
 
     fn unwind(&mut self, exc_type: Type, exc: InnerException) -> FnResult {
-        let frame = self.exception_frames.get(&exc_type.into());
-        match frame.and_then(|vec| vec.last().cloned()) {
-            Option::Some((location, frame_height)) => {
-                self.unwind_to_height(location, frame_height, exc)
+        match self.find_handler(exc_type) {
+            Option::Some((handler_type, location, frame_height)) => {
+                self.unwind_to_height(location, frame_height, handler_type, exc)
             }
             Option::None => self.unwind_to_empty(exc),
         }
     }
 
+    /// Finds the innermost handler which catches exceptions of the given
+    /// type, i.e. the most recently entered one whose type is the same as or
+    /// a superclass of `exc_type`, or which is a finally block.
+    fn find_handler(&self, exc_type: Type) -> Option<(Variable, u32, usize)> {
+        let (handler_type, _) =
+            self.exception_stack.iter().rev().find(|(handler, _)| {
+                match handler.clone().into_type() {
+                    Result::Ok(t) => exc_type.is_subclass(&t, self),
+                    Result::Err(handler) => handler.is_null(),
+                }
+            })?;
+        let (location, frame_height) = *self.exception_frames.get(handler_type)?.last()?;
+        Option::Some((handler_type.clone(), location, frame_height))
+    }
+
     pub fn do_static(&mut self) -> bool {
         let last_frame = self.last_frame();
         assert!(!last_frame.is_native());
@@ -430,6 +630,22 @@ impl Runtime {
     }
 
     pub fn add_exception_handler(&mut self, exception_type: Variable, jump_loc: u32) {
+        let base = self.exception_stack.len();
+        self.push_handler(exception_type, jump_loc, base);
+    }
+
+    /// Adds the handlers for each except clause of a single try, in order.
+    ///
+    /// When one of them catches an exception, the others are removed along
+    /// with it.
+    pub fn add_exception_handlers(&mut self, handlers: Vec<(Variable, u32)>) {
+        let base = self.exception_stack.len();
+        for (exception_type, jump_loc) in handlers {
+            self.push_handler(exception_type, jump_loc, base);
+        }
+    }
+
+    fn push_handler(&mut self, exception_type: Variable, jump_loc: u32, base: usize) {
         match self.exception_frames.get_mut(&exception_type) {
             Option::Some(val) => val.push((jump_loc, self.frames.len())),
             Option::None => {
@@ -439,7 +655,55 @@ impl Runtime {
         }
         self.last_mut_frame()
             .add_exception_handler(exception_type.clone());
-        self.exception_stack.push(exception_type);
+        self.exception_stack.push((exception_type, base));
+    }
+
+    /// Adds a handler for a finally block, which starts at `jump_loc`.
+    ///
+    /// Finally handlers are stored as handlers with a null type, and catch
+    /// every exception. When the block is entered, either by an exception or
+    /// by [`Runtime::pop_handler`] as the try block exits normally, it is
+    /// recorded as pending until [`Runtime::end_finally`] is called at the
+    /// end of the block.
+    pub fn add_finally_handler(&mut self, jump_loc: u32) {
+        self.add_exception_handler(Variable::null(), jump_loc);
+    }
+
+    /// Ends the innermost pending finally block, continuing to unwind if it
+    /// was entered because of an exception.
+    pub fn end_finally(&mut self) -> FnResult {
+        let (height, exception) = self
+            .pending_finally
+            .pop()
+            .expect("Called end_finally with no pending finally block");
+        debug_assert_eq!(height, self.frames.len());
+        match exception {
+            Option::Some(exc) => self.unwind(exc.get_type(), exc),
+            Option::None => FnResult::Ok(()),
+        }
+    }
+
+    /// Throws the exception being handled by the innermost except block
+    /// again, keeping the stack frames from where it was first thrown.
+    pub fn rethrow(&mut self) -> FnResult {
+        let (_, exception) = self
+            .handled_exceptions
+            .pop()
+            .expect("Called rethrow outside of an except block");
+        self.unwind(exception.get_type(), exception)
+    }
+
+    /// Marks the end of the innermost except block, after which its
+    /// exception can no longer be rethrown.
+    ///
+    /// Every except block must end with this (unless it leaves the function
+    /// by returning or throwing); otherwise its exception is kept until the
+    /// frame is popped, and a later [`Runtime::rethrow`] in the same frame
+    /// could throw it again.
+    pub fn end_except(&mut self) {
+        self.handled_exceptions
+            .pop()
+            .expect("Called end_except outside of an except block");
     }
 
     pub fn remove_exception_handler(&mut self, exception_type: &Variable) {
@@ -455,11 +719,14 @@ impl Runtime {
     }
 
     pub fn pop_handler(&mut self) {
-        let val = self
+        let (val, _) = self
             .exception_stack
             .pop()
             .expect("Called pop_handler with empty exception stack");
         self.remove_exception_handler(&val);
+        if val.is_null() {
+            self.pending_finally.push((self.frames.len(), Option::None));
+        }
     }
 
     pub fn load_fn(&self, fn_no: u16) -> Variable {
@@ -680,6 +947,7 @@ impl Runtime {
         &mut self,
         location: u32,
         frame_height: usize,
+        handler_type: Variable,
         exception: InnerException,
     ) -> FnResult {
         while self.frames.len() > frame_height {
@@ -689,11 +957,25 @@ impl Runtime {
             }
             self.pop_stack();
         }
-        self.exception_frames
-            .get_mut(&exception.get_type().into())
-            .unwrap()
-            .pop();
-        self.remove_exception_handler(&exception.get_type().into());
+        let index = self
+            .exception_stack
+            .iter()
+            .rposition(|(x, _)| *x == handler_type)
+            .expect("Handler should be in the exception stack");
+        // Every handler above the one which caught is from a try nested in
+        // its own, and those from its base up are the other except clauses of
+        // the same try, so none of them can be entered any more
+        let base = self.exception_stack[index].1;
+        while self.exception_stack.len() > base {
+            let (handler, _) = self.exception_stack.pop().unwrap();
+            self.remove_exception_handler(&handler);
+        }
+        if handler_type.is_null() {
+            self.pending_finally
+                .push((self.frames.len(), Option::Some(exception)));
+        } else {
+            self.handled_exceptions.push((self.frames.len(), exception));
+        }
         self.goto(location);
         FnResult::Ok(())
     }
@@ -709,12 +991,26 @@ impl Runtime {
             self.pop_stack();
         }
         self.ret_count = old_ret;
-        panic!(
-            "{}",
-            exception
-                .str(self)
-                .expect("Exception.operator str should not throw an exception")
-        );
+        self.thrown_exception = Option::Some(exception);
+        FnResult::Err(())
+    }
+
+    /// Whether an exception has unwound every frame off the stack.
+    pub fn is_unwound(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Takes the exception which unwound the whole stack, if there is one.
+    pub fn uncaught_exception(&mut self) -> Option<UncaughtException> {
+        if !self.is_unwound() {
+            return Option::None;
+        }
+        let exception = self.thrown_exception.take()?;
+        let exc_type = exception.get_type();
+        let message = exception
+            .str(self)
+            .expect("Exception.operator str should not throw an exception");
+        Option::Some(UncaughtException { exc_type, message })
     }
 
     pub fn test<F>(f: F) -> Result<Variable, ()>
@@ -722,6 +1018,9 @@ impl Runtime {
         F: FnOnce(&mut Runtime) -> FnResult,
     {
         let mut test_runtime = Self::new(vec![], 0);
+        // There is no file for a non-native frame to refer to, and a native
+        // frame catches any exceptions thrown instead of unwinding off the stack
+        test_runtime.frames = vec![StackFrame::native(0)];
         match f(&mut test_runtime) {
             Result::Ok(_) => Result::Ok(test_runtime.pop_return()),
             Result::Err(_) => Result::Err(()),
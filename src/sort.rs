@@ -0,0 +1,100 @@
+use std::iter::Peekable;
+use std::vec::IntoIter;
+
+/// Sorts `values` using a stable merge sort, where the comparison may fail.
+///
+/// `less_than` is called as `less_than(a, b)` and should return whether `a`
+/// must come strictly before `b`. If it ever returns an error, sorting stops
+/// immediately and the error is returned; the values are lost in that case.
+pub fn merge_sort<T, F>(values: Vec<T>, less_than: &mut F) -> Result<Vec<T>, ()>
+where
+    F: FnMut(&T, &T) -> Result<bool, ()>,
+{
+    if values.len() <= 1 {
+        return Result::Ok(values);
+    }
+    let mut left = values;
+    let right = left.split_off(left.len() / 2);
+    let left = merge_sort(left, less_than)?;
+    let right = merge_sort(right, less_than)?;
+    merge(left, right, less_than)
+}
+
+fn merge<T, F>(left: Vec<T>, right: Vec<T>, less_than: &mut F) -> Result<Vec<T>, ()>
+where
+    F: FnMut(&T, &T) -> Result<bool, ()>,
+{
+    let mut result = Vec::with_capacity(left.len() + right.len());
+    let mut left = left.into_iter().peekable();
+    let mut right = right.into_iter().peekable();
+    loop {
+        match (left.peek(), right.peek()) {
+            (Option::Some(l), Option::Some(r)) => {
+                // Only take from the right when strictly less, to keep the sort stable
+                if less_than(r, l)? {
+                    result.push(next(&mut right));
+                } else {
+                    result.push(next(&mut left));
+                }
+            }
+            (Option::Some(_), Option::None) => {
+                result.extend(left);
+                return Result::Ok(result);
+            }
+            (Option::None, _) => {
+                result.extend(right);
+                return Result::Ok(result);
+            }
+        }
+    }
+}
+
+fn next<T>(iter: &mut Peekable<IntoIter<T>>) -> T {
+    iter.next().expect("Value should have already been peeked")
+}
+
+#[cfg(test)]
+mod test {
+    use crate::sort::merge_sort;
+
+    fn less_than(a: &i32, b: &i32) -> Result<bool, ()> {
+        Result::Ok(a < b)
+    }
+
+    #[test]
+    fn empty() {
+        let result = merge_sort(Vec::<i32>::new(), &mut less_than);
+        assert_eq!(result, Result::Ok(vec![]));
+    }
+
+    #[test]
+    fn sort() {
+        let result = merge_sort(vec![5, 3, 1, 4, 2, 0], &mut less_than);
+        assert_eq!(result, Result::Ok(vec![0, 1, 2, 3, 4, 5]));
+    }
+
+    #[test]
+    fn stable() {
+        let values = vec![(1, 'a'), (0, 'b'), (1, 'c'), (0, 'd')];
+        let result = merge_sort(values, &mut |a, b| Result::Ok(a.0 < b.0));
+        assert_eq!(
+            result,
+            Result::Ok(vec![(0, 'b'), (0, 'd'), (1, 'a'), (1, 'c')])
+        );
+    }
+
+    #[test]
+    fn error() {
+        let mut count = 0;
+        let result = merge_sort(vec![3, 2, 1], &mut |a, b| {
+            count += 1;
+            if count == 2 {
+                Result::Err(())
+            } else {
+                Result::Ok(a < b)
+            }
+        });
+        assert_eq!(result, Result::Err(()));
+        assert_eq!(count, 2);
+    }
+}
@@ -191,15 +191,26 @@ pub fn frame_strings(frames: impl IntoIterator<Item = SFInfo>, runtime: &Runtime
             let file = runtime.file_no(frame.file_no());
             let fn_no = frame.fn_no();
             let fn_pos = frame.current_pos();
-            let func = &file.get_functions()[fn_no as usize];
-            let fn_name = func.get_name();
-            writeln!(
-                result,
-                "    at {}:{} ({})",
-                fn_name,
-                fn_pos,
-                file.get_name()
-            )
+            let fn_name = file.get_functions()[fn_no as usize].get_name();
+            // The frame has already advanced past the current instruction, so
+            // look up the line of the byte before it
+            match file.line_for(fn_no, fn_pos.saturating_sub(1)) {
+                Option::Some(line) => writeln!(
+                    result,
+                    "    at {} ({}, line {}, byte {})",
+                    fn_name,
+                    file.get_name(),
+                    line,
+                    fn_pos
+                ),
+                Option::None => writeln!(
+                    result,
+                    "    at {} ({}, byte {})",
+                    fn_name,
+                    file.get_name(),
+                    fn_pos
+                ),
+            }
             .unwrap();
         } else {
             result.push_str("    at [unknown native function]\n")
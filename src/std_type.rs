@@ -1,6 +1,7 @@
 use crate::builtin_functions::{char_fn, string_fn};
 use crate::builtins::default_methods;
 use crate::custom_types::exceptions::value_error;
+use crate::custom_types::list::List;
 use crate::custom_types::types::{CustomType, TypeIdentity};
 use crate::first;
 use crate::lang_union::{UnionMethod, UnionType, UnionTypeMethod};
@@ -13,7 +14,7 @@ use crate::runtime::Runtime;
 use crate::std_variable::{StdVarMethod, StdVariable};
 use crate::string_var::StringVar;
 use crate::tuple::LangTuple;
-use crate::variable::{FnResult, Variable};
+use crate::variable::{value_id, FnResult, IdTag, Variable};
 use num::ToPrimitive;
 use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
@@ -114,6 +115,7 @@ impl Type {
 
     pub fn is_subclass(&self, other: &Type, runtime: &Runtime) -> bool {
         match (self, other) {
+            (_, Type::Object) => true,
             (Type::Standard(t), _) => t.is_subclass(other, runtime),
             (Type::Null, Type::Null) => true,
             (Type::Bool, Type::Bool) => true,
@@ -124,7 +126,6 @@ impl Type {
             (Type::Decimal, Type::Decimal) => true,
             (Type::Tuple, Type::Tuple) => true,
             (Type::Type, Type::Type) => true,
-            (Type::Object, _) => true,
             (Type::Custom(t), _) => t.is_subclass(other, runtime),
             (Type::Union(t), Type::Union(u)) => ptr::eq(*t, *u),
             (Type::Option(i1, t1), Type::Option(i2, t2)) => i1 == i2 && t1 == t2,
@@ -161,16 +162,36 @@ impl Type {
     }
 
     pub fn index(self, index: Name, runtime: &mut Runtime) -> Variable {
+        // The reflective attributes are only used if the type does not have
+        // its own attribute of the same name
+        match index {
+            Name::Attribute("name") if !self.has_static("name", runtime) => {
+                return self.str().into();
+            }
+            Name::Attribute("bases") if !self.has_static("bases", runtime) => {
+                let bases = self
+                    .bases(runtime)
+                    .into_iter()
+                    .map(Variable::from)
+                    .collect();
+                return List::from_values(Type::Type, bases).into();
+            }
+            _ => {}
+        }
         match self {
             Type::Standard(std_t) => std_t.index(index, runtime),
             Type::Union(union_t) => union_t.index(index),
             Type::Custom(custom_t) => custom_t.index(index),
             Type::String => match index {
-                Name::Attribute(s) => string_fn::static_attr(s),
+                Name::Attribute(s) => {
+                    string_fn::static_attr(s).unwrap_or_else(|| unimplemented!("str.{}", s))
+                }
                 _ => unimplemented!("str.{}", index.as_str()),
             },
             Type::Char => match index {
-                Name::Attribute(s) => char_fn::static_attr(s),
+                Name::Attribute(s) => {
+                    char_fn::static_attr(s).unwrap_or_else(|| unimplemented!("char.{}", s))
+                }
                 _ => unimplemented!("char.{}", index.as_str()),
             },
             _ => unimplemented!(
@@ -182,6 +203,26 @@ impl Type {
         }
     }
 
+    /// Gets the given static attribute, or [`None`] if it does not exist.
+    pub fn try_index_attr(self, attr: &str, runtime: &mut Runtime) -> Option<Variable> {
+        if self.has_static(attr, runtime) || attr == "name" || attr == "bases" {
+            Option::Some(self.index(Name::Attribute(attr), runtime))
+        } else {
+            Option::None
+        }
+    }
+
+    fn has_static(self, attr: &str, runtime: &Runtime) -> bool {
+        match self {
+            Type::Standard(std_t) => std_t.has_static(attr, runtime),
+            Type::Union(union_t) => union_t.has_static(attr),
+            Type::Custom(custom_t) => custom_t.has_static(attr),
+            Type::String => string_fn::static_attr(attr).is_some(),
+            Type::Char => char_fn::static_attr(attr).is_some(),
+            _ => false,
+        }
+    }
+
     pub fn str(&self) -> StringVar {
         match self {
             Type::Standard(t) => t.name().clone(),
@@ -200,6 +241,17 @@ impl Type {
         }
     }
 
+    /// The direct superclasses of the type.
+    pub fn bases(&self, runtime: &Runtime) -> Vec<Type> {
+        match self {
+            Type::Standard(t) => t.bases(runtime),
+            Type::Bool => vec![Type::Bigint],
+            Type::Custom(t) => t.get_supers().to_vec(),
+            Type::Union(u) => u.bases(runtime),
+            _ => Vec::new(),
+        }
+    }
+
     pub fn set(&self, index: &str, value: Variable, runtime: &mut Runtime) {
         match self {
             Type::Standard(_) | Type::Custom(_) => {
@@ -214,7 +266,21 @@ impl Type {
             Type::Standard(t) => *t as *const _ as usize,
             Type::Custom(t) => *t as *const _ as *const () as usize,
             Type::Union(u) => *u as *const _ as usize,
-            _ => todo!("Unique ids for special types"),
+            Type::Null => value_id(IdTag::Type, 0),
+            Type::Bool => value_id(IdTag::Type, 1),
+            Type::Bigint => value_id(IdTag::Type, 2),
+            Type::String => value_id(IdTag::Type, 3),
+            Type::Decimal => value_id(IdTag::Type, 4),
+            Type::Char => value_id(IdTag::Type, 5),
+            Type::Tuple => value_id(IdTag::Type, 6),
+            Type::Type => value_id(IdTag::Type, 7),
+            Type::Object => value_id(IdTag::Type, 8),
+            // Option types are compared by value, so they only need to be
+            // distinct from each other and from the types they wrap
+            Type::Option(i, t) => {
+                let base = Type::from(*t).id();
+                value_id(IdTag::Type, base.rotate_left(8) ^ (*i + 8))
+            }
         }
     }
 
@@ -308,6 +374,21 @@ impl StdType {
         name.do_each(|_| Option::None, |s| self.properties.get(s))
     }
 
+    pub(crate) fn has_attr(&self, name: &str, runtime: &Runtime) -> bool {
+        let name = Name::Attribute(name);
+        self.get_property(name).is_some() || self.try_method(name, runtime).is_some()
+    }
+
+    fn has_static(&'static self, attr: &str, runtime: &Runtime) -> bool {
+        let name = Name::Attribute(attr);
+        self.index_method(name).is_some()
+            || runtime.static_attr(&Type::Standard(self), name).is_some()
+    }
+
+    pub(crate) fn has_operator(&self, op: Operator, runtime: &Runtime) -> bool {
+        self.try_method(Name::Operator(op), runtime).is_some()
+    }
+
     fn is_subclass(&self, other: &Type, runtime: &Runtime) -> bool {
         if let Type::Standard(o) = other {
             if self == *o {
@@ -326,6 +407,14 @@ impl StdType {
         &self.name
     }
 
+    fn bases(&self, runtime: &Runtime) -> Vec<Type> {
+        let constants = runtime.file_no(self.file_no).get_constants();
+        self.supers
+            .iter()
+            .map(|&sup| constants[sup as usize].clone().into())
+            .collect()
+    }
+
     fn index(&'static self, index: Name, runtime: &Runtime) -> Variable {
         match self.index_method(index) {
             Option::Some(index_pair) => {
@@ -386,6 +475,29 @@ impl StdType {
         })
     }
 
+    /// Gets the method for the given operator.
+    ///
+    /// Operators defined directly on the class (or on none of its
+    /// superclasses, if it has none) are cheap to look up already; anything
+    /// inherited from a superclass is cached on the runtime so repeated calls
+    /// skip the walk up the class hierarchy. Failed lookups are not cached.
+    pub(crate) fn get_operator(&'static self, op: Operator, runtime: &mut Runtime) -> StdVarMethod {
+        if let Option::Some(method) = self.methods.get_op(op) {
+            return *method;
+        } else if self.supers.is_empty() {
+            return self.get_method(Name::Operator(op), runtime);
+        }
+        let cls = Type::Standard(self);
+        match runtime.cached_operator(cls, op) {
+            Option::Some(method) => method,
+            Option::None => {
+                let method = self.get_method(Name::Operator(op), runtime);
+                runtime.cache_operator(cls, op, method);
+                method
+            }
+        }
+    }
+
     fn try_method(&self, name: Name, runtime: &Runtime) -> Option<StdVarMethod> {
         match self.methods.get(name) {
             Option::Some(t) => Option::Some(*t),
@@ -414,10 +526,12 @@ impl PartialEq for Type {
             (Type::Char, Type::Char) => true,
             (Type::Tuple, Type::Tuple) => true,
             (Type::Type, Type::Type) => true,
+            (Type::Object, Type::Object) => true,
             (Type::Custom(a), Type::Custom(b)) => {
                 ptr::eq(*a as *const _ as *const (), *b as *const _ as *const ())
             }
             (Type::Union(t), Type::Union(u)) => ptr::eq(*t, *u),
+            (Type::Option(i1, t1), Type::Option(i2, t2)) => i1 == i2 && t1 == t2,
             _ => false,
         }
     }
@@ -437,6 +551,7 @@ impl PartialEq for OptionType {
             (OptionType::Char, OptionType::Char) => true,
             (OptionType::Tuple, OptionType::Tuple) => true,
             (OptionType::Type, OptionType::Type) => true,
+            (OptionType::Object, OptionType::Object) => true,
             (OptionType::Custom(a), OptionType::Custom(b)) => {
                 ptr::eq(*a as *const _ as *const (), *b as *const _ as *const ())
             }
@@ -463,7 +578,10 @@ impl Hash for Type {
             Type::Object => 8.hash(state),
             Type::Custom(b) => ptr::hash(*b, state),
             Type::Union(c) => ptr::hash(*c, state),
-            Type::Option(_, t) => ptr::hash(t, state),
+            Type::Option(i, t) => {
+                i.hash(state);
+                Type::from(*t).hash(state)
+            }
         }
     }
 }
@@ -486,3 +604,133 @@ impl From<OptionType> for Type {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::custom_types::list::List;
+    use crate::custom_types::range::Range;
+    use crate::custom_types::types::CustomType;
+    use crate::custom_var::downcast_var;
+    use crate::file_info::FileInfo;
+    use crate::function::Function;
+    use crate::name::Name;
+    use crate::name_map::NameMap;
+    use crate::runtime::Runtime;
+    use crate::std_type::Type;
+    use crate::string_var::StringVar;
+    use crate::variable::{FnResult, InnerVar, Variable};
+    use std::collections::{HashMap, HashSet};
+
+    fn create(_args: Vec<Variable>, _runtime: &mut Runtime) -> FnResult {
+        unimplemented!()
+    }
+
+    fn bases(cls: Type, runtime: &mut Runtime) -> Vec<Variable> {
+        let bases = cls.index(Name::Attribute("bases"), runtime);
+        let bases = downcast_var::<List>(bases).unwrap();
+        let values = bases.values().to_vec();
+        values
+    }
+
+    #[test]
+    fn builtin_name() {
+        let mut runtime = Runtime::new(Vec::new(), 0);
+        let name = Type::Bigint.index(Name::Attribute("name"), &mut runtime);
+        assert_eq!(name, StringVar::from("int").into());
+        let name = Range::range_type().index(Name::Attribute("name"), &mut runtime);
+        assert_eq!(name, StringVar::from("range").into());
+    }
+
+    #[test]
+    fn builtin_bases() {
+        let mut runtime = Runtime::new(Vec::new(), 0);
+        assert_eq!(bases(Type::Bool, &mut runtime), vec![Type::Bigint.into()]);
+        assert!(bases(Type::Bigint, &mut runtime).is_empty());
+        assert!(bases(Range::range_type(), &mut runtime).is_empty());
+    }
+
+    #[test]
+    fn custom_bases() {
+        let cls = Box::leak(Box::new(CustomType::new(
+            "SubRange".into(),
+            vec![Range::range_type()],
+            Function::Native(create),
+            NameMap::new(),
+        )));
+        let mut runtime = Runtime::new(Vec::new(), 0);
+        let cls = Type::Custom(cls);
+        assert_eq!(
+            cls.index(Name::Attribute("name"), &mut runtime),
+            StringVar::from("SubRange").into()
+        );
+        assert_eq!(bases(cls, &mut runtime), vec![Range::range_type().into()]);
+    }
+
+    #[test]
+    fn std_bases() {
+        let new_type = |name: &'static str, supers| {
+            Type::new_std(
+                name.into(),
+                0,
+                supers,
+                HashSet::new(),
+                NameMap::new(),
+                NameMap::new(),
+                HashMap::new(),
+            )
+        };
+        let parent = new_type("Parent", Vec::new());
+        let child = new_type("Child", vec![0]);
+        let file = FileInfo::new(
+            String::new(),
+            vec![parent.into()],
+            Vec::new(),
+            HashMap::new(),
+            Vec::new(),
+        );
+        let mut runtime = Runtime::new(vec![file], 0);
+        assert_eq!(bases(child, &mut runtime), vec![parent.into()]);
+        assert!(bases(parent, &mut runtime).is_empty());
+        assert_eq!(
+            child.index(Name::Attribute("name"), &mut runtime),
+            StringVar::from("Child").into()
+        );
+    }
+
+    #[test]
+    fn static_name_shadows() {
+        let cls = Type::new_std(
+            "Named".into(),
+            0,
+            Vec::new(),
+            HashSet::new(),
+            NameMap::new(),
+            NameMap::new(),
+            HashMap::new(),
+        );
+        let mut runtime = Runtime::new(Vec::new(), 0);
+        let static_name: Variable = StringVar::from("static").into();
+        runtime.set_static_attr(&cls, Name::Attribute("name"), static_name.clone());
+        assert_eq!(
+            cls.index(Name::Attribute("name"), &mut runtime),
+            static_name
+        );
+    }
+
+    #[test]
+    fn custom_static_name_shadows() {
+        let mut statics = NameMap::new();
+        statics.insert(Name::Attribute("name"), Function::Native(create));
+        let cls = Box::leak(Box::new(CustomType::new(
+            "Named".into(),
+            Vec::new(),
+            Function::Native(create),
+            statics,
+        )));
+        let mut runtime = Runtime::new(Vec::new(), 0);
+        let name = Type::Custom(cls).index(Name::Attribute("name"), &mut runtime);
+        // Native functions can't be compared reliably, so check it isn't the
+        // type's own name instead
+        assert!(matches!(name, Variable::Normal(InnerVar::Function(_))));
+    }
+}
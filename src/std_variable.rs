@@ -11,7 +11,7 @@ use std::cell::RefCell;
 use std::cmp::{Eq, PartialEq};
 use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
 use std::sync::Arc;
 use std::vec::Vec;
 
@@ -22,6 +22,13 @@ pub struct StdVariable {
     value: Rc<RefCell<InnerVar>>,
 }
 
+/// A non-owning reference to a [`StdVariable`], which does not keep the
+/// variable alive.
+#[derive(Debug, Clone)]
+pub struct WeakStdVariable {
+    value: Weak<RefCell<InnerVar>>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct InnerVar {
     pub cls: &'static StdType,
@@ -48,11 +55,8 @@ impl StdVariable {
         args: Vec<Variable>,
         runtime: &mut Runtime,
     ) -> FnResult {
-        let inner_method = self
-            .value
-            .borrow()
-            .cls
-            .get_method(Name::Operator(op), runtime);
+        let cls = self.value.borrow().cls;
+        let inner_method = cls.get_operator(op, runtime);
         inner_method.call(self, args, runtime)
     }
 
@@ -66,11 +70,8 @@ impl StdVariable {
         args: Vec<Variable>,
         runtime: &mut Runtime,
     ) -> FnResult {
-        let inner_method = self
-            .value
-            .borrow()
-            .cls
-            .get_method(Name::Operator(op), runtime);
+        let cls = self.value.borrow().cls;
+        let inner_method = cls.get_operator(op, runtime);
         inner_method.call_or_goto(self, args, runtime)
     }
 
@@ -90,6 +91,25 @@ impl StdVariable {
         }
     }
 
+    /// Gets the given attribute, or [`None`] if it does not exist.
+    pub fn try_index(&self, index: &str, runtime: &mut Runtime) -> Result<Option<Variable>, ()> {
+        if self.has_attr(index, runtime) {
+            self.index(Name::Attribute(index), runtime)
+                .map(Option::Some)
+        } else {
+            Result::Ok(Option::None)
+        }
+    }
+
+    pub fn has_attr(&self, index: &str, runtime: &Runtime) -> bool {
+        let self_value = self.value.borrow();
+        self_value.values.contains_key(index) || self_value.cls.has_attr(index, runtime)
+    }
+
+    pub fn has_operator(&self, op: Operator, runtime: &Runtime) -> bool {
+        self.value.borrow().cls.has_operator(op, runtime)
+    }
+
     fn index_harder(&self, index: Name, runtime: &mut Runtime) -> Result<Variable, ()> {
         match self.value.borrow().cls.get_property(index) {
             Option::Some(val) => {
@@ -135,9 +155,64 @@ impl StdVariable {
         Type::Standard(self.value.borrow().cls)
     }
 
+    pub fn downgrade(&self) -> WeakStdVariable {
+        WeakStdVariable {
+            value: Rc::downgrade(&self.value),
+        }
+    }
+
     pub fn var_ptr(&self) -> usize {
         self.value.as_ptr() as usize
     }
+
+    /// The `repr` used by classes which do not define one, listing each
+    /// field alongside the `repr` of its value, e.g. `Point { x: 1, y: 2 }`.
+    ///
+    /// Fields are listed in alphabetical order. A value which is reached
+    /// again while its own `repr` is being computed is shown as
+    /// `Point { ... }`, so self-referential objects do not loop forever.
+    pub fn default_repr(&self, runtime: &mut Runtime) -> Result<StringVar, ()> {
+        let name = self.get_type().str();
+        let ptr = self.var_ptr();
+        if !runtime.repr_stack().insert(ptr) {
+            return Result::Ok(format!("{} {{ ... }}", name).into());
+        }
+        let fields = self.fields_repr(runtime);
+        runtime.repr_stack().remove(&ptr);
+        let fields = fields?;
+        if fields.is_empty() {
+            Result::Ok(name)
+        } else {
+            Result::Ok(format!("{} {{ {} }}", name, fields).into())
+        }
+    }
+
+    fn fields_repr(&self, runtime: &mut Runtime) -> Result<String, ()> {
+        let mut values = self
+            .value
+            .borrow()
+            .values
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect::<Vec<_>>();
+        values.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        let mut result = String::new();
+        for (i, (name, value)) in values.into_iter().enumerate() {
+            if i != 0 {
+                result.push_str(", ");
+            }
+            result.push_str(&name);
+            result.push_str(": ");
+            result.push_str(value.repr(runtime)?.as_str());
+        }
+        Result::Ok(result)
+    }
+}
+
+impl WeakStdVariable {
+    pub fn upgrade(&self) -> Option<StdVariable> {
+        self.value.upgrade().map(|value| StdVariable { value })
+    }
 }
 
 impl InnerVar {
@@ -165,3 +240,200 @@ impl PartialEq for StdVariable {
         Rc::ptr_eq(&self.value, &other.value)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::file_info::FileInfo;
+    use crate::int_var::IntVar;
+    use crate::name::Name;
+    use crate::name_map::NameMap;
+    use crate::operator::Operator;
+    use crate::runtime::Runtime;
+    use crate::std_type::{StdType, Type};
+    use crate::std_variable::{StdVarMethod, StdVariable};
+    use crate::string_var::StringVar;
+    use crate::variable::{FnResult, Variable};
+    use std::collections::{HashMap, HashSet};
+    use std::time::Instant;
+
+    fn add_one(_this: StdVariable, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert_eq!(args.len(), 1);
+        let value = IntVar::from(args.into_iter().next().unwrap());
+        runtime.return_1((value + IntVar::from(1)).into())
+    }
+
+    fn add_two(_this: StdVariable, args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+        debug_assert_eq!(args.len(), 1);
+        let value = IntVar::from(args.into_iter().next().unwrap());
+        runtime.return_1((value + IntVar::from(2)).into())
+    }
+
+    fn std_type(
+        name: &'static str,
+        supers: Vec<u32>,
+        ops: Vec<(Operator, StdVarMethod)>,
+    ) -> &'static StdType {
+        let mut methods = NameMap::new();
+        for (op, method) in ops {
+            methods.insert(Name::Operator(op), method);
+        }
+        let cls = Type::new_std(
+            name.into(),
+            0,
+            supers,
+            HashSet::new(),
+            methods,
+            NameMap::new(),
+            HashMap::new(),
+        );
+        match cls {
+            Type::Standard(cls) => cls,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Creates a runtime whose current file has the given classes as its
+    /// constants, so they may be used as superclasses.
+    fn runtime_with(classes: Vec<&'static StdType>) -> Runtime {
+        let constants = classes
+            .into_iter()
+            .map(|cls| Type::Standard(cls).into())
+            .collect();
+        let file = FileInfo::new(
+            String::new(),
+            constants,
+            Vec::new(),
+            HashMap::new(),
+            Vec::new(),
+        );
+        Runtime::new(vec![file], 0)
+    }
+
+    fn instance(cls: &'static StdType) -> StdVariable {
+        StdVariable::new(cls, HashMap::new())
+    }
+
+    fn add(var: &StdVariable, value: usize, runtime: &mut Runtime) -> Result<IntVar, ()> {
+        var.clone()
+            .call_operator(Operator::Add, vec![IntVar::from(value).into()], runtime)?;
+        Result::Ok(runtime.pop_return().into())
+    }
+
+    #[test]
+    fn cached_mixed_types() {
+        let parent = std_type(
+            "One",
+            Vec::new(),
+            vec![(Operator::Add, StdVarMethod::Native(add_one))],
+        );
+        let child = std_type("Child", vec![0], Vec::new());
+        let two = std_type(
+            "Two",
+            vec![0],
+            vec![(Operator::Add, StdVarMethod::Native(add_two))],
+        );
+        let mut runtime = runtime_with(vec![parent]);
+        let vars = [instance(parent), instance(child), instance(two)];
+        let mut total = IntVar::from(0);
+        for i in 0..99 {
+            let value = add(&vars[i % 3], i, &mut runtime).unwrap();
+            let expected = if i % 3 == 2 { i + 2 } else { i + 1 };
+            assert_eq!(value, IntVar::from(expected));
+            total += value;
+        }
+        // 0..99 summed, plus one for each of the 66 instances of One and
+        // Child and two for each of the 33 instances of Two
+        assert_eq!(total, IntVar::from(4851 + 66 + 2 * 33));
+        assert!(runtime
+            .cached_operator(Type::Standard(child), Operator::Add)
+            .is_some());
+    }
+
+    #[test]
+    fn cached_default() {
+        let parent = std_type("Empty", Vec::new(), Vec::new());
+        let child = std_type("Child", vec![0], Vec::new());
+        let var = instance(child);
+        let mut runtime = runtime_with(vec![parent]);
+        for _ in 0..2 {
+            var.clone()
+                .call_operator(Operator::Equals, vec![var.clone().into()], &mut runtime)
+                .unwrap();
+            assert!(bool::from(runtime.pop_return()));
+        }
+        assert!(runtime
+            .cached_operator(Type::Standard(child), Operator::Equals)
+            .is_some());
+    }
+
+    fn with_fields(cls: &'static StdType, fields: Vec<(&str, Variable)>) -> StdVariable {
+        let values = fields
+            .into_iter()
+            .map(|(name, value)| (name.into(), value))
+            .collect();
+        StdVariable::new(cls, values)
+    }
+
+    #[test]
+    fn default_repr_fields() {
+        let cls = std_type("Point", Vec::new(), Vec::new());
+        let var = with_fields(
+            cls,
+            vec![
+                ("y", StringVar::from("a").into()),
+                ("x", IntVar::from(1).into()),
+            ],
+        );
+        let mut runtime = runtime_with(Vec::new());
+        let repr = var.repr(&mut runtime).unwrap();
+        assert_eq!(repr.as_str(), "Point { x: 1, y: \"a\" }");
+    }
+
+    #[test]
+    fn default_repr_cycle() {
+        let cls = std_type("Node", Vec::new(), Vec::new());
+        let var = with_fields(cls, vec![("next", Variable::null())]);
+        let mut runtime = runtime_with(Vec::new());
+        var.set("next", var.clone().into(), &mut runtime).unwrap();
+        let repr = var.clone().repr(&mut runtime).unwrap();
+        assert_eq!(repr.as_str(), "Node { next: Node { ... } }");
+        assert!(runtime.repr_stack().is_empty());
+        // Break the cycle so the instance is freed
+        var.set("next", Variable::null(), &mut runtime).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn missing_operator() {
+        let parent = std_type("Empty", Vec::new(), Vec::new());
+        let var = instance(std_type("Child", vec![0], Vec::new()));
+        let mut runtime = runtime_with(vec![parent]);
+        let _ = var.call_operator(Operator::Add, vec![IntVar::from(1).into()], &mut runtime);
+    }
+
+    /// Microbenchmark comparing cached and uncached lookups of an inherited
+    /// operator; run with `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn operator_lookup_bench() {
+        const COUNT: usize = 1_000_000;
+        let parent = std_type(
+            "One",
+            Vec::new(),
+            vec![(Operator::Add, StdVarMethod::Native(add_one))],
+        );
+        let child = std_type("Child", vec![0], Vec::new());
+        let mut runtime = runtime_with(vec![parent]);
+        let start = Instant::now();
+        for _ in 0..COUNT {
+            let _ = child.get_method(Name::Operator(Operator::Add), &runtime);
+        }
+        let uncached = start.elapsed();
+        let start = Instant::now();
+        for _ in 0..COUNT {
+            let _ = child.get_operator(Operator::Add, &mut runtime);
+        }
+        let cached = start.elapsed();
+        println!("uncached: {:?}, cached: {:?}", uncached, cached);
+    }
+}
@@ -0,0 +1,125 @@
+use ascii::{AsciiChar, AsciiStr};
+use std::iter::FusedIterator;
+
+/// An iterator over the lines of a string, where each line retains its
+/// terminator (either `\n` or `\r\n`).
+///
+/// Unlike [`str::lines`], concatenating every yielded line reproduces the
+/// original string exactly.
+pub struct StrLinesKeepEnds<'a> {
+    value: &'a str,
+}
+
+/// The [`AsciiStr`] equivalent of [`StrLinesKeepEnds`].
+pub struct AsciiLinesKeepEnds<'a> {
+    value: &'a AsciiStr,
+}
+
+impl StrLinesKeepEnds<'_> {
+    pub fn new(value: &str) -> StrLinesKeepEnds<'_> {
+        StrLinesKeepEnds { value }
+    }
+}
+
+impl AsciiLinesKeepEnds<'_> {
+    pub fn new(value: &AsciiStr) -> AsciiLinesKeepEnds<'_> {
+        AsciiLinesKeepEnds { value }
+    }
+}
+
+impl<'a> Iterator for StrLinesKeepEnds<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.value.is_empty() {
+            return Option::None;
+        }
+        // '\n' is a single byte in UTF-8 and can never appear within a multi-byte sequence, so
+        // the index after it is always a valid char boundary; "\r\n" ends in '\n' and needs no
+        // special handling
+        let end = self
+            .value
+            .bytes()
+            .position(|x| x == b'\n')
+            .map_or(self.value.len(), |x| x + 1);
+        let (line, rest) = self.value.split_at(end);
+        self.value = rest;
+        Option::Some(line)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.value.is_empty() {
+            (0, Option::Some(0))
+        } else {
+            (1, Option::Some(self.value.len()))
+        }
+    }
+}
+
+impl<'a> Iterator for AsciiLinesKeepEnds<'a> {
+    type Item = &'a AsciiStr;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.value.is_empty() {
+            return Option::None;
+        }
+        let end = self
+            .value
+            .chars()
+            .position(|ch| ch == AsciiChar::LineFeed)
+            .map_or(self.value.len(), |x| x + 1);
+        let line = &self.value[..end];
+        self.value = &self.value[end..];
+        Option::Some(line)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.value.is_empty() {
+            (0, Option::Some(0))
+        } else {
+            (1, Option::Some(self.value.len()))
+        }
+    }
+}
+
+impl FusedIterator for StrLinesKeepEnds<'_> {}
+
+impl FusedIterator for AsciiLinesKeepEnds<'_> {}
+
+#[cfg(test)]
+mod test {
+    use crate::string_var::lines::{AsciiLinesKeepEnds, StrLinesKeepEnds};
+    use ascii::AsciiStr;
+
+    #[test]
+    fn empty() {
+        let lines = StrLinesKeepEnds::new("");
+        assert_eq!(lines.collect::<Vec<_>>(), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn mixed_endings() {
+        let lines = StrLinesKeepEnds::new("a\nb\r\nc");
+        assert_eq!(lines.collect::<Vec<_>>(), vec!["a\n", "b\r\n", "c"]);
+    }
+
+    #[test]
+    fn trailing_newline() {
+        let lines = StrLinesKeepEnds::new("a\r\nb\n");
+        assert_eq!(lines.collect::<Vec<_>>(), vec!["a\r\n", "b\n"]);
+    }
+
+    #[test]
+    fn non_ascii() {
+        let lines = StrLinesKeepEnds::new("é\r\nü\nö");
+        assert_eq!(lines.collect::<Vec<_>>(), vec!["é\r\n", "ü\n", "ö"]);
+    }
+
+    #[test]
+    fn ascii_mixed_endings() {
+        let str = AsciiStr::from_ascii("a\nb\r\n\nc").unwrap();
+        let lines = AsciiLinesKeepEnds::new(str);
+        let expected = vec!["a\n", "b\r\n", "\n", "c"];
+        assert_eq!(lines.map(AsciiStr::as_str).collect::<Vec<_>>(), expected);
+    }
+}
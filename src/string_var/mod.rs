@@ -1,5 +1,6 @@
 mod chunks;
 mod impls;
+mod lines;
 mod maybe;
 mod owned;
 mod vars;
@@ -9,8 +10,10 @@ pub use owned::OwnedStringVar;
 pub use vars::{AsciiVar, StrVar};
 
 use crate::character;
+use crate::runtime::Runtime;
 use crate::string_var::chunks::{AsciiChunks, StrChunks};
 use crate::string_var::impls::{MixedIter, OwnedIter};
+use crate::string_var::lines::{AsciiLinesKeepEnds, StrLinesKeepEnds};
 use ascii::{AsAsciiStr, AsAsciiStrError, AsciiChar, AsciiStr, AsciiString};
 use std::borrow::{Borrow, Cow};
 use std::fmt::{Debug, Formatter};
@@ -64,6 +67,27 @@ impl StringVar {
         StringVar::Ascii(arc)
     }
 
+    /// Creates a string sharing its allocation with all other interned
+    /// strings of equal value.
+    ///
+    /// This is useful when many equal strings are expected to be created, as
+    /// it both saves memory and makes equality checks between them a pointer
+    /// comparison.
+    pub fn interned(var: &str, runtime: &mut Runtime) -> StringVar {
+        let pool = runtime.interned_strings();
+        match pool.get(var) {
+            Option::Some(result) => result.clone(),
+            Option::None => {
+                let result = match var.as_ascii_str() {
+                    Result::Ok(ascii) => StringVar::from_ascii_ref(ascii),
+                    Result::Err(_) => StringVar::from_str_ref(var),
+                };
+                pool.insert(result.clone());
+                result
+            }
+        }
+    }
+
     pub fn char_at(&self, i: usize) -> Option<char> {
         match self {
             StringVar::Literal(l) => l.chars().nth(i),
@@ -123,6 +147,15 @@ impl StringVar {
         }
     }
 
+    pub fn owned_lines_keep_ends(&self) -> impl Iterator<Item = StringVar> + '_ {
+        match self {
+            StringVar::Literal(s) => OwnedIter::Literal(StrLinesKeepEnds::new(s)),
+            StringVar::AsciiLiteral(a) => OwnedIter::AsciiLiteral(AsciiLinesKeepEnds::new(a)),
+            StringVar::Other(s) => OwnedIter::Normal(StrLinesKeepEnds::new(s)),
+            StringVar::Ascii(a) => OwnedIter::Ascii(AsciiLinesKeepEnds::new(a)),
+        }
+    }
+
     pub fn chunks(&self, count: usize) -> impl Iterator<Item = MaybeAscii<'_>> {
         match self.as_maybe_ascii() {
             MaybeAscii::Standard(s) => MixedIter::Normal(StrChunks::new(s, count)),
@@ -254,7 +287,9 @@ impl Borrow<str> for StringVar {
 
 impl PartialEq for StringVar {
     fn eq(&self, other: &Self) -> bool {
-        **self == **other
+        // Shortcut for shared (e.g. interned) strings
+        let (this, other) = (&**self, &**other);
+        (this.as_ptr() == other.as_ptr() && this.len() == other.len()) || this == other
     }
 }
 
@@ -304,7 +339,9 @@ impl<'a> FromIterator<&'a AsciiStr> for StringVar {
 
 #[cfg(test)]
 mod test {
+    use crate::runtime::Runtime;
     use crate::string_var::StringVar;
+    use crate::variable::Variable;
     use ascii::AsciiChar;
 
     #[test]
@@ -331,4 +368,31 @@ mod test {
         let a = StringVar::from("abc");
         assert_eq!(&*a.repr(), "\"abc\"")
     }
+
+    #[test]
+    fn interned() {
+        let mut runtime = Runtime::new(vec![], 0);
+        let a: Variable = StringVar::interned(&format!("ab{}", "c"), &mut runtime).into();
+        let b: Variable = StringVar::interned(&format!("ab{}", "c"), &mut runtime).into();
+        let c: Variable = StringVar::interned("abd", &mut runtime).into();
+        assert_eq!(a.id(), b.id());
+        assert_ne!(a.id(), c.id());
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn interned_non_ascii() {
+        let mut runtime = Runtime::new(vec![], 0);
+        let a: Variable = StringVar::interned("é", &mut runtime).into();
+        let b: Variable = StringVar::interned("é", &mut runtime).into();
+        assert_eq!(a.id(), b.id());
+    }
+
+    #[test]
+    fn not_interned() {
+        let a: Variable = StringVar::from("abc".to_owned()).into();
+        let b: Variable = StringVar::from("abc".to_owned()).into();
+        assert_ne!(a.id(), b.id());
+        assert_eq!(a, b);
+    }
 }
@@ -80,15 +80,15 @@ impl CustomVar for LangMetadata {
         }
     }
 
-    fn get_attribute(self: Rc<Self>, name: &str) -> Variable {
-        match name {
+    fn get_attribute(self: Rc<Self>, name: &str) -> Option<Variable> {
+        Option::Some(match name {
             "isDir" => self.value.is_dir().into(),
             "isFile" => self.value.is_file().into(),
             "length" => self.value.len().into(),
             "fileType" => LangFileType::new(self.value.file_type()).into(),
             "permissions" => LangPermissions::new(self.value.permissions()).into(),
-            x => unimplemented!("Metadata.{}", x),
-        }
+            _ => return Option::None,
+        })
     }
 }
 
@@ -105,13 +105,13 @@ impl CustomVar for LangFileType {
         todo!()
     }
 
-    fn get_attribute(self: Rc<Self>, name: &str) -> Variable {
-        match name {
+    fn get_attribute(self: Rc<Self>, name: &str) -> Option<Variable> {
+        Option::Some(match name {
             "isDir" => self.value.is_dir().into(),
             "isFile" => self.value.is_file().into(),
             "isSymlink" => self.value.is_symlink().into(),
-            x => unimplemented!("FileType.{}", x),
-        }
+            _ => return Option::None,
+        })
     }
 }
 
@@ -128,11 +128,11 @@ impl CustomVar for LangPermissions {
         unimplemented!()
     }
 
-    fn get_attribute(self: Rc<Self>, name: &str) -> Variable {
-        match name {
+    fn get_attribute(self: Rc<Self>, name: &str) -> Option<Variable> {
+        Option::Some(match name {
             "readOnly" => self.value.readonly().into(),
             "mode" => self.mode().map(From::from).into(),
-            x => unimplemented!("Permissions.{}", x),
-        }
+            _ => return Option::None,
+        })
     }
 }
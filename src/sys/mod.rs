@@ -1,8 +1,10 @@
 use crate::custom_types::bytes::LangBytes;
 use crate::custom_types::exceptions::io_error;
+use crate::custom_types::list::List;
 use crate::custom_var::downcast_var;
 use crate::function::{Function, NativeFunction};
 use crate::runtime::Runtime;
+use crate::std_type::Type;
 use crate::string_var::StringVar;
 use crate::sys::os::os_name;
 use crate::variable::{FnResult, Variable};
@@ -16,24 +18,40 @@ mod files;
 mod metadata;
 mod os;
 
-// Numbers borrowed from https://filippo.io/linux-syscall-table/
+// Numbers borrowed from https://filippo.io/linux-syscall-table/; values which
+// are not syscalls are numbered from 1000
 pub fn sys_name(x: usize) -> &'static str {
     match x {
         79 => "getcwd",
         80 => "chdir",
         83 => "mkdir",
+        1000 => "argv",
+        1001 => "stdin",
+        1002 => "stdout",
+        1003 => "stderr",
         _ => unimplemented!("syscall no. {}", x),
     }
 }
 
-pub fn get_value(x: &str) -> Variable {
+pub fn get_value(x: &str, runtime: &mut Runtime) -> Variable {
     match x {
         "FILE_SEPARATOR" => MAIN_SEPARATOR.into(),
         "NAME" => StringVar::from(os_name()).into(),
+        "argv" => argv(runtime),
+        "stdin" => runtime.stdin().into(),
+        "stdout" => runtime.stdout().into(),
+        "stderr" => runtime.stderr().into(),
         _ => Function::Native(get_syscall(x)).into(),
     }
 }
 
+/// A new list of the program's arguments, so that modifying it won't affect
+/// later uses of `sys.argv`.
+fn argv(runtime: &mut Runtime) -> Variable {
+    let args = runtime.args().iter().cloned().map(Variable::from).collect();
+    List::from_values(Type::String, args).into()
+}
+
 #[inline]
 pub fn get_syscall(x: &str) -> NativeFunction {
     match x {
@@ -100,3 +118,96 @@ fn os_do_1<T: Into<Variable>>(
         Option::None => filename_err(runtime),
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::base_fn::BaseFunction;
+    use crate::bytecode::Bytecode;
+    use crate::custom_types::file::FileObj;
+    use crate::custom_types::list::List;
+    use crate::custom_var::downcast_var;
+    use crate::executor::execute;
+    use crate::file_info::FileInfo;
+    use crate::runtime::Runtime;
+    use crate::string_var::StringVar;
+    use crate::sys::get_value;
+    use crate::variable::Variable;
+    use std::collections::HashMap;
+    use std::io;
+    use std::rc::Rc;
+
+    /// A file which stores the result of `GetSys` with the given index.
+    fn get_sys_file(index: u16) -> FileInfo {
+        let [hi, lo] = index.to_be_bytes();
+        let bytes = vec![Bytecode::GetSys as u8, hi, lo, Bytecode::Store as u8, 0, 0];
+        let function = BaseFunction::new(String::new(), 1, bytes).without_decoding();
+        FileInfo::new(
+            String::new(),
+            Vec::new(),
+            vec![function],
+            HashMap::new(),
+            Vec::new(),
+        )
+    }
+
+    fn run_get_sys(runtime: &mut Runtime) -> Variable {
+        execute(runtime).unwrap();
+        runtime.load_value(0).clone()
+    }
+
+    fn strings(values: &[&str]) -> Vec<StringVar> {
+        values
+            .iter()
+            .map(|&x| StringVar::from(x.to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn argv() {
+        let args = strings(&["--verbose", "input.txt"]);
+        let mut runtime = Runtime::with_args(Vec::new(), 0, args.clone());
+        let argv = downcast_var::<List>(get_value("argv", &mut runtime)).unwrap();
+        let expected: Vec<Variable> = args.into_iter().map(Variable::from).collect();
+        assert_eq!(argv.values().to_vec(), expected);
+    }
+
+    #[test]
+    fn argv_empty() {
+        let mut runtime = Runtime::new(Vec::new(), 0);
+        let argv = downcast_var::<List>(get_value("argv", &mut runtime)).unwrap();
+        assert!(argv.values().is_empty());
+    }
+
+    #[test]
+    fn argv_bytecode() {
+        let args = strings(&["--verbose", "input.txt"]);
+        let mut runtime = Runtime::with_args(vec![get_sys_file(1000)], 0, args.clone());
+        let argv = downcast_var::<List>(run_get_sys(&mut runtime)).unwrap();
+        let expected: Vec<Variable> = args.into_iter().map(Variable::from).collect();
+        assert_eq!(argv.values().to_vec(), expected);
+    }
+
+    #[test]
+    fn std_streams() {
+        let mut runtime = Runtime::new(Vec::new(), 0);
+        let stdout = FileObj::from_writer("<test>", Box::new(io::sink()));
+        runtime.set_stdout(stdout.clone());
+        let value = downcast_var::<FileObj>(get_value("stdout", &mut runtime)).unwrap();
+        assert!(Rc::ptr_eq(&value, &stdout));
+        let stdin = downcast_var::<FileObj>(get_value("stdin", &mut runtime)).unwrap();
+        assert!(Rc::ptr_eq(&stdin, &runtime.stdin()));
+    }
+
+    fn check_stream(index: u16, stream: fn(&Runtime) -> Rc<FileObj>) {
+        let mut runtime = Runtime::new(vec![get_sys_file(index)], 0);
+        let value = downcast_var::<FileObj>(run_get_sys(&mut runtime)).unwrap();
+        assert!(Rc::ptr_eq(&value, &stream(&runtime)), "{}", index);
+    }
+
+    #[test]
+    fn std_streams_bytecode() {
+        check_stream(1001, Runtime::stdin);
+        check_stream(1002, Runtime::stdout);
+        check_stream(1003, Runtime::stderr);
+    }
+}
@@ -0,0 +1,147 @@
+//! The `time` module.
+//!
+//! Times are returned as decimals of seconds, which represent the nanosecond
+//! values given by the system exactly.
+
+use crate::custom_types::exceptions::value_error;
+use crate::custom_types::module::Module;
+use crate::function::{Function, NativeFunction};
+use crate::int_var::IntVar;
+use crate::rational_var::RationalVar;
+use crate::runtime::Runtime;
+use crate::variable::{FnResult, InnerVar, Variable};
+use crate::{check_args, first};
+use num::{BigInt, BigRational, Signed, ToPrimitive};
+use once_cell::sync::Lazy;
+use std::rc::Rc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// The point `monotonic()` measures from, which is set the first time it is
+/// called.
+static START: Lazy<Instant> = Lazy::new(Instant::now);
+
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+pub fn get_value(x: &str) -> Option<Variable> {
+    get_function(x).map(|f| Function::Native(f).into())
+}
+
+#[inline]
+pub fn get_function(x: &str) -> Option<NativeFunction> {
+    Option::Some(match x {
+        "now" => now,
+        "monotonic" => monotonic,
+        "sleep" => sleep,
+        _ => return Option::None,
+    })
+}
+
+/// The object the `time` builtin refers to.
+pub fn module() -> Rc<Module> {
+    Module::new("time", get_value)
+}
+
+fn duration_to_dec(duration: Duration) -> RationalVar {
+    let nanos = BigInt::from(duration.as_nanos());
+    BigRational::new(nanos, BigInt::from(NANOS_PER_SEC)).into()
+}
+
+fn now(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("time.now", &args, 0, runtime)?;
+    // Times before the epoch are represented as negative numbers
+    let now = match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Result::Ok(x) => duration_to_dec(x),
+        Result::Err(e) => -duration_to_dec(e.duration()),
+    };
+    runtime.return_1(now.into())
+}
+
+fn monotonic(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("time.monotonic", &args, 0, runtime)?;
+    runtime.return_1(duration_to_dec(START.elapsed()).into())
+}
+
+fn sleep(args: Vec<Variable>, runtime: &mut Runtime) -> FnResult {
+    check_args("time.sleep", &args, 1, runtime)?;
+    let seconds = match first(args) {
+        Variable::Normal(InnerVar::Decimal(d)) => d.into_inner(),
+        x => BigRational::from_integer(IntVar::from(x).into()),
+    };
+    if seconds.is_negative() {
+        return runtime.throw_quick(
+            value_error(),
+            format!("time.sleep requires a non-negative time, not {}", seconds),
+        );
+    }
+    let nanos = (seconds * BigInt::from(NANOS_PER_SEC)).to_integer();
+    // Saturating is fine here, as u64::MAX nanoseconds is over 500 years
+    thread::sleep(Duration::from_nanos(nanos.to_u64().unwrap_or(u64::MAX)));
+    runtime.return_0()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::custom_types::exceptions::value_error;
+    use crate::int_var::IntVar;
+    use crate::rational_var::RationalVar;
+    use crate::runtime::Runtime;
+    use crate::time::{monotonic, now, sleep};
+    use crate::variable::{InnerVar, Variable};
+    use num::{BigInt, BigRational};
+    use std::time::{Duration, Instant};
+
+    fn decimal(value: Result<Variable, ()>) -> BigRational {
+        match value {
+            Result::Ok(Variable::Normal(InnerVar::Decimal(d))) => d.into_inner(),
+            x => panic!("Expected a decimal, got {:?}", x),
+        }
+    }
+
+    #[test]
+    fn monotonic_non_decreasing() {
+        let first = decimal(Runtime::test(|runtime| monotonic(vec![], runtime)));
+        let second = decimal(Runtime::test(|runtime| monotonic(vec![], runtime)));
+        assert!(first <= second);
+    }
+
+    #[test]
+    fn now_after_epoch() {
+        // 2020-01-01T00:00:00Z
+        let result = decimal(Runtime::test(|runtime| now(vec![], runtime)));
+        assert!(result > BigRational::from_integer(BigInt::from(1_577_836_800)));
+    }
+
+    #[test]
+    fn sleep_zero() {
+        let start = Instant::now();
+        let result = Runtime::test(|runtime| {
+            sleep(vec![IntVar::from(0).into()], runtime)?;
+            runtime.return_1(true.into())
+        });
+        assert_eq!(result, Result::Ok(true.into()));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn sleep_fraction() {
+        let start = Instant::now();
+        let millis = RationalVar::from(BigRational::new(1.into(), 1000.into()));
+        let result = Runtime::test(|runtime| {
+            sleep(vec![millis.into()], runtime)?;
+            runtime.return_1(true.into())
+        });
+        assert_eq!(result, Result::Ok(true.into()));
+        assert!(start.elapsed() >= Duration::from_millis(1));
+    }
+
+    #[test]
+    fn sleep_negative() {
+        let result = Runtime::test(|runtime| {
+            assert!(sleep(vec![IntVar::from(-1).into()], runtime).is_err());
+            let exc = runtime.pop_err()?;
+            runtime.return_1(exc.get_type().into())
+        });
+        assert_eq!(result, Result::Ok(value_error().into()));
+    }
+}
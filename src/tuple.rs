@@ -24,9 +24,7 @@ impl LangTuple {
     }
 
     pub fn str(&self, runtime: &mut Runtime) -> Result<StringVar, ()> {
-        let mut result = join_values(&self.values, |x| x.str(runtime))?;
-        surround_paren(&mut result);
-        Result::Ok(StringVar::from(result))
+        self.repr(runtime)
     }
 
     pub fn repr(&self, runtime: &mut Runtime) -> Result<StringVar, ()> {
@@ -44,14 +42,7 @@ impl LangTuple {
     }
 
     pub fn lang_hash(&self, runtime: &mut Runtime) -> Result<usize, ()> {
-        // Copied from Python's tuple hash algorithm
-        let mut x: usize = 0x345678;
-        let mul = 1000003;
-        for value in &*self.values {
-            let y = value.clone().hash(runtime)?;
-            x = (x ^ y).wrapping_mul(mul);
-        }
-        Result::Ok(x)
+        hash_values(&self.values, runtime)
     }
 
     pub fn id(&self) -> usize {
@@ -90,6 +81,18 @@ impl Index<usize> for LangTuple {
     }
 }
 
+/// Hashes a sequence of values, as is done for tuples.
+pub fn hash_values(values: &[Variable], runtime: &mut Runtime) -> Result<usize, ()> {
+    // Copied from Python's tuple hash algorithm
+    let mut x: usize = 0x345678;
+    let mul = 1000003;
+    for value in values {
+        let y = value.clone().hash(runtime)?;
+        x = (x ^ y).wrapping_mul(mul);
+    }
+    Result::Ok(x)
+}
+
 fn surround_paren(str: &mut MaybeString) {
     str.insert_ascii(0, AsciiChar::ParenOpen);
     str.push_ascii(AsciiChar::ParenClose);
@@ -20,7 +20,7 @@ use crate::string_var::StringVar;
 use crate::tuple::LangTuple;
 use num::bigint::BigInt;
 use num::traits::Zero;
-use num::{BigRational, ToPrimitive};
+use num::{BigRational, Integer, Signed, ToPrimitive};
 use std::boxed::Box;
 use std::clone::Clone;
 use std::cmp::PartialEq;
@@ -133,6 +133,18 @@ impl Variable {
         }
     }
 
+    /// Gets the given attribute, or [`None`] if it does not exist.
+    pub fn try_index_attr(
+        self,
+        index: &str,
+        runtime: &mut Runtime,
+    ) -> Result<Option<Variable>, ()> {
+        match self {
+            Variable::Normal(var) => var.try_index_attr(index, runtime),
+            Variable::Option(var) => Result::Ok(option_fn::get_attr(var, index)),
+        }
+    }
+
     pub fn set(self, index: &str, value: Variable, runtime: &mut Runtime) -> FnResult {
         match self {
             Variable::Normal(var) => var.set(index, value, runtime)?,
@@ -353,26 +365,31 @@ impl InnerVar {
             }
             InnerVar::Bigint(val) => match index {
                 Name::Operator(o) => int_fn::get_operator(val, o),
-                Name::Attribute(s) => int_fn::get_attribute(val, s),
+                Name::Attribute(s) => int_fn::get_attribute(val, s)
+                    .unwrap_or_else(|| unimplemented!("int.{} unimplemented", s)),
             },
             InnerVar::String(val) => match index {
                 Name::Operator(o) => string_fn::get_operator(val, o),
-                Name::Attribute(s) => string_fn::get_attr(val, s),
+                Name::Attribute(s) => {
+                    string_fn::get_attr(val, s).unwrap_or_else(|| unimplemented!("str.{}", s))
+                }
             },
             InnerVar::Tuple(val) => match index {
                 Name::Operator(o) => tuple_fn::get_operator(val, o),
-                Name::Attribute(s) => tuple_fn::get_attr(val, s),
-            },
-            InnerVar::Decimal(val) => {
-                if let Name::Operator(o) = index {
-                    dec_fn::get_operator(val, o)
-                } else {
-                    unimplemented!("dec.{}\n{}", index.as_str(), runtime.frame_strings())
+                Name::Attribute(s) => {
+                    tuple_fn::get_attr(val, s).unwrap_or_else(|| unimplemented!("tuple.{}", s))
                 }
-            }
+            },
+            InnerVar::Decimal(val) => match index {
+                Name::Operator(o) => dec_fn::get_operator(val, o),
+                Name::Attribute(s) => dec_fn::get_attribute(val, s)
+                    .unwrap_or_else(|| unimplemented!("dec.{} unimplemented", s)),
+            },
             InnerVar::Char(val) => match index {
                 Name::Operator(o) => char_fn::get_operator(val, o),
-                Name::Attribute(s) => char_fn::get_attribute(val, s),
+                Name::Attribute(s) => {
+                    char_fn::get_attribute(val, s).unwrap_or_else(|| unimplemented!("char.{}", s))
+                }
             },
             InnerVar::Type(t) => t.index(index, runtime),
             InnerVar::Custom(val) => val.into_inner().get_attr(index),
@@ -394,6 +411,28 @@ impl InnerVar {
         })
     }
 
+    /// Gets the given attribute, or [`None`] if it does not exist.
+    pub fn try_index_attr(
+        self,
+        index: &str,
+        runtime: &mut Runtime,
+    ) -> Result<Option<Variable>, ()> {
+        Result::Ok(match self {
+            InnerVar::Null() | InnerVar::Bool(_) | InnerVar::Method(_) | InnerVar::Function(_) => {
+                Option::None
+            }
+            InnerVar::Standard(val) => return val.try_index(index, runtime),
+            InnerVar::Bigint(val) => int_fn::get_attribute(val, index),
+            InnerVar::String(val) => string_fn::get_attr(val, index),
+            InnerVar::Tuple(val) => tuple_fn::get_attr(val, index),
+            InnerVar::Decimal(val) => dec_fn::get_attribute(val, index),
+            InnerVar::Char(val) => char_fn::get_attribute(val, index),
+            InnerVar::Type(t) => t.try_index_attr(index, runtime),
+            InnerVar::Custom(val) => val.into_inner().get_attribute(index),
+            InnerVar::Union(val) => return val.try_index(index, runtime),
+        })
+    }
+
     pub fn set(self, index: &str, value: Variable, runtime: &mut Runtime) -> FnResult {
         match self {
             InnerVar::Standard(val) => val.set(index, value, runtime)?,
@@ -446,7 +485,9 @@ impl InnerVar {
             InnerVar::Bool(b) => Result::Ok(if b { 0 } else { 1 }),
             InnerVar::Bigint(i) => {
                 let max = IntVar::Big(Rc::new(BigInt::from(usize::MAX) + 1));
-                let hash = i % max;
+                let hash = i % max.clone();
+                // The remainder takes the sign of the dividend, so negative values need wrapping
+                let hash = if hash.is_negative() { hash + max } else { hash };
                 Result::Ok(hash.to_usize().unwrap())
             }
             InnerVar::String(s) => Result::Ok(
@@ -457,11 +498,11 @@ impl InnerVar {
             ),
             InnerVar::Decimal(d) => {
                 let max = BigInt::from(usize::MAX) + 1;
-                let hash: BigInt = d.to_integer() % &max;
+                let hash: BigInt = d.to_integer().mod_floor(&max);
                 Result::Ok(hash.to_usize().unwrap())
             }
             InnerVar::Char(c) => Result::Ok(c as usize),
-            InnerVar::Type(_) => unimplemented!(),
+            InnerVar::Type(t) => Result::Ok(t.id()),
             InnerVar::Standard(v) => {
                 runtime.push_native();
                 v.call_operator(Operator::Hash, Vec::new(), runtime)?;
@@ -553,21 +594,33 @@ impl InnerVar {
         matches!(self, InnerVar::Null())
     }
 
+    /// A unique identifier for the value.
+    ///
+    /// For heap-allocated values this is derived from the address of the
+    /// allocation, so two values share an id if and only if they are
+    /// identical. Value types (bools, small ints, chars, and most builtin
+    /// types) have no allocation, so their ids are derived from the value
+    /// itself instead and equal values may share an id. See [`value_id`] for
+    /// how the two are kept from colliding.
     pub fn id(&self) -> usize {
         match self {
             InnerVar::Null() => 0,
-            InnerVar::Bool(b) => b.then(|| 3).unwrap_or(1),
+            InnerVar::Bool(b) => value_id(IdTag::Bool, *b as usize),
             InnerVar::Bigint(b) => match b {
-                IntVar::Small(s) => (2 * *s + 1).unsigned_abs(),
+                IntVar::Small(s) => {
+                    // Zigzag-encode so small negative numbers stay small
+                    let zigzag = (*s << 1) ^ (*s >> (isize::BITS - 1));
+                    value_id(IdTag::Int, zigzag as usize)
+                }
                 IntVar::Big(b) => &**b as *const _ as usize,
             },
             InnerVar::String(s) => s.as_str() as *const str as *const () as usize,
             InnerVar::Decimal(d) => &**d as *const BigRational as usize,
-            InnerVar::Char(_) => todo!("Unique ids for char"),
+            InnerVar::Char(c) => value_id(IdTag::Char, *c as usize),
             InnerVar::Type(t) => t.id(),
             InnerVar::Standard(s) => s.var_ptr(),
             InnerVar::Tuple(t) => t.id(),
-            InnerVar::Method(_) => todo!("Unique ids for method"),
+            InnerVar::Method(m) => &**m as *const dyn Method as *const () as usize,
             InnerVar::Function(f) => f.id(),
             InnerVar::Custom(c) => &**c as *const _ as *const () as usize,
             InnerVar::Union(u) => u.get_value().id(),
@@ -575,6 +628,30 @@ impl InnerVar {
     }
 }
 
+#[derive(Debug, Copy, Clone)]
+pub(crate) enum IdTag {
+    Int = 1,
+    Char = 2,
+    Bool = 3,
+    Type = 4,
+    Function = 5,
+}
+
+/// Creates an id for a value that does not have an allocation to derive one
+/// from.
+///
+/// The lowest three bits of the result are the (nonzero) tag, so ids of
+/// different kinds of values never collide with each other, nor with the
+/// addresses of allocations, which are aligned to at least 8 bytes. (The
+/// exception is strings, whose ids are the address of their first byte, and
+/// so may in theory collide with a value id.)
+///
+/// The value is shifted left to make room for the tag, so values with any of
+/// their top three bits set may share an id.
+pub(crate) fn value_id(tag: IdTag, value: usize) -> usize {
+    (value << 3) | tag as usize
+}
+
 impl OptionVar {
     pub fn new(i: usize, var: Option<InnerVar>) -> OptionVar {
         OptionVar {